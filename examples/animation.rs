@@ -0,0 +1,43 @@
+// Demonstrates animating retained shapes with the built-in animation components
+
+use bevy::prelude::*;
+use bevy_vector_shapes::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Shape2dPlugin::default())
+        .insert_resource(ClearColor(Color::DARK_GRAY))
+        .add_startup_system(setup)
+        .add_system(pulse)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut shapes: ShapeCommands) {
+    commands.spawn(Camera2dBundle::default());
+
+    shapes.hollow = true;
+    shapes.thickness = 4.0;
+    shapes.circle(50.0);
+}
+
+// Restart the animations whenever the previous ones have finished
+fn pulse(
+    mut commands: Commands,
+    mut grow: Local<bool>,
+    circles: Query<Entity, (With<Disc>, Without<AnimateRadius>)>,
+) {
+    for entity in &circles {
+        *grow = !*grow;
+        let (radius, thickness, color) = if *grow {
+            (150.0, 16.0, Color::CYAN)
+        } else {
+            (50.0, 4.0, Color::WHITE)
+        };
+        commands.entity(entity).insert((
+            AnimateRadius::new(radius, 1.0, Easing::CubicInOut),
+            AnimateThickness::new(thickness, 1.0, Easing::CubicInOut),
+            AnimateColor::new(color, 1.0, Easing::Linear),
+        ));
+    }
+}
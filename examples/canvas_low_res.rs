@@ -21,9 +21,10 @@ fn main() {
 }
 
 fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
-    let mut config = CanvasConfig::new(256, 256);
-    config.sampler = ImageSampler::nearest();
-    commands.spawn_canvas(images.as_mut(), config);
+    commands
+        .canvas(256, 256)
+        .sampler(ImageSampler::nearest())
+        .spawn(images.as_mut());
 
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(0., 0., 16.).looking_at(Vec3::ZERO, Vec3::Y),
@@ -16,8 +16,7 @@ fn main() {
 }
 
 fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
-    let config = CanvasConfig::new(1024, 1024);
-    commands.spawn_canvas(images.as_mut(), config);
+    commands.canvas(1024, 1024).spawn(images.as_mut());
 
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(0., 0., 16.).looking_at(Vec3::ZERO, Vec3::Y),
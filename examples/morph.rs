@@ -0,0 +1,53 @@
+// Demonstrates morphing between primitive shapes
+
+use bevy::prelude::*;
+use bevy_vector_shapes::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Shape2dPlugin::default())
+        .insert_resource(ClearColor(Color::DARK_GRAY))
+        .add_startup_system(setup)
+        .add_system(draw)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn draw(mut painter: ShapePainter, time: Res<Time>) {
+    let progress = time.elapsed_seconds().sin() * 0.5 + 0.5;
+
+    painter.color = Color::CYAN;
+    painter.translate(Vec3::NEG_X * 150.0);
+    painter.morph(
+        MorphTarget::Circle { radius: 100.0 },
+        MorphTarget::Rectangle {
+            size: Vec2::splat(180.0),
+            corner_radius: 20.0,
+        },
+        progress,
+    );
+
+    painter.hollow = true;
+    painter.thickness = 8.0;
+    painter.color = Color::ORANGE;
+    painter.translate(Vec3::X * 300.0);
+    painter.morph(
+        MorphTarget::Ngon {
+            sides: 3.0,
+            radius: 100.0,
+            roundness: 0.0,
+        },
+        MorphTarget::Ngon {
+            sides: 6.0,
+            radius: 100.0,
+            roundness: 10.0,
+        },
+        progress,
+    );
+
+    painter.reset();
+}
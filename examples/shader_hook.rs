@@ -0,0 +1,42 @@
+// Demonstrates injecting a fragment shader hook into the built-in shape shaders
+
+use bevy::prelude::*;
+use bevy_vector_shapes::prelude::*;
+
+// Darkens every other pair of pixel rows to create a scanline effect that scrolls over time
+const SCANLINES: &str = r"
+fn get_color(in: ShapeHookInput) -> vec4<f32> {
+    var row = u32(abs(in.uv.y * 64.0 + shape_view.time * 8.0)) % 2u;
+    return vec4<f32>(in.color.rgb * (0.5 + 0.5 * f32(row)), in.color.a);
+}
+";
+
+#[derive(Resource)]
+struct Scanlines(Handle<ShapeHook>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Shape2dPlugin::default())
+        .insert_resource(ClearColor(Color::DARK_GRAY))
+        .add_startup_system(setup)
+        .add_system(draw)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut hooks: ResMut<Assets<ShapeHook>>) {
+    commands.spawn(Camera2dBundle::default());
+    commands.insert_resource(Scanlines(hooks.add(ShapeHook::from_wgsl(SCANLINES))));
+}
+
+fn draw(mut painter: ShapePainter, scanlines: Res<Scanlines>) {
+    painter.color = Color::CYAN;
+    painter.translate(Vec3::NEG_X * 150.0);
+    painter.circle(100.0);
+
+    painter.hook = Some(scanlines.0.clone());
+    painter.translate(Vec3::X * 300.0);
+    painter.circle(100.0);
+
+    painter.reset();
+}
@@ -0,0 +1,46 @@
+// Demonstrates clipping shapes to the area covered by other shapes using the stencil buffer
+
+use bevy::prelude::*;
+use bevy_vector_shapes::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Shape2dPlugin::default())
+        .insert_resource(ClearColor(Color::DARK_GRAY))
+        .add_startup_system(setup)
+        .add_system(draw)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn draw(time: Res<Time>, mut painter: ShapePainter) {
+    // Write a circular mask, mask shapes are not visible
+    painter.stencil = StencilMode::Write(1);
+    painter.circle(200.0);
+
+    // Write a nested mask that only covers the area within the first mask
+    painter.stencil = StencilMode::Write(2);
+    painter.translate(Vec3::X * time.elapsed_seconds().sin() * 200.0);
+    painter.rect(Vec2::new(200.0, 600.0));
+    painter.reset();
+
+    // Draw stripes clipped to the first mask
+    painter.stencil = StencilMode::Read(1);
+    painter.color = Color::CRIMSON;
+    painter.thickness = 20.0;
+    for i in -10..=10 {
+        let x = i as f32 * 40.0;
+        painter.line(Vec3::new(x, -300.0, 0.0), Vec3::new(x, 300.0, 0.0));
+    }
+
+    // Draw a rectangle clipped to the nested mask
+    painter.stencil = StencilMode::Read(2);
+    painter.color = Color::SEA_GREEN;
+    painter.rect(Vec2::splat(600.0));
+
+    painter.reset();
+}
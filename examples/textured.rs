@@ -20,9 +20,10 @@ fn main() {
 }
 
 fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
-    let mut config = CanvasConfig::new(256, 256);
-    config.clear_color = ClearColorConfig::Custom(Color::WHITE * 0.5);
-    let (_, _entity) = commands.spawn_canvas(images.as_mut(), config);
+    commands
+        .canvas(256, 256)
+        .clear_color(ClearColorConfig::Custom(Color::WHITE * 0.5))
+        .spawn(images.as_mut());
 
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(0., 0., 16.).looking_at(Vec3::ZERO, Vec3::Y),
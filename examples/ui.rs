@@ -0,0 +1,70 @@
+// Demonstrates drawing shapes in the UI pass layered between bevy_ui nodes
+
+use bevy::prelude::*;
+use bevy_vector_shapes::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Shape2dPlugin::default())
+        .insert_resource(ClearColor(Color::DARK_GRAY))
+        .add_startup_system(setup)
+        .add_system(draw)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Panel with a child node
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(50.0), Val::Auto, Val::Px(50.0), Val::Auto),
+                size: Size::new(Val::Px(300.0), Val::Px(300.0)),
+                ..default()
+            },
+            background_color: Color::MIDNIGHT_BLUE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(100.0), Val::Px(100.0)),
+                    ..default()
+                },
+                background_color: Color::ORANGE_RED.into(),
+                ..default()
+            });
+
+            // Rounded panel sized by the layout and drawn by the shape pipeline
+            parent.spawn(ShapeNodeBundle {
+                node: NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(150.0), Val::Px(100.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                shape: ShapeNode::rounded(16.0),
+                shape_style: ShapeNodeStyle {
+                    color: Color::ALICE_BLUE,
+                    ..default()
+                },
+            });
+        });
+}
+
+fn draw(mut painter: ShapePainter, time: Res<Time>) {
+    painter.pass = ShapePass::Ui;
+    // UI coordinates are in logical pixels from the top left of the window, z orders the shape among UI nodes
+    painter.set_translation(Vec3::new(200.0, 200.0, 0.5));
+
+    painter.hollow = true;
+    painter.thickness = 8.0;
+    painter.color = Color::CYAN;
+    painter.arc(120.0, 0.0, time.elapsed_seconds() % std::f32::consts::TAU);
+
+    painter.reset();
+}
@@ -0,0 +1,494 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Easing function applied to the progress of a shape animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in the range 0..=1 to eased progress.
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineInOut => -((std::f32::consts::PI * t).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+/// Progress of an animation from the value the shape had when the animation started towards a target value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween<V> {
+    /// Value of the shape once the animation has finished.
+    pub target: V,
+    /// Duration of the animation in seconds.
+    pub duration: f32,
+    pub easing: Easing,
+    elapsed: f32,
+    start: Option<V>,
+}
+
+impl<V> Tween<V> {
+    pub fn new(target: V, duration: f32, easing: Easing) -> Self {
+        Self {
+            target,
+            duration,
+            easing,
+            elapsed: 0.0,
+            start: None,
+        }
+    }
+
+    /// Time in seconds since the animation started.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Whether the animation has reached it's target value.
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Trait implemented by shape components whose properties can be animated.
+pub trait AnimatedShape: Component {
+    fn color_mut(&mut self) -> &mut Color;
+
+    fn thickness_mut(&mut self) -> &mut f32;
+
+    /// Radius of the shape, if it has one.
+    fn radius_mut(&mut self) -> Option<&mut f32> {
+        None
+    }
+
+    /// Progress of the shape's morph, only implemented by [`ShapeMorph`].
+    fn morph_progress_mut(&mut self) -> Option<&mut f32> {
+        None
+    }
+}
+
+/// Trait implemented by animation components to drive a single property of an [`AnimatedShape`].
+pub trait ShapeAnimation: Component {
+    type Value: Copy;
+
+    fn tween(&mut self) -> &mut Tween<Self::Value>;
+
+    /// Property of the shape driven by the animation, animations on shapes without the property are removed.
+    fn value<T: AnimatedShape>(shape: &mut T) -> Option<&mut Self::Value>;
+
+    fn lerp(start: Self::Value, end: Self::Value, t: f32) -> Self::Value;
+}
+
+/// Animates the color of a shape towards the target color, interpolating in linear space.
+///
+/// The component is removed once the animation has finished.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct AnimateColor(pub Tween<Color>);
+
+impl AnimateColor {
+    pub fn new(target: Color, duration: f32, easing: Easing) -> Self {
+        Self(Tween::new(target, duration, easing))
+    }
+}
+
+impl ShapeAnimation for AnimateColor {
+    type Value = Color;
+
+    fn tween(&mut self) -> &mut Tween<Color> {
+        &mut self.0
+    }
+
+    fn value<T: AnimatedShape>(shape: &mut T) -> Option<&mut Color> {
+        Some(shape.color_mut())
+    }
+
+    fn lerp(start: Color, end: Color, t: f32) -> Color {
+        let start = Vec4::from(start.as_linear_rgba_f32());
+        let end = Vec4::from(end.as_linear_rgba_f32());
+        let [r, g, b, a] = start.lerp(end, t).to_array();
+        Color::rgba_linear(r, g, b, a)
+    }
+}
+
+/// Animates the thickness of a shape towards the target thickness.
+///
+/// The component is removed once the animation has finished.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct AnimateThickness(pub Tween<f32>);
+
+impl AnimateThickness {
+    pub fn new(target: f32, duration: f32, easing: Easing) -> Self {
+        Self(Tween::new(target, duration, easing))
+    }
+}
+
+impl ShapeAnimation for AnimateThickness {
+    type Value = f32;
+
+    fn tween(&mut self) -> &mut Tween<f32> {
+        &mut self.0
+    }
+
+    fn value<T: AnimatedShape>(shape: &mut T) -> Option<&mut f32> {
+        Some(shape.thickness_mut())
+    }
+
+    fn lerp(start: f32, end: f32, t: f32) -> f32 {
+        start + (end - start) * t
+    }
+}
+
+/// Animates the radius of a [`Disc`] or [`RegularPolygon`] towards the target radius.
+///
+/// The component is removed once the animation has finished.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct AnimateRadius(pub Tween<f32>);
+
+impl AnimateRadius {
+    pub fn new(target: f32, duration: f32, easing: Easing) -> Self {
+        Self(Tween::new(target, duration, easing))
+    }
+}
+
+impl ShapeAnimation for AnimateRadius {
+    type Value = f32;
+
+    fn tween(&mut self) -> &mut Tween<f32> {
+        &mut self.0
+    }
+
+    fn value<T: AnimatedShape>(shape: &mut T) -> Option<&mut f32> {
+        shape.radius_mut()
+    }
+
+    fn lerp(start: f32, end: f32, t: f32) -> f32 {
+        start + (end - start) * t
+    }
+}
+
+/// Animates the progress of a [`ShapeMorph`] towards the target progress.
+///
+/// The component is removed once the animation has finished.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct AnimateMorph(pub Tween<f32>);
+
+impl AnimateMorph {
+    pub fn new(target: f32, duration: f32, easing: Easing) -> Self {
+        Self(Tween::new(target, duration, easing))
+    }
+}
+
+impl ShapeAnimation for AnimateMorph {
+    type Value = f32;
+
+    fn tween(&mut self) -> &mut Tween<f32> {
+        &mut self.0
+    }
+
+    fn value<T: AnimatedShape>(shape: &mut T) -> Option<&mut f32> {
+        shape.morph_progress_mut()
+    }
+
+    fn lerp(start: f32, end: f32, t: f32) -> f32 {
+        start + (end - start) * t
+    }
+}
+
+/// Advances all animations of the given type on shapes of the given type, removing them once finished.
+pub fn animate_shapes<A: ShapeAnimation, T: AnimatedShape>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shapes: Query<(Entity, &mut T, &mut A)>,
+) {
+    for (entity, mut shape, mut animation) in &mut shapes {
+        let Some(value) = A::value(&mut *shape) else {
+            commands.entity(entity).remove::<A>();
+            continue;
+        };
+
+        let tween = animation.tween();
+        let start = *tween.start.get_or_insert(*value);
+        tween.elapsed += time.delta_seconds();
+
+        let progress = if tween.duration > 0.0 {
+            tween.elapsed / tween.duration
+        } else {
+            1.0
+        };
+        *value = A::lerp(start, tween.target, tween.easing.ease(progress));
+
+        if tween.finished() {
+            commands.entity(entity).remove::<A>();
+        }
+    }
+}
+
+/// Plays a range of frames of a sprite sheet at a fixed rate.
+///
+/// Sets the [`Rectangle::frame_index`] of the entity's rectangle if it has one, otherwise the current frame
+/// can be read with [`AnimateFrames::frame`] to draw it with [`RectPainter::image_frame`].
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AnimateFrames {
+    /// Index of the first frame played.
+    pub first: u32,
+    /// Number of frames played.
+    pub count: u32,
+    /// Frames per second.
+    pub fps: f32,
+    /// Whether to restart from the first frame after the last, otherwise the last frame is held.
+    pub looping: bool,
+    elapsed: f32,
+}
+
+impl AnimateFrames {
+    pub fn new(first: u32, count: u32, fps: f32) -> Self {
+        Self {
+            first,
+            count,
+            fps,
+            looping: true,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Plays the frames once, holding the last frame.
+    pub fn once(mut self) -> Self {
+        self.looping = false;
+        self
+    }
+
+    /// Index of the current frame in the sprite sheet.
+    pub fn frame(&self) -> u32 {
+        let count = self.count.max(1);
+        let played = (self.elapsed * self.fps).max(0.0) as u32;
+        match self.looping {
+            true => self.first + played % count,
+            false => self.first + played.min(count - 1),
+        }
+    }
+
+    /// Whether a non-looping animation has reached it's last frame.
+    pub fn finished(&self) -> bool {
+        !self.looping && (self.elapsed * self.fps) as u32 >= self.count.max(1) - 1
+    }
+
+    /// Restarts the animation from the first frame.
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+/// Advances each [`AnimateFrames`] and updates the frame of the entity's rectangle.
+pub fn animate_frames(
+    time: Res<Time>,
+    mut animations: Query<(&mut AnimateFrames, Option<&mut Rectangle>)>,
+) {
+    for (mut animation, rectangle) in &mut animations {
+        animation.elapsed += time.delta_seconds();
+        let frame = animation.frame();
+        if let Some(mut rectangle) = rectangle {
+            // Avoid triggering change detection while the frame is unchanged
+            if rectangle.frame_index != frame {
+                rectangle.frame_index = frame;
+            }
+        }
+    }
+}
+
+/// Despawns a retained shape once it's lifetime has elapsed, optionally fading it out beforehand.
+///
+/// Useful for temporary markers such as debug points or hit indicators.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ShapeLifetime {
+    /// Time in seconds after which the shape is despawned along with it's children.
+    pub duration: f32,
+    /// Time in seconds before the end of the lifetime over which the shape's alpha fades to zero.
+    ///
+    /// Set to zero to despawn without fading or to `duration` to fade over the entire lifetime.
+    pub fade: f32,
+    elapsed: f32,
+    alpha: Option<f32>,
+}
+
+impl ShapeLifetime {
+    pub fn new(duration: f32, fade: f32) -> Self {
+        Self {
+            duration,
+            fade,
+            elapsed: 0.0,
+            alpha: None,
+        }
+    }
+
+    /// Time in seconds until the shape is despawned.
+    pub fn remaining(&self) -> f32 {
+        (self.duration - self.elapsed).max(0.0)
+    }
+}
+
+/// Fades out shapes of the given type as they approach the end of their [`ShapeLifetime`].
+pub fn fade_shapes<T: AnimatedShape>(mut shapes: Query<(&mut T, &mut ShapeLifetime)>) {
+    for (mut shape, mut lifetime) in &mut shapes {
+        // Take the starting alpha without marking the shape as changed
+        let alpha = *lifetime
+            .alpha
+            .get_or_insert_with(|| shape.bypass_change_detection().color_mut().a());
+
+        let remaining = lifetime.remaining();
+        if remaining < lifetime.fade {
+            shape.color_mut().set_a(alpha * remaining / lifetime.fade);
+        }
+    }
+}
+
+/// Advances each [`ShapeLifetime`] and despawns the shapes whose lifetime has elapsed.
+pub fn update_shape_lifetimes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lifetimes: Query<(Entity, &mut ShapeLifetime)>,
+) {
+    for (entity, mut lifetime) in &mut lifetimes {
+        lifetime.elapsed += time.delta_seconds();
+        if lifetime.elapsed >= lifetime.duration {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Extension trait for [`App`] to register the animation systems of a shape type.
+pub trait ShapeAnimationAppExt {
+    /// Animates shapes of the given type with [`AnimateColor`], [`AnimateThickness`], [`AnimateRadius`] and [`AnimateMorph`],
+    /// and fades them out according to their [`ShapeLifetime`].
+    ///
+    /// Called automatically for the built-in shape types.
+    fn add_shape_animations<T: AnimatedShape>(&mut self) -> &mut Self;
+}
+
+impl ShapeAnimationAppExt for App {
+    fn add_shape_animations<T: AnimatedShape>(&mut self) -> &mut Self {
+        self.add_systems((
+            animate_shapes::<AnimateColor, T>,
+            animate_shapes::<AnimateThickness, T>,
+            animate_shapes::<AnimateRadius, T>,
+            animate_shapes::<AnimateMorph, T>,
+            fade_shapes::<T>.before(update_shape_lifetimes),
+        ))
+    }
+}
+
+/// Plugin that animates the built-in shape types, added by [`Shape2dPlugin`].
+pub struct ShapeAnimationPlugin;
+
+impl Plugin for ShapeAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((update_shape_lifetimes, animate_frames))
+            .add_shape_animations::<Line>()
+            .add_shape_animations::<QuadBezier>()
+            .add_shape_animations::<Disc>()
+            .add_shape_animations::<Rectangle>()
+            .add_shape_animations::<RegularPolygon>()
+            .add_shape_animations::<ShapeMorph>();
+    }
+}
+
+impl AnimatedShape for Line {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+}
+
+impl AnimatedShape for QuadBezier {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+}
+
+impl AnimatedShape for Disc {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+
+    fn radius_mut(&mut self) -> Option<&mut f32> {
+        Some(&mut self.radius)
+    }
+}
+
+impl AnimatedShape for Rectangle {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+}
+
+impl AnimatedShape for RegularPolygon {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+
+    fn radius_mut(&mut self) -> Option<&mut f32> {
+        Some(&mut self.radius)
+    }
+}
+
+impl AnimatedShape for ShapeMorph {
+    fn color_mut(&mut self) -> &mut Color {
+        &mut self.color
+    }
+
+    fn thickness_mut(&mut self) -> &mut f32 {
+        &mut self.thickness
+    }
+
+    fn morph_progress_mut(&mut self) -> Option<&mut f32> {
+        Some(&mut self.progress)
+    }
+}
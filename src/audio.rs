@@ -0,0 +1,236 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Style of the visualizers drawn with [`AudioPainter`].
+#[derive(Clone, Debug)]
+pub struct AudioVisualizerStyle {
+    pub color: Color,
+    /// Thickness of waveform lines, interpreted using the painter's thickness type.
+    pub thickness: f32,
+    /// Maximum number of segments of a waveform line or bars of a visualizer, samples are grouped to fit.
+    ///
+    /// Bounds the number of instances submitted regardless of the length of the buffer,
+    /// roughly one per pixel of the rect's width is enough for a waveform to look exact.
+    pub resolution: usize,
+    /// Gap between bars as a fraction of the space given to each bar.
+    pub bar_gap: f32,
+    /// Radius of the hole in a radial spectrum as a fraction of it's radius.
+    pub radial_hole: f32,
+}
+
+impl Default for AudioVisualizerStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            thickness: 1.5,
+            resolution: 256,
+            bar_gap: 0.2,
+            radial_hole: 0.3,
+        }
+    }
+}
+
+/// Splits the values into at most `count` groups of consecutive values, folding each group with `f`.
+fn buckets<'a>(
+    values: &'a [f32],
+    count: usize,
+    f: impl Fn(f32, f32) -> f32 + 'a,
+) -> impl ExactSizeIterator<Item = f32> + 'a {
+    let count = count.clamp(1, values.len().max(1));
+    let len = values.len();
+    (0..count).map(move |i| {
+        // Groups are never empty as there are at most as many groups as values
+        values[i * len / count..(i + 1) * len / count]
+            .iter()
+            .copied()
+            .reduce(&f)
+            .unwrap_or(0.0)
+    })
+}
+
+/// Sample of the largest magnitude keeping it's sign, such that peaks survive downsampling.
+fn peak(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() {
+        b
+    } else {
+        a
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to visualize audio sample buffers and spectrums.
+///
+/// Visualizers are drawn within a rect in the painter's local space with y pointing up. Buffers longer than the style's
+/// resolution are downsampled by keeping the peak of each group of samples, so the number of instances stays bounded
+/// and bars that are too short to see are skipped. The painter's config is restored once each visualizer is drawn.
+pub trait AudioPainter {
+    /// Draws samples in the range -1.0 to 1.0 as a line across the rect, centered vertically.
+    fn waveform(&mut self, rect: Rect, samples: &[f32], style: &AudioVisualizerStyle) -> &mut Self;
+
+    /// Draws samples in the range -1.0 to 1.0 as bars mirrored around the middle of the rect, each as tall as the peak it covers.
+    fn waveform_bars(
+        &mut self,
+        rect: Rect,
+        samples: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self;
+
+    /// Draws magnitudes in the range 0.0 to 1.0 as bars rising from the bottom of the rect, lowest frequency first.
+    fn spectrum(
+        &mut self,
+        rect: Rect,
+        magnitudes: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self;
+
+    /// Draws magnitudes in the range 0.0 to 1.0 as bars pointing outwards from the hole of the largest circle in the rect,
+    /// clockwise from the top.
+    fn radial_spectrum(
+        &mut self,
+        rect: Rect,
+        magnitudes: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self;
+}
+
+impl<'w, 's> AudioPainter for ShapePainter<'w, 's> {
+    fn waveform(&mut self, rect: Rect, samples: &[f32], style: &AudioVisualizerStyle) -> &mut Self {
+        if samples.len() < 2 {
+            return self;
+        }
+        let config = self.config().clone();
+        self.color = style.color;
+        self.thickness = style.thickness;
+        self.cap = Cap::Round;
+
+        let points = buckets(samples, style.resolution + 1, peak);
+        let step = rect.width() / (points.len() - 1).max(1) as f32;
+        let center = rect.center().y;
+        let half_height = rect.height() / 2.0;
+        let mut previous: Option<Vec2> = None;
+        for (i, sample) in points.enumerate() {
+            let point = Vec2::new(
+                rect.min.x + i as f32 * step,
+                center + sample.clamp(-1.0, 1.0) * half_height,
+            );
+            if let Some(previous) = previous {
+                self.line(previous.extend(0.0), point.extend(0.0));
+            }
+            previous = Some(point);
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn waveform_bars(
+        &mut self,
+        rect: Rect,
+        samples: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self {
+        if samples.is_empty() {
+            return self;
+        }
+        let config = self.config().clone();
+        self.color = style.color;
+        self.hollow = false;
+        self.corner_radii = Vec4::ZERO;
+
+        let peaks = buckets(samples, style.resolution, |a, b| a.abs().max(b.abs()));
+        let slot = rect.width() / peaks.len() as f32;
+        let width = slot * (1.0 - style.bar_gap);
+        let center = rect.center().y;
+        for (i, peak) in peaks.enumerate() {
+            let height = peak.abs().min(1.0) * rect.height();
+            if height <= 0.0 {
+                continue;
+            }
+            let x = rect.min.x + (i as f32 + 0.5) * slot;
+            self.transform = config
+                .transform
+                .mul_transform(Transform::from_xyz(x, center, 0.0));
+            self.rect(Vec2::new(width, height));
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn spectrum(
+        &mut self,
+        rect: Rect,
+        magnitudes: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self {
+        if magnitudes.is_empty() {
+            return self;
+        }
+        let config = self.config().clone();
+        self.color = style.color;
+        self.hollow = false;
+        self.corner_radii = Vec4::ZERO;
+
+        let bars = buckets(magnitudes, style.resolution, f32::max);
+        let slot = rect.width() / bars.len() as f32;
+        let width = slot * (1.0 - style.bar_gap);
+        for (i, magnitude) in bars.enumerate() {
+            let height = magnitude.clamp(0.0, 1.0) * rect.height();
+            if height <= 0.0 {
+                continue;
+            }
+            let x = rect.min.x + (i as f32 + 0.5) * slot;
+            self.transform = config.transform.mul_transform(Transform::from_xyz(
+                x,
+                rect.min.y + height / 2.0,
+                0.0,
+            ));
+            self.rect(Vec2::new(width, height));
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn radial_spectrum(
+        &mut self,
+        rect: Rect,
+        magnitudes: &[f32],
+        style: &AudioVisualizerStyle,
+    ) -> &mut Self {
+        if magnitudes.is_empty() {
+            return self;
+        }
+        let config = self.config().clone();
+        let radius = rect.width().min(rect.height()) / 2.0;
+        let inner_radius = radius * style.radial_hole.clamp(0.0, 1.0);
+        let bars = buckets(magnitudes, style.resolution, f32::max);
+        let slot = TAU / bars.len() as f32;
+
+        // Each bar is a single line as wide as it's share of the inner circumference, but no thinner than the style's thickness
+        self.color = style.color;
+        self.cap = Cap::None;
+        self.thickness = (slot * inner_radius * (1.0 - style.bar_gap)).max(style.thickness);
+        self.thickness_type = ThicknessType::World;
+        self.transform = config
+            .transform
+            .mul_transform(Transform::from_translation(rect.center().extend(0.0)));
+        for (i, magnitude) in bars.enumerate() {
+            let length = magnitude.clamp(0.0, 1.0) * (radius - inner_radius);
+            if length <= 0.0 {
+                continue;
+            }
+            let angle = (i as f32 + 0.5) * slot;
+            let direction = Vec2::new(angle.sin(), angle.cos());
+            self.line(
+                (direction * inner_radius).extend(0.0),
+                (direction * (inner_radius + length)).extend(0.0),
+            );
+        }
+
+        self.set_config(config);
+        self
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Step between ticks for a range of the given span, rounded to 1, 2 or 5 times a power of 10.
+///
+/// Empty, negative and non-finite spans have no ticks and return a step of zero.
+pub fn nice_step(span: f32, count: usize) -> f32 {
+    if !span.is_finite() || span <= 0.0 {
+        return 0.0;
+    }
+    let raw = span / count.max(1) as f32;
+    let magnitude = 10f32.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+    let nice = if normalized < 1.5 {
+        1.0
+    } else if normalized < 3.0 {
+        2.0
+    } else if normalized < 7.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Values between min and max, inclusive, that are multiples of a nice step.
+///
+/// Far from zero the step may be smaller than the precision of f32, ticks that round to the same value are only returned once.
+pub fn nice_ticks(min: f32, max: f32, count: usize) -> impl Iterator<Item = f32> {
+    let step = nice_step(max - min, count) as f64;
+    // Step from the first tick in floating point as tick indices far from zero overflow integers
+    let (first, max) = match step > 0.0 {
+        true => ((min as f64 / step).ceil() * step, max as f64 + step * 1e-6),
+        false => (0.0, -1.0),
+    };
+    let mut previous = None;
+    (0..)
+        .map(move |i| first + i as f64 * step)
+        .take_while(move |&value| value <= max)
+        .map(|value| value as f32)
+        .filter(move |&value| previous.replace(value) != Some(value))
+}
+
+/// Label of a tick with as many decimals as the step between ticks needs.
+pub fn tick_label(value: f32, step: f32) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    // Avoid labelling ticks at zero as -0 due to rounding
+    let value = if value.abs() < step * 1e-3 {
+        0.0
+    } else {
+        value
+    };
+    format!("{value:.decimals$}")
+}
+
+/// Style of axes drawn with [`AxesPainter`].
+#[derive(Clone, Debug)]
+pub struct AxesStyle {
+    pub axis_color: Color,
+    /// Thickness of axes and ticks, interpreted using the painter's thickness type.
+    pub axis_thickness: f32,
+    /// Length of ticks pointing out of the rect in the painter's local units.
+    pub tick_length: f32,
+    /// Approximate number of ticks along each axis, rounded to a step of 1, 2 or 5 times a power of 10.
+    pub ticks: usize,
+    /// Color of gridlines across the rect at each tick, gridlines are skipped if unset.
+    pub grid_color: Option<Color>,
+    /// Thickness of gridlines, interpreted using the painter's thickness type.
+    pub grid_thickness: f32,
+    /// Color of tick labels drawn by [`AxesPainter::axes_labeled`].
+    pub label_color: Color,
+    /// Font size of tick labels in the painter's local units.
+    pub label_size: f32,
+    /// Width reserved for labels left of the y axis, labels are right aligned within it.
+    pub label_width: f32,
+}
+
+impl Default for AxesStyle {
+    fn default() -> Self {
+        Self {
+            axis_color: Color::GRAY,
+            axis_thickness: 1.0,
+            tick_length: 4.0,
+            ticks: 5,
+            grid_color: Some(Color::rgba(0.5, 0.5, 0.5, 0.25)),
+            grid_thickness: 1.0,
+            label_color: Color::GRAY,
+            label_size: 12.0,
+            label_width: 48.0,
+        }
+    }
+}
+
+/// Ticks along both axes as (value, position along the axis in the painter's local space), along with their steps.
+pub(crate) struct AxisTicks {
+    pub x: Vec<(f32, f32)>,
+    pub y: Vec<(f32, f32)>,
+    /// Steps between ticks, only needed to format labels.
+    #[cfg(feature = "text")]
+    pub step: Vec2,
+}
+
+impl AxisTicks {
+    fn new(scale: &ChartScale, count: usize, x_ticks: bool) -> Self {
+        let data = scale.data;
+        let x = match x_ticks {
+            true => nice_ticks(data.min.x, data.max.x, count)
+                .map(|x| (x, scale.map(Vec2::new(x, data.min.y)).x))
+                .collect(),
+            false => Vec::new(),
+        };
+        let y = nice_ticks(data.min.y, data.max.y, count)
+            .map(|y| (y, scale.map(Vec2::new(data.min.x, y)).y))
+            .collect();
+        Self {
+            x,
+            y,
+            #[cfg(feature = "text")]
+            step: Vec2::new(
+                nice_step(data.width(), count),
+                nice_step(data.height(), count),
+            ),
+        }
+    }
+}
+
+/// Draws a line in the painter's local space.
+pub(crate) fn segment(painter: &mut ShapePainter, start: Vec2, end: Vec2) {
+    painter.line(start.extend(0.0), end.extend(0.0));
+}
+
+/// Draws the gridlines, axes and ticks of the scale, returning the ticks for labelling.
+///
+/// The x axis runs along the given y value in data space, with ticks along it only if `x_ticks` is set.
+pub(crate) fn draw_axes(
+    painter: &mut ShapePainter,
+    scale: &ChartScale,
+    style: &AxesStyle,
+    baseline: f32,
+    x_ticks: bool,
+) -> AxisTicks {
+    let ticks = AxisTicks::new(scale, style.ticks, x_ticks);
+    let screen = scale.screen;
+    let baseline = scale.map(Vec2::new(scale.data.min.x, baseline)).y;
+
+    painter.cap = Cap::None;
+    if let Some(grid_color) = style.grid_color {
+        painter.color = grid_color;
+        painter.thickness = style.grid_thickness;
+        for &(_, x) in &ticks.x {
+            segment(
+                painter,
+                Vec2::new(x, screen.min.y),
+                Vec2::new(x, screen.max.y),
+            );
+        }
+        for &(_, y) in &ticks.y {
+            segment(
+                painter,
+                Vec2::new(screen.min.x, y),
+                Vec2::new(screen.max.x, y),
+            );
+        }
+    }
+
+    painter.color = style.axis_color;
+    painter.thickness = style.axis_thickness;
+    for &(_, x) in &ticks.x {
+        segment(
+            painter,
+            Vec2::new(x, baseline - style.tick_length),
+            Vec2::new(x, baseline),
+        );
+    }
+    for &(_, y) in &ticks.y {
+        segment(
+            painter,
+            Vec2::new(screen.min.x - style.tick_length, y),
+            Vec2::new(screen.min.x, y),
+        );
+    }
+
+    painter.cap = Cap::Square;
+    segment(
+        painter,
+        Vec2::new(screen.min.x, baseline),
+        Vec2::new(screen.max.x, baseline),
+    );
+    segment(painter, screen.min, Vec2::new(screen.min.x, screen.max.y));
+    ticks
+}
+
+/// Extension trait for [`ShapePainter`] to draw the axes of a plot.
+///
+/// Axes run along the bottom and left edges of the screen rect in the painter's local space with y pointing up,
+/// with ticks and gridlines at multiples of a nice step within the data range. The painter's config is restored afterwards.
+/// Data can be drawn over the axes by mapping it with a [`ChartScale`] of the same rects.
+pub trait AxesPainter {
+    /// Draws gridlines, axis lines and tick marks for the data range mapped to the screen rect.
+    fn axes(&mut self, data: Rect, screen: Rect, style: &AxesStyle) -> &mut Self;
+
+    /// Draws axes as with [`AxesPainter::axes`] along with a label at each tick, requires the `text` feature.
+    ///
+    /// Labels of the x axis are centered below their ticks, labels of the y axis are right aligned left of their ticks.
+    #[cfg(feature = "text")]
+    fn axes_labeled(
+        &mut self,
+        fonts: &mut ShapeFonts,
+        data: Rect,
+        screen: Rect,
+        style: &AxesStyle,
+    ) -> &mut Self;
+}
+
+impl<'w, 's> AxesPainter for ShapePainter<'w, 's> {
+    fn axes(&mut self, data: Rect, screen: Rect, style: &AxesStyle) -> &mut Self {
+        if data.width() <= 0.0 || data.height() <= 0.0 {
+            return self;
+        }
+        let config = self.config().clone();
+        draw_axes(
+            self,
+            &ChartScale::new(data, screen),
+            style,
+            data.min.y,
+            true,
+        );
+        self.set_config(config);
+        self
+    }
+
+    #[cfg(feature = "text")]
+    fn axes_labeled(
+        &mut self,
+        fonts: &mut ShapeFonts,
+        data: Rect,
+        screen: Rect,
+        style: &AxesStyle,
+    ) -> &mut Self {
+        if data.width() <= 0.0 || data.height() <= 0.0 {
+            return self;
+        }
+        let config = self.config().clone();
+        let ticks = draw_axes(
+            self,
+            &ChartScale::new(data, screen),
+            style,
+            data.min.y,
+            true,
+        );
+
+        self.color = style.label_color;
+        self.hollow = false;
+        let line_height = style.label_size * 1.25;
+        let gap = style.tick_length * 2.0;
+        // Labels of the x axis are centered within the space between ticks
+        let spacing = match ticks.x.as_slice() {
+            [(_, a), (_, b), ..] => b - a,
+            _ => screen.width(),
+        };
+        let mut layout = RichTextLayout {
+            font_size: style.label_size,
+            line_height,
+            width: Some(spacing),
+            align: RichTextAlign::Center,
+        };
+        for &(value, x) in &ticks.x {
+            let label = tick_label(value, ticks.step.x);
+            self.transform = config.transform.mul_transform(Transform::from_xyz(
+                x - spacing / 2.0,
+                screen.min.y - gap,
+                0.0,
+            ));
+            self.text_rich(fonts, &[(label.as_str(), default())], &layout);
+        }
+
+        layout.width = Some(style.label_width);
+        layout.align = RichTextAlign::Right;
+        for &(value, y) in &ticks.y {
+            let label = tick_label(value, ticks.step.y);
+            self.transform = config.transform.mul_transform(Transform::from_xyz(
+                screen.min.x - gap - style.label_width,
+                y + line_height / 2.0,
+                0.0,
+            ));
+            self.text_rich(fonts, &[(label.as_str(), default())], &layout);
+        }
+
+        self.set_config(config);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_are_multiples_of_step() {
+        let ticks: Vec<f32> = nice_ticks(-0.3, 1.2, 5).collect();
+        assert_eq!(ticks, [0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn ticks_far_from_zero() {
+        let (min, max) = (1e7, 1e7 + 1024.0);
+        let ticks: Vec<f32> = nice_ticks(min, max, 1000).collect();
+        assert_eq!(ticks.len(), 1025);
+        assert_eq!(ticks[0], min);
+        assert!(ticks.iter().all(|tick| (min..=max).contains(tick)));
+
+        // Tick indices around 1e10 don't fit in an i32, and f32s there are 1024 apart so ticks must not repeat
+        let (min, max) = (1e10, 1e10 + 1024.0);
+        let ticks: Vec<f32> = nice_ticks(min, max, 1000).collect();
+        assert_eq!(ticks, [min, max]);
+
+        // Ranges narrower than the precision of f32 have no ticks rather than looping forever
+        assert_eq!(nice_ticks(1e9, 1e9 + 1.0, 5).count(), 0);
+    }
+
+    #[test]
+    fn empty_ranges_have_no_ticks() {
+        assert_eq!(nice_step(0.0, 5), 0.0);
+        assert_eq!(nice_step(-1.0, 5), 0.0);
+        assert_eq!(nice_step(f32::NAN, 5), 0.0);
+        assert_eq!(nice_ticks(1.0, 1.0, 5).count(), 0);
+        assert_eq!(nice_ticks(2.0, 1.0, 5).count(), 0);
+        assert_eq!(nice_ticks(0.0, f32::INFINITY, 5).count(), 0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    axes::{draw_axes, segment},
+    prelude::*,
+};
+
+/// Style shared by the chart helpers of [`ChartPainter`].
+#[derive(Clone, Debug)]
+pub struct ChartStyle {
+    /// Colors given to each series, bar or slice in order, repeating once exhausted.
+    pub palette: Vec<Color>,
+    /// Style of the axes drawn under the chart, axes are skipped if unset and pie charts have none.
+    pub axes: Option<AxesStyle>,
+    /// Thickness of the lines of a line chart, interpreted using the painter's thickness type.
+    pub line_thickness: f32,
+    /// Radius of the points of a scatter plot in the painter's local units.
+    pub point_radius: f32,
+    /// Gap between bars as a fraction of the space given to each bar.
+    pub bar_gap: f32,
+    /// Radius of the hole in a pie chart as a fraction of it's radius, values above 0.0 draw a donut chart.
+    pub pie_hole: f32,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        Self {
+            palette: vec![
+                Color::rgb(0.27, 0.51, 0.71),
+                Color::rgb(0.96, 0.55, 0.2),
+                Color::rgb(0.36, 0.71, 0.36),
+                Color::rgb(0.84, 0.3, 0.3),
+                Color::rgb(0.58, 0.44, 0.74),
+                Color::rgb(0.55, 0.4, 0.33),
+            ],
+            axes: Some(AxesStyle {
+                grid_color: None,
+                ..default()
+            }),
+            line_thickness: 2.0,
+            point_radius: 3.0,
+            bar_gap: 0.2,
+            pie_hole: 0.0,
+        }
+    }
+}
+
+impl ChartStyle {
+    /// Color for the series, bar or slice at the given index.
+    pub fn color(&self, index: usize) -> Color {
+        if self.palette.is_empty() {
+            return Color::WHITE;
+        }
+        self.palette[index % self.palette.len()]
+    }
+}
+
+/// Mapping from a rect in data space to a rect in the painter's local space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChartScale {
+    pub data: Rect,
+    pub screen: Rect,
+}
+
+impl ChartScale {
+    pub fn new(data: Rect, screen: Rect) -> Self {
+        Self { data, screen }
+    }
+
+    /// Scale fitting the bounds of the given points to the screen rect, empty ranges are padded so that they can be mapped.
+    pub fn fit(points: impl IntoIterator<Item = Vec2>, screen: Rect) -> Self {
+        let mut points = points.into_iter();
+        let first = points.next().unwrap_or(Vec2::ZERO);
+        let mut data = points.fold(Rect::from_corners(first, first), |data, point| {
+            data.union_point(point)
+        });
+        if data.width() <= 0.0 {
+            data.min.x -= 0.5;
+            data.max.x += 0.5;
+        }
+        if data.height() <= 0.0 {
+            data.min.y -= 0.5;
+            data.max.y += 0.5;
+        }
+        Self { data, screen }
+    }
+
+    /// Maps a point in data space to the painter's local space.
+    pub fn map(&self, point: Vec2) -> Vec2 {
+        self.screen.min + (point - self.data.min) / self.data.size() * self.screen.size()
+    }
+}
+
+/// Draws the chart's axes if enabled, with the x axis along zero if it's in range, otherwise along the bottom of the rect.
+fn chart_axes(painter: &mut ShapePainter, scale: &ChartScale, x_ticks: bool, style: &ChartStyle) {
+    let Some(axes) = &style.axes else {
+        return;
+    };
+    let baseline = match scale.data.min.y <= 0.0 && scale.data.max.y >= 0.0 {
+        true => 0.0,
+        false => scale.data.min.y,
+    };
+    draw_axes(painter, scale, axes, baseline, x_ticks);
+}
+
+/// Extension trait for [`ShapePainter`] to draw charts from slices of data.
+///
+/// Charts are drawn within a rect in the painter's local space with y pointing up, data is scaled to fit the rect
+/// with [`ChartScale::fit`]. The painter's config is restored once each chart is drawn.
+pub trait ChartPainter {
+    /// Draws each series of points as a line, points are connected in the order given.
+    fn line_chart(&mut self, rect: Rect, series: &[&[Vec2]], style: &ChartStyle) -> &mut Self;
+
+    /// Draws a bar for each value rising from zero, values may be negative.
+    fn bar_chart(&mut self, rect: Rect, values: &[f32], style: &ChartStyle) -> &mut Self;
+
+    /// Draws a slice for each value proportional to their total, clockwise from the top of the largest circle in the rect.
+    ///
+    /// Values that are zero or negative are skipped.
+    fn pie_chart(&mut self, rect: Rect, values: &[f32], style: &ChartStyle) -> &mut Self;
+
+    /// Draws a circle at each point.
+    fn scatter(&mut self, rect: Rect, points: &[Vec2], style: &ChartStyle) -> &mut Self;
+}
+
+impl<'w, 's> ChartPainter for ShapePainter<'w, 's> {
+    fn line_chart(&mut self, rect: Rect, series: &[&[Vec2]], style: &ChartStyle) -> &mut Self {
+        let config = self.config().clone();
+        let scale = ChartScale::fit(
+            series.iter().flat_map(|points| points.iter().copied()),
+            rect,
+        );
+        chart_axes(self, &scale, true, style);
+
+        self.thickness = style.line_thickness;
+        self.cap = Cap::Round;
+        for (i, points) in series.iter().enumerate() {
+            self.color = style.color(i);
+            for pair in points.windows(2) {
+                segment(self, scale.map(pair[0]), scale.map(pair[1]));
+            }
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn bar_chart(&mut self, rect: Rect, values: &[f32], style: &ChartStyle) -> &mut Self {
+        if values.is_empty() {
+            return self;
+        }
+        let config = self.config().clone();
+        // Bars rise from zero, so zero is always in range
+        let x_range = [Vec2::ZERO, Vec2::new(values.len() as f32, 0.0)];
+        let points = values.iter().map(|value| Vec2::new(0.0, *value));
+        let scale = ChartScale::fit(x_range.into_iter().chain(points), rect);
+
+        self.hollow = false;
+        self.corner_radii = Vec4::ZERO;
+        for (i, value) in values.iter().enumerate() {
+            let slot = Vec2::new(i as f32, 0.0);
+            let bottom_left = scale.map(slot + Vec2::new(style.bar_gap / 2.0, 0.0));
+            let top_right = scale.map(slot + Vec2::new(1.0 - style.bar_gap / 2.0, *value));
+            let bar = Rect::from_corners(bottom_left, top_right);
+            self.color = style.color(i);
+            self.transform = config
+                .transform
+                .mul_transform(Transform::from_translation(bar.center().extend(0.0)));
+            self.rect(bar.size());
+        }
+        self.transform = config.transform;
+
+        chart_axes(self, &scale, false, style);
+        self.set_config(config);
+        self
+    }
+
+    fn pie_chart(&mut self, rect: Rect, values: &[f32], style: &ChartStyle) -> &mut Self {
+        let total: f32 = values.iter().filter(|value| **value > 0.0).sum();
+        if total <= 0.0 {
+            return self;
+        }
+        let config = self.config().clone();
+        let radius = rect.width().min(rect.height()) / 2.0;
+        self.transform = config
+            .transform
+            .mul_transform(Transform::from_translation(rect.center().extend(0.0)));
+        self.cap = Cap::None;
+        if style.pie_hole > 0.0 {
+            self.hollow = true;
+            self.thickness = radius * (1.0 - style.pie_hole);
+            self.thickness_type = ThicknessType::World;
+        } else {
+            self.hollow = false;
+        }
+
+        let mut start_angle = 0.0;
+        for (i, value) in values.iter().enumerate() {
+            if *value <= 0.0 {
+                continue;
+            }
+            let end_angle = start_angle + value / total * TAU;
+            self.color = style.color(i);
+            self.arc(radius, start_angle, end_angle);
+            start_angle = end_angle;
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn scatter(&mut self, rect: Rect, points: &[Vec2], style: &ChartStyle) -> &mut Self {
+        let config = self.config().clone();
+        let scale = ChartScale::fit(points.iter().copied(), rect);
+        chart_axes(self, &scale, true, style);
+
+        self.hollow = false;
+        self.color = style.color(0);
+        for point in points {
+            self.transform = config
+                .transform
+                .mul_transform(Transform::from_translation(scale.map(*point).extend(0.0)));
+            self.circle(style.point_radius);
+        }
+
+        self.set_config(config);
+        self
+    }
+}
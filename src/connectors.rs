@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// How a [`Connector`] is routed between it's endpoints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectorRoute {
+    /// S-curve leaving and entering horizontally, made of two quadratic beziers.
+    #[default]
+    Curve,
+    /// Horizontal, vertical and horizontal lines meeting halfway between the endpoints.
+    Orthogonal,
+}
+
+/// Style of a connector drawn with [`ConnectorPainter::connector`].
+#[derive(Clone, Debug)]
+pub struct ConnectorStyle {
+    pub route: ConnectorRoute,
+    pub color: Color,
+    pub thickness: f32,
+    /// Color used while the cursor is over the connector.
+    pub hover_color: Color,
+    /// Thickness used while the cursor is over the connector.
+    pub hover_thickness: f32,
+    /// Length of the arrowhead at the end of the connector in the painter's local units, 0.0 disables it.
+    pub arrow_size: f32,
+    /// Cursor position in the painter's local space, used to draw the connector as hovered.
+    pub cursor: Option<Vec2>,
+}
+
+impl Default for ConnectorStyle {
+    fn default() -> Self {
+        Self {
+            route: default(),
+            color: Color::GRAY,
+            thickness: 2.0,
+            hover_color: Color::WHITE,
+            hover_thickness: 4.0,
+            arrow_size: 8.0,
+            cursor: None,
+        }
+    }
+}
+
+/// Segment of the path of a [`Connector`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectorSegment {
+    Line {
+        start: Vec2,
+        end: Vec2,
+    },
+    QuadBezier {
+        start: Vec2,
+        control: Vec2,
+        end: Vec2,
+    },
+}
+
+/// Path of a connector between two points, as drawn by [`ConnectorPainter::connector`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Connector {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub route: ConnectorRoute,
+}
+
+impl Connector {
+    pub fn new(start: Vec2, end: Vec2, route: ConnectorRoute) -> Self {
+        Self { start, end, route }
+    }
+
+    /// Connector from the middle of the right edge of `from` to the middle of the left edge of `to`.
+    pub fn between(from: Rect, to: Rect, route: ConnectorRoute) -> Self {
+        Self::new(
+            Vec2::new(from.max.x, from.center().y),
+            Vec2::new(to.min.x, to.center().y),
+            route,
+        )
+    }
+
+    /// Direction the connector enters it's end point along.
+    pub fn end_direction(&self) -> Vec2 {
+        match self.route {
+            ConnectorRoute::Curve => Vec2::X,
+            ConnectorRoute::Orthogonal => match self.end.x >= self.start.x {
+                true => Vec2::X,
+                false => Vec2::NEG_X,
+            },
+        }
+    }
+
+    /// Segments making up the connector's path.
+    pub fn segments(&self) -> Vec<ConnectorSegment> {
+        let (start, end) = (self.start, self.end);
+        let middle = (start + end) / 2.0;
+        match self.route {
+            ConnectorRoute::Curve => {
+                // Controls mirrored about the middle keep the two halves tangent where they meet
+                let offset = Vec2::new(((end.x - start.x).abs() / 2.0).max(20.0), 0.0);
+                vec![
+                    ConnectorSegment::QuadBezier {
+                        start,
+                        control: start + offset,
+                        end: middle,
+                    },
+                    ConnectorSegment::QuadBezier {
+                        start: middle,
+                        control: end - offset,
+                        end,
+                    },
+                ]
+            }
+            ConnectorRoute::Orthogonal => {
+                let a = Vec2::new(middle.x, start.y);
+                let b = Vec2::new(middle.x, end.y);
+                vec![
+                    ConnectorSegment::Line { start, end: a },
+                    ConnectorSegment::Line { start: a, end: b },
+                    ConnectorSegment::Line { start: b, end },
+                ]
+            }
+        }
+    }
+
+    /// Whether the point is within a stroke of the given thickness along the connector's path, in the painter's local space.
+    ///
+    /// Uses the hit tests of [`Line`] and [`QuadBezier`] with round caps, without the arrowhead.
+    pub fn contains_point(&self, point: Vec2, thickness: f32) -> bool {
+        let config = ShapeConfig {
+            thickness,
+            thickness_type: ThicknessType::World,
+            cap: Cap::Round,
+            ..ShapeConfig::default_2d()
+        };
+        let tf = GlobalTransform::IDENTITY;
+        self.segments().into_iter().any(|segment| match segment {
+            ConnectorSegment::Line { start, end } => {
+                Line::new(&config, start.extend(0.0), end.extend(0.0)).contains_point_2d(&tf, point)
+            }
+            ConnectorSegment::QuadBezier {
+                start,
+                control,
+                end,
+            } => QuadBezier::new(
+                &config,
+                start.extend(0.0),
+                end.extend(0.0),
+                control.extend(0.0),
+            )
+            .contains_point_2d(&tf, point),
+        })
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to draw connectors between boxes in node editor style UIs.
+pub trait ConnectorPainter {
+    /// Draws a connector from the right edge of `from` to the left edge of `to`, with an arrowhead at `to`.
+    ///
+    /// Boxes are in the painter's local space, the connector is drawn with the hover color and thickness while
+    /// the style's cursor is within the hover thickness of it. The painter's config is restored afterwards.
+    fn connector(&mut self, from: Rect, to: Rect, style: &ConnectorStyle) -> &mut Self;
+}
+
+impl<'w, 's> ConnectorPainter for ShapePainter<'w, 's> {
+    fn connector(&mut self, from: Rect, to: Rect, style: &ConnectorStyle) -> &mut Self {
+        let config = self.config().clone();
+        let mut connector = Connector::between(from, to, style.route);
+        let hovered = style.cursor.map_or(false, |cursor| {
+            connector.contains_point(cursor, style.hover_thickness)
+        });
+        let (color, thickness) = match hovered {
+            true => (style.hover_color, style.hover_thickness),
+            false => (style.color, style.thickness),
+        };
+        self.color = color;
+
+        if style.arrow_size > 0.0 {
+            // Shorten the path so that it's cap doesn't poke out of the arrowhead's tip
+            let direction = connector.end_direction();
+            let tip = connector.end;
+            connector.end -= direction * style.arrow_size;
+            let side = direction.perp() * style.arrow_size / 2.0;
+            self.hollow = false;
+            self.triangle(tip, connector.end + side, connector.end - side);
+        }
+
+        self.thickness = thickness;
+        self.cap = Cap::Round;
+        for segment in connector.segments() {
+            match segment {
+                ConnectorSegment::Line { start, end } => {
+                    self.line(start.extend(0.0), end.extend(0.0));
+                }
+                ConnectorSegment::QuadBezier {
+                    start,
+                    control,
+                    end,
+                } => {
+                    self.quad_bezier(start.extend(0.0), control.extend(0.0), end.extend(0.0));
+                }
+            }
+        }
+
+        self.set_config(config);
+        self
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Style of the navigation overlays drawn with [`NavDebugPainter`].
+#[derive(Clone, Debug)]
+pub struct NavDebugStyle {
+    /// Color of navmesh polygon edges, their fill uses the same color scaled by `mesh_fill_alpha`.
+    pub mesh_color: Color,
+    /// Alpha of navmesh polygon fills relative to their edges, 0.0 disables fills.
+    pub mesh_fill_alpha: f32,
+    pub path_color: Color,
+    /// Radius of the point drawn at each waypoint of a path in the painter's local units, 0.0 disables them.
+    pub waypoint_radius: f32,
+    /// Length of the arrows pointing along each segment of a path in the painter's local units, 0.0 disables them.
+    pub arrow_size: f32,
+    /// Distance between arrows along a path in the painter's local units, segments shorter than this get a single arrow.
+    pub arrow_spacing: f32,
+    pub agent_color: Color,
+    /// Thickness of edges, paths and agent outlines, interpreted using the painter's thickness type.
+    pub thickness: f32,
+}
+
+impl Default for NavDebugStyle {
+    fn default() -> Self {
+        Self {
+            mesh_color: Color::rgb(0.2, 0.6, 1.0),
+            mesh_fill_alpha: 0.2,
+            path_color: Color::YELLOW,
+            waypoint_radius: 3.0,
+            arrow_size: 8.0,
+            arrow_spacing: 64.0,
+            agent_color: Color::ORANGE_RED,
+            thickness: 1.5,
+        }
+    }
+}
+
+/// Fills a convex polygon as a fan of triangles, anti-aliasing only the polygon's outer edges.
+fn fill_polygon(painter: &mut ShapePainter, points: &[Vec2]) {
+    if points.len() < 3 {
+        return;
+    }
+    let last = points.len() - 2;
+    for i in 1..=last {
+        let mut edges = Triangle::EDGE_BC;
+        if i == 1 {
+            edges |= Triangle::EDGE_AB;
+        }
+        if i == last {
+            edges |= Triangle::EDGE_CA;
+        }
+        let data = TriangleData::with_edges(
+            painter.config(),
+            [points[0], points[i], points[i + 1]],
+            edges,
+        );
+        painter.send(data);
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to draw navigation data for debugging AI.
+///
+/// Data is given as plain slices in the painter's local space, so any navmesh or pathfinding crate can be visualized
+/// without conversion. The painter's config is restored once each overlay is drawn.
+pub trait NavDebugPainter {
+    /// Fills each polygon of a navmesh and outlines it's edges, polygons are expected to be convex as in most navmeshes.
+    fn navmesh(&mut self, polygons: &[&[Vec2]], style: &NavDebugStyle) -> &mut Self;
+
+    /// Draws a path through the points in order with arrows pointing along it and a point at each waypoint.
+    fn nav_path(&mut self, points: &[Vec2], style: &NavDebugStyle) -> &mut Self;
+
+    /// Outlines a disc for each agent as (position, radius).
+    fn agent_radii(&mut self, agents: &[(Vec2, f32)], style: &NavDebugStyle) -> &mut Self;
+}
+
+impl<'w, 's> NavDebugPainter for ShapePainter<'w, 's> {
+    fn navmesh(&mut self, polygons: &[&[Vec2]], style: &NavDebugStyle) -> &mut Self {
+        let config = self.config().clone();
+
+        if style.mesh_fill_alpha > 0.0 {
+            self.hollow = false;
+            self.color = style.mesh_color;
+            self.color
+                .set_a(style.mesh_color.a() * style.mesh_fill_alpha);
+            for polygon in polygons {
+                fill_polygon(self, polygon);
+            }
+        }
+
+        self.color = style.mesh_color;
+        self.thickness = style.thickness;
+        self.cap = Cap::Round;
+        for polygon in polygons {
+            for (i, start) in polygon.iter().enumerate() {
+                let end = polygon[(i + 1) % polygon.len()];
+                self.line(start.extend(0.0), end.extend(0.0));
+            }
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn nav_path(&mut self, points: &[Vec2], style: &NavDebugStyle) -> &mut Self {
+        let config = self.config().clone();
+        self.color = style.path_color;
+        self.thickness = style.thickness;
+        self.cap = Cap::Round;
+        for pair in points.windows(2) {
+            self.line(pair[0].extend(0.0), pair[1].extend(0.0));
+        }
+
+        self.hollow = false;
+        if style.arrow_size > 0.0 {
+            for pair in points.windows(2) {
+                let delta = pair[1] - pair[0];
+                let length = delta.length();
+                if length <= style.arrow_size {
+                    continue;
+                }
+                let direction = delta / length;
+                let side = direction.perp() * style.arrow_size / 2.0;
+                // Arrows are spread evenly along the segment, centered on each part
+                let count = (length / style.arrow_spacing.max(style.arrow_size))
+                    .floor()
+                    .max(1.0);
+                for i in 0..count as usize {
+                    let center = pair[0] + delta * (i as f32 + 0.5) / count;
+                    let tip = center + direction * style.arrow_size / 2.0;
+                    let base = center - direction * style.arrow_size / 2.0;
+                    self.triangle(tip, base + side, base - side);
+                }
+            }
+        }
+
+        if style.waypoint_radius > 0.0 {
+            for point in points {
+                self.transform = config
+                    .transform
+                    .mul_transform(Transform::from_translation(point.extend(0.0)));
+                self.circle(style.waypoint_radius);
+            }
+        }
+
+        self.set_config(config);
+        self
+    }
+
+    fn agent_radii(&mut self, agents: &[(Vec2, f32)], style: &NavDebugStyle) -> &mut Self {
+        let config = self.config().clone();
+        self.hollow = true;
+        self.color = style.agent_color;
+        self.thickness = style.thickness;
+        for (position, radius) in agents {
+            self.transform = config
+                .transform
+                .mul_transform(Transform::from_translation(position.extend(0.0)));
+            self.circle(*radius);
+        }
+
+        self.set_config(config);
+        self
+    }
+}
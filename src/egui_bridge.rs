@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+use egui::{
+    epaint::{
+        CircleShape, ClippedShape, CubicBezierShape, PathShape, QuadraticBezierShape, RectShape,
+    },
+    Color32, Pos2, Shape, Stroke,
+};
+
+use crate::prelude::*;
+
+/// Converts egui's premultiplied colors to a bevy color.
+fn color(color: Color32) -> Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    Color::rgba_u8(r, g, b, a)
+}
+
+fn point(pos: Pos2) -> Vec2 {
+    Vec2::new(pos.x, pos.y)
+}
+
+/// Config for a shape centered on the given point relative to the painter's transform.
+fn centered(config: &ShapeConfig, center: Vec2) -> ShapeConfig {
+    let mut config = config.clone();
+    config.transform = config
+        .transform
+        .mul_transform(Transform::from_translation(center.extend(0.0)));
+    config
+}
+
+/// Extension trait for [`ShapePainter`] to draw [`egui`] shapes, requires the `egui` feature.
+///
+/// Shapes are drawn in the UI pass, where egui's points map to logical pixels, relative to the painter's transform.
+/// This allows tooling UIs built with egui and game overlays drawn with the painter to share one vector style,
+/// any shape built with egui's constructors such as `Shape::circle_filled` or collected from an `egui::Painter` can be drawn.
+/// Strokes are centered on the outline as in egui, with path segments joined by round caps.
+/// Text, paint callbacks and mesh textures are skipped.
+pub trait EguiShapePainter {
+    fn egui_shape(&mut self, shape: &Shape) -> &mut Self;
+
+    /// Draws each shape clipped to it's clip rect, such as the shapes output by an egui context at the end of a frame.
+    fn egui_clipped_shapes<'a>(
+        &mut self,
+        shapes: impl IntoIterator<Item = &'a ClippedShape>,
+    ) -> &mut Self;
+}
+
+impl<'w, 's> EguiShapePainter for ShapePainter<'w, 's> {
+    fn egui_shape(&mut self, shape: &Shape) -> &mut Self {
+        let mut config = self.config().clone();
+        config.pass = ShapePass::Ui;
+        config.thickness_type = ThicknessType::Pixels;
+        draw_shape(self, &config, shape);
+        self
+    }
+
+    fn egui_clipped_shapes<'a>(
+        &mut self,
+        shapes: impl IntoIterator<Item = &'a ClippedShape>,
+    ) -> &mut Self {
+        let mut config = self.config().clone();
+        config.pass = ShapePass::Ui;
+        config.thickness_type = ThicknessType::Pixels;
+        for ClippedShape(clip, shape) in shapes {
+            config.clip = Some(Rect::new(clip.min.x, clip.min.y, clip.max.x, clip.max.y));
+            draw_shape(self, &config, shape);
+        }
+        self
+    }
+}
+
+fn draw_shape(painter: &mut ShapePainter, config: &ShapeConfig, shape: &Shape) {
+    match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                draw_shape(painter, config, shape);
+            }
+        }
+        Shape::Circle(CircleShape {
+            center,
+            radius,
+            fill,
+            stroke,
+        }) => {
+            let mut config = centered(config, point(*center));
+            if *fill != Color32::TRANSPARENT {
+                config.color = color(*fill);
+                config.hollow = false;
+                painter.send_with_config(&config, DiscData::circle(&config, *radius));
+            }
+            if !stroke.is_empty() {
+                config.color = color(stroke.color);
+                config.hollow = true;
+                config.thickness = stroke.width;
+                let radius = radius + stroke.width / 2.0;
+                painter.send_with_config(&config, DiscData::circle(&config, radius));
+            }
+        }
+        Shape::LineSegment { points, stroke } => {
+            if !stroke.is_empty() {
+                let mut config = config.clone();
+                config.color = color(stroke.color);
+                config.thickness = stroke.width;
+                config.cap = Cap::None;
+                let (start, end) = (point(points[0]), point(points[1]));
+                painter.send_with_config(
+                    &config,
+                    LineData::new(&config, start.extend(0.0), end.extend(0.0)),
+                );
+            }
+        }
+        Shape::Rect(RectShape {
+            rect,
+            rounding,
+            fill,
+            stroke,
+            ..
+        }) => {
+            let mut config = centered(config, point(rect.center()));
+            let size = Vec2::new(rect.width(), rect.height());
+            // Corner radii are ordered top right, top left, bottom left, bottom right in UI coordinates
+            let radii = Vec4::new(rounding.ne, rounding.nw, rounding.sw, rounding.se);
+            if *fill != Color32::TRANSPARENT {
+                config.color = color(*fill);
+                config.hollow = false;
+                config.corner_radii = radii;
+                painter.send_with_config(&config, RectData::new(&config, size));
+            }
+            if !stroke.is_empty() {
+                config.color = color(stroke.color);
+                config.hollow = true;
+                config.thickness = stroke.width;
+                // Grow the rect so that the stroke is centered on the outline, sharp corners stay sharp
+                config.corner_radii = Vec4::select(
+                    radii.cmpgt(Vec4::ZERO),
+                    radii + stroke.width / 2.0,
+                    Vec4::ZERO,
+                );
+                painter.send_with_config(&config, RectData::new(&config, size + stroke.width));
+            }
+        }
+        Shape::Path(PathShape {
+            points,
+            closed,
+            fill,
+            stroke,
+        }) => {
+            let points: Vec<_> = points.iter().copied().map(point).collect();
+            draw_polygon(painter, config, &points, *closed, *fill, *stroke);
+        }
+        Shape::QuadraticBezier(bezier) => {
+            let QuadraticBezierShape {
+                points,
+                closed,
+                fill,
+                stroke,
+            } = bezier;
+            if *closed && *fill != Color32::TRANSPARENT {
+                let outline: Vec<_> = bezier.flatten(None).into_iter().map(point).collect();
+                draw_polygon(painter, config, &outline, true, *fill, Stroke::NONE);
+            }
+            if !stroke.is_empty() {
+                let mut config = config.clone();
+                config.color = color(stroke.color);
+                config.thickness = stroke.width;
+                config.cap = Cap::Round;
+                let [start, control, end] = points.map(|pos| point(pos).extend(0.0));
+                painter
+                    .send_with_config(&config, QuadBezierData::new(&config, start, control, end));
+                if *closed {
+                    painter.send_with_config(&config, LineData::new(&config, end, start));
+                }
+            }
+        }
+        Shape::CubicBezier(bezier) => {
+            let CubicBezierShape {
+                closed,
+                fill,
+                stroke,
+                ..
+            } = bezier;
+            let outline: Vec<_> = bezier.flatten(None).into_iter().map(point).collect();
+            draw_polygon(painter, config, &outline, *closed, *fill, *stroke);
+        }
+        Shape::Mesh(mesh) => {
+            // Meshes are already feathered by egui so their edges are not anti-aliased again
+            let mut config = config.clone();
+            for indices in mesh.indices.chunks_exact(3) {
+                let vertices = [0, 1, 2].map(|i| &mesh.vertices[indices[i] as usize]);
+                config.color = color(vertices[0].color);
+                let points = vertices.map(|vertex| point(vertex.pos));
+                painter.send_with_config(&config, TriangleData::with_edges(&config, points, 0));
+            }
+        }
+        Shape::Noop | Shape::Text(_) | Shape::Callback(_) => {}
+    }
+}
+
+/// Draws a convex polygon as a fan of triangles and it's outline as a series of round capped lines.
+fn draw_polygon(
+    painter: &mut ShapePainter,
+    config: &ShapeConfig,
+    points: &[Vec2],
+    closed: bool,
+    fill: Color32,
+    stroke: Stroke,
+) {
+    let mut config = config.clone();
+    if closed && fill != Color32::TRANSPARENT && points.len() >= 3 {
+        config.color = color(fill);
+        let last = points.len() - 2;
+        for i in 1..=last {
+            // Only the edges on the outline of the polygon are anti-aliased
+            let mut edges = Triangle::EDGE_BC;
+            if i == 1 {
+                edges |= Triangle::EDGE_AB;
+            }
+            if i == last {
+                edges |= Triangle::EDGE_CA;
+            }
+            let triangle = [points[0], points[i], points[i + 1]];
+            painter.send_with_config(&config, TriangleData::with_edges(&config, triangle, edges));
+        }
+    }
+
+    if !stroke.is_empty() && points.len() >= 2 {
+        config.color = color(stroke.color);
+        config.thickness = stroke.width;
+        config.cap = Cap::Round;
+        let closing = closed.then_some([points[points.len() - 1], points[0]]);
+        for [start, end] in points
+            .windows(2)
+            .map(|segment| [segment[0], segment[1]])
+            .chain(closing)
+        {
+            painter.send_with_config(
+                &config,
+                LineData::new(&config, start.extend(0.0), end.extend(0.0)),
+            );
+        }
+    }
+}
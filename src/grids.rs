@@ -0,0 +1,223 @@
+use std::f32::consts::FRAC_PI_6;
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Rectangular grid of cells with it's origin at the bottom left corner and rows going up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SquareGrid {
+    pub origin: Vec2,
+    /// Size of each cell.
+    pub cell: Vec2,
+    /// Number of columns and rows.
+    pub dims: UVec2,
+}
+
+impl SquareGrid {
+    pub fn new(origin: Vec2, cell: Vec2, dims: UVec2) -> Self {
+        Self { origin, cell, dims }
+    }
+
+    /// Whether the cell is within the grid's dimensions.
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(IVec2::ZERO).all() && cell.as_uvec2().cmplt(self.dims).all()
+    }
+
+    /// Center of the given cell.
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * self.cell
+    }
+
+    /// Cell containing the given point, whether or not it is within the grid's dimensions.
+    pub fn cell_at(&self, point: Vec2) -> IVec2 {
+        ((point - self.origin) / self.cell).floor().as_ivec2()
+    }
+
+    /// Cell containing the given point if it's within the grid.
+    pub fn pick(&self, point: Vec2) -> Option<IVec2> {
+        let cell = self.cell_at(point);
+        self.contains(cell).then_some(cell)
+    }
+}
+
+/// Orientation of the cells of a [`HexGrid`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// Cells have a vertex at the top, rows are staggered with odd rows shifted right.
+    #[default]
+    PointyTop,
+    /// Cells have an edge at the top, columns are staggered with odd columns shifted up.
+    FlatTop,
+}
+
+/// Grid of hexagonal cells with it's origin at the center of the first cell.
+///
+/// Cells are addressed either with axial coordinates, where the second axis is 60 degrees from the first and
+/// neighbours are easy to compute, or with offset coordinates of a column and row within the grid's dimensions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexGrid {
+    pub origin: Vec2,
+    /// Distance from the center of each cell to it's vertices.
+    pub radius: f32,
+    /// Number of columns and rows.
+    pub dims: UVec2,
+    pub orientation: HexOrientation,
+}
+
+impl HexGrid {
+    pub fn new(origin: Vec2, radius: f32, dims: UVec2, orientation: HexOrientation) -> Self {
+        Self {
+            origin,
+            radius,
+            dims,
+            orientation,
+        }
+    }
+
+    /// Axial directions to the six neighbours of a cell.
+    pub const NEIGHBOURS: [IVec2; 6] = [
+        IVec2::new(1, 0),
+        IVec2::new(1, -1),
+        IVec2::new(0, -1),
+        IVec2::new(-1, 0),
+        IVec2::new(-1, 1),
+        IVec2::new(0, 1),
+    ];
+
+    /// Center of the cell with the given axial coordinates.
+    pub fn axial_to_world(&self, hex: IVec2) -> Vec2 {
+        let hex = hex.as_vec2();
+        let offset = match self.orientation {
+            HexOrientation::PointyTop => Vec2::new(SQRT_3 * (hex.x + hex.y / 2.0), 1.5 * hex.y),
+            HexOrientation::FlatTop => Vec2::new(1.5 * hex.x, SQRT_3 * (hex.y + hex.x / 2.0)),
+        };
+        self.origin + offset * self.radius
+    }
+
+    /// Axial coordinates of the cell containing the given point.
+    pub fn world_to_axial(&self, point: Vec2) -> IVec2 {
+        let p = (point - self.origin) / self.radius;
+        let hex = match self.orientation {
+            HexOrientation::PointyTop => Vec2::new(SQRT_3 / 3.0 * p.x - p.y / 3.0, 2.0 / 3.0 * p.y),
+            HexOrientation::FlatTop => Vec2::new(2.0 / 3.0 * p.x, SQRT_3 / 3.0 * p.y - p.x / 3.0),
+        };
+        round_axial(hex)
+    }
+
+    /// Converts offset coordinates of a column and row to axial coordinates.
+    pub fn offset_to_axial(&self, cell: IVec2) -> IVec2 {
+        match self.orientation {
+            HexOrientation::PointyTop => IVec2::new(cell.x - (cell.y - (cell.y & 1)) / 2, cell.y),
+            HexOrientation::FlatTop => IVec2::new(cell.x, cell.y - (cell.x - (cell.x & 1)) / 2),
+        }
+    }
+
+    /// Converts axial coordinates to offset coordinates of a column and row.
+    pub fn axial_to_offset(&self, hex: IVec2) -> IVec2 {
+        match self.orientation {
+            HexOrientation::PointyTop => IVec2::new(hex.x + (hex.y - (hex.y & 1)) / 2, hex.y),
+            HexOrientation::FlatTop => IVec2::new(hex.x, hex.y + (hex.x - (hex.x & 1)) / 2),
+        }
+    }
+
+    /// Number of steps between two cells in axial coordinates.
+    pub fn distance(a: IVec2, b: IVec2) -> i32 {
+        let d = a - b;
+        (d.x.abs() + d.y.abs() + (d.x + d.y).abs()) / 2
+    }
+
+    /// Whether the cell in offset coordinates is within the grid's dimensions.
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(IVec2::ZERO).all() && cell.as_uvec2().cmplt(self.dims).all()
+    }
+
+    /// Center of the cell in offset coordinates.
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        self.axial_to_world(self.offset_to_axial(cell))
+    }
+
+    /// Cell in offset coordinates containing the given point if it's within the grid.
+    pub fn pick(&self, point: Vec2) -> Option<IVec2> {
+        let cell = self.axial_to_offset(self.world_to_axial(point));
+        self.contains(cell).then_some(cell)
+    }
+}
+
+/// Rounds fractional axial coordinates to the nearest cell, using the implicit third cube coordinate.
+fn round_axial(hex: Vec2) -> IVec2 {
+    let cube = hex.extend(-hex.x - hex.y);
+    let rounded = cube.round();
+    let diff = (rounded - cube).abs();
+    if diff.x > diff.y && diff.x > diff.z {
+        IVec2::new((-rounded.y - rounded.z) as i32, rounded.y as i32)
+    } else if diff.y > diff.z {
+        IVec2::new(rounded.x as i32, (-rounded.x - rounded.z) as i32)
+    } else {
+        IVec2::new(rounded.x as i32, rounded.y as i32)
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to draw grid overlays, see [`SquareGrid`] and [`HexGrid`] for picking cells.
+///
+/// Grids are drawn in the painter's local space with it's color and thickness, the painter's config is restored afterwards.
+pub trait GridPainter {
+    /// Draws the lines of a grid of `dims` cells of size `cell`, with it's bottom left corner at `origin`.
+    fn square_grid(&mut self, origin: Vec2, cell: Vec2, dims: UVec2) -> &mut Self;
+
+    /// Draws the outline of each cell of a hex grid, with the center of the first cell at `origin`.
+    fn hex_grid(
+        &mut self,
+        origin: Vec2,
+        radius: f32,
+        dims: UVec2,
+        orientation: HexOrientation,
+    ) -> &mut Self;
+}
+
+impl<'w, 's> GridPainter for ShapePainter<'w, 's> {
+    fn square_grid(&mut self, origin: Vec2, cell: Vec2, dims: UVec2) -> &mut Self {
+        let config = self.config().clone();
+        let size = dims.as_vec2() * cell;
+        for x in 0..=dims.x {
+            let start = origin + Vec2::new(x as f32 * cell.x, 0.0);
+            self.line(start.extend(0.0), (start + Vec2::Y * size.y).extend(0.0));
+        }
+        for y in 0..=dims.y {
+            let start = origin + Vec2::new(0.0, y as f32 * cell.y);
+            self.line(start.extend(0.0), (start + Vec2::X * size.x).extend(0.0));
+        }
+        self.set_config(config);
+        self
+    }
+
+    fn hex_grid(
+        &mut self,
+        origin: Vec2,
+        radius: f32,
+        dims: UVec2,
+        orientation: HexOrientation,
+    ) -> &mut Self {
+        let config = self.config().clone();
+        let grid = HexGrid::new(origin, radius, dims, orientation);
+        // Hexagons are drawn with a vertex at the top
+        let rotation = match orientation {
+            HexOrientation::PointyTop => Quat::IDENTITY,
+            HexOrientation::FlatTop => Quat::from_rotation_z(FRAC_PI_6),
+        };
+        self.hollow = true;
+        for row in 0..dims.y as i32 {
+            for column in 0..dims.x as i32 {
+                let center = grid.cell_center(IVec2::new(column, row));
+                self.transform = config.transform.mul_transform(
+                    Transform::from_translation(center.extend(0.0)).with_rotation(rotation),
+                );
+                self.ngon(6.0, radius);
+            }
+        }
+        self.set_config(config);
+        self
+    }
+}
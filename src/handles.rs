@@ -0,0 +1,256 @@
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, view::RenderLayers},
+    window::{PrimaryWindow, WindowRef},
+};
+
+use crate::{prelude::*, shapes::intersect_plane};
+
+/// Component that makes a retained shape a draggable handle, such as a control point of a bezier curve.
+///
+/// Handles are tested against the cursor with [`HitTestShape::raycast`] when the left mouse button is pressed
+/// and dragged across the plane facing the camera through the point that was grabbed,
+/// sending a [`HandleDragEvent`] as the drag starts, moves and ends.
+#[derive(Component, Clone, Debug)]
+pub struct ShapeHandle {
+    /// Whether the handle's transform follows the cursor while dragged, otherwise only events are sent.
+    pub follow_cursor: bool,
+}
+
+impl Default for ShapeHandle {
+    fn default() -> Self {
+        Self {
+            follow_cursor: true,
+        }
+    }
+}
+
+/// Stage of a drag reported by a [`HandleDragEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragPhase {
+    Start,
+    Drag,
+    End,
+}
+
+/// Event sent as a [`ShapeHandle`] is dragged.
+#[derive(Clone, Copy, Debug)]
+pub struct HandleDragEvent {
+    pub handle: Entity,
+    pub phase: DragPhase,
+    /// World position of the handle's origin following the cursor.
+    pub position: Vec3,
+    /// Movement of the position since the previous event.
+    pub delta: Vec3,
+}
+
+/// Handle that is currently being dragged.
+#[derive(Clone, Copy, Debug)]
+struct ActiveDrag {
+    handle: Entity,
+    camera: Entity,
+    normal: Vec3,
+    /// Offset from the grabbed point to the handle's origin.
+    offset: Vec3,
+    position: Vec3,
+}
+
+/// Resource tracking the [`ShapeHandle`] under the cursor and the handle being dragged.
+#[derive(Resource, Default)]
+pub struct ShapeHandleDrag {
+    /// Closest handle hit this frame as (handle, camera, distance, point).
+    candidate: Option<(Entity, Entity, f32, Vec3)>,
+    active: Option<ActiveDrag>,
+}
+
+impl ShapeHandleDrag {
+    /// The handle currently being dragged.
+    pub fn dragged(&self) -> Option<Entity> {
+        self.active.map(|drag| drag.handle)
+    }
+}
+
+/// Systems that update [`ShapeHandle`]s.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShapeHandleSet {
+    /// Tests handles of each shape type against the cursor.
+    Pick,
+    /// Starts, moves and ends drags, sending [`HandleDragEvent`]s.
+    Drag,
+}
+
+/// Whether the camera is active and draws to the primary window.
+pub(crate) fn is_window_camera(camera: &Camera) -> bool {
+    camera.is_active && matches!(camera.target, RenderTarget::Window(WindowRef::Primary))
+}
+
+/// Finds the closest handle of the given shape type under the cursor when the left mouse button is pressed.
+pub fn pick_shape_handles<T: HitTestShape + Component>(
+    mut drag: ResMut<ShapeHandleDrag>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform, Option<&RenderLayers>), Without<Canvas>>,
+    handles: Query<
+        (
+            Entity,
+            &T,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&RenderLayers>,
+        ),
+        With<ShapeHandle>,
+    >,
+) {
+    if drag.active.is_some() || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (camera_entity, camera, camera_tf, camera_layers) in &cameras {
+        if !is_window_camera(camera) {
+            continue;
+        }
+        let Some(ray) = camera.viewport_to_world(camera_tf, cursor) else {
+            continue;
+        };
+        for (entity, shape, tf, visibility, layers) in &handles {
+            if !visibility.is_visible()
+                || !camera_layers
+                    .copied()
+                    .unwrap_or_default()
+                    .intersects(&layers.copied().unwrap_or_default())
+            {
+                continue;
+            }
+            let Some(hit) = shape.raycast(tf, ray, camera, camera_tf) else {
+                continue;
+            };
+            if drag
+                .candidate
+                .map_or(true, |(_, _, distance, _)| hit.distance < distance)
+            {
+                drag.candidate = Some((entity, camera_entity, hit.distance, hit.point));
+            }
+        }
+    }
+}
+
+/// Starts dragging the picked handle and moves it with the cursor until the left mouse button is released.
+pub fn drag_shape_handles(
+    mut drag: ResMut<ShapeHandleDrag>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut handles: Query<(
+        &ShapeHandle,
+        &GlobalTransform,
+        &mut Transform,
+        Option<&Parent>,
+    )>,
+    parents: Query<&GlobalTransform>,
+    mut events: EventWriter<HandleDragEvent>,
+) {
+    if let Some((handle, camera, _, point)) = drag.candidate.take() {
+        if let (Ok((_, camera_tf)), Ok((_, tf, _, _))) = (cameras.get(camera), handles.get(handle))
+        {
+            let position = tf.translation();
+            drag.active = Some(ActiveDrag {
+                handle,
+                camera,
+                normal: camera_tf.back(),
+                offset: position - point,
+                position,
+            });
+            events.send(HandleDragEvent {
+                handle,
+                phase: DragPhase::Start,
+                position,
+                delta: Vec3::ZERO,
+            });
+        }
+    }
+
+    let Some(mut active) = drag.active else {
+        return;
+    };
+    if !mouse.pressed(MouseButton::Left) || handles.get(active.handle).is_err() {
+        drag.active = None;
+        events.send(HandleDragEvent {
+            handle: active.handle,
+            phase: DragPhase::End,
+            position: active.position,
+            delta: Vec3::ZERO,
+        });
+        return;
+    }
+
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let Ok((camera, camera_tf)) = cameras.get(active.camera) else {
+        return;
+    };
+    let Some(ray) = camera.viewport_to_world(camera_tf, cursor) else {
+        return;
+    };
+    let grabbed = active.position - active.offset;
+    let Some(distance) = intersect_plane(ray, grabbed, active.normal) else {
+        return;
+    };
+
+    let position = ray.get_point(distance) + active.offset;
+    let delta = position - active.position;
+    if delta == Vec3::ZERO {
+        return;
+    }
+    active.position = position;
+    drag.active = Some(active);
+
+    let (handle, _, mut transform, parent) = handles.get_mut(active.handle).unwrap();
+    if handle.follow_cursor {
+        // Handles may be children of the shape they control so the position is moved into the parent's space
+        transform.translation = match parent.and_then(|parent| parents.get(parent.get()).ok()) {
+            Some(parent_tf) => parent_tf.affine().inverse().transform_point3(position),
+            None => position,
+        };
+    }
+    events.send(HandleDragEvent {
+        handle: active.handle,
+        phase: DragPhase::Drag,
+        position,
+        delta,
+    });
+}
+
+/// Plugin that makes retained shapes with a [`ShapeHandle`] draggable with the mouse.
+pub struct ShapeHandlePlugin;
+
+impl Plugin for ShapeHandlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeHandleDrag>()
+            .add_event::<HandleDragEvent>()
+            .configure_set(ShapeHandleSet::Pick.before(ShapeHandleSet::Drag))
+            .add_systems(
+                (
+                    pick_shape_handles::<Line>,
+                    pick_shape_handles::<QuadBezier>,
+                    pick_shape_handles::<Disc>,
+                    pick_shape_handles::<Rectangle>,
+                    pick_shape_handles::<RegularPolygon>,
+                    pick_shape_handles::<ShapeMorph>,
+                )
+                    .in_set(ShapeHandleSet::Pick),
+            )
+            .add_system(drag_shape_handles.in_set(ShapeHandleSet::Drag));
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::{prelude::*, render::view::RenderLayers, window::PrimaryWindow};
+
+use crate::{handles::is_window_camera, prelude::*};
+
+/// Component that makes a retained shape interactive, keeping it's [`Interaction`] up to date with the cursor
+/// and applying a [`ShapeStyle`] for each state, such that shapes can be used as buttons.
+///
+/// Shapes are tested against the cursor with [`HitTestShape::raycast`], only the closest shape under the cursor is hovered.
+/// An [`Interaction`] is inserted if the entity doesn't have one, it is [`Interaction::Clicked`] from when the left mouse
+/// button is pressed over the shape until it is released.
+///
+/// Whenever the interaction changes the normal style is applied followed by the style of the new state,
+/// so the normal style should set every property that the hovered and clicked styles set.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ShapeInteractionStyle {
+    pub normal: ShapeStyle,
+    pub hovered: ShapeStyle,
+    pub clicked: ShapeStyle,
+}
+
+impl ShapeInteractionStyle {
+    /// Style that only changes the shape's color.
+    pub fn colors(normal: Color, hovered: Color, clicked: Color) -> Self {
+        Self {
+            normal: ShapeStyle {
+                color: Some(normal),
+                ..default()
+            },
+            hovered: ShapeStyle {
+                color: Some(hovered),
+                ..default()
+            },
+            clicked: ShapeStyle {
+                color: Some(clicked),
+                ..default()
+            },
+        }
+    }
+
+    /// Style applied for the given interaction on top of the normal style.
+    pub fn style(&self, interaction: Interaction) -> &ShapeStyle {
+        match interaction {
+            Interaction::Clicked => &self.clicked,
+            Interaction::Hovered => &self.hovered,
+            Interaction::None => &self.normal,
+        }
+    }
+}
+
+/// Resource tracking the interactive shape under the cursor and the shape being clicked.
+#[derive(Resource, Default)]
+pub struct ShapeInteractions {
+    /// Closest shape hit this frame as (shape, distance).
+    candidate: Option<(Entity, f32)>,
+    hovered: Option<Entity>,
+    clicked: Option<Entity>,
+}
+
+impl ShapeInteractions {
+    /// The shape under the cursor.
+    pub fn hovered(&self) -> Option<Entity> {
+        self.hovered
+    }
+
+    /// The shape the left mouse button was pressed over, while it is held.
+    pub fn clicked(&self) -> Option<Entity> {
+        self.clicked
+    }
+}
+
+/// Systems that update interactive shapes.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShapeInteractionSet {
+    /// Tests interactive shapes of each shape type against the cursor.
+    Pick,
+    /// Updates the [`Interaction`] of each interactive shape.
+    Update,
+    /// Applies the [`ShapeInteractionStyle`] of shapes whose interaction changed.
+    Style,
+}
+
+/// Finds the closest interactive shape of the given shape type under the cursor.
+pub fn pick_shape_interactions<T: HitTestShape + Component>(
+    mut interactions: ResMut<ShapeInteractions>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&RenderLayers>), Without<Canvas>>,
+    shapes: Query<
+        (
+            Entity,
+            &T,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&RenderLayers>,
+        ),
+        With<ShapeInteractionStyle>,
+    >,
+) {
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (camera, camera_tf, camera_layers) in &cameras {
+        if !is_window_camera(camera) {
+            continue;
+        }
+        let Some(ray) = camera.viewport_to_world(camera_tf, cursor) else {
+            continue;
+        };
+        for (entity, shape, tf, visibility, layers) in &shapes {
+            if !visibility.is_visible()
+                || !camera_layers
+                    .copied()
+                    .unwrap_or_default()
+                    .intersects(&layers.copied().unwrap_or_default())
+            {
+                continue;
+            }
+            let Some(hit) = shape.raycast(tf, ray, camera, camera_tf) else {
+                continue;
+            };
+            if interactions
+                .candidate
+                .map_or(true, |(_, distance)| hit.distance < distance)
+            {
+                interactions.candidate = Some((entity, hit.distance));
+            }
+        }
+    }
+}
+
+/// Updates the hovered and clicked shapes from the picked shape and the left mouse button, then the [`Interaction`] of each interactive shape.
+pub fn update_shape_interactions(
+    mut commands: Commands,
+    mut interactions: ResMut<ShapeInteractions>,
+    mouse: Res<Input<MouseButton>>,
+    mut shapes: Query<(Entity, Option<&mut Interaction>), With<ShapeInteractionStyle>>,
+) {
+    let hovered = interactions.candidate.take().map(|(entity, _)| entity);
+    interactions.hovered = hovered;
+    if mouse.just_pressed(MouseButton::Left) {
+        interactions.clicked = hovered;
+    } else if !mouse.pressed(MouseButton::Left) {
+        interactions.clicked = None;
+    }
+
+    for (entity, interaction) in &mut shapes {
+        let state = if interactions.clicked == Some(entity) {
+            Interaction::Clicked
+        } else if interactions.hovered == Some(entity) {
+            Interaction::Hovered
+        } else {
+            Interaction::None
+        };
+        match interaction {
+            Some(mut interaction) => {
+                // Avoid triggering change detection when the state is unchanged
+                if *interaction != state {
+                    *interaction = state;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+    }
+}
+
+/// Applies the [`ShapeInteractionStyle`] of shapes of the given type whose interaction or style changed.
+pub fn apply_interaction_styles<T: StyledShape>(
+    mut shapes: Query<
+        (&mut T, &ShapeInteractionStyle, &Interaction),
+        Or<(Changed<Interaction>, Changed<ShapeInteractionStyle>)>,
+    >,
+) {
+    for (mut shape, style, interaction) in &mut shapes {
+        shape.apply_style(&style.normal);
+        if *interaction != Interaction::None {
+            shape.apply_style(style.style(*interaction));
+        }
+    }
+}
+
+/// Plugin that styles retained shapes with a [`ShapeInteractionStyle`] as they are hovered and clicked with the mouse.
+pub struct ShapeInteractionPlugin;
+
+impl Plugin for ShapeInteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeInteractions>()
+            .configure_set(ShapeInteractionSet::Pick.before(ShapeInteractionSet::Update))
+            .configure_set(ShapeInteractionSet::Update.before(ShapeInteractionSet::Style))
+            .add_systems(
+                (
+                    pick_shape_interactions::<Line>,
+                    pick_shape_interactions::<QuadBezier>,
+                    pick_shape_interactions::<Disc>,
+                    pick_shape_interactions::<Rectangle>,
+                    pick_shape_interactions::<RegularPolygon>,
+                    pick_shape_interactions::<ShapeMorph>,
+                )
+                    .in_set(ShapeInteractionSet::Pick),
+            )
+            .add_system(update_shape_interactions.in_set(ShapeInteractionSet::Update))
+            .add_systems(
+                (
+                    apply_interaction_styles::<Line>,
+                    apply_interaction_styles::<QuadBezier>,
+                    apply_interaction_styles::<Disc>,
+                    apply_interaction_styles::<Rectangle>,
+                    apply_interaction_styles::<RegularPolygon>,
+                    apply_interaction_styles::<ShapeMorph>,
+                )
+                    .in_set(ShapeInteractionSet::Style),
+            );
+    }
+}
@@ -41,19 +41,131 @@ use shapes::*;
 
 /// Rendering specific traits and structs.
 pub mod render;
-use render::{Shape3dRenderPlugin, ShapeRenderPlugin, ShapeType3dPlugin, ShapeTypePlugin};
+use render::{
+    Shape3dRenderPlugin, ShapeAppExt, ShapeDepth, ShapeLayerCapturePlugin, ShapeLowSpec,
+    ShapeRenderPlugin, ShapeType3dPlugin,
+};
 
 /// Structs and components used by the [`ShapePainter`], [`ShapeCommands`] and [`Canvas`] APIs.
 pub mod painter;
 use painter::*;
 
+/// Components and systems for animating the properties of retained shapes.
+pub mod animation;
+use animation::ShapeAnimationPlugin;
+
+/// Picking backend for retained shapes using `bevy_mod_picking`.
+#[cfg(feature = "picking")]
+pub mod picking;
+
+/// Draggable handles for building editors on top of retained shapes.
+pub mod handles;
+
+/// Hover and click styling of retained shapes.
+pub mod interaction;
+
+/// Polygonized shape silhouettes for casting shadows with 2D lighting crates.
+pub mod occluders;
+
+/// Immediate mode visualizers of audio waveforms and spectrums.
+pub mod audio;
+
+/// Axes, ticks and gridlines for plots with nice tick steps.
+pub mod axes;
+
+/// Immediate mode helpers for drawing charts with the [`ShapePainter`].
+pub mod charts;
+
+/// Minimaps drawn to a [`Canvas`] with markers for registered entities.
+pub mod minimap;
+
+/// Helpers for drawing and hit testing connectors between boxes in node editor style UIs.
+pub mod connectors;
+
+/// Debug overlays for navmeshes, paths and agents drawn with the [`ShapePainter`].
+pub mod debug;
+
+/// Square and hex grid overlays along with conversions between cell coordinates and positions.
+pub mod grids;
+
+/// Shape prefab assets loaded from RON files.
+#[cfg(feature = "prefab")]
+pub mod prefab;
+
+/// Importer converting SVG files into shape prefabs.
+#[cfg(feature = "svg_import")]
+pub mod svg_import;
+
+/// Keyframed animations of shapes loaded from RON or JSON files.
+#[cfg(feature = "vector_animation")]
+pub mod vector_animation;
+
+/// Bridge drawing egui shapes with the [`ShapePainter`].
+#[cfg(feature = "egui")]
+pub mod egui_bridge;
+
+/// Backend drawing rapier's debug render with the [`ShapePainter`].
+#[cfg(feature = "rapier2d")]
+pub mod rapier_debug;
+
+/// Plugin drawing bevy_xpbd's debug render with the [`ShapePainter`].
+#[cfg(feature = "xpbd_2d")]
+pub mod xpbd_debug;
+
+/// Compatibility layer mirroring the API of `bevy_prototype_lyon`.
+#[cfg(feature = "lyon_compat")]
+pub mod lyon_compat;
+
 /// `use bevy_vector_shapes::prelude::*` to import commonly used items.
 pub mod prelude {
     pub use crate::painter::{
-        BuildShapeChildren, Canvas, CanvasCommands, CanvasConfig, CanvasMode, ShapeChildBuilder,
-        ShapeCommands, ShapeConfig, ShapeEntityCommands, ShapePainter, ShapeSpawner,
+        BuildShapeChildren, Canvas, CanvasBuilder, CanvasCommands, CanvasConfig, CanvasHandle,
+        CanvasMode, CanvasSizing, PaintedShape, PainterCommand, ShapeBatchCommands,
+        ShapeChildBuilder, ShapeCommandLog, ShapeCommands, ShapeConfig, ShapeEntityCommands,
+        ShapeGizmos, ShapeGizmosPlugin, ShapePainter, ShapePool, ShapeSpawner,
+    };
+    pub use crate::{
+        animation::{
+            AnimateColor, AnimateFrames, AnimateMorph, AnimateRadius, AnimateThickness,
+            AnimatedShape, Easing, ShapeAnimationAppExt, ShapeLifetime,
+        },
+        audio::{AudioPainter, AudioVisualizerStyle},
+        axes::{AxesPainter, AxesStyle},
+        charts::{ChartPainter, ChartScale, ChartStyle},
+        connectors::{Connector, ConnectorPainter, ConnectorRoute, ConnectorStyle},
+        debug::{NavDebugPainter, NavDebugStyle},
+        grids::{GridPainter, HexGrid, HexOrientation, SquareGrid},
+        handles::{HandleDragEvent, ShapeHandle, ShapeHandlePlugin},
+        interaction::{ShapeInteractionPlugin, ShapeInteractionStyle, ShapeInteractions},
+        minimap::{Minimap, MinimapMarker, MinimapMarkerShape, MinimapPlugin},
+        occluders::{OccluderShape, ShapeOccluder, ShapeOccluderOutline, ShapeOccluderPlugin},
+        render::{
+            create_texture_array, NoShapeRendering, ShapeAppExt, ShapeBufferPolicy,
+            ShapeCameraSettings, ShapeColorSpace, ShapeDebug, ShapeDepth, ShapeDithering,
+            ShapeGpuCulling, ShapeHook, ShapeImageArrays, ShapeLayerCaptured, ShapeLayerCaptures,
+            ShapeLowSpec, ShapePipelineKey, ShapePixelScale, ShapePrepass, ShapeSort2d,
+            ShapeSort3d, ShapeViewKey,
+        },
+        shapes::*,
+        BaseShapeConfig, Shape2dPlugin, ShapePlugin,
     };
-    pub use crate::{shapes::*, BaseShapeConfig, Shape2dPlugin, ShapePlugin};
+
+    #[cfg(feature = "egui")]
+    pub use crate::egui_bridge::EguiShapePainter;
+    #[cfg(feature = "picking")]
+    pub use crate::picking::ShapePickingPlugin;
+    #[cfg(feature = "prefab")]
+    pub use crate::prefab::{ShapePrefab, ShapePrefabPlugin, ShapePrefabRoot, ShapePrefabSpawner};
+    #[cfg(feature = "rapier2d")]
+    pub use crate::rapier_debug::ShapeDebugRenderBackend;
+    #[cfg(feature = "save_canvas")]
+    pub use crate::render::{CanvasSaved, SaveCanvas};
+    #[cfg(feature = "vector_animation")]
+    pub use crate::vector_animation::{
+        VectorAnimation, VectorAnimationPlayer, VectorAnimationPlugin, VectorAnimationSpawner,
+    };
+    #[cfg(feature = "xpbd_2d")]
+    pub use crate::xpbd_debug::{XpbdDebugConfig, XpbdDebugPlugin};
 }
 
 /// Resource that represents the default shape config to be used by [`ShapePainter`] and [`ShapeCommands`] APIs.
@@ -68,32 +180,72 @@ pub struct Shape2dPlugin {
     ///
     /// Available as a resource [`BaseShapeConfig`].
     pub base_config: ShapeConfig,
+    /// Whether to use simplified shaders for low end devices, see [`ShapeLowSpec`].
+    pub low_spec: bool,
 }
 
 impl Default for Shape2dPlugin {
     fn default() -> Self {
         Self {
             base_config: ShapeConfig::default_2d(),
+            low_spec: false,
         }
     }
 }
 
 impl Shape2dPlugin {
     pub fn new(base_config: ShapeConfig) -> Self {
-        Self { base_config }
+        Self {
+            base_config,
+            ..default()
+        }
+    }
+
+    /// Plugin configured for mobile and WebGL targets, see [`ShapeLowSpec`].
+    pub fn low_spec() -> Self {
+        Self {
+            low_spec: true,
+            ..default()
+        }
     }
 }
 
 impl Plugin for Shape2dPlugin {
     fn build(&self, app: &mut App) {
+        if self.low_spec {
+            app.insert_resource(ShapeLowSpec { enabled: true });
+        }
         app.insert_resource(BaseShapeConfig(self.base_config.clone()))
             .add_plugin(PainterPlugin)
+            .add_plugin(ShapeAnimationPlugin)
+            .add_plugin(ShapeStylePlugin)
+            .add_plugin(ShapeThemePlugin)
+            .add_plugin(ShapeMaterialAssetPlugin)
+            .add_plugin(ShapeLodPlugin)
+            .add_plugin(ShapeOpacityPlugin)
+            .add_plugin(LocalZOrderPlugin)
+            .add_plugin(ShapeShaderParamsPlugin)
+            .add_plugin(DynamicShapePlugin)
+            .add_plugin(ShapeSvgCapturePlugin)
+            .add_plugin(ShapeLayerCapturePlugin)
+            .add_plugin(BitmapTextPlugin)
             .add_plugin(ShapeRenderPlugin)
-            .add_plugin(ShapeTypePlugin::<Line>::default())
-            .add_plugin(ShapeType3dPlugin::<QuadBezier>::default())
-            .add_plugin(ShapeTypePlugin::<Disc>::default())
-            .add_plugin(ShapeTypePlugin::<Rectangle>::default())
-            .add_plugin(ShapeTypePlugin::<RegularPolygon>::default());
+            .add_shape_type::<Line>()
+            .add_shape_type::<QuadBezier>()
+            .add_shape_type::<Disc>()
+            .add_shape_type::<Rectangle>()
+            .add_shape_type::<RegularPolygon>()
+            .add_shape_type::<ShapeMorph>()
+            .add_shape_type::<Triangle>();
+
+        #[cfg(feature = "ui")]
+        app.add_plugin(ShapeNodePlugin);
+        #[cfg(feature = "lyon")]
+        app.add_plugin(TessellatedPathPlugin);
+        #[cfg(feature = "text")]
+        app.add_plugin(ShapeTextPlugin);
+        #[cfg(feature = "save_canvas")]
+        app.add_plugin(render::CanvasSavePlugin);
     }
 }
 
@@ -107,6 +259,12 @@ pub struct ShapePlugin {
     ///
     /// Useful if you want to add the 3d functionality when another plugin has already added the 2d plugin.
     pub exclude_2d: bool,
+    /// Whether the 3D depth buffer uses reverse-Z, as is the case for Bevy's core 3D pipeline.
+    ///
+    /// Available as a resource [`ShapeDepth`].
+    pub reverse_z: bool,
+    /// Whether to use simplified shaders for low end devices, see [`ShapeLowSpec`].
+    pub low_spec: bool,
 }
 
 impl ShapePlugin {
@@ -116,6 +274,14 @@ impl ShapePlugin {
             ..default()
         }
     }
+
+    /// Plugin configured for mobile and WebGL targets, see [`ShapeLowSpec`].
+    pub fn low_spec() -> Self {
+        Self {
+            low_spec: true,
+            ..default()
+        }
+    }
 }
 
 impl Default for ShapePlugin {
@@ -123,6 +289,8 @@ impl Default for ShapePlugin {
         Self {
             base_config: ShapeConfig::default_3d(),
             exclude_2d: false,
+            reverse_z: true,
+            low_spec: false,
         }
     }
 }
@@ -130,13 +298,23 @@ impl Default for ShapePlugin {
 impl Plugin for ShapePlugin {
     fn build(&self, app: &mut App) {
         if !self.exclude_2d {
-            app.add_plugin(Shape2dPlugin::new(self.base_config.clone()));
+            app.add_plugin(Shape2dPlugin {
+                base_config: self.base_config.clone(),
+                low_spec: self.low_spec,
+            });
+        } else if self.low_spec {
+            app.insert_resource(ShapeLowSpec { enabled: true });
         }
-        app.add_plugin(Shape3dRenderPlugin)
-            .add_plugin(ShapeType3dPlugin::<Line>::default())
-            .add_plugin(ShapeType3dPlugin::<QuadBezier>::default())
-            .add_plugin(ShapeType3dPlugin::<Disc>::default())
-            .add_plugin(ShapeType3dPlugin::<Rectangle>::default())
-            .add_plugin(ShapeType3dPlugin::<RegularPolygon>::default());
+        app.insert_resource(ShapeDepth {
+            reverse_z: self.reverse_z,
+        })
+        .add_plugin(Shape3dRenderPlugin)
+        .add_plugin(ShapeType3dPlugin::<Line>::default())
+        .add_plugin(ShapeType3dPlugin::<QuadBezier>::default())
+        .add_plugin(ShapeType3dPlugin::<Disc>::default())
+        .add_plugin(ShapeType3dPlugin::<Rectangle>::default())
+        .add_plugin(ShapeType3dPlugin::<RegularPolygon>::default())
+        .add_plugin(ShapeType3dPlugin::<ShapeMorph>::default())
+        .add_plugin(ShapeType3dPlugin::<Triangle>::default());
     }
 }
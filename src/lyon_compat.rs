@@ -0,0 +1,411 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use lyon::{
+    geom::{point, vector, Angle},
+    path::{Path as LyonPath, Winding},
+};
+
+use crate::prelude::*;
+
+/// Shapes mirroring those of `bevy_prototype_lyon`, added to a [`GeometryBuilder`] to build a [`Path`].
+pub mod shapes {
+    use bevy::prelude::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Circle {
+        pub radius: f32,
+        pub center: Vec2,
+    }
+
+    impl Default for Circle {
+        fn default() -> Self {
+            Self {
+                radius: 1.0,
+                center: Vec2::ZERO,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Ellipse {
+        pub radii: Vec2,
+        pub center: Vec2,
+    }
+
+    impl Default for Ellipse {
+        fn default() -> Self {
+            Self {
+                radii: Vec2::ONE,
+                center: Vec2::ZERO,
+            }
+        }
+    }
+
+    /// Point of a [`Rectangle`] placed at the origin.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum RectangleOrigin {
+        #[default]
+        Center,
+        BottomLeft,
+        BottomRight,
+        TopRight,
+        TopLeft,
+        /// Places the center of the rectangle at the given point.
+        CustomCenter(Vec2),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Rectangle {
+        pub extents: Vec2,
+        pub origin: RectangleOrigin,
+    }
+
+    impl Default for Rectangle {
+        fn default() -> Self {
+            Self {
+                extents: Vec2::ONE,
+                origin: default(),
+            }
+        }
+    }
+
+    /// Measurement determining the size of a [`RegularPolygon`].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum RegularPolygonFeature {
+        /// Distance from the center to a vertex.
+        Radius(f32),
+        /// Distance from the center to the middle of a side.
+        Apothem(f32),
+        SideLength(f32),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct RegularPolygon {
+        pub sides: usize,
+        pub center: Vec2,
+        pub feature: RegularPolygonFeature,
+    }
+
+    impl Default for RegularPolygon {
+        fn default() -> Self {
+            Self {
+                sides: 3,
+                center: Vec2::ZERO,
+                feature: RegularPolygonFeature::Radius(1.0),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Line(pub Vec2, pub Vec2);
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Polygon {
+        pub points: Vec<Vec2>,
+        pub closed: bool,
+    }
+}
+
+/// Primitive making up a [`Path`], mapped onto the built-in shapes where possible.
+#[derive(Clone)]
+enum Primitive {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Rect {
+        center: Vec2,
+        size: Vec2,
+    },
+    Ngon {
+        center: Vec2,
+        sides: f32,
+        radius: f32,
+    },
+    Line {
+        start: Vec2,
+        end: Vec2,
+    },
+    Path(LyonPath),
+}
+
+/// Trait implemented by shapes that can be added to a [`GeometryBuilder`].
+pub trait Geometry {
+    fn add_geometry(&self, builder: &mut GeometryBuilder);
+}
+
+impl Geometry for shapes::Circle {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        builder.0.push(Primitive::Circle {
+            center: self.center,
+            radius: self.radius,
+        });
+    }
+}
+
+impl Geometry for shapes::Ellipse {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        let mut path = LyonPath::builder();
+        path.add_ellipse(
+            point(self.center.x, self.center.y),
+            vector(self.radii.x, self.radii.y),
+            Angle::zero(),
+            Winding::Positive,
+        );
+        builder.add_lyon_path(path.build());
+    }
+}
+
+impl Geometry for shapes::Rectangle {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        let half = self.extents / 2.0;
+        let center = match self.origin {
+            shapes::RectangleOrigin::Center => Vec2::ZERO,
+            shapes::RectangleOrigin::BottomLeft => half,
+            shapes::RectangleOrigin::BottomRight => Vec2::new(-half.x, half.y),
+            shapes::RectangleOrigin::TopRight => -half,
+            shapes::RectangleOrigin::TopLeft => Vec2::new(half.x, -half.y),
+            shapes::RectangleOrigin::CustomCenter(center) => center,
+        };
+        builder.0.push(Primitive::Rect {
+            center,
+            size: self.extents,
+        });
+    }
+}
+
+impl Geometry for shapes::RegularPolygon {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        let sides = self.sides as f32;
+        let radius = match self.feature {
+            shapes::RegularPolygonFeature::Radius(radius) => radius,
+            shapes::RegularPolygonFeature::Apothem(apothem) => apothem / (PI / sides).cos(),
+            shapes::RegularPolygonFeature::SideLength(length) => {
+                length / (2.0 * (PI / sides).sin())
+            }
+        };
+        builder.0.push(Primitive::Ngon {
+            center: self.center,
+            sides,
+            radius,
+        });
+    }
+}
+
+impl Geometry for shapes::Line {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        builder.0.push(Primitive::Line {
+            start: self.0,
+            end: self.1,
+        });
+    }
+}
+
+impl Geometry for shapes::Polygon {
+    fn add_geometry(&self, builder: &mut GeometryBuilder) {
+        let Some((first, rest)) = self.points.split_first() else {
+            return;
+        };
+        let mut path = LyonPath::builder();
+        path.begin(point(first.x, first.y));
+        for p in rest {
+            path.line_to(point(p.x, p.y));
+        }
+        path.end(self.closed);
+        builder.add_lyon_path(path.build());
+    }
+}
+
+/// Builder combining any number of shapes into a single [`Path`], mirroring `bevy_prototype_lyon`'s builder.
+#[derive(Default)]
+pub struct GeometryBuilder(Vec<Primitive>);
+
+impl GeometryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, shape: &impl Geometry) -> Self {
+        shape.add_geometry(&mut self);
+        self
+    }
+
+    pub fn build(self) -> Path {
+        Path(self.0)
+    }
+
+    /// Adds an arbitrary lyon path, allowing [`Geometry`] to be implemented for custom shapes.
+    pub fn add_lyon_path(&mut self, path: LyonPath) {
+        self.0.push(Primitive::Path(path));
+    }
+
+    /// Builds a path containing only the given shape.
+    pub fn build_as(shape: &impl Geometry) -> Path {
+        Self::new().add(shape).build()
+    }
+}
+
+/// Component containing the shapes built by a [`GeometryBuilder`], drawn with it's entity's [`Fill`] and [`Stroke`].
+///
+/// Circles, rectangles, regular polygons and lines are drawn as the equivalent built-in shapes,
+/// ellipses and polygons are drawn as [`TessellatedPath`]s. Each is spawned as a child entity, respawned when the path,
+/// fill or stroke changes.
+#[derive(Component, Clone, Default)]
+pub struct Path(Vec<Primitive>);
+
+/// Component filling the shapes of a [`Path`].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Fill {
+    pub color: Color,
+}
+
+impl Fill {
+    pub fn color(color: impl Into<Color>) -> Self {
+        Self {
+            color: color.into(),
+        }
+    }
+}
+
+/// Component stroking the outlines of the shapes of a [`Path`], strokes are centered on the outline.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    pub color: Color,
+    /// Width of the stroke in world units.
+    pub line_width: f32,
+}
+
+impl Stroke {
+    pub fn new(color: impl Into<Color>, line_width: f32) -> Self {
+        Self {
+            color: color.into(),
+            line_width,
+        }
+    }
+
+    pub fn color(color: impl Into<Color>) -> Self {
+        Self::new(color, 1.0)
+    }
+}
+
+/// Bundle mirroring `bevy_prototype_lyon`'s `ShapeBundle`, insert a [`Fill`] and/or [`Stroke`] alongside it.
+#[derive(Bundle, Clone, Default)]
+pub struct ShapeBundle {
+    pub path: Path,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub computed_visibility: ComputedVisibility,
+}
+
+/// Marker for the child entities drawing a [`Path`].
+#[derive(Component)]
+struct PathShape;
+
+/// Spawns a child entity drawing the primitive with the given config, strokes are grown to be centered on the outline.
+fn spawn_primitive(
+    commands: &mut Commands,
+    config: &ShapeConfig,
+    primitive: &Primitive,
+    parent: Entity,
+) {
+    let mut config = config.clone();
+    let grow = if config.hollow { config.thickness } else { 0.0 };
+    let mut entity = match primitive {
+        Primitive::Circle { center, radius } => {
+            config.translate(center.extend(0.0));
+            let disc = Disc::circle(&config, radius + grow / 2.0);
+            commands.spawn(crate::shapes::ShapeBundle::new(&config, disc))
+        }
+        Primitive::Rect { center, size } => {
+            config.translate(center.extend(0.0));
+            let rect = Rectangle::new(&config, *size + grow);
+            commands.spawn(crate::shapes::ShapeBundle::new(&config, rect))
+        }
+        Primitive::Ngon {
+            center,
+            sides,
+            radius,
+        } => {
+            config.translate(center.extend(0.0));
+            // Built-in polygons start from a vertex below the center, rotate to start from the same vertex as lyon
+            config.rotate_z(PI / sides);
+            let radius = radius + grow / 2.0 / (PI / sides).cos();
+            let polygon = RegularPolygon::new(&config, *sides, radius);
+            commands.spawn(crate::shapes::ShapeBundle::new(&config, polygon))
+        }
+        Primitive::Line { start, end } => {
+            // Lines have no area to fill
+            if !config.hollow {
+                return;
+            }
+            config.cap = Cap::None;
+            let line = Line::new(&config, start.extend(0.0), end.extend(0.0));
+            commands.spawn(crate::shapes::ShapeBundle::new(&config, line))
+        }
+        Primitive::Path(path) => commands.spawn(TessellatedPathBundle::new(&config, path.clone())),
+    };
+    entity.insert(PathShape).set_parent(parent);
+}
+
+/// Respawns the shapes drawing each [`Path`] when it, or it's [`Fill`] or [`Stroke`], changes.
+pub fn spawn_path_shapes(
+    mut commands: Commands,
+    paths: Query<
+        (
+            Entity,
+            &Path,
+            Option<&Fill>,
+            Option<&Stroke>,
+            Option<&Children>,
+        ),
+        Or<(Changed<Path>, Changed<Fill>, Changed<Stroke>)>,
+    >,
+    shapes: Query<(), With<PathShape>>,
+) {
+    for (entity, path, fill, stroke, children) in &paths {
+        for &child in children.into_iter().flatten() {
+            if shapes.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        let mut config = ShapeConfig::default_2d();
+        if let Some(fill) = fill {
+            config.color = fill.color;
+            for primitive in &path.0 {
+                spawn_primitive(&mut commands, &config, primitive, entity);
+            }
+        }
+        if let Some(stroke) = stroke {
+            // Strokes are drawn just above fills
+            config.transform.translation.z = LocalZOrder::STEP;
+            config.color = stroke.color;
+            config.hollow = true;
+            config.thickness = stroke.line_width;
+            for primitive in &path.0 {
+                spawn_primitive(&mut commands, &config, primitive, entity);
+            }
+        }
+    }
+}
+
+/// Plugin that draws the [`Path`]s of the `bevy_prototype_lyon` compatibility layer, requires the `lyon_compat` feature.
+///
+/// Projects migrating from `bevy_prototype_lyon` can replace it's `ShapePlugin` with [`Shape2dPlugin`] and this plugin,
+/// and it's prelude with [`prelude`].
+pub struct LyonCompatPlugin;
+
+impl Plugin for LyonCompatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_path_shapes);
+    }
+}
+
+/// `use bevy_vector_shapes::lyon_compat::prelude::*` in place of `bevy_prototype_lyon`'s prelude.
+pub mod prelude {
+    pub use super::{
+        shapes, Fill, Geometry, GeometryBuilder, LyonCompatPlugin, Path, ShapeBundle, Stroke,
+    };
+}
@@ -0,0 +1,199 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::prelude::*;
+
+/// Component that turns a [`Canvas`] into a minimap, redrawn every frame with a marker for each [`MinimapMarker`].
+///
+/// The minimap is centered on the entity it follows, showing `extent` world units across the canvas.
+/// Markers keep their size in canvas pixels regardless of the extent, so the map can be zoomed by changing the extent alone.
+/// Canvases in [`CanvasMode::Persistent`] or [`CanvasMode::OnDemand`] are marked for redraw every frame.
+#[derive(Component, Clone, Debug)]
+pub struct Minimap {
+    /// Entity the minimap is centered on, the world origin is used if unset or the entity has no transform.
+    pub follow: Option<Entity>,
+    /// Size of the area of the world shown by the minimap in world units.
+    pub extent: Vec2,
+    /// Whether the minimap rotates with the followed entity such that it's local y axis always points up.
+    pub rotate: bool,
+    /// Color of a rect filling the canvas behind the markers.
+    pub background: Option<Color>,
+    center: Vec2,
+    rotation: f32,
+    scale: Vec2,
+}
+
+impl Minimap {
+    pub fn new(extent: Vec2) -> Self {
+        Self {
+            follow: None,
+            extent,
+            rotate: false,
+            background: None,
+            center: Vec2::ZERO,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+        }
+    }
+
+    /// Sets the entity the minimap is centered on.
+    pub fn following(mut self, entity: Entity) -> Self {
+        self.follow = Some(entity);
+        self
+    }
+
+    /// Center of the minimap in world space as of the last redraw.
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    /// Maps a position in world space to the minimap canvas, in pixels relative to it's center with y pointing up.
+    ///
+    /// Uses the center, rotation and canvas size as of the last redraw.
+    pub fn world_to_map(&self, position: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.rotation).rotate(position - self.center) * self.scale
+    }
+
+    /// Maps a position on the minimap canvas, as returned by [`Minimap::world_to_map`], back to world space.
+    pub fn map_to_world(&self, position: Vec2) -> Vec2 {
+        Vec2::from_angle(self.rotation).rotate(position / self.scale) + self.center
+    }
+}
+
+/// Shape drawn for a [`MinimapMarker`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MinimapMarkerShape {
+    #[default]
+    Circle,
+    Square,
+    /// Triangle pointing along the entity's local y axis, useful with [`MinimapMarker::rotate`].
+    Triangle,
+    Ring,
+}
+
+/// Component that registers an entity to be drawn as a marker on every [`Minimap`].
+#[derive(Component, Clone, Debug)]
+pub struct MinimapMarker {
+    pub shape: MinimapMarkerShape,
+    pub color: Color,
+    /// Size of the marker in canvas pixels.
+    pub size: f32,
+    /// Whether the marker rotates with the entity about it's z axis.
+    pub rotate: bool,
+}
+
+impl MinimapMarker {
+    pub fn new(shape: MinimapMarkerShape, color: Color, size: f32) -> Self {
+        Self {
+            shape,
+            color,
+            size,
+            rotate: false,
+        }
+    }
+
+    pub fn circle(color: Color, size: f32) -> Self {
+        Self::new(MinimapMarkerShape::Circle, color, size)
+    }
+
+    pub fn square(color: Color, size: f32) -> Self {
+        Self::new(MinimapMarkerShape::Square, color, size)
+    }
+
+    /// Triangle that rotates with the entity.
+    pub fn arrow(color: Color, size: f32) -> Self {
+        Self {
+            rotate: true,
+            ..Self::new(MinimapMarkerShape::Triangle, color, size)
+        }
+    }
+
+    pub fn ring(color: Color, size: f32) -> Self {
+        Self::new(MinimapMarkerShape::Ring, color, size)
+    }
+}
+
+/// Angle of the transform's local y axis about the z axis, measured counter-clockwise from world y.
+fn z_angle(transform: &GlobalTransform) -> f32 {
+    let up = transform.affine().transform_vector3(Vec3::Y).truncate();
+    Vec2::Y.angle_between(up)
+}
+
+/// Updates the mapping of each [`Minimap`] and draws it's background and markers.
+pub fn draw_minimaps(
+    mut painter: ShapePainter,
+    mut minimaps: Query<(Entity, &mut Minimap, &mut Canvas)>,
+    targets: Query<&GlobalTransform>,
+    markers: Query<(&MinimapMarker, &GlobalTransform, &ComputedVisibility)>,
+) {
+    for (entity, mut minimap, mut canvas) in &mut minimaps {
+        let target = minimap.follow.and_then(|entity| targets.get(entity).ok());
+        let size = Vec2::new(canvas.width as f32, canvas.height as f32);
+        minimap.center = target.map_or(Vec2::ZERO, |target| target.translation().truncate());
+        minimap.rotation = match (minimap.rotate, target) {
+            (true, Some(target)) => z_angle(target),
+            _ => 0.0,
+        };
+        minimap.scale = size / minimap.extent.max(Vec2::splat(f32::EPSILON));
+        canvas.redraw();
+
+        painter.reset();
+        painter.set_canvas(entity);
+        if let Some(background) = minimap.background {
+            painter.hollow = false;
+            painter.color = background;
+            painter.corner_radii = Vec4::ZERO;
+            painter.rect(size);
+        }
+
+        let bounds = Rect::from_center_size(Vec2::ZERO, size);
+        for (marker, transform, visibility) in &markers {
+            if !visibility.is_visible_in_hierarchy() {
+                continue;
+            }
+            let position = minimap.world_to_map(transform.translation().truncate());
+            if !bounds.inset(marker.size).contains(position) {
+                continue;
+            }
+            let rotation = match marker.rotate {
+                true => z_angle(transform) - minimap.rotation,
+                false => 0.0,
+            };
+
+            painter.transform = Transform::from_translation(position.extend(0.0))
+                .with_rotation(Quat::from_rotation_z(rotation));
+            painter.color = marker.color;
+            painter.thickness_type = ThicknessType::World;
+            painter.hollow = false;
+            let radius = marker.size / 2.0;
+            match marker.shape {
+                MinimapMarkerShape::Circle => painter.circle(radius),
+                MinimapMarkerShape::Square => painter.rect(Vec2::splat(marker.size)),
+                MinimapMarkerShape::Triangle => {
+                    // Triangles have a vertex along negative x
+                    painter.rotate_z(-FRAC_PI_2);
+                    painter.ngon(3.0, radius)
+                }
+                MinimapMarkerShape::Ring => {
+                    painter.hollow = true;
+                    painter.thickness = radius / 2.0;
+                    painter.circle(radius)
+                }
+            };
+        }
+    }
+}
+
+/// Plugin that draws each [`Minimap`], requires [`Shape2dPlugin`] or [`ShapePlugin`].
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            draw_minimaps
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
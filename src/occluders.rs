@@ -0,0 +1,426 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{prelude::*, shapes::clock_point};
+
+/// Component marking a retained shape as an occluder for 2D lighting.
+///
+/// The shape's silhouette is polygonized into a [`ShapeOccluderOutline`] in world space whenever the shape or it's transform changes,
+/// such that lighting crates can cast shadows from walls drawn with rectangles and polygons without duplicating their geometry.
+/// Curved edges are approximated by straight segments deviating at most `tolerance` world units from the true edge.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct ShapeOccluder {
+    pub tolerance: f32,
+}
+
+impl Default for ShapeOccluder {
+    fn default() -> Self {
+        Self { tolerance: 1.0 }
+    }
+}
+
+/// Outline of a [`ShapeOccluder`] in world space, inserted and kept up to date by [`ShapeOccluderPlugin`].
+///
+/// Points form a closed polygon wound counter-clockwise, the last point connects back to the first.
+/// Use [`Changed<ShapeOccluderOutline>`] to only update the occluders of a lighting crate when the outline moves.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ShapeOccluderOutline {
+    points: Vec<Vec2>,
+}
+
+impl ShapeOccluderOutline {
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Pairs of points forming each edge of the outline.
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points.iter().copied().zip(next.copied())
+    }
+
+    /// Signed distance from the point to the outline, negative inside.
+    ///
+    /// Exact for the polygonized outline, use [`HitTestShape`] to query the analytic shape instead.
+    pub fn signed_distance(&self, point: Vec2) -> f32 {
+        let mut distance = f32::MAX;
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let edge = b - a;
+            let t =
+                ((point - a).dot(edge) / edge.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+            distance = distance.min(point.distance(a + edge * t));
+            // Even-odd crossing test against a ray towards positive x
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * edge.x
+            {
+                inside = !inside;
+            }
+        }
+        if inside {
+            -distance
+        } else {
+            distance
+        }
+    }
+}
+
+/// Shapes that can be polygonized into an occluder outline.
+///
+/// Hollow shapes occlude their whole silhouette as a polygon can't contain holes, split hollow walls into separate shapes instead.
+/// Thicknesses are treated as world units regardless of their [`ThicknessType`].
+pub trait OccluderShape {
+    /// Outline of the shape in local space, curves deviate at most `tolerance` from the true edge.
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2>;
+}
+
+/// Number of segments approximating an arc such that no segment deviates more than `tolerance` from it.
+fn arc_segments(radius: f32, angle: f32, tolerance: f32) -> usize {
+    if radius <= tolerance {
+        return 1;
+    }
+    let step = 2.0 * (1.0 - tolerance.max(0.001) / radius).acos();
+    (angle.abs() / step).ceil().clamp(1.0, 256.0) as usize
+}
+
+/// Appends points along an arc measured clockwise from positive y as in [`clock_point`], including both ends.
+fn push_arc(
+    points: &mut Vec<Vec2>,
+    center: Vec2,
+    radius: f32,
+    start: f32,
+    end: f32,
+    tolerance: f32,
+) {
+    if radius <= 0.0 {
+        points.push(center);
+        return;
+    }
+    let segments = arc_segments(radius, end - start, tolerance);
+    for i in 0..=segments {
+        let angle = start + (end - start) * i as f32 / segments as f32;
+        points.push(center + clock_point(angle, radius));
+    }
+}
+
+impl OccluderShape for Rectangle {
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2> {
+        let half = self.size / 2.0;
+        let max_radius = half.x.min(half.y);
+        // Corner radii are ordered top right, top left, bottom left, bottom right in the shader's space, whose y is flipped
+        let corners = [
+            (Vec2::new(1.0, 1.0), self.corner_radii.w),
+            (Vec2::new(1.0, -1.0), self.corner_radii.x),
+            (Vec2::new(-1.0, -1.0), self.corner_radii.y),
+            (Vec2::new(-1.0, 1.0), self.corner_radii.z),
+        ];
+        let mut points = Vec::new();
+        for (i, (sign, radius)) in corners.into_iter().enumerate() {
+            let radius = radius.clamp(0.0, max_radius);
+            let start = i as f32 * FRAC_PI_2;
+            push_arc(
+                &mut points,
+                (half - radius) * sign,
+                radius,
+                start,
+                start + FRAC_PI_2,
+                tolerance,
+            );
+        }
+        points
+    }
+}
+
+impl OccluderShape for Disc {
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2> {
+        let mut points = Vec::new();
+        if !self.arc || self.end_angle - self.start_angle >= TAU {
+            let segments = arc_segments(self.radius, TAU, tolerance).max(3);
+            for i in 0..segments {
+                let angle = TAU * i as f32 / segments as f32;
+                points.push(clock_point(angle, self.radius));
+            }
+            return points;
+        }
+
+        let (start, end) = (self.start_angle, self.end_angle);
+        push_arc(&mut points, Vec2::ZERO, self.radius, start, end, tolerance);
+        let inner_radius = if self.hollow {
+            (self.radius - self.thickness).max(0.0)
+        } else {
+            0.0
+        };
+        push_arc(&mut points, Vec2::ZERO, inner_radius, end, start, tolerance);
+        points
+    }
+}
+
+impl OccluderShape for RegularPolygon {
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2> {
+        let sides = self.sides.round().max(3.0);
+        let central_angle = TAU / sides;
+        let apothem = (central_angle / 2.0).cos() * self.radius;
+        let rounding = self.roundness.clamp(0.0, apothem);
+        // Rounding shrinks the polygon by it's radius so that the rounded polygon keeps it's size
+        let inner_radius = (apothem - rounding) / (central_angle / 2.0).cos();
+
+        // Polygons point up in the shader's space, whose y is flipped, so the first edge is centered on negative y
+        let mut points = Vec::new();
+        for i in 0..sides as usize {
+            let angle = PI + (i as f32 + 0.5) * central_angle;
+            let vertex = clock_point(angle, inner_radius);
+            let half_turn = central_angle / 2.0;
+            push_arc(
+                &mut points,
+                vertex,
+                rounding,
+                angle - half_turn,
+                angle + half_turn,
+                tolerance,
+            );
+        }
+        points
+    }
+}
+
+impl OccluderShape for Line {
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2> {
+        let (start, end) = (self.start.truncate(), self.end.truncate());
+        let half_thickness = self.thickness / 2.0;
+        let direction = (end - start).try_normalize().unwrap_or(Vec2::X);
+        let angle = direction.x.atan2(direction.y);
+
+        let mut points = Vec::new();
+        match self.cap {
+            Cap::Round => {
+                push_arc(
+                    &mut points,
+                    end,
+                    half_thickness,
+                    angle - FRAC_PI_2,
+                    angle + FRAC_PI_2,
+                    tolerance,
+                );
+                push_arc(
+                    &mut points,
+                    start,
+                    half_thickness,
+                    angle + FRAC_PI_2,
+                    angle + 3.0 * FRAC_PI_2,
+                    tolerance,
+                );
+            }
+            Cap::None | Cap::Square => {
+                let extension = if self.cap == Cap::Square {
+                    direction * half_thickness
+                } else {
+                    Vec2::ZERO
+                };
+                let side = direction.perp() * half_thickness;
+                points.extend([
+                    end + extension - side,
+                    end + extension + side,
+                    start - extension + side,
+                    start - extension - side,
+                ]);
+            }
+        }
+        points
+    }
+}
+
+impl OccluderShape for Triangle {
+    fn occluder_outline(&self, _tolerance: f32) -> Vec<Vec2> {
+        self.points.to_vec()
+    }
+}
+
+impl OccluderShape for DynamicShape {
+    fn occluder_outline(&self, tolerance: f32) -> Vec<Vec2> {
+        match self {
+            DynamicShape::Line(shape) => shape.occluder_outline(tolerance),
+            DynamicShape::Disc(shape) => shape.occluder_outline(tolerance),
+            DynamicShape::Rectangle(shape) => shape.occluder_outline(tolerance),
+            DynamicShape::RegularPolygon(shape) => shape.occluder_outline(tolerance),
+            DynamicShape::Triangle(shape) => shape.occluder_outline(tolerance),
+            // Curves and morphs have no simple silhouette
+            DynamicShape::QuadBezier(_) | DynamicShape::Morph(_) => Vec::new(),
+        }
+    }
+}
+
+/// Outline of the shape drawn with the given transform in world space, wound counter-clockwise.
+fn world_outline(
+    shape: &impl OccluderShape,
+    occluder: &ShapeOccluder,
+    tf: &GlobalTransform,
+) -> Vec<Vec2> {
+    // Tolerance is given in world units so scale it into the shape's local space
+    let scale = tf.compute_transform().scale.truncate().abs().max_element();
+    let tolerance = occluder.tolerance / scale.max(f32::EPSILON);
+    let mut points: Vec<Vec2> = shape
+        .occluder_outline(tolerance)
+        .into_iter()
+        .map(|point| tf.transform_point(point.extend(0.0)).truncate())
+        .collect();
+
+    // Mirrored transforms and clockwise shapes flip the winding
+    let area: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum();
+    if area < 0.0 {
+        points.reverse();
+    }
+    points
+}
+
+/// Updates the [`ShapeOccluderOutline`] of each occluder of the given shape type whose shape, transform or tolerance changed.
+pub fn update_shape_occluders<T: OccluderShape + Component>(
+    mut commands: Commands,
+    mut shapes: Query<
+        (
+            Entity,
+            &T,
+            &ShapeOccluder,
+            &GlobalTransform,
+            Option<&mut ShapeOccluderOutline>,
+        ),
+        Or<(
+            Changed<T>,
+            Changed<ShapeOccluder>,
+            Changed<GlobalTransform>,
+            Without<ShapeOccluderOutline>,
+        )>,
+    >,
+) {
+    for (entity, shape, occluder, tf, outline) in &mut shapes {
+        let points = world_outline(shape, occluder, tf);
+        match outline {
+            Some(mut outline) => outline.points = points,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ShapeOccluderOutline { points });
+            }
+        }
+    }
+}
+
+/// Removes the outline of entities that are no longer occluders.
+pub fn remove_shape_occluder_outlines(
+    mut commands: Commands,
+    mut removed: RemovedComponents<ShapeOccluder>,
+) {
+    for entity in removed.iter() {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<ShapeOccluderOutline>();
+        }
+    }
+}
+
+/// Plugin that keeps the [`ShapeOccluderOutline`] of each [`ShapeOccluder`] up to date.
+///
+/// Outlines are updated in [`CoreSet::PostUpdate`] after transforms are propagated,
+/// systems converting them into occluders of a lighting crate should run after [`update_shape_occluders`].
+pub struct ShapeOccluderPlugin;
+
+impl Plugin for ShapeOccluderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                update_shape_occluders::<Line>,
+                update_shape_occluders::<Disc>,
+                update_shape_occluders::<Rectangle>,
+                update_shape_occluders::<RegularPolygon>,
+                update_shape_occluders::<Triangle>,
+                update_shape_occluders::<DynamicShape>,
+                remove_shape_occluder_outlines,
+            )
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the outline covers the same points as the hit test, away from the polygonized edge.
+    fn assert_matches_hit_test(shape: &(impl OccluderShape + HitTestShape), tf: GlobalTransform) {
+        let occluder = ShapeOccluder { tolerance: 0.001 };
+        let outline = ShapeOccluderOutline {
+            points: world_outline(shape, &occluder, &tf),
+        };
+        let center = tf.translation().truncate();
+        for x in -40..=40 {
+            for y in -40..=40 {
+                let point = center + Vec2::new(x as f32, y as f32) * 0.05;
+                let distance = outline.signed_distance(point);
+                if distance.abs() < 0.01 {
+                    continue;
+                }
+                assert_eq!(
+                    distance < 0.0,
+                    shape.contains_point_2d(&tf, point),
+                    "outline and hit test disagree at {point}"
+                );
+            }
+        }
+    }
+
+    fn transforms() -> [GlobalTransform; 2] {
+        [
+            GlobalTransform::IDENTITY,
+            GlobalTransform::from(
+                Transform::from_xyz(3.0, -2.0, 0.0).with_rotation(Quat::from_rotation_z(0.7)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn rectangle_corners_match_hit_test() {
+        let rect = Rectangle {
+            size: Vec2::new(1.6, 1.0),
+            corner_radii: Vec4::new(0.4, 0.0, 0.1, 0.25),
+            ..default()
+        };
+        for tf in transforms() {
+            assert_matches_hit_test(&rect, tf);
+        }
+    }
+
+    #[test]
+    fn disc_arc_matches_hit_test() {
+        let disc = Disc {
+            radius: 1.5,
+            arc: true,
+            start_angle: 0.3,
+            end_angle: 2.0,
+            ..default()
+        };
+        for tf in transforms() {
+            assert_matches_hit_test(&disc, tf);
+        }
+    }
+
+    #[test]
+    fn odd_sided_polygon_matches_hit_test() {
+        for sides in [3.0, 5.0] {
+            for roundness in [0.0, 0.2] {
+                let polygon = RegularPolygon {
+                    sides,
+                    radius: 1.5,
+                    roundness,
+                    ..default()
+                };
+                for tf in transforms() {
+                    assert_matches_hit_test(&polygon, tf);
+                }
+            }
+        }
+    }
+}
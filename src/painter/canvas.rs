@@ -1,11 +1,16 @@
 use bevy::{
+    asset::HandleId,
     core_pipeline::clear_color::ClearColorConfig,
     ecs::system::EntityCommands,
     prelude::*,
     render::{camera::RenderTarget, texture::ImageSampler, view::RenderLayers},
+    utils::HashMap,
+    window::PrimaryWindow,
 };
 use wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
 
+use crate::{prelude::*, render::ShapeComponent};
+
 /// Prepares the camera assosciated with each canvas.
 ///
 /// Replaces the image handle when the canvas is resized and applies [`CanvasMode`] behaviours.
@@ -50,8 +55,138 @@ pub fn update_canvases(
     })
 }
 
+/// Resizes canvases according to their [`CanvasSizing`].
+///
+/// Runs before [`update_canvases`] so that the camera is retargeted to the new image in the same frame.
+pub fn resize_canvases(
+    mut canvases: Query<&mut Canvas>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<&Camera, Without<Canvas>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for mut canvas in &mut canvases {
+        let size = match canvas.sizing {
+            CanvasSizing::Fixed => continue,
+            CanvasSizing::TrackWindow => windows
+                .get_single()
+                .ok()
+                .map(|window| UVec2::new(window.physical_width(), window.physical_height())),
+            CanvasSizing::TrackCamera(entity) => cameras
+                .get(entity)
+                .ok()
+                .and_then(|camera| camera.physical_viewport_size()),
+        };
+
+        let Some(size) = size else {
+            continue;
+        };
+
+        // Minimized windows report a size of zero which cannot be used for a texture
+        if size.x == 0 || size.y == 0 || (size.x == canvas.width && size.y == canvas.height) {
+            continue;
+        }
+
+        canvas.resize(images.as_mut(), size.x, size.y);
+    }
+}
+
+/// Marks canvases to be redrawn when a retained shape of the given type drawn to them changes or is removed.
+///
+/// Keeps [`CanvasMode::OnDemand`] canvases up to date without redrawing them every frame,
+/// shapes are considered changed when their component, transform, [`ShapeMaterial`] or [`Visibility`] changes.
+pub fn redraw_changed_canvases<T: ShapeComponent>(
+    shapes: Query<
+        (Entity, Option<&ShapeMaterial>),
+        (
+            With<T>,
+            Or<(
+                Changed<T>,
+                Changed<GlobalTransform>,
+                Changed<ShapeMaterial>,
+                Changed<Visibility>,
+            )>,
+        ),
+    >,
+    mut removed: RemovedComponents<T>,
+    mut canvases: Query<&mut Canvas>,
+    mut targets: Local<HashMap<Entity, Entity>>,
+) {
+    let mut redraw = |canvas: Entity| {
+        if let Ok(mut canvas) = canvases.get_mut(canvas) {
+            canvas.redraw();
+        }
+    };
+
+    for (entity, material) in &shapes {
+        let canvas = material.and_then(|material| material.canvas);
+        let previous = match canvas {
+            Some(canvas) => targets.insert(entity, canvas),
+            None => targets.remove(&entity),
+        };
+        // Shapes moved to another canvas must also be cleared from the old one
+        if let Some(previous) = previous.filter(|previous| Some(*previous) != canvas) {
+            redraw(previous);
+        }
+        if let Some(canvas) = canvas {
+            redraw(canvas);
+        }
+    }
+
+    for entity in removed.iter() {
+        if let Some(canvas) = targets.remove(&entity) {
+            redraw(canvas);
+        }
+    }
+}
+
+/// Releases the resources of despawned canvases.
+///
+/// Other cameras still rendering to the canvas' image are deactivated, the image itself is freed by [`Assets<Image>`]
+/// once the last handle to it is dropped.
+pub fn cleanup_canvases(
+    mut removed: RemovedComponents<Canvas>,
+    canvases: Query<(Entity, Ref<Canvas>)>,
+    mut cameras: Query<&mut Camera, Without<Canvas>>,
+    mut tracked: Local<HashMap<Entity, HandleId>>,
+) {
+    for (entity, canvas) in &canvases {
+        if canvas.is_changed() {
+            tracked.insert(entity, canvas.image.id());
+        }
+    }
+
+    for entity in removed.iter() {
+        // Canvases may have been removed and re-inserted on the same entity
+        if canvases.contains(entity) {
+            continue;
+        }
+        let Some(image) = tracked.remove(&entity) else {
+            continue;
+        };
+        for mut camera in &mut cameras {
+            if matches!(&camera.target, RenderTarget::Image(target) if target.id() == image) {
+                camera.is_active = false;
+            }
+        }
+    }
+}
+
+/// Enum that determines how a canvas' target texture is sized.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanvasSizing {
+    /// Keep the size given at creation or by calls to [`Canvas::resize`].
+    #[default]
+    Fixed,
+    /// Match the physical size of the primary window, reallocating the image whenever it is resized.
+    TrackWindow,
+    /// Match the physical viewport size of the given camera, reallocating the image whenever it changes.
+    TrackCamera(Entity),
+}
+
 /// Enum that determines when canvases are cleared and redrawn.
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CanvasMode {
     /// Always clear and draw each frame
     #[default]
@@ -64,7 +199,7 @@ pub enum CanvasMode {
 
 /// Component containing data and methods for a given canvas.
 ///
-/// Can be spawned as part of a [`CanvasBundle`] with [`CanvasCommands::spawn_canvas`].
+/// Can be spawned as part of a [`CanvasBundle`] with [`CanvasCommands::canvas`].
 #[derive(Component)]
 pub struct Canvas {
     /// Handle to the canvas' target texture.
@@ -77,6 +212,8 @@ pub struct Canvas {
     pub mode: CanvasMode,
     /// Clear mode to revert to for [`CanvasMode::OnDemand`].
     pub clear_color: ClearColorConfig,
+    /// Determines how the canvas' target texture is sized, see [`CanvasSizing`].
+    pub sizing: CanvasSizing,
     redraw: bool,
 }
 
@@ -104,6 +241,7 @@ impl Canvas {
                 sample_count: 1,
                 usage: TextureUsages::TEXTURE_BINDING
                     | TextureUsages::COPY_DST
+                    | TextureUsages::COPY_SRC
                     | TextureUsages::RENDER_ATTACHMENT,
                 view_formats: &[],
             },
@@ -131,9 +269,24 @@ impl Canvas {
         };
         let mut new_image = image.clone();
         new_image.resize(size);
-        let handle = assets.add(new_image);
-        self.image = handle.clone();
-        handle
+        // The previous image is freed once the last handle to it, such as this one, is dropped
+        self.image = assets.add(new_image);
+        self.image.clone()
+    }
+
+    /// Retargets the canvas to an existing image.
+    ///
+    /// The canvas takes it's size from the image and is set to [`CanvasSizing::Fixed`],
+    /// as resizing would replace the image. The previous image is freed once no other handles to it remain.
+    pub fn set_image(&mut self, assets: &mut Assets<Image>, image: Handle<Image>) {
+        if let Some(target) = assets.get(&image) {
+            let size = target.size();
+            self.width = size.x as u32;
+            self.height = size.y as u32;
+        }
+        self.image = image;
+        self.sizing = CanvasSizing::Fixed;
+        self.redraw = true;
     }
 
     /// Mark this canvas to be redraw this frame, behaviour depends on [`CanvasMode`].
@@ -143,7 +296,10 @@ impl Canvas {
 }
 
 /// Configuration to be used when creating a [`CanvasBundle`]
+///
+/// With the `serde` feature enabled the config can be serialized, the sampler is skipped and reset to it's default.
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CanvasConfig {
     /// Clear mode analagous to [`Camera2d`].
     pub clear_color: ClearColorConfig,
@@ -156,7 +312,10 @@ pub struct CanvasConfig {
     /// Camera order analagous to [`Camera`].
     pub order: isize,
     /// [`ImageSampler`] to be used when creating the target texture.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub sampler: ImageSampler,
+    /// Determines how the canvas' target texture is sized, see [`CanvasSizing`].
+    pub sizing: CanvasSizing,
 }
 
 impl CanvasConfig {
@@ -168,13 +327,14 @@ impl CanvasConfig {
             height,
             order: -1,
             sampler: ImageSampler::Default,
+            sizing: CanvasSizing::Fixed,
         }
     }
 }
 
 /// Bundle containing requisite components for a [`Canvas`] entity.
 ///
-/// Can be spawned with [`CanvasCommands::spawn_canvas`].
+/// Can be spawned with [`CanvasCommands::canvas`].
 #[derive(Bundle)]
 pub struct CanvasBundle {
     camera: Camera2dBundle,
@@ -204,6 +364,7 @@ impl CanvasBundle {
 
                 mode: config.mode,
                 clear_color: config.clear_color,
+                sizing: config.sizing,
                 redraw: true,
             },
             render_layers: RenderLayers::none(),
@@ -211,11 +372,101 @@ impl CanvasBundle {
     }
 }
 
+/// Canvas entity along with the image it was created with.
+///
+/// Returned by [`CanvasBuilder::spawn`]. The image can be drawn as a texture right away,
+/// canvases with a [`CanvasSizing`] other than [`CanvasSizing::Fixed`] replace it whenever they are resized,
+/// read the current [`Canvas::image`] from the entity for those.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CanvasHandle {
+    pub image: Handle<Image>,
+    pub entity: Entity,
+}
+
+impl From<&CanvasHandle> for Entity {
+    fn from(handle: &CanvasHandle) -> Self {
+        handle.entity
+    }
+}
+
+impl From<CanvasHandle> for Handle<Image> {
+    fn from(handle: CanvasHandle) -> Self {
+        handle.image
+    }
+}
+
+/// Builder for spawning a [`CanvasBundle`], created with [`CanvasCommands::canvas`].
+pub struct CanvasBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    config: CanvasConfig,
+}
+
+impl<'w, 's, 'a> CanvasBuilder<'w, 's, 'a> {
+    /// Sets the [`CanvasMode`] of the canvas.
+    pub fn mode(mut self, mode: CanvasMode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    /// Sets the clear color of the canvas, analagous to [`Camera2d`].
+    pub fn clear_color(mut self, clear_color: ClearColorConfig) -> Self {
+        self.config.clear_color = clear_color;
+        self
+    }
+
+    /// Sets the order of the canvas' camera, analagous to [`Camera`].
+    pub fn order(mut self, order: isize) -> Self {
+        self.config.order = order;
+        self
+    }
+
+    /// Sets the [`ImageSampler`] used when creating the target texture.
+    pub fn sampler(mut self, sampler: ImageSampler) -> Self {
+        self.config.sampler = sampler;
+        self
+    }
+
+    /// Sets the [`CanvasSizing`] of the canvas.
+    ///
+    /// Width and height are still used for the initial texture and are replaced on the first resize.
+    pub fn sizing(mut self, sizing: CanvasSizing) -> Self {
+        self.config.sizing = sizing;
+        self
+    }
+
+    /// Replaces the entire [`CanvasConfig`] used by this builder.
+    pub fn config(mut self, config: CanvasConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Creates the target texture and spawns the [`CanvasBundle`].
+    ///
+    /// Returns a [`CanvasHandle`] and the [`EntityCommands`] for the spawned canvas.
+    pub fn spawn(self, assets: &mut Assets<Image>) -> (CanvasHandle, EntityCommands<'w, 's, 'a>) {
+        let Self { commands, config } = self;
+        let image =
+            Canvas::create_image(assets, config.width, config.height, config.sampler.clone());
+        let e = commands.spawn(CanvasBundle::new(image.clone(), config));
+        (
+            CanvasHandle {
+                image,
+                entity: e.id(),
+            },
+            e,
+        )
+    }
+}
+
 /// Extension trait for [`Commands`] to allow spawning of [`CanvasBundle`] entities.
 pub trait CanvasCommands<'w, 's> {
+    /// Creates a [`CanvasBuilder`] for a canvas of the given size in pixels.
+    fn canvas(&mut self, width: u32, height: u32) -> CanvasBuilder<'w, 's, '_>;
+
     /// Spawns a [`CanvasBundle`] according to the given [`CanvasConfig`].
     ///
     /// Returns the created [`Handle<Image>`] and [`EntityCommands`].
+    #[deprecated(note = "use `canvas` and `CanvasBuilder::config` instead")]
     fn spawn_canvas(
         &mut self,
         assets: &mut Assets<Image>,
@@ -224,16 +475,22 @@ pub trait CanvasCommands<'w, 's> {
 }
 
 impl<'w, 's> CanvasCommands<'w, 's> for Commands<'w, 's> {
+    fn canvas(&mut self, width: u32, height: u32) -> CanvasBuilder<'w, 's, '_> {
+        CanvasBuilder {
+            commands: self,
+            config: CanvasConfig::new(width, height),
+        }
+    }
+
     fn spawn_canvas(
         &mut self,
         assets: &mut Assets<Image>,
         config: CanvasConfig,
     ) -> (Handle<Image>, EntityCommands<'w, 's, '_>) {
-        let handle =
-            Canvas::create_image(assets, config.width, config.height, config.sampler.clone());
-        (
-            handle.clone(),
-            self.spawn(CanvasBundle::new(handle, config)),
-        )
+        let (handle, e) = self
+            .canvas(config.width, config.height)
+            .config(config)
+            .spawn(assets);
+        (handle.image, e)
     }
 }
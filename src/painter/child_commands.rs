@@ -77,6 +77,8 @@ pub struct ShapeChildBuilder<'w, 's, 'a> {
 impl<'w, 's, 'a> ShapeChildBuilder<'w, 's, 'a> {
     /// Spawns an entity with the given bundle and inserts it into the parent entity's [`Children`].
     /// Also adds [`Parent`] component to the created entity.
+    ///
+    /// Entities with shapes below them should include a [`SpatialBundle`] so that visibility propagates through them.
     pub fn spawn(&mut self, bundle: impl Bundle) -> EntityCommands<'w, 's, '_> {
         let e = self.commands.spawn(bundle);
         self.push_children.children.push(e.id());
@@ -113,6 +115,9 @@ impl<'w, 's, 'a> ShapeSpawner<'w, 's> for ShapeChildBuilder<'w, 's, 'a> {
         if let Some(layers) = config.render_layers {
             e.insert(layers);
         }
+        if let Some(layer) = &config.paint_layer {
+            e.insert(PaintLayer(layer.clone()));
+        }
         if let ShapePipelineType::Shape3d = config.pipeline {
             e.insert(Shape3d);
         }
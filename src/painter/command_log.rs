@@ -0,0 +1,105 @@
+use std::any::Any;
+
+use bevy::prelude::*;
+
+use crate::{prelude::*, render::ShapeData};
+
+/// Shape data of a single recorded [`ShapePainter`] call.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PaintedShape {
+    Line(LineData),
+    QuadBezier(QuadBezierData),
+    Disc(DiscData),
+    Rectangle(RectData),
+    RegularPolygon(NgonData),
+    Morph(MorphData),
+    Triangle(TriangleData),
+}
+
+impl PaintedShape {
+    /// Wraps the given shape data if it is one of the built-in shape types.
+    pub fn from_data<T: ShapeData>(data: &T) -> Option<Self> {
+        let data: &dyn Any = data;
+        data.downcast_ref()
+            .copied()
+            .map(Self::Line)
+            .or_else(|| data.downcast_ref().copied().map(Self::QuadBezier))
+            .or_else(|| data.downcast_ref().copied().map(Self::Disc))
+            .or_else(|| data.downcast_ref().copied().map(Self::Rectangle))
+            .or_else(|| data.downcast_ref().copied().map(Self::RegularPolygon))
+            .or_else(|| data.downcast_ref().copied().map(Self::Morph))
+            .or_else(|| data.downcast_ref().copied().map(Self::Triangle))
+    }
+}
+
+/// A recorded [`ShapePainter`] call along with a snapshot of the config it was drawn with.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PainterCommand {
+    pub config: ShapeConfig,
+    pub shape: PaintedShape,
+}
+
+/// Resource that records every shape drawn by a [`ShapePainter`] while recording, not inserted by default.
+///
+/// Intended as a foundation for drawing tools, the log can be inspected, truncated to undo commands and
+/// drawn each frame with [`ShapePainter::replay`]. Shapes drawn by replaying are not recorded again.
+/// Only the built-in shape types are recorded, with the `serde` feature the log can be serialized,
+/// references to entities and assets in the recorded configs are skipped.
+#[derive(Resource, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeCommandLog {
+    /// Whether painter calls are currently being recorded.
+    pub recording: bool,
+    commands: Vec<PainterCommand>,
+}
+
+impl ShapeCommandLog {
+    /// Creates a log that starts recording immediately.
+    pub fn recording() -> Self {
+        Self {
+            recording: true,
+            ..default()
+        }
+    }
+
+    /// Records the given shape if recording.
+    pub fn record<T: ShapeData>(&mut self, config: &ShapeConfig, data: &T) {
+        if !self.recording {
+            return;
+        }
+        if let Some(shape) = PaintedShape::from_data(data) {
+            self.commands.push(PainterCommand {
+                config: config.clone(),
+                shape,
+            });
+        }
+    }
+
+    pub fn commands(&self) -> &[PainterCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Drops all commands after the first `len`, returning the dropped commands so they can be redone.
+    pub fn truncate(&mut self, len: usize) -> Vec<PainterCommand> {
+        self.commands.split_off(len.min(self.commands.len()))
+    }
+
+    /// Appends previously recorded commands, such as those returned by [`ShapeCommandLog::truncate`].
+    pub fn extend(&mut self, commands: impl IntoIterator<Item = PainterCommand>) {
+        self.commands.extend(commands);
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
@@ -1,34 +1,116 @@
+use std::borrow::Cow;
+
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 
 use crate::prelude::*;
-use crate::render::ShapePipelineType;
+use crate::render::{ShapeHook, ShapePipelineType};
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 /// Describes a configuration that can be applied to a spawned shape.
+///
+/// With the `serde` feature enabled the config can be serialized, references to entities and assets are skipped.
 #[derive(Clone, Reflect, FromReflect)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapeConfig {
     /// Transform with which the shape will be spawned.
     pub transform: Transform,
 
     pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub thickness: f32,
     pub thickness_type: ThicknessType,
     pub alignment: Alignment,
+    /// Distance in world units to offset the shape towards the camera in the vertex shader.
+    ///
+    /// Offsetting along the view ray leaves the shape's screen position unchanged,
+    /// useful for preventing z-fighting of shapes drawn on top of coplanar 3D geometry.
+    pub depth_bias: f32,
+    /// If true spawned shape will be drawn on top of other geometry, ignoring the depth test in 3D.
+    ///
+    /// Useful for selection outlines and debug markers that should remain visible through geometry.
+    pub overlay: bool,
+    /// If true spawned shape will be shaded by the scene's directional and ambient lights using the normal of it's plane.
+    ///
+    /// Intended for 3D shapes, so that in world markers match the lighting of the scene.
+    pub lit: bool,
+    /// If true spawned shape will fade with distance according to the camera's `FogSettings`.
+    pub fog: bool,
+    /// If true spawned shape will have it's position and pixel thickness snapped to the device pixel grid.
+    ///
+    /// Keeps thin 2D lines and UI outlines crisp rather than blurred across two rows of pixels.
+    /// To snap all shapes set this on the `base_config` of the plugin.
+    pub pixel_snap: bool,
+    /// If set spawned shape will be clipped to the given rect in logical pixels relative to the top left of the render target.
+    ///
+    /// Matches the coordinates of `bevy_ui` nodes, allowing shapes to be clipped to panels such as scrolling lists.
+    pub clip: Option<Rect>,
+    /// Arbitrary data passed to the spawned shape's [`ShapeHook`] or custom shader, ignored by the built-in shaders.
+    ///
+    /// Allows effects to be parameterized per instance without breaking batches.
+    pub user_data: Vec4,
+    /// Multiplier applied to the spawned shape's color, kept separate from alpha.
+    ///
+    /// Values above 1.0 on an HDR camera drive bloom, allowing highlights to be animated without re-encoding colors.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
+    pub intensity: f32,
     /// If true spawned shape will be hollow, taking into account thickness and thickness_type.
     pub hollow: bool,
     pub cap: Cap,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub roundness: f32,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub corner_radii: Vec4,
 
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub render_layers: Option<RenderLayers>,
+    /// Named layer of [`ShapeLayers`] the shape belongs to, used to toggle and order categories of shapes at runtime.
+    pub paint_layer: Option<Cow<'static, str>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::shapes::AlphaModeDef"))]
     pub alpha_mode: AlphaMode,
     /// Forcibly disables local anti-aliasing for all shapes.
     pub disable_laa: bool,
-    /// [`Canvas`] to draw the shape to.
+    /// If true opaque and alpha masked shapes keep their anti-aliased edges by writing alpha to the MSAA coverage mask.
+    ///
+    /// Shapes stay in the opaque phases so large numbers of solid shapes avoid transparent sorting and blending.
+    /// Without MSAA edges fall back to being aliased.
+    pub alpha_to_coverage: bool,
+    /// [`Canvas`] or camera to draw the shape to, bypassing render layers.
+    ///
+    /// Any camera may be targeted, including user cameras rendering to an image, see [`ShapeConfig::set_target_camera`].
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub canvas: Option<Entity>,
     /// Texture to apply to the shape, color is determined as color * sample.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub texture: Option<Handle<Image>>,
+    /// Layer to sample when the texture is an array texture, see [`create_texture_array`](crate::render::create_texture_array).
+    ///
+    /// Shapes sampling different layers of the same array are drawn in a single batch.
+    pub texture_layer: Option<u32>,
+    /// Part of the texture drawn on rectangles in normalized texture coordinates with the origin at the top left,
+    /// the whole texture is drawn if unset.
+    ///
+    /// Allows drawing individual cells of an atlas such as a sprite sheet or bitmap font.
+    pub texture_rect: Option<Rect>,
+    /// Fragment shader hook to apply to the shape, see [`ShapeHook`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hook: Option<Handle<ShapeHook>>,
+    /// How the shape interacts with the stencil buffer, see [`StencilMode`].
+    pub stencil: StencilMode,
+    /// Render pass in which the shape is drawn, see [`ShapePass`].
+    pub pass: ShapePass,
+    /// Cameras that may draw the shape in addition to filtering by render layers, if empty the shape is drawn by all cameras.
+    ///
+    /// Useful for drawing overlays to a single viewport in split-screen or editor setups.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub visible_to: Vec<Entity>,
     /// Set with set_2d, set_3d and set_canvas.
     pub pipeline: ShapePipelineType,
 }
@@ -87,6 +169,15 @@ impl ShapeConfig {
         self.canvas = Some(canvas);
     }
 
+    /// Helper method to draw shapes only to the given camera, regardless of render layers.
+    ///
+    /// Unlike [`ShapeConfig::set_canvas`] the pipeline is left unchanged, allowing shapes to target any 2D or 3D camera
+    /// and by extension any [`RenderTarget`](bevy::render::camera::RenderTarget) such as an existing render to texture setup.
+    /// The camera's pipeline must match, use [`ShapeConfig::set_3d`] when targeting a 3D camera.
+    pub fn set_target_camera(&mut self, camera: Entity) {
+        self.canvas = Some(camera);
+    }
+
     /// Helper method to change the target pipeline to the 3d pipeline.
     pub fn set_3d(&mut self) {
         self.pipeline = ShapePipelineType::Shape3d;
@@ -115,16 +206,32 @@ impl ShapeConfig {
             thickness: 0.1,
             thickness_type: default(),
             alignment: default(),
+            depth_bias: 0.0,
+            overlay: false,
+            lit: false,
+            fog: false,
+            pixel_snap: false,
+            clip: None,
+            user_data: Vec4::ZERO,
+            intensity: 1.0,
             hollow: false,
             cap: default(),
             roundness: default(),
             corner_radii: default(),
 
             render_layers: None,
+            paint_layer: None,
             alpha_mode: AlphaMode::Blend,
             disable_laa: false,
+            alpha_to_coverage: false,
             canvas: None,
             texture: None,
+            texture_layer: None,
+            texture_rect: None,
+            hook: None,
+            stencil: default(),
+            pass: default(),
+            visible_to: Vec::new(),
             pipeline: ShapePipelineType::Shape2d,
         }
     }
@@ -0,0 +1,210 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Resource providing the immediate mode drawing methods of bevy's `Gizmos` implemented with shapes, added by [`ShapeGizmosPlugin`].
+///
+/// Methods share the names and arguments of their `Gizmos` counterparts, allowing existing gizmo calls to be upgraded
+/// to thick, capped and anti-aliased shapes by swapping the system param for `ResMut<ShapeGizmos>`.
+/// Shapes are drawn by a [`ShapePainter`] at the end of the frame they were added in, builders returned by `Gizmos`
+/// such as circle segment counts aren't needed as shapes are drawn exactly.
+#[derive(Resource)]
+pub struct ShapeGizmos {
+    /// Config the gizmos are drawn with, the transform and color are set by each method.
+    ///
+    /// Initialized from the [`BaseShapeConfig`] with a thickness of 2 logical pixels.
+    pub config: ShapeConfig,
+    shapes: Vec<PaintedShape>,
+}
+
+impl FromWorld for ShapeGizmos {
+    fn from_world(world: &mut World) -> Self {
+        let mut config = world.resource::<BaseShapeConfig>().0.clone();
+        config.thickness = 2.0;
+        config.thickness_type = ThicknessType::Pixels;
+        config.cap = Cap::Round;
+        config.hollow = true;
+        Self {
+            config,
+            shapes: Vec::new(),
+        }
+    }
+}
+
+impl ShapeGizmos {
+    /// Config for a shape with the given color and transform.
+    fn config(&mut self, color: Color, transform: Transform) -> &ShapeConfig {
+        self.config.color = color;
+        self.config.transform = transform;
+        &self.config
+    }
+
+    /// Adds a line drawn around it's direction to face the camera.
+    fn push_line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        let alignment = self.config.alignment;
+        self.config.alignment = Alignment::Billboard;
+        let data = LineData::new(self.config(color, Transform::IDENTITY), start, end);
+        self.config.alignment = alignment;
+        self.shapes.push(PaintedShape::Line(data));
+    }
+
+    /// Adds a hollow circle in the xy plane of the given transform.
+    fn push_circle(&mut self, transform: Transform, radius: f32, color: Color) {
+        let data = DiscData::circle(self.config(color, transform), radius);
+        self.shapes.push(PaintedShape::Disc(data));
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.push_line(start, end, color);
+    }
+
+    pub fn line_2d(&mut self, start: Vec2, end: Vec2, color: Color) {
+        self.push_line(start.extend(0.0), end.extend(0.0), color);
+    }
+
+    pub fn ray(&mut self, start: Vec3, vector: Vec3, color: Color) {
+        self.push_line(start, start + vector, color);
+    }
+
+    pub fn ray_2d(&mut self, start: Vec2, vector: Vec2, color: Color) {
+        self.line_2d(start, start + vector, color);
+    }
+
+    pub fn linestrip(&mut self, positions: impl IntoIterator<Item = Vec3>, color: Color) {
+        let mut positions = positions.into_iter();
+        let Some(mut previous) = positions.next() else {
+            return;
+        };
+        for position in positions {
+            self.push_line(previous, position, color);
+            previous = position;
+        }
+    }
+
+    pub fn linestrip_2d(&mut self, positions: impl IntoIterator<Item = Vec2>, color: Color) {
+        self.linestrip(positions.into_iter().map(|p| p.extend(0.0)), color);
+    }
+
+    /// Draws a circle facing along the given normal.
+    pub fn circle(&mut self, position: Vec3, normal: Vec3, radius: f32, color: Color) {
+        let rotation = Quat::from_rotation_arc(Vec3::Z, normal.normalize());
+        self.push_circle(
+            Transform::from_translation(position).with_rotation(rotation),
+            radius,
+            color,
+        );
+    }
+
+    pub fn circle_2d(&mut self, position: Vec2, radius: f32, color: Color) {
+        self.push_circle(
+            Transform::from_translation(position.extend(0.0)),
+            radius,
+            color,
+        );
+    }
+
+    /// Draws a sphere as a circle around each of it's axes.
+    pub fn sphere(&mut self, position: Vec3, rotation: Quat, radius: f32, color: Color) {
+        let transform = Transform::from_translation(position).with_rotation(rotation);
+        for axis_rotation in [
+            Quat::IDENTITY,
+            Quat::from_rotation_x(TAU / 4.0),
+            Quat::from_rotation_y(TAU / 4.0),
+        ] {
+            self.push_circle(
+                transform * Transform::from_rotation(axis_rotation),
+                radius,
+                color,
+            );
+        }
+    }
+
+    pub fn rect(&mut self, position: Vec3, rotation: Quat, size: Vec2, color: Color) {
+        let transform = Transform::from_translation(position).with_rotation(rotation);
+        self.config.corner_radii = Vec4::ZERO;
+        let data = RectData::new(self.config(color, transform), size);
+        self.shapes.push(PaintedShape::Rectangle(data));
+    }
+
+    /// Draws a rect rotated by the given angle in radians.
+    pub fn rect_2d(&mut self, position: Vec2, rotation: f32, size: Vec2, color: Color) {
+        self.rect(
+            position.extend(0.0),
+            Quat::from_rotation_z(rotation),
+            size,
+            color,
+        );
+    }
+
+    /// Draws the edges of a unit cube transformed by the given transform.
+    pub fn cuboid(&mut self, transform: Transform, color: Color) {
+        let corner = |x: f32, y: f32, z: f32| transform.transform_point(Vec3::new(x, y, z) * 0.5);
+        for (a, b) in [
+            // Edges along x
+            ((-1., -1., -1.), (1., -1., -1.)),
+            ((-1., 1., -1.), (1., 1., -1.)),
+            ((-1., -1., 1.), (1., -1., 1.)),
+            ((-1., 1., 1.), (1., 1., 1.)),
+            // Edges along y
+            ((-1., -1., -1.), (-1., 1., -1.)),
+            ((1., -1., -1.), (1., 1., -1.)),
+            ((-1., -1., 1.), (-1., 1., 1.)),
+            ((1., -1., 1.), (1., 1., 1.)),
+            // Edges along z
+            ((-1., -1., -1.), (-1., -1., 1.)),
+            ((1., -1., -1.), (1., -1., 1.)),
+            ((-1., 1., -1.), (-1., 1., 1.)),
+            ((1., 1., -1.), (1., 1., 1.)),
+        ] {
+            self.push_line(corner(a.0, a.1, a.2), corner(b.0, b.1, b.2), color);
+        }
+    }
+
+    /// Draws an arc of the given angle centered on `direction_angle`, both in radians measured clockwise from the y axis.
+    pub fn arc_2d(
+        &mut self,
+        position: Vec2,
+        direction_angle: f32,
+        arc_angle: f32,
+        radius: f32,
+        color: Color,
+    ) {
+        let transform = Transform::from_translation(position.extend(0.0));
+        let data = DiscData::arc(
+            self.config(color, transform),
+            radius,
+            direction_angle - arc_angle / 2.0,
+            direction_angle + arc_angle / 2.0,
+        );
+        self.shapes.push(PaintedShape::Disc(data));
+    }
+}
+
+/// Draws the shapes added to [`ShapeGizmos`] this frame.
+pub fn draw_shape_gizmos(mut painter: ShapePainter, mut gizmos: ResMut<ShapeGizmos>) {
+    let gizmos = &mut *gizmos;
+    for shape in gizmos.shapes.drain(..) {
+        let config = &gizmos.config;
+        match shape {
+            PaintedShape::Line(data) => painter.send_with_config(config, data),
+            PaintedShape::QuadBezier(data) => painter.send_with_config(config, data),
+            PaintedShape::Disc(data) => painter.send_with_config(config, data),
+            PaintedShape::Rectangle(data) => painter.send_with_config(config, data),
+            PaintedShape::RegularPolygon(data) => painter.send_with_config(config, data),
+            PaintedShape::Morph(data) => painter.send_with_config(config, data),
+            PaintedShape::Triangle(data) => painter.send_with_config(config, data),
+        };
+    }
+}
+
+/// Plugin that sets up the [`ShapeGizmos`] resource, requires [`Shape2dPlugin`] or [`ShapePlugin`].
+pub struct ShapeGizmosPlugin;
+
+impl Plugin for ShapeGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeGizmos>()
+            .add_system(draw_shape_gizmos.in_base_set(CoreSet::Last));
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::DerefMut;
 
-use crate::prelude::*;
+use crate::{prelude::*, render::pack_shape_images};
 use bevy::{prelude::*, render::camera::CameraUpdateSystem};
 
 mod config;
@@ -18,6 +18,15 @@ pub use shape_painter::*;
 mod canvas;
 pub use canvas::*;
 
+mod command_log;
+pub use command_log::*;
+
+mod pool;
+pub use pool::*;
+
+mod gizmos;
+pub use gizmos::*;
+
 #[derive(Deref, DerefMut)]
 struct LocalShapeConfig(pub ShapeConfig);
 
@@ -45,11 +54,17 @@ pub struct PainterPlugin;
 impl Plugin for PainterPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ShapeStorage>()
-            .add_system(
-                update_canvases
+            .init_resource::<ShapePool>()
+            .init_resource::<ShapeLayers>()
+            .init_resource::<ShapeImageArrays>()
+            .add_systems(
+                (resize_canvases, update_canvases)
+                    .chain()
                     .in_base_set(CoreSet::PostUpdate)
                     .before(CameraUpdateSystem),
             )
-            .add_system(clear_storage.in_base_set(CoreSet::PreUpdate));
+            .add_system(clear_storage.in_base_set(CoreSet::PreUpdate))
+            .add_system(pack_shape_images.in_base_set(CoreSet::PostUpdate))
+            .add_system(cleanup_canvases.in_base_set(CoreSet::Last));
     }
 }
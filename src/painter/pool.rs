@@ -0,0 +1,70 @@
+use std::any::TypeId;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    prelude::*,
+    render::{ShapeComponent, ShapePipelineType},
+};
+
+/// Resource that recycles retained shape entities, avoiding the cost of spawning and despawning them.
+///
+/// Released entities are hidden rather than despawned and are reused by the next spawn of the same shape type,
+/// inserting the new bundle over the old components so the entity never changes archetype.
+/// Intended for large numbers of short-lived shapes such as projectiles.
+/// Components inserted after spawning are not reset, and released entities should not have a parent.
+#[derive(Resource, Default)]
+pub struct ShapePool {
+    free: HashMap<(TypeId, ShapePipelineType), Vec<Entity>>,
+}
+
+impl ShapePool {
+    /// Spawns the given bundle, reusing a released entity of the same shape type and pipeline if there is one.
+    pub fn spawn<T: ShapeComponent>(
+        &mut self,
+        commands: &mut Commands,
+        bundle: ShapeBundle<T>,
+    ) -> Entity {
+        let key = (TypeId::of::<T>(), bundle.shape.pipeline);
+        let reused = self.free.get_mut(&key).and_then(|free| free.pop());
+        match reused {
+            // The bundle's visibility replaces the hidden visibility of the released entity
+            Some(entity) => commands.entity(entity).insert(bundle).id(),
+            None => {
+                let mut entity = commands.spawn(bundle);
+                if let ShapePipelineType::Shape3d = key.1 {
+                    entity.insert(Shape3d);
+                }
+                entity.id()
+            }
+        }
+    }
+
+    /// Hides the given shape entity and stores it to be reused, in place of despawning it.
+    ///
+    /// The entity must have been spawned with a [`ShapeBundle<T>`] of the given pipeline.
+    pub fn release<T: ShapeComponent>(
+        &mut self,
+        commands: &mut Commands,
+        entity: Entity,
+        pipeline: ShapePipelineType,
+    ) {
+        commands.entity(entity).insert(Visibility::Hidden);
+        self.free
+            .entry((TypeId::of::<T>(), pipeline))
+            .or_default()
+            .push(entity);
+    }
+
+    /// Number of released entities waiting to be reused.
+    pub fn free_count(&self) -> usize {
+        self.free.values().map(Vec::len).sum()
+    }
+
+    /// Despawns all released entities.
+    pub fn clear(&mut self, commands: &mut Commands) {
+        for entity in self.free.drain().flat_map(|(_, free)| free) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -2,7 +2,11 @@ use std::ops::{Deref, DerefMut};
 
 use bevy::{ecs::system::SystemParam, prelude::*};
 
-use crate::{painter::LocalShapeConfig, prelude::*, render::ShapePipelineType};
+use crate::{
+    painter::LocalShapeConfig,
+    prelude::*,
+    render::{ShapeComponent, ShapePipelineType},
+};
 
 /// A system param that allows ergonomic spawning of shape entities.
 ///
@@ -33,6 +37,9 @@ impl<'w, 's, 'a> ShapeSpawner<'w, 's> for ShapeCommands<'w, 's> {
         if let Some(layers) = config.render_layers {
             e.insert(layers);
         }
+        if let Some(layer) = &config.paint_layer {
+            e.insert(PaintLayer(layer.clone()));
+        }
         if let ShapePipelineType::Shape3d = config.pipeline {
             e.insert(Shape3d);
         }
@@ -65,3 +72,46 @@ impl<'w, 's> DerefMut for ShapeCommands<'w, 's> {
         &mut self.config
     }
 }
+
+/// Extension trait for [`Commands`] to allow spawning many retained shapes at once.
+pub trait ShapeBatchCommands {
+    /// Spawns a shape entity for each pair of transform and shape component using [`Commands::spawn_batch`].
+    ///
+    /// All shapes share the material, render layers and pipeline of the given config while the transform
+    /// and component of each shape override the rest, avoiding the per entity overhead of [`ShapeCommands`]
+    /// when spawning tens of thousands of shapes.
+    fn spawn_shape_batch<T: ShapeComponent>(
+        &mut self,
+        config: &ShapeConfig,
+        shapes: impl IntoIterator<Item = (Transform, T)>,
+    );
+}
+
+impl<'w, 's> ShapeBatchCommands for Commands<'w, 's> {
+    fn spawn_shape_batch<T: ShapeComponent>(
+        &mut self,
+        config: &ShapeConfig,
+        shapes: impl IntoIterator<Item = (Transform, T)>,
+    ) {
+        let bundles = shapes
+            .into_iter()
+            .map(|(transform, shape)| ShapeBundle::new(config, shape).with_transform(transform));
+
+        match (config.pipeline, config.paint_layer.clone()) {
+            (ShapePipelineType::Shape3d, Some(layer)) => self.spawn_batch(
+                bundles
+                    .map(|bundle| (bundle, Shape3d, PaintLayer(layer.clone())))
+                    .collect::<Vec<_>>(),
+            ),
+            (ShapePipelineType::Shape3d, None) => {
+                self.spawn_batch(bundles.map(|bundle| (bundle, Shape3d)).collect::<Vec<_>>())
+            }
+            (ShapePipelineType::Shape2d, Some(layer)) => self.spawn_batch(
+                bundles
+                    .map(|bundle| (bundle, PaintLayer(layer.clone())))
+                    .collect::<Vec<_>>(),
+            ),
+            (ShapePipelineType::Shape2d, None) => self.spawn_batch(bundles.collect::<Vec<_>>()),
+        }
+    }
+}
@@ -11,7 +11,9 @@ use any_vec::AnyVec;
 use crate::{
     painter::LocalShapeConfig,
     prelude::*,
-    render::{ShapeData, ShapeInstance, ShapePipelineMaterial, ShapePipelineType},
+    render::{
+        ShapeData, ShapeImageArrays, ShapeInstance, ShapePipelineMaterial, ShapePipelineType,
+    },
 };
 
 /// A system param for type erased storage of [`ShapeInstance`].
@@ -64,11 +66,18 @@ pub fn clear_storage(mut storage: ResMut<ShapeStorage>) {
 /// Subsequent calls to `reset()` will reset the config back to whatever is currently stored within the [`BaseShapeConfig`] resource.
 ///
 /// Shapes are spawned via events which will be extracted for rendering.
+/// If the [`ShapeCommandLog`] resource exists each shape is also recorded to it while recording.
+/// Shapes are also recorded to the [`ShapeSvgCapture`] resource while a capture is in progress.
+/// Shapes drawn with a [`ShapeConfig::paint_layer`] take on the settings of that layer in [`ShapeLayers`].
 #[derive(SystemParam)]
 pub struct ShapePainter<'w, 's> {
     config: Local<'s, LocalShapeConfig>,
     event_writer: ResMut<'w, ShapeStorage>,
     default_config: Res<'w, BaseShapeConfig>,
+    log: Option<ResMut<'w, ShapeCommandLog>>,
+    capture: Option<ResMut<'w, ShapeSvgCapture>>,
+    layers: Res<'w, ShapeLayers>,
+    image_arrays: Option<ResMut<'w, ShapeImageArrays>>,
 }
 
 impl<'w, 's> ShapePainter<'w, 's> {
@@ -80,21 +89,78 @@ impl<'w, 's> ShapePainter<'w, 's> {
         self.config.0 = config;
     }
 
-    pub fn send<T: ShapeData>(&mut self, data: T) -> &mut Self {
+    /// Array and layer the image has been packed into by [`ShapeImageArrays`], if any.
+    pub(crate) fn packed_image(&mut self, image: &Handle<Image>) -> Option<(Handle<Image>, u32)> {
+        self.image_arrays.as_mut()?.layer(image)
+    }
+
+    pub fn send<T: ShapeData>(&mut self, mut data: T) -> &mut Self {
         let Self {
             config,
             event_writer,
+            log,
+            capture,
+            layers,
             ..
         } = self;
+        if let Some(log) = log {
+            log.record(config, &data);
+        }
+        if let Some(layer) = &config.paint_layer {
+            if !layers.apply(layer, &mut data) {
+                return self;
+            }
+        }
+        if let Some(capture) = capture {
+            capture.record(config, &data);
+        }
         event_writer.send(config, data);
         self
     }
 
-    pub fn send_with_config<T: ShapeData>(&mut self, config: &ShapeConfig, data: T) -> &mut Self {
+    pub fn send_with_config<T: ShapeData>(
+        &mut self,
+        config: &ShapeConfig,
+        mut data: T,
+    ) -> &mut Self {
+        if let Some(log) = &mut self.log {
+            log.record(config, &data);
+        }
+        if let Some(layer) = &config.paint_layer {
+            if !self.layers.apply(layer, &mut data) {
+                return self;
+            }
+        }
+        if let Some(capture) = &mut self.capture {
+            capture.record(config, &data);
+        }
         self.event_writer.send(config, data);
         self
     }
 
+    /// Draws previously recorded commands, such as the contents of a [`ShapeCommandLog`], without recording them again.
+    pub fn replay<'a>(
+        &mut self,
+        commands: impl IntoIterator<Item = &'a PainterCommand>,
+    ) -> &mut Self {
+        for command in commands {
+            if let Some(capture) = &mut self.capture {
+                capture.record_command(command);
+            }
+            let PainterCommand { config, shape } = command;
+            match *shape {
+                PaintedShape::Line(data) => self.event_writer.send(config, data),
+                PaintedShape::QuadBezier(data) => self.event_writer.send(config, data),
+                PaintedShape::Disc(data) => self.event_writer.send(config, data),
+                PaintedShape::Rectangle(data) => self.event_writer.send(config, data),
+                PaintedShape::RegularPolygon(data) => self.event_writer.send(config, data),
+                PaintedShape::Morph(data) => self.event_writer.send(config, data),
+                PaintedShape::Triangle(data) => self.event_writer.send(config, data),
+            }
+        }
+        self
+    }
+
     /// Takes a closure which builds children for this shape.
     ///
     /// While event based shapes don't have the parent child relationship that entities have,
@@ -0,0 +1,181 @@
+use bevy::{prelude::*, render::view::RenderLayers, window::PrimaryWindow};
+use bevy_picking_core::backend::prelude::{
+    HitData, PickSet, PointerHits, PointerId, PointerLocation,
+};
+
+use crate::{prelude::*, render::is_shape_visible, shapes::local_point};
+
+/// Plugin that makes retained shapes pickable through `bevy_mod_picking` 0.13, requires the `picking` feature.
+///
+/// Pointers are tested against shapes using [`HitTestShape`], shapes drawn to a [`Canvas`] are
+/// pickable wherever the canvas' image is displayed on a [`Rectangle`].
+pub struct ShapePickingPlugin;
+
+impl Plugin for ShapePickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                shape_picking::<Line>,
+                shape_picking::<QuadBezier>,
+                shape_picking::<Disc>,
+                shape_picking::<Rectangle>,
+                shape_picking::<RegularPolygon>,
+                shape_picking::<Triangle>,
+                shape_picking::<ShapeMorph>,
+            )
+                .in_set(PickSet::Backend),
+        );
+    }
+}
+
+/// Depth just in front of the given depth, used so that shapes on a canvas are not blocked by the rectangle displaying it.
+fn in_front_of(depth: f32) -> f32 {
+    depth - depth.abs().max(1.0) * 1e-5
+}
+
+/// Whether a shape is drawn by the given camera, shapes drawn to a canvas are only drawn by that canvas.
+fn is_drawn_by(
+    canvas: Option<Entity>,
+    material: Option<&ShapeMaterial>,
+    camera_layers: Option<&RenderLayers>,
+    layers: Option<&RenderLayers>,
+) -> bool {
+    canvas == material.and_then(|material| material.canvas)
+        && (canvas.is_some()
+            || camera_layers
+                .copied()
+                .unwrap_or_default()
+                .intersects(&layers.copied().unwrap_or_default()))
+}
+
+/// Sends [`PointerHits`] for every retained shape of the given type under each pointer.
+pub fn shape_picking<T: HitTestShape + Component>(
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform, Option<&RenderLayers>), Without<Canvas>>,
+    canvases: Query<(Entity, &Canvas, &Camera, &GlobalTransform)>,
+    displays: Query<(
+        &Rectangle,
+        &GlobalTransform,
+        &ComputedVisibility,
+        &ShapeMaterial,
+        Option<&RenderLayers>,
+    )>,
+    shapes: Query<(
+        Entity,
+        &T,
+        &GlobalTransform,
+        &ComputedVisibility,
+        Option<&ShapeMaterial>,
+        Option<&RenderLayers>,
+    )>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let primary_window = primary_window.get_single().ok();
+
+    // Hits for shapes drawn by the given camera under a ray cast from it, reported at the given depth if set
+    let pick = |ray: Ray,
+                (camera, camera_tf): (&Camera, &GlobalTransform),
+                canvas: Option<Entity>,
+                camera_layers: Option<&RenderLayers>,
+                depth_override: Option<f32>,
+                picks: &mut Vec<(Entity, HitData)>,
+                camera_entity: Entity| {
+        for (entity, shape, tf, visibility, material, layers) in &shapes {
+            if !is_shape_visible(visibility, material)
+                || !is_drawn_by(canvas, material, camera_layers, layers)
+            {
+                continue;
+            }
+            if let Some(hit) = shape.raycast(tf, ray, camera, camera_tf) {
+                let depth = depth_override.unwrap_or(hit.distance);
+                picks.push((
+                    entity,
+                    HitData::new(camera_entity, depth, Some(hit.point), Some(hit.normal)),
+                ));
+            }
+        }
+    };
+
+    for (pointer, location) in pointers
+        .iter()
+        .filter_map(|(pointer, location)| Some((pointer, location.location()?)))
+    {
+        for (camera_entity, camera, camera_tf, camera_layers) in &cameras {
+            if !camera.is_active
+                || camera.target.normalize(primary_window).as_ref() != Some(&location.target)
+            {
+                continue;
+            }
+            let Some((viewport_min, viewport_max)) = camera.logical_viewport_rect() else {
+                continue;
+            };
+            if location.position.cmplt(viewport_min).any()
+                || location.position.cmpgt(viewport_max).any()
+            {
+                continue;
+            }
+            let Some(ray) = camera.viewport_to_world(camera_tf, location.position - viewport_min)
+            else {
+                continue;
+            };
+
+            let mut picks = Vec::new();
+            let view = (camera, camera_tf);
+            pick(
+                ray,
+                view,
+                None,
+                camera_layers,
+                None,
+                &mut picks,
+                camera_entity,
+            );
+
+            // Map the pointer through each rectangle displaying a canvas into that canvas' viewport
+            for (rect, tf, visibility, material, layers) in &displays {
+                if !visibility.is_visible()
+                    || !is_drawn_by(None, Some(material), camera_layers, layers)
+                {
+                    continue;
+                }
+                let Some(texture) = &material.texture else {
+                    continue;
+                };
+                let Some((canvas_entity, _, canvas_camera, canvas_tf)) = canvases
+                    .iter()
+                    .find(|(_, canvas, _, _)| canvas.image.id() == texture.id())
+                else {
+                    continue;
+                };
+                let Some(hit) = rect.raycast(tf, ray, camera, camera_tf) else {
+                    continue;
+                };
+
+                // Texture coordinates are taken from the rectangle's quad as in the shader, with v pointing down
+                let local = local_point(tf, rect.alignment, hit.point, camera, camera_tf);
+                let half = rect.size / 2.0;
+                let texture_uv = Vec2::new(local.x / half.x + 1.0, 1.0 - local.y / half.y) / 2.0;
+                let Some(canvas_size) = canvas_camera.logical_viewport_size() else {
+                    continue;
+                };
+                let Some(canvas_ray) =
+                    canvas_camera.viewport_to_world(canvas_tf, texture_uv * canvas_size)
+                else {
+                    continue;
+                };
+                pick(
+                    canvas_ray,
+                    (canvas_camera, canvas_tf),
+                    Some(canvas_entity),
+                    None,
+                    Some(in_front_of(hit.distance)),
+                    &mut picks,
+                    camera_entity,
+                );
+            }
+
+            output.send(PointerHits::new(*pointer, picks, camera.order as f32));
+        }
+    }
+}
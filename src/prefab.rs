@@ -0,0 +1,181 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{prelude::*, render::ShapePipelineType};
+
+/// Asset describing a reusable hierarchy of shapes, loaded from `.shape.ron` files, requires the `prefab` feature.
+///
+/// Each node has an optional shape, a transform relative to it's parent and any number of children,
+/// every field may be omitted:
+/// ```ron
+/// (
+///     shape: Some(Disc((radius: 1.0, color: Rgba(red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0)))),
+///     children: [
+///         (
+///             transform: (translation: (2.0, 0.0, 0.0), rotation: (0.0, 0.0, 0.0, 1.0), scale: (1.0, 1.0, 1.0)),
+///             shape: Some(Rectangle((size: (1.0, 1.0)))),
+///         ),
+///     ],
+/// )
+/// ```
+/// Spawn prefabs with [`ShapePrefabSpawner::spawn_shape_prefab`].
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Default)]
+#[uuid = "4c2e8d1a-7f3b-4e69-9a05-b8d6c1f2e347"]
+#[serde(default)]
+pub struct ShapePrefab {
+    pub transform: Transform,
+    pub shape: Option<DynamicShape>,
+    pub children: Vec<ShapePrefab>,
+}
+
+/// Loads [`ShapePrefab`] assets from RON files with the `.shape.ron` extension.
+#[derive(Default)]
+pub struct ShapePrefabLoader;
+
+impl AssetLoader for ShapePrefabLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let prefab: ShapePrefab = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(prefab));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["shape.ron"]
+    }
+}
+
+/// Component on the root entity of a spawned [`ShapePrefab`], the prefab's shapes are spawned as it's descendants.
+///
+/// Shapes are spawned once the asset has loaded and respawned whenever the asset is modified.
+#[derive(Component)]
+pub struct ShapePrefabRoot {
+    /// Config whose material and render settings are used for every shape in the prefab.
+    pub config: ShapeConfig,
+    spawned: Option<Entity>,
+}
+
+impl ShapePrefabRoot {
+    pub fn new(config: ShapeConfig) -> Self {
+        Self {
+            config,
+            spawned: None,
+        }
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to spawn [`ShapePrefab`] assets using the spawner's config.
+pub trait ShapePrefabSpawner<'w, 's>: ShapeSpawner<'w, 's> {
+    /// Spawns an entity at the config's transform that the prefab's shapes are spawned below once it has loaded.
+    fn spawn_shape_prefab(
+        &mut self,
+        prefab: Handle<ShapePrefab>,
+    ) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> ShapePrefabSpawner<'w, 's> for T {
+    fn spawn_shape_prefab(
+        &mut self,
+        prefab: Handle<ShapePrefab>,
+    ) -> ShapeEntityCommands<'w, 's, '_> {
+        let config = self.config().clone();
+        self.spawn_shape((
+            SpatialBundle::from_transform(config.transform),
+            prefab,
+            ShapePrefabRoot::new(config),
+        ))
+    }
+}
+
+/// Spawns the shape of a prefab node and it's children below the given parent.
+fn spawn_prefab_node(
+    commands: &mut Commands,
+    node: &ShapePrefab,
+    config: &mut ShapeConfig,
+    parent: Entity,
+) {
+    config.transform = node.transform;
+    let mut entity = match node.shape.clone() {
+        Some(DynamicShape::Line(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        Some(DynamicShape::QuadBezier(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        Some(DynamicShape::Disc(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        Some(DynamicShape::Rectangle(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        Some(DynamicShape::RegularPolygon(shape)) => {
+            commands.spawn(ShapeBundle::new(config, shape))
+        }
+        Some(DynamicShape::Morph(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        Some(DynamicShape::Triangle(shape)) => commands.spawn(ShapeBundle::new(config, shape)),
+        None => commands.spawn(SpatialBundle::from_transform(node.transform)),
+    };
+    if let ShapePipelineType::Shape3d = config.pipeline {
+        entity.insert(Shape3d);
+    }
+    let entity = entity.set_parent(parent).id();
+
+    for child in &node.children {
+        spawn_prefab_node(commands, child, config, entity);
+    }
+}
+
+/// Spawns the shapes of each [`ShapePrefabRoot`] once it's prefab has loaded, respawning them when the prefab is modified.
+pub fn spawn_shape_prefabs(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ShapePrefab>>,
+    prefabs: Res<Assets<ShapePrefab>>,
+    mut roots: Query<(Entity, Ref<Handle<ShapePrefab>>, &mut ShapePrefabRoot)>,
+) {
+    let modified: HashSet<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle.id()),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (entity, handle, mut root) in &mut roots {
+        if root.spawned.is_some() && !handle.is_changed() && !modified.contains(&handle.id()) {
+            continue;
+        }
+        let Some(prefab) = prefabs.get(&*handle) else {
+            continue;
+        };
+
+        if let Some(spawned) = root.spawned.take() {
+            commands.entity(spawned).despawn_recursive();
+        }
+        let mut config = root.config.clone();
+        // The root entity already has the config's transform, so the prefab's shapes are spawned relative to it
+        let spawned = commands
+            .spawn(SpatialBundle::default())
+            .set_parent(entity)
+            .id();
+        spawn_prefab_node(&mut commands, prefab, &mut config, spawned);
+        root.spawned = Some(spawned);
+    }
+}
+
+/// Plugin that loads and spawns [`ShapePrefab`] assets, requires the `prefab` feature.
+///
+/// With the `svg_import` feature SVG files are also loaded as prefabs, see [`svg_to_prefab`](crate::svg_import::svg_to_prefab).
+pub struct ShapePrefabPlugin;
+
+impl Plugin for ShapePrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ShapePrefab>()
+            .init_asset_loader::<ShapePrefabLoader>()
+            .add_system(spawn_shape_prefabs);
+
+        #[cfg(feature = "svg_import")]
+        app.init_asset_loader::<crate::svg_import::ShapeSvgLoader>();
+    }
+}
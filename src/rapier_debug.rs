@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use rapier2d::{
+    geometry::ShapeType,
+    math::{Isometry, Point, Real, Vector},
+    pipeline::{DebugRenderBackend, DebugRenderObject},
+};
+
+use crate::prelude::*;
+
+/// Backend for rapier's `DebugRenderPipeline` drawing with a [`ShapePainter`], requires the `rapier2d` feature.
+///
+/// Lines are drawn with the painter's thickness, thickness type and cap, balls are drawn as true circles and the outlines of convex colliders are filled.
+/// Colors are taken from the pipeline's `DebugRenderStyle`, the painter's color and hollow properties are restored when the backend is dropped.
+///
+/// With `bevy_rapier2d` the pipeline is rendered with the bodies, colliders, joints and narrow phase of the `RapierContext`,
+/// setting [`ShapeDebugRenderBackend::scale`] to the context's physics scale.
+pub struct ShapeDebugRenderBackend<'a, 'w, 's> {
+    painter: &'a mut ShapePainter<'w, 's>,
+    /// World units per physics unit.
+    pub scale: f32,
+    /// Alpha of the fill of convex colliders relative to their outline, 0.0 disables fills.
+    pub fill_alpha: f32,
+    color: Color,
+    hollow: bool,
+}
+
+impl<'a, 'w, 's> ShapeDebugRenderBackend<'a, 'w, 's> {
+    pub fn new(painter: &'a mut ShapePainter<'w, 's>) -> Self {
+        let (color, hollow) = (painter.color, painter.hollow);
+        Self {
+            painter,
+            scale: 1.0,
+            fill_alpha: 0.25,
+            color,
+            hollow,
+        }
+    }
+
+    /// Converts a point in physics space to world space.
+    fn point(&self, point: Point<Real>) -> Vec2 {
+        Vec2::new(point.x, point.y) * self.scale
+    }
+
+    /// Transforms vertices in the local space of a shape to world space.
+    fn points(
+        &self,
+        vertices: &[Point<Real>],
+        transform: &Isometry<Real>,
+        scale: &Vector<Real>,
+    ) -> Vec<Vec2> {
+        vertices
+            .iter()
+            .map(|vertex| self.point(transform * Point::from(vertex.coords.component_mul(scale))))
+            .collect()
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2, color: [f32; 4]) {
+        self.painter.color = hsla(color);
+        self.painter.line(a.extend(0.0), b.extend(0.0));
+    }
+
+    /// Fills a convex outline as a fan of triangles, anti-aliasing only the outer edges.
+    fn fill(&mut self, points: &[Vec2], color: [f32; 4]) {
+        if self.fill_alpha <= 0.0 || points.len() < 3 {
+            return;
+        }
+        self.painter.color = hsla(color);
+        self.painter.color.set_a(color[3] * self.fill_alpha);
+        let last = points.len() - 2;
+        for i in 1..=last {
+            let mut edges = Triangle::EDGE_BC;
+            if i == 1 {
+                edges |= Triangle::EDGE_AB;
+            }
+            if i == last {
+                edges |= Triangle::EDGE_CA;
+            }
+            let data = TriangleData::with_edges(
+                self.painter.config(),
+                [points[0], points[i], points[i + 1]],
+                edges,
+            );
+            self.painter.send(data);
+        }
+    }
+}
+
+impl<'a, 'w, 's> Drop for ShapeDebugRenderBackend<'a, 'w, 's> {
+    fn drop(&mut self) {
+        self.painter.color = self.color;
+        self.painter.hollow = self.hollow;
+    }
+}
+
+/// Converts rapier's debug render colors, which are in HSLA.
+fn hsla(color: [f32; 4]) -> Color {
+    Color::hsla(color[0], color[1], color[2], color[3])
+}
+
+/// Whether the object is a collider with a convex outline that can be filled.
+fn is_convex(object: DebugRenderObject) -> bool {
+    let DebugRenderObject::Collider(_, collider) = object else {
+        return false;
+    };
+    matches!(
+        collider.shape().shape_type(),
+        ShapeType::Ball
+            | ShapeType::Cuboid
+            | ShapeType::RoundCuboid
+            | ShapeType::Capsule
+            | ShapeType::Triangle
+            | ShapeType::RoundTriangle
+            | ShapeType::ConvexPolygon
+            | ShapeType::RoundConvexPolygon
+    )
+}
+
+impl<'a, 'w, 's> DebugRenderBackend for ShapeDebugRenderBackend<'a, 'w, 's> {
+    fn draw_line(
+        &mut self,
+        _object: DebugRenderObject,
+        a: Point<Real>,
+        b: Point<Real>,
+        color: [f32; 4],
+    ) {
+        let (a, b) = (self.point(a), self.point(b));
+        self.line(a, b, color);
+    }
+
+    fn draw_polyline(
+        &mut self,
+        object: DebugRenderObject,
+        vertices: &[Point<Real>],
+        indices: &[[u32; 2]],
+        transform: &Isometry<Real>,
+        scale: &Vector<Real>,
+        color: [f32; 4],
+    ) {
+        // Balls are drawn as circles rather than the polyline approximating them
+        if let DebugRenderObject::Collider(_, collider) = object {
+            if let Some(ball) = collider.shape().as_ball() {
+                let center = self.point(Point::from(transform.translation.vector));
+                let radius = ball.radius * self.scale;
+                let transform = self.painter.transform;
+                self.painter.translate(center.extend(0.0));
+                if self.fill_alpha > 0.0 {
+                    self.painter.hollow = false;
+                    self.painter.color = hsla(color);
+                    self.painter.color.set_a(color[3] * self.fill_alpha);
+                    self.painter.circle(radius);
+                }
+                self.painter.hollow = true;
+                self.painter.color = hsla(color);
+                self.painter.circle(radius);
+                self.painter.hollow = self.hollow;
+                self.painter.transform = transform;
+                return;
+            }
+        }
+
+        let points = self.points(vertices, transform, scale);
+        for [a, b] in indices {
+            self.line(points[*a as usize], points[*b as usize], color);
+        }
+    }
+
+    fn draw_line_strip(
+        &mut self,
+        object: DebugRenderObject,
+        vertices: &[Point<Real>],
+        transform: &Isometry<Real>,
+        scale: &Vector<Real>,
+        color: [f32; 4],
+        closed: bool,
+    ) {
+        let points = self.points(vertices, transform, scale);
+        if closed && is_convex(object) {
+            self.fill(&points, color);
+        }
+        for segment in points.windows(2) {
+            self.line(segment[0], segment[1], color);
+        }
+        if closed && points.len() > 2 {
+            self.line(points[points.len() - 1], points[0], color);
+        }
+    }
+}
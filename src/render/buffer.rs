@@ -0,0 +1,238 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource,
+        render_resource::*,
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+use bytemuck::Pod;
+
+use crate::render::{ShapeData, ShapeGpuCulling};
+
+/// Resource that controls how the GPU buffer holding shape instances is resized between frames.
+///
+/// The buffer is reused across frames, growing to the next power of two when it is too small
+/// and shrinking only once it has been underused for a number of consecutive frames.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+pub struct ShapeBufferPolicy {
+    /// Minimum size of the buffer in bytes.
+    pub min_size: u64,
+    /// Fraction of the buffer's capacity below which a frame's usage is considered underused.
+    pub shrink_threshold: f32,
+    /// Number of consecutive underused frames before the buffer is shrunk, `None` disables shrinking.
+    pub shrink_delay: Option<u32>,
+}
+
+impl Default for ShapeBufferPolicy {
+    fn default() -> Self {
+        Self {
+            min_size: 1 << 16,
+            shrink_threshold: 0.25,
+            shrink_delay: Some(300),
+        }
+    }
+}
+
+impl ShapeBufferPolicy {
+    /// Capacity to allocate for the given number of bytes.
+    pub fn capacity(&self, size: u64) -> u64 {
+        size.next_power_of_two().max(self.min_size)
+    }
+}
+
+/// Bytes uploaded to a GPU buffer that is reused across frames.
+///
+/// The data is pushed anew each frame, but only the range of bytes that differs from the previous upload is written,
+/// so frames where nothing has changed leave the GPU copy untouched. The buffer is resized as determined by the [`ShapeBufferPolicy`].
+#[derive(Default)]
+pub struct PersistentBuffer {
+    data: Vec<u8>,
+    uploaded: Vec<u8>,
+    buffer: Option<Buffer>,
+    underused_frames: u32,
+}
+
+impl PersistentBuffer {
+    /// Append the given values returning the range of bytes they occupy.
+    pub fn push<T: Pod>(&mut self, values: &[T]) -> Range<u64> {
+        let start = self.data.len() as u64;
+        self.data.extend_from_slice(bytemuck::cast_slice(values));
+        start..self.data.len() as u64
+    }
+
+    /// The GPU buffer containing the last uploaded data, if it has been written.
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Uploads the data pushed since the last upload and clears it for the next frame.
+    pub fn write(
+        &mut self,
+        label: &'static str,
+        usage: BufferUsages,
+        policy: &ShapeBufferPolicy,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+    ) {
+        let Self {
+            data,
+            uploaded,
+            buffer,
+            underused_frames,
+        } = self;
+
+        let size = data.len() as u64;
+        let capacity = buffer.as_ref().map_or(0, |buffer| buffer.size());
+        let usage_changed = buffer
+            .as_ref()
+            .map_or(false, |buffer| buffer.usage() != usage);
+        let reallocate = if size > capacity || usage_changed {
+            true
+        } else if (size as f32) < capacity as f32 * policy.shrink_threshold
+            && capacity > policy.capacity(size)
+        {
+            *underused_frames += 1;
+            policy
+                .shrink_delay
+                .map_or(false, |delay| *underused_frames >= delay)
+        } else {
+            *underused_frames = 0;
+            false
+        };
+
+        if reallocate {
+            *underused_frames = 0;
+            *buffer = Some(render_device.create_buffer(&BufferDescriptor {
+                label: Some(label),
+                size: policy.capacity(size),
+                usage,
+                mapped_at_creation: false,
+            }));
+            // A new buffer holds none of the previous frame's data
+            uploaded.clear();
+        }
+
+        if let Some(buffer) = buffer {
+            if let Some(range) = changed_range(data, uploaded) {
+                render_queue.write_buffer(buffer, range.start as u64, &data[range]);
+            }
+        }
+        std::mem::swap(data, uploaded);
+        data.clear();
+    }
+}
+
+/// Instance data for every shape type and view packed into a single vertex buffer.
+///
+/// Each [`ShapeDataBuffer`](super::ShapeDataBuffer) references a range of this buffer,
+/// the data is uploaded once per frame after all shape buffers have been prepared.
+/// This only shares the allocation and upload between batches, each batch is still drawn separately
+/// with the pipeline of it's shape type so shapes of different types are never merged into one draw.
+/// Only the range of bytes that differs from the previous frame is uploaded, so frames where
+/// no shapes have changed leave the GPU copy untouched.
+#[derive(Resource, Default)]
+pub struct ShapeInstanceBuffer {
+    instances: PersistentBuffer,
+}
+
+impl ShapeInstanceBuffer {
+    /// Append the given instances returning the range of bytes they occupy.
+    pub fn push<T: ShapeData>(&mut self, instances: &[T]) -> Range<u64> {
+        self.instances.push(instances)
+    }
+
+    /// The GPU buffer containing this frame's instance data, if it has been written.
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.instances.buffer()
+    }
+}
+
+/// Range of bytes in which the given data differs from the previously uploaded data, aligned for buffer copies.
+fn changed_range(data: &[u8], uploaded: &[u8]) -> Option<Range<usize>> {
+    let start = data.iter().zip(uploaded).position(|(a, b)| a != b);
+    let start = match start {
+        Some(start) => start,
+        None if data.len() > uploaded.len() => uploaded.len(),
+        None => return None,
+    };
+    let end = if data.len() == uploaded.len() {
+        data.len()
+            - data
+                .iter()
+                .rev()
+                .zip(uploaded.iter().rev())
+                .position(|(a, b)| a != b)
+                .unwrap_or(0)
+    } else {
+        data.len()
+    };
+
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT as usize;
+    let start = start / alignment * alignment;
+    let end = ((end + alignment - 1) / alignment * alignment).min(data.len());
+    Some(start..end)
+}
+
+/// Uploads the contents of [`ShapeInstanceBuffer`] and clears it for the next frame.
+///
+/// The GPU buffer is only reallocated as determined by the [`ShapeBufferPolicy`].
+pub fn write_shape_instance_buffer(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    policy: Res<ShapeBufferPolicy>,
+    culling: Option<Res<ShapeGpuCulling>>,
+    mut instance_buffer: ResMut<ShapeInstanceBuffer>,
+) {
+    // Instances are additionally read as a storage buffer when culled on the GPU
+    let mut usage = BufferUsages::VERTEX | BufferUsages::COPY_DST;
+    if culling.map_or(false, |culling| culling.enabled) {
+        usage |= BufferUsages::STORAGE;
+    }
+    instance_buffer.instances.write(
+        "shape_instance_buffer",
+        usage,
+        &policy,
+        &render_device,
+        &render_queue,
+    );
+}
+
+/// Instance data of static shapes packed into a vertex buffer of it's own.
+///
+/// Unlike the [`ShapeInstanceBuffer`] this buffer persists across frames, it is only replaced when the set of
+/// [`StaticShape`](crate::shapes::StaticShape)s it was built from changes.
+#[derive(Default)]
+pub struct StaticShapeBuffer {
+    data: Vec<u8>,
+    buffer: Option<Buffer>,
+}
+
+impl StaticShapeBuffer {
+    /// Append the given instances returning the range of bytes they occupy.
+    pub fn push<T: ShapeData>(&mut self, instances: &[T]) -> Range<u64> {
+        let start = self.data.len() as u64;
+        self.data.extend_from_slice(bytemuck::cast_slice(instances));
+        start..self.data.len() as u64
+    }
+
+    /// Uploads the pushed instances to a new GPU buffer, replacing the previous one.
+    pub fn write(&mut self, render_device: &RenderDevice) {
+        self.buffer = (!self.data.is_empty()).then(|| {
+            render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("static_shape_buffer"),
+                contents: &self.data,
+                usage: BufferUsages::VERTEX,
+            })
+        });
+        self.data.clear();
+    }
+
+    /// The GPU buffer containing the static instances, if there are any.
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    num::NonZeroU32,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::Buffer,
+        renderer::{RenderDevice, RenderQueue},
+        Extract, RenderApp, RenderSet,
+    },
+    tasks::AsyncComputeTaskPool,
+};
+use wgpu::{
+    util::align_to, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor,
+    Extent3d, ImageCopyBuffer, ImageDataLayout, Maintain, MapMode, TextureFormat,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use crate::prelude::*;
+
+/// Event requesting the current contents of a [`Canvas`] to be saved as a PNG, requires the `save_canvas` feature.
+///
+/// The canvas' texture is read back without blocking the render world and encoded on the [`AsyncComputeTaskPool`],
+/// a [`CanvasSaved`] event is sent once the file has been written or saving failed.
+#[derive(Clone, Debug)]
+pub struct SaveCanvas {
+    /// Entity with the [`Canvas`] component.
+    pub entity: Entity,
+    pub path: PathBuf,
+}
+
+/// Event sent once a [`SaveCanvas`] request has completed.
+#[derive(Clone, Debug)]
+pub struct CanvasSaved {
+    pub entity: Entity,
+    pub path: PathBuf,
+    /// Description of the error if the canvas couldn't be read back or written.
+    pub result: Result<(), String>,
+}
+
+/// Requests received this frame, extracted to the render world.
+#[derive(Resource, Clone, Default)]
+struct CanvasSaveQueue(Vec<(SaveCanvas, Handle<Image>)>);
+
+/// Completed saves, shared with the main world.
+#[derive(Resource, Clone, Default)]
+struct CanvasSaveResults(Arc<Mutex<Vec<CanvasSaved>>>);
+
+/// Readback waiting for it's buffer to be mapped.
+struct PendingCanvasSave {
+    request: SaveCanvas,
+    buffer: Buffer,
+    /// Result of mapping the buffer, set by the map callback.
+    mapped: Arc<Mutex<Option<Result<(), BufferAsyncError>>>>,
+    size: UVec2,
+    padded_bytes_per_row: u32,
+    /// Whether the texture stores blue before red.
+    bgra: bool,
+}
+
+/// Readbacks in flight in the render world.
+#[derive(Resource, Default)]
+struct PendingCanvasSaves(Vec<PendingCanvasSave>);
+
+/// Queues each [`SaveCanvas`] request with the canvas' current image.
+fn queue_canvas_saves(
+    mut queue: ResMut<CanvasSaveQueue>,
+    mut requests: EventReader<SaveCanvas>,
+    results: Res<CanvasSaveResults>,
+    canvases: Query<&Canvas>,
+) {
+    queue.0.clear();
+    for request in requests.iter() {
+        match canvases.get(request.entity) {
+            Ok(canvas) => queue.0.push((request.clone(), canvas.image.clone())),
+            Err(_) => results.0.lock().unwrap().push(CanvasSaved {
+                entity: request.entity,
+                path: request.path.clone(),
+                result: Err(format!("{:?} has no canvas", request.entity)),
+            }),
+        }
+    }
+}
+
+/// Sends a [`CanvasSaved`] event for each completed save.
+fn send_canvas_saved_events(results: Res<CanvasSaveResults>, mut events: EventWriter<CanvasSaved>) {
+    events.send_batch(results.0.lock().unwrap().drain(..));
+}
+
+fn extract_canvas_saves(
+    mut queue: ResMut<CanvasSaveQueue>,
+    requests: Extract<Res<CanvasSaveQueue>>,
+) {
+    queue.0.clone_from(&requests.0);
+}
+
+/// Copies the texture of each requested canvas into a buffer that is mapped asynchronously.
+fn copy_canvas_saves(
+    queue: Res<CanvasSaveQueue>,
+    results: Res<CanvasSaveResults>,
+    mut pending: ResMut<PendingCanvasSaves>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for (request, handle) in &queue.0 {
+        let Some(gpu_image) = images.get(handle) else {
+            results.0.lock().unwrap().push(CanvasSaved {
+                entity: request.entity,
+                path: request.path.clone(),
+                result: Err("Canvas image is not loaded".to_string()),
+            });
+            continue;
+        };
+        let bgra = match gpu_image.texture_format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            format => {
+                results.0.lock().unwrap().push(CanvasSaved {
+                    entity: request.entity,
+                    path: request.path.clone(),
+                    result: Err(format!("Unsupported canvas format {format:?}")),
+                });
+                continue;
+            }
+        };
+
+        let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+        let padded_bytes_per_row = align_to(width * 4, COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("canvas_save_buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("canvas_save_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        render_queue.submit([encoder.finish()]);
+
+        let mapped = Arc::new(Mutex::new(None));
+        let callback_mapped = mapped.clone();
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            *callback_mapped.lock().unwrap() = Some(result);
+        });
+        pending.0.push(PendingCanvasSave {
+            request: request.clone(),
+            buffer,
+            mapped,
+            size: UVec2::new(width, height),
+            padded_bytes_per_row,
+            bgra,
+        });
+    }
+}
+
+/// Polls pending readbacks without blocking, spawning a task to encode and write each one that has been mapped.
+fn poll_canvas_saves(
+    results: Res<CanvasSaveResults>,
+    mut pending: ResMut<PendingCanvasSaves>,
+    render_device: Res<RenderDevice>,
+) {
+    if pending.0.is_empty() {
+        return;
+    }
+    render_device.wgpu_device().poll(Maintain::Poll);
+
+    pending.0.retain(|save| {
+        let Some(result) = save.mapped.lock().unwrap().take() else {
+            return true;
+        };
+        if result.is_err() {
+            results.0.lock().unwrap().push(CanvasSaved {
+                entity: save.request.entity,
+                path: save.request.path.clone(),
+                result: Err("Failed to read back canvas".to_string()),
+            });
+            return false;
+        }
+
+        // Rows are padded to the copy alignment
+        let row_bytes = save.size.x as usize * 4;
+        let data = save.buffer.slice(..).get_mapped_range();
+        let mut pixels = Vec::with_capacity(row_bytes * save.size.y as usize);
+        for row in data.chunks(save.padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..row_bytes]);
+        }
+        drop(data);
+        save.buffer.unmap();
+
+        let (request, size, bgra) = (save.request.clone(), save.size, save.bgra);
+        let results = results.0.clone();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                if bgra {
+                    for pixel in pixels.chunks_exact_mut(4) {
+                        pixel.swap(0, 2);
+                    }
+                }
+                let result = image::save_buffer_with_format(
+                    &request.path,
+                    &pixels,
+                    size.x,
+                    size.y,
+                    image::ColorType::Rgba8,
+                    image::ImageFormat::Png,
+                )
+                .map_err(|error| error.to_string());
+                results.lock().unwrap().push(CanvasSaved {
+                    entity: request.entity,
+                    path: request.path,
+                    result,
+                });
+            })
+            .detach();
+        false
+    });
+}
+
+/// Plugin that saves canvases in response to [`SaveCanvas`] events, requires the `save_canvas` feature.
+///
+/// Added by [`Shape2dPlugin`] when the feature is enabled.
+pub struct CanvasSavePlugin;
+
+impl Plugin for CanvasSavePlugin {
+    fn build(&self, app: &mut App) {
+        let results = CanvasSaveResults::default();
+        app.init_resource::<CanvasSaveQueue>()
+            .insert_resource(results.clone())
+            .add_event::<SaveCanvas>()
+            .add_event::<CanvasSaved>()
+            .add_system(send_canvas_saved_events.in_base_set(CoreSet::First))
+            .add_system(queue_canvas_saves.in_base_set(CoreSet::Last));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(results)
+            .init_resource::<CanvasSaveQueue>()
+            .init_resource::<PendingCanvasSaves>()
+            .add_system(extract_canvas_saves.in_schedule(ExtractSchedule))
+            .add_systems(
+                (copy_canvas_saves, poll_canvas_saves)
+                    .chain()
+                    .in_set(RenderSet::Cleanup),
+            );
+    }
+}
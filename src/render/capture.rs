@@ -0,0 +1,350 @@
+use std::{
+    num::NonZeroU32,
+    sync::{mpsc, Arc, Mutex},
+};
+
+use bevy::{
+    core_pipeline::{clear_color::ClearColorConfig, tonemapping::Tonemapping},
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssets,
+        renderer::{RenderDevice, RenderQueue},
+        view::RenderLayers,
+        Extract, RenderApp, RenderSet,
+    },
+    window::PrimaryWindow,
+};
+use wgpu::{
+    util::align_to, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
+    ImageCopyBuffer, ImageDataLayout, Maintain, MapMode, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use crate::{prelude::*, render::target_scale_factor};
+
+/// Resource that renders the shapes seen by a camera, and nothing else, into an image read back to the main world.
+///
+/// Each capture spawns an offscreen camera matching the source camera's transform, projection and viewport size,
+/// which clears to transparent and draws only the shapes on the source camera's render layers. Meshes, sprites and
+/// `bevy_ui` nodes are hidden, as are shapes in the [`ShapePass::Ui`] pass or only visible to specific cameras.
+/// Once rendered the image is read back and sent as a [`ShapeLayerCaptured`] event, typically two frames later,
+/// allowing the vector overlay to be diffed in visual tests or exported without the scene behind it.
+#[derive(Resource, Default)]
+pub struct ShapeLayerCaptures {
+    requests: Vec<Entity>,
+}
+
+impl ShapeLayerCaptures {
+    /// Requests a capture of the shapes drawn by the given camera.
+    pub fn capture(&mut self, camera: Entity) {
+        self.requests.push(camera);
+    }
+}
+
+/// Event sent when a capture requested with [`ShapeLayerCaptures`] has been read back.
+pub struct ShapeLayerCaptured {
+    /// Camera the capture was requested for.
+    pub camera: Entity,
+    /// Captured shapes with a transparent background, in [`TextureFormat::Rgba8UnormSrgb`] at the camera's physical viewport size.
+    pub image: Image,
+}
+
+/// Component added to the offscreen cameras spawned by [`ShapeLayerCaptures`].
+#[derive(Component, Clone)]
+pub struct ShapeLayerCaptureCamera {
+    /// Camera being captured.
+    pub source: Entity,
+    /// Render layers of the source camera, used in place of the capture camera's own layers when drawing shapes.
+    pub(crate) layers: RenderLayers,
+    image: Handle<Image>,
+    /// Number of frames the camera has been rendering for.
+    frames: u32,
+}
+
+/// Images read back in the render world, shared with the main world.
+#[derive(Resource, Clone, Default)]
+struct ShapeLayerReadbacks(Arc<Mutex<Vec<(Entity, Image)>>>);
+
+/// Capture cameras ready to be read back this frame.
+#[derive(Resource, Default)]
+struct ExtractedShapeLayerCaptures(Vec<(Entity, Handle<Image>)>);
+
+/// Creates a target texture for a capture that can be copied from.
+fn create_capture_image(assets: &mut Assets<Image>, size: UVec2) -> Handle<Image> {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+
+    image.resize(size);
+    assets.add(image)
+}
+
+/// Spawns a capture camera for each requested capture and keeps existing ones in sync with their source camera.
+#[allow(clippy::type_complexity)]
+fn update_shape_layer_captures(
+    mut commands: Commands,
+    mut captures: ResMut<ShapeLayerCaptures>,
+    mut images: ResMut<Assets<Image>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    windows: Query<&Window>,
+    sources: Query<
+        (
+            &Camera,
+            &GlobalTransform,
+            Option<&OrthographicProjection>,
+            Option<&Projection>,
+            Option<&Camera3d>,
+            Option<&Tonemapping>,
+            Option<&RenderLayers>,
+            Option<&ShapePixelScale>,
+        ),
+        Without<ShapeLayerCaptureCamera>,
+    >,
+    mut capture_cameras: Query<(
+        Entity,
+        &mut ShapeLayerCaptureCamera,
+        &mut Transform,
+        &mut GlobalTransform,
+    )>,
+) {
+    for (entity, mut capture, mut transform, mut global_transform) in &mut capture_cameras {
+        let Ok((_, source_transform, ..)) = sources.get(capture.source) else {
+            images.remove(&capture.image);
+            commands.entity(entity).despawn();
+            continue;
+        };
+        *global_transform = *source_transform;
+        *transform = source_transform.compute_transform();
+        capture.frames += 1;
+    }
+
+    let primary_window = primary_window.get_single().ok();
+    for source in captures.requests.drain(..) {
+        let Ok((
+            camera,
+            global_transform,
+            orthographic,
+            projection,
+            camera_3d,
+            tonemapping,
+            layers,
+            pixel_scale,
+        )) = sources.get(source)
+        else {
+            warn!("Requested shape layer capture of {source:?}, which is not a camera.");
+            continue;
+        };
+        let Some(size) = camera.physical_viewport_size() else {
+            warn!("Requested shape layer capture of {source:?}, which has no render target.");
+            continue;
+        };
+
+        // The image target has a scale factor of 1, so window sized projections are scaled to match
+        let scale_factor = target_scale_factor(camera, primary_window, &windows).unwrap_or(1.0);
+        let image = create_capture_image(&mut images, size);
+        let capture_camera = Camera {
+            target: RenderTarget::Image(image.clone()),
+            order: camera.order,
+            hdr: camera.hdr,
+            ..default()
+        };
+        let clear_color = ClearColorConfig::Custom(Color::NONE);
+        let transform = global_transform.compute_transform();
+        let mut entity = match (camera_3d, projection) {
+            (Some(camera_3d), Some(projection)) => {
+                let mut projection = projection.clone();
+                if let Projection::Orthographic(orthographic) = &mut projection {
+                    orthographic.scale /= scale_factor;
+                }
+                commands.spawn(Camera3dBundle {
+                    camera: capture_camera,
+                    camera_3d: Camera3d {
+                        clear_color,
+                        ..camera_3d.clone()
+                    },
+                    projection,
+                    tonemapping: tonemapping.cloned().unwrap_or_default(),
+                    transform,
+                    global_transform: *global_transform,
+                    ..default()
+                })
+            }
+            _ => {
+                let mut projection = orthographic.cloned().unwrap_or_default();
+                projection.scale /= scale_factor;
+                commands.spawn(Camera2dBundle {
+                    camera: capture_camera,
+                    camera_2d: Camera2d { clear_color },
+                    projection,
+                    tonemapping: tonemapping.cloned().unwrap_or_default(),
+                    transform,
+                    global_transform: *global_transform,
+                    ..default()
+                })
+            }
+        };
+        entity.insert((
+            RenderLayers::none(),
+            ShapePixelScale(pixel_scale.map_or(scale_factor, |scale| scale.0)),
+            ShapeLayerCaptureCamera {
+                source,
+                layers: layers.copied().unwrap_or_default(),
+                image,
+                frames: 0,
+            },
+        ));
+        #[cfg(feature = "ui")]
+        entity.insert(UiCameraConfig { show_ui: false });
+    }
+}
+
+/// Sends a [`ShapeLayerCaptured`] event for each capture read back by the render world and despawns it's camera.
+fn receive_shape_layer_captures(
+    mut commands: Commands,
+    readbacks: Res<ShapeLayerReadbacks>,
+    mut images: ResMut<Assets<Image>>,
+    capture_cameras: Query<&ShapeLayerCaptureCamera>,
+    mut events: EventWriter<ShapeLayerCaptured>,
+) {
+    for (entity, image) in readbacks.0.lock().unwrap().drain(..) {
+        // A capture may be read back more than once with pipelined rendering
+        let Ok(capture) = capture_cameras.get(entity) else {
+            continue;
+        };
+        images.remove(&capture.image);
+        commands.entity(entity).despawn();
+        events.send(ShapeLayerCaptured {
+            camera: capture.source,
+            image,
+        });
+    }
+}
+
+/// Extracts the capture cameras that have rendered at least one full frame.
+fn extract_shape_layer_captures(
+    mut extracted: ResMut<ExtractedShapeLayerCaptures>,
+    capture_cameras: Extract<Query<(Entity, &ShapeLayerCaptureCamera)>>,
+) {
+    extracted.0.clear();
+    for (entity, capture) in &capture_cameras {
+        if capture.frames >= 2 {
+            extracted.0.push((entity, capture.image.clone()));
+        }
+    }
+}
+
+/// Copies each extracted capture's texture into a buffer and waits for it to be mapped.
+fn read_back_shape_layer_captures(
+    extracted: Res<ExtractedShapeLayerCaptures>,
+    readbacks: Res<ShapeLayerReadbacks>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for (entity, handle) in &extracted.0 {
+        let Some(gpu_image) = images.get(handle) else {
+            continue;
+        };
+        let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = align_to(unpadded_bytes_per_row, COPY_BYTES_PER_ROW_ALIGNMENT);
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("shape_layer_capture_buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("shape_layer_capture_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        render_queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        render_device.wgpu_device().poll(Maintain::Wait);
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            warn!("Failed to read back shape layer capture.");
+            continue;
+        }
+
+        // Rows are padded to the copy alignment
+        let data = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+        drop(data);
+        buffer.unmap();
+
+        let image = Image::new(
+            size,
+            TextureDimension::D2,
+            pixels,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        readbacks.0.lock().unwrap().push((*entity, image));
+    }
+}
+
+/// Plugin that sets up [`ShapeLayerCaptures`], added by [`Shape2dPlugin`].
+pub struct ShapeLayerCapturePlugin;
+
+impl Plugin for ShapeLayerCapturePlugin {
+    fn build(&self, app: &mut App) {
+        let readbacks = ShapeLayerReadbacks::default();
+        app.init_resource::<ShapeLayerCaptures>()
+            .insert_resource(readbacks.clone())
+            .add_event::<ShapeLayerCaptured>()
+            .add_system(receive_shape_layer_captures.in_base_set(CoreSet::First))
+            .add_system(update_shape_layer_captures.in_base_set(CoreSet::Last));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(readbacks)
+            .init_resource::<ExtractedShapeLayerCaptures>()
+            .add_system(extract_shape_layer_captures.in_schedule(ExtractSchedule))
+            .add_system(read_back_shape_layer_captures.in_set(RenderSet::Cleanup));
+    }
+}
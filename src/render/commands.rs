@@ -10,7 +10,8 @@ use bevy::{
     render::{
         render_asset::RenderAssets,
         render_phase::{
-            PhaseItem, RenderCommand, RenderCommandResult, SetItemPipeline, TrackedRenderPass,
+            BatchedPhaseItem, PhaseItem, RenderCommand, RenderCommandResult, SetItemPipeline,
+            TrackedRenderPass,
         },
         render_resource::BindGroup,
         view::ViewUniformOffset,
@@ -32,6 +33,14 @@ pub type DrawShapeCommand = (
     DrawShape,
 );
 
+/// Draw command for phase items covering a range of instances within a [`ShapeDataBuffer`].
+pub type DrawShapeRangeCommand = (
+    SetItemPipeline,
+    SetShapeViewBindGroup<0>,
+    SetShapeTextureBindGroup<1>,
+    DrawShapeRange,
+);
+
 #[derive(Component, Debug)]
 pub struct ShapeViewBindGroup {
     value: BindGroup,
@@ -42,15 +51,25 @@ pub fn queue_shape_view_bind_groups(
     render_device: Res<RenderDevice>,
     shape_pipeline: Res<ShapePipelines>,
     view_uniforms: Res<ViewUniforms>,
-    views: Query<Entity, With<ExtractedView>>,
+    shape_view_uniforms: Res<ShapeViewUniforms>,
+    views: Query<Entity, (With<ExtractedView>, With<ShapeViewUniformOffset>)>,
 ) {
-    if let Some(view_binding) = view_uniforms.uniforms.binding() {
+    if let (Some(view_binding), Some(shape_view_binding)) = (
+        view_uniforms.uniforms.binding(),
+        shape_view_uniforms.uniforms.binding(),
+    ) {
         for entity in views.iter() {
             let view_bind_group = render_device.create_bind_group(&BindGroupDescriptor {
-                entries: &[BindGroupEntry {
-                    binding: 0,
-                    resource: view_binding.clone(),
-                }],
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: view_binding.clone(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: shape_view_binding.clone(),
+                    },
+                ],
                 label: Some("shape_view_bind_group"),
                 layout: &shape_pipeline.view_layout,
             });
@@ -65,19 +84,30 @@ pub fn queue_shape_view_bind_groups(
 pub struct SetShapeViewBindGroup<const I: usize>;
 
 impl<const I: usize, P: PhaseItem> RenderCommand<P> for SetShapeViewBindGroup<I> {
-    type ViewWorldQuery = (Read<ViewUniformOffset>, Read<ShapeViewBindGroup>);
+    type ViewWorldQuery = (
+        Read<ViewUniformOffset>,
+        Read<ShapeViewUniformOffset>,
+        Read<ShapeViewBindGroup>,
+    );
     type ItemWorldQuery = ();
     type Param = ();
 
     #[inline]
     fn render<'w>(
         _item: &P,
-        (view_uniform, shape_view_bind_group): ROQueryItem<'w, Self::ViewWorldQuery>,
+        (view_uniform, shape_view_uniform, shape_view_bind_group): ROQueryItem<
+            'w,
+            Self::ViewWorldQuery,
+        >,
         _entity: ROQueryItem<'w, Self::ItemWorldQuery>,
         _param: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        pass.set_bind_group(I, &shape_view_bind_group.value, &[view_uniform.offset]);
+        pass.set_bind_group(
+            I,
+            &shape_view_bind_group.value,
+            &[view_uniform.offset, shape_view_uniform.offset],
+        );
         RenderCommandResult::Success
     }
 }
@@ -103,7 +133,10 @@ pub fn queue_shape_texture_bind_groups(
                     .or_insert_with(|| {
                         render_device.create_bind_group(&BindGroupDescriptor {
                             label: Some("shape_texture_bind_group"),
-                            layout: &shape_pipelines.texture_layout,
+                            layout: match buffer.material.texture_array {
+                                true => &shape_pipelines.texture_array_layout,
+                                false => &shape_pipelines.texture_layout,
+                            },
                             entries: &[
                                 BindGroupEntry {
                                     binding: 0,
@@ -147,7 +180,7 @@ impl<const I: usize, P: PhaseItem> RenderCommand<P> for SetShapeTextureBindGroup
 pub struct DrawShape;
 
 impl<P: PhaseItem> RenderCommand<P> for DrawShape {
-    type Param = ();
+    type Param = SRes<ShapeInstanceBuffer>;
     type ViewWorldQuery = ();
     type ItemWorldQuery = Read<ShapeDataBuffer>;
 
@@ -156,12 +189,45 @@ impl<P: PhaseItem> RenderCommand<P> for DrawShape {
         _item: &P,
         _view: (),
         shape_buffer: &'w ShapeDataBuffer,
-        _param: SystemParamItem<'w, '_, Self::Param>,
+        instance_buffer: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        pass.set_vertex_buffer(0, shape_buffer.buffer.slice(..));
+        let Some(buffer) = shape_buffer.buffer(instance_buffer.into_inner()) else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_vertex_buffer(0, buffer.slice(shape_buffer.range.clone()));
         pass.draw(0..6, 0..shape_buffer.length as u32);
 
         RenderCommandResult::Success
     }
 }
+
+/// Draws the instances within the item's batch range, or the entire buffer if it has none.
+pub struct DrawShapeRange;
+
+impl<P: BatchedPhaseItem> RenderCommand<P> for DrawShapeRange {
+    type Param = SRes<ShapeInstanceBuffer>;
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = Read<ShapeDataBuffer>;
+
+    #[inline]
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        shape_buffer: &'w ShapeDataBuffer,
+        instance_buffer: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(buffer) = shape_buffer.buffer(instance_buffer.into_inner()) else {
+            return RenderCommandResult::Failure;
+        };
+        let range = item
+            .batch_range()
+            .clone()
+            .unwrap_or(0..shape_buffer.length as u32);
+        pass.set_vertex_buffer(0, buffer.slice(shape_buffer.range.clone()));
+        pass.draw(0..6, range);
+
+        RenderCommandResult::Success
+    }
+}
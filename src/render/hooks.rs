@@ -0,0 +1,115 @@
+use std::{any::TypeId, borrow::Cow};
+
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        extract_resource::ExtractResource,
+        render_resource::{ShaderImport, ShaderRef},
+    },
+    utils::HashMap,
+};
+
+use super::ShapeData;
+
+/// Asset containing a WGSL snippet that is injected into the fragment stage of a shape shader.
+///
+/// The snippet must define a function with the following signature:
+/// ```wgsl
+/// fn get_color(in: ShapeHookInput) -> vec4<f32>
+/// ```
+/// Where `ShapeHookInput` contains the shape's `color`, with alpha already masked to the shape, it's `uv`
+/// and the `user_data` set on the shape.
+/// The returned color replaces the shape's color before alpha mode and texture handling is applied.
+///
+/// All items available to the shape shader such as the `view` uniform can be used within the snippet,
+/// `shape_view.time` and `shape_view.delta_time` allow hooks to animate without updating shapes each frame.
+/// Hooks are supported by any shape whose shader is referenced by handle and declares a `#define_import_path`, this includes all built-in shapes.
+#[derive(TypeUuid, Clone, Debug)]
+#[uuid = "5c4a8d1e-73f2-4b0c-9e61-2d8a0f6b93c7"]
+pub struct ShapeHook {
+    source: Cow<'static, str>,
+}
+
+impl ShapeHook {
+    /// Create a [`ShapeHook`] from the given WGSL source.
+    pub fn from_wgsl(source: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    /// The WGSL source of this hook.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Resource mapping each pair of shape type and [`ShapeHook`] to a generated shader.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct ShapeHookShaders {
+    shaders: HashMap<(TypeId, HandleId), Handle<Shader>>,
+}
+
+impl ShapeHookShaders {
+    /// Get the generated shader for the given shape type and hook, if it has been created.
+    pub fn get<T: ShapeData>(&self, hook: &Handle<ShapeHook>) -> Option<Handle<Shader>> {
+        self.shaders.get(&(TypeId::of::<T>(), hook.id())).cloned()
+    }
+}
+
+fn shape_import<T: ShapeData>(shaders: &Assets<Shader>) -> Option<String> {
+    let ShaderRef::Handle(handle) = T::shader() else {
+        return None;
+    };
+    match shaders.get(&handle)?.import_path()? {
+        ShaderImport::Custom(path) => Some(format!("#import {path}")),
+        ShaderImport::AssetPath(path) => Some(format!("#import \"{path}\"")),
+    }
+}
+
+/// Generates a shader for each [`ShapeHook`] that imports the shape shader for the given type followed by the hook's source.
+pub fn prepare_shape_hooks<T: ShapeData>(
+    mut events: EventReader<AssetEvent<ShapeHook>>,
+    hooks: Res<Assets<ShapeHook>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut hook_shaders: ResMut<ShapeHookShaders>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                let Some(hook) = hooks.get(handle) else {
+                    continue;
+                };
+                let Some(import) = shape_import::<T>(&shaders) else {
+                    warn!("Shape shader does not declare an import path, hooks are not supported.");
+                    continue;
+                };
+
+                let shader = Shader::from_wgsl(format!("{}\n\n{}", import, hook.source));
+                let key = (TypeId::of::<T>(), handle.id());
+                match hook_shaders.shaders.get(&key) {
+                    // Replace the existing shader so that cached pipelines are re-specialized
+                    Some(existing) => {
+                        if let Some(existing) = shaders.get_mut(existing) {
+                            *existing = shader;
+                        }
+                    }
+                    None => {
+                        let shader = shaders.add(shader);
+                        hook_shaders.shaders.insert(key, shader);
+                    }
+                }
+            }
+            AssetEvent::Removed { handle } => {
+                if let Some(shader) = hook_shaders
+                    .shaders
+                    .remove(&(TypeId::of::<T>(), handle.id()))
+                {
+                    shaders.remove(shader);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,434 @@
+// The field checks generated by deriving `ShaderType` are reported as unused by newer compilers
+#![allow(dead_code)]
+
+use std::ops::Range;
+
+use bevy::{
+    core_pipeline::{core_2d, core_3d},
+    ecs::{
+        query::ROQueryItem,
+        system::{
+            lifetimeless::{Read, SRes},
+            SystemParamItem,
+        },
+    },
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource,
+        primitives::Frustum,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType},
+        render_phase::{
+            PhaseItem, RenderCommand, RenderCommandResult, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        view::ExtractedView,
+    },
+};
+
+use crate::render::*;
+
+/// Name of the [`ShapeCullNode`] in the core 2d and 3d render graphs.
+pub const SHAPE_CULL_NODE: &str = "shape_cull_pass";
+
+const CULL_WORKGROUP_SIZE: u32 = 64;
+
+/// Resource that enables culling of shapes on the GPU.
+///
+/// When enabled each batch of shapes is culled against the view frustum in a compute pass,
+/// visible instances are compacted into a separate buffer and drawn with a single indirect draw.
+/// The buffers used for culling are shared by every batch and reused across frames, see [`ShapeCullBuffers`].
+/// This avoids any per shape visibility work on the CPU for very large numbers of shapes,
+/// retained shapes should additionally be spawned with [`NoFrustumCulling`](bevy::render::view::NoFrustumCulling)
+/// to skip CPU frustum culling entirely.
+///
+/// Requires compute shader and storage buffer support, on devices without it such as WebGL2 this has no effect.
+/// 2D shapes sorted individually with [`ShapeSort2d::Axis`] and [`StaticShape`](crate::shapes::StaticShape)s are never culled on the GPU.
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub struct ShapeGpuCulling {
+    pub enabled: bool,
+}
+
+/// Range of the bounding radii of a batch of shapes within the [`ShapeCullBuffers`], for batches that will be culled on the GPU.
+#[derive(Component, Clone)]
+pub struct ShapeCullInstances {
+    radii: Range<u32>,
+    /// Size of a single instance in bytes.
+    stride: u32,
+}
+
+#[derive(ShaderType, Clone, Copy)]
+struct CullParams {
+    stride: u32,
+    count: u32,
+    offset: u32,
+    radii: u32,
+    indirect: u32,
+    planes: [Vec4; 6],
+}
+
+/// Buffers shared by every batch of shapes culled on the GPU, reused across frames and only reallocated when too small.
+#[derive(Resource)]
+pub struct ShapeCullBuffers {
+    /// Bounding radius of each culled instance, only the range that changed since last frame is uploaded.
+    radii: PersistentBuffer,
+    /// Visible instances of each batch, compacted within the same range of bytes the batch occupies in the [`ShapeInstanceBuffer`].
+    visible: Option<Buffer>,
+    /// Draw arguments of each batch, rewritten every frame to reset the instance counts of the last cull pass.
+    indirect: BufferVec<[u32; 4]>,
+    params: DynamicUniformBuffer<CullParams>,
+    bind_group: Option<BindGroup>,
+}
+
+impl Default for ShapeCullBuffers {
+    fn default() -> Self {
+        Self {
+            radii: default(),
+            visible: None,
+            indirect: BufferVec::new(BufferUsages::INDIRECT | BufferUsages::STORAGE),
+            params: default(),
+            bind_group: None,
+        }
+    }
+}
+
+impl ShapeCullBuffers {
+    /// Append the bounding radii of a batch of instances, in the order they were pushed to the [`ShapeInstanceBuffer`].
+    pub fn push<T: ShapeData>(&mut self, radii: &[f32]) -> ShapeCullInstances {
+        let range = self.radii.push(radii);
+        ShapeCullInstances {
+            radii: range.start as u32 / 4..range.end as u32 / 4,
+            stride: std::mem::size_of::<T>() as u32,
+        }
+    }
+}
+
+/// Slots of a batch culled on the GPU within the [`ShapeCullBuffers`].
+#[derive(Component)]
+pub struct ShapeIndirectBatch {
+    params_offset: u32,
+    indirect_offset: u64,
+    workgroups: u32,
+}
+
+#[derive(Resource)]
+pub struct ShapeCullPipeline {
+    layout: BindGroupLayout,
+    pipeline: CachedComputePipelineId,
+}
+
+fn storage_entry(binding: u32, read_only: bool) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+/// Whether the render device supports the compute shaders and storage buffers needed to cull shapes.
+pub(crate) fn supports_gpu_culling(render_device: &RenderDevice) -> bool {
+    let limits = render_device.limits();
+    limits.max_compute_workgroups_per_dimension > 0
+        && limits.max_storage_buffers_per_shader_stage >= 4
+}
+
+impl FromWorld for ShapeCullPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("shape_cull_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: Some(CullParams::min_size()),
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, true),
+                storage_entry(3, false),
+                storage_entry(4, false),
+            ],
+        });
+
+        let pipeline =
+            world
+                .resource::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some("shape_cull_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: CULL_HANDLE.typed::<Shader>(),
+                    shader_defs: vec![],
+                    entry_point: "cull".into(),
+                });
+
+        Self { layout, pipeline }
+    }
+}
+
+/// Writes the cull parameters and draw arguments of every batch culled on the GPU, reusing the buffers of previous frames.
+///
+/// Must run after the [`ShapeInstanceBuffer`] has been written.
+#[allow(clippy::too_many_arguments)]
+pub fn queue_shape_culling(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    policy: Res<ShapeBufferPolicy>,
+    cull_pipeline: Res<ShapeCullPipeline>,
+    instance_buffer: Res<ShapeInstanceBuffer>,
+    mut cull_buffers: ResMut<ShapeCullBuffers>,
+    views: Query<&ExtractedView>,
+    batches: Query<(Entity, &ShapeDataBuffer, &ShapeCullInstances)>,
+) {
+    let ShapeCullBuffers {
+        radii,
+        visible,
+        indirect,
+        params,
+        bind_group,
+    } = cull_buffers.as_mut();
+    radii.write(
+        "shape_cull_radii_buffer",
+        BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        &policy,
+        &render_device,
+        &render_queue,
+    );
+    indirect.clear();
+    params.clear();
+    *bind_group = None;
+
+    let Some(instances) = instance_buffer.buffer() else {
+        return;
+    };
+    if visible
+        .as_ref()
+        .map_or(true, |visible| visible.size() < instances.size())
+    {
+        *visible = Some(render_device.create_buffer(&BufferDescriptor {
+            label: Some("shape_visible_instance_buffer"),
+            size: instances.size(),
+            usage: BufferUsages::VERTEX | BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }));
+    }
+
+    for (entity, batch, cull) in &batches {
+        let Ok(view) = views.get(batch.view) else {
+            continue;
+        };
+
+        let view_proj = view.projection * view.transform.compute_matrix().inverse();
+        let frustum = Frustum::from_view_projection(&view_proj);
+
+        let count = cull.radii.end - cull.radii.start;
+        let params_offset = params.push(CullParams {
+            stride: cull.stride / 4,
+            count,
+            offset: (batch.range.start / 4) as u32,
+            radii: cull.radii.start,
+            indirect: indirect.len() as u32,
+            planes: frustum.planes.map(|plane| plane.normal_d()),
+        });
+        // Vertex count, instance count, first vertex and first instance
+        let index = indirect.push([6, 0, 0, 0]);
+
+        commands.entity(entity).insert(ShapeIndirectBatch {
+            params_offset,
+            indirect_offset: (index * std::mem::size_of::<[u32; 4]>()) as u64,
+            workgroups: (count + CULL_WORKGROUP_SIZE - 1) / CULL_WORKGROUP_SIZE,
+        });
+    }
+    if indirect.is_empty() {
+        return;
+    }
+
+    params.write_buffer(&render_device, &render_queue);
+    indirect.write_buffer(&render_device, &render_queue);
+
+    let (Some(params), Some(radii), Some(visible), Some(indirect)) = (
+        params.binding(),
+        radii.buffer(),
+        visible.as_ref(),
+        indirect.buffer(),
+    ) else {
+        return;
+    };
+    *bind_group = Some(render_device.create_bind_group(&BindGroupDescriptor {
+        label: Some("shape_cull_bind_group"),
+        layout: &cull_pipeline.layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: params,
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: instances.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: radii.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: visible.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 4,
+                resource: indirect.as_entire_binding(),
+            },
+        ],
+    }));
+}
+
+/// Render graph node that culls each batch of shapes for a view before the main pass.
+pub struct ShapeCullNode {
+    batches: QueryState<(&'static ShapeDataBuffer, &'static ShapeIndirectBatch)>,
+}
+
+impl ShapeCullNode {
+    pub const IN_VIEW: &'static str = "view";
+
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            batches: world.query(),
+        }
+    }
+}
+
+impl Node for ShapeCullNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(Self::IN_VIEW, SlotType::Entity)]
+    }
+
+    fn update(&mut self, world: &mut World) {
+        self.batches.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let view_entity = graph.get_input_entity(Self::IN_VIEW)?;
+        let Some(pipeline) = world
+            .get_resource::<ShapeCullPipeline>()
+            .and_then(|cull_pipeline| {
+                world
+                    .resource::<PipelineCache>()
+                    .get_compute_pipeline(cull_pipeline.pipeline)
+            })
+        else {
+            return Ok(());
+        };
+        let Some(bind_group) = world.resource::<ShapeCullBuffers>().bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("shape_cull_pass"),
+                });
+        pass.set_pipeline(pipeline);
+
+        for (batch, indirect) in self.batches.iter_manual(world) {
+            if batch.view != view_entity {
+                continue;
+            }
+            pass.set_bind_group(0, bind_group, &[indirect.params_offset]);
+            pass.dispatch_workgroups(indirect.workgroups, 1, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the instances of a batch that were determined visible by the [`ShapeCullNode`].
+pub struct DrawShapeIndirect;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawShapeIndirect {
+    type Param = SRes<ShapeCullBuffers>;
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = (Read<ShapeDataBuffer>, Read<ShapeIndirectBatch>);
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        (shape_buffer, batch): ROQueryItem<'w, Self::ItemWorldQuery>,
+        cull_buffers: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let cull_buffers = cull_buffers.into_inner();
+        let (Some(visible), Some(indirect)) = (
+            cull_buffers.visible.as_ref(),
+            cull_buffers.indirect.buffer(),
+        ) else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_vertex_buffer(0, visible.slice(shape_buffer.range.clone()));
+        pass.draw_indirect(indirect, batch.indirect_offset);
+
+        RenderCommandResult::Success
+    }
+}
+
+pub type DrawShapeIndirectCommand = (
+    SetItemPipeline,
+    SetShapeViewBindGroup<0>,
+    SetShapeTextureBindGroup<1>,
+    DrawShapeIndirect,
+);
+
+/// Adds the [`ShapeCullNode`] to the given sub graph before the main pass.
+pub(crate) fn add_cull_node(
+    render_app: &mut App,
+    sub_graph: &'static str,
+    input: &'static str,
+    main_pass: &'static str,
+) {
+    let node = ShapeCullNode::new(&mut render_app.world);
+    let mut graph = render_app.world.resource_mut::<RenderGraph>();
+    let Some(graph) = graph.get_sub_graph_mut(sub_graph) else {
+        return;
+    };
+
+    graph.add_node(SHAPE_CULL_NODE, node);
+    graph.add_slot_edge(
+        graph.input_node().id,
+        input,
+        SHAPE_CULL_NODE,
+        ShapeCullNode::IN_VIEW,
+    );
+    graph.add_node_edge(SHAPE_CULL_NODE, main_pass);
+}
+
+pub(crate) fn add_cull_nodes(render_app: &mut App) {
+    add_cull_node(
+        render_app,
+        core_2d::graph::NAME,
+        core_2d::graph::input::VIEW_ENTITY,
+        core_2d::graph::node::MAIN_PASS,
+    );
+    add_cull_node(
+        render_app,
+        core_3d::graph::NAME,
+        core_3d::graph::input::VIEW_ENTITY,
+        core_3d::graph::node::MAIN_PASS,
+    );
+}
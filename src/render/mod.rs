@@ -1,39 +1,84 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range, sync::Arc};
 
+#[cfg(feature = "ui")]
+use bevy::ui::TransparentUi;
 use bevy::{
     asset::load_internal_asset,
     core_pipeline::{
         core_2d::Transparent2d,
         core_3d::{AlphaMask3d, Opaque3d, Transparent3d},
+        prepass::{AlphaMask3dPrepass, Opaque3dPrepass},
     },
     prelude::*,
     reflect::{GetTypeRegistration, TypeUuid},
     render::{
-        render_phase::AddRenderCommand,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        primitives::Aabb,
+        render_phase::{sort_phase_system, AddRenderCommand, DrawFunctions},
         render_resource::{Buffer, ShaderRef},
-        view::RenderLayers,
+        renderer::RenderDevice,
+        view::{RenderLayers, VisibilitySystems},
         Extract, RenderApp, RenderSet,
     },
-    utils::FloatOrd,
+    transform::TransformSystem,
+    utils::{FloatOrd, HashMap},
 };
 use bitfield::bitfield;
 use bytemuck::Pod;
 use wgpu::VertexAttribute;
 
-use crate::prelude::*;
+use crate::{
+    painter::{redraw_changed_canvases, update_canvases},
+    prelude::*,
+};
 
 pub(crate) mod pipeline;
+pub use pipeline::ShapePipelineKey;
 use pipeline::*;
 
 pub(crate) mod commands;
 use commands::*;
 
+mod buffer;
+pub use buffer::*;
+
 pub(crate) mod render_2d;
 use render_2d::*;
 
 pub(crate) mod render_3d;
 use render_3d::*;
 
+mod hooks;
+pub use hooks::*;
+
+pub(crate) mod stencil;
+use stencil::*;
+
+pub(crate) mod post_process;
+use post_process::*;
+
+mod indirect;
+pub use indirect::*;
+
+mod view;
+pub use view::*;
+
+mod capture;
+pub use capture::*;
+
+#[cfg(feature = "save_canvas")]
+mod canvas_save;
+#[cfg(feature = "save_canvas")]
+pub use canvas_save::*;
+
+mod texture_array;
+pub use texture_array::*;
+
+#[cfg(feature = "asset_shaders")]
+mod shader_assets;
+#[cfg(feature = "asset_shaders")]
+pub use shader_assets::*;
+
 /// Handler to shader containing shared functionality.
 pub const BINDINGS_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 13215291696265391738);
@@ -62,7 +107,22 @@ pub const NGON_HANDLE: HandleUntyped =
 pub const RECT_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 15069348348279052351);
 
+/// Handler to shader for drawing shapes morphing between primitives.
+pub const MORPH_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 11872037745219368541);
+
+/// Handler to shader for drawing triangles.
+pub const TRIANGLE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 14730591268243076829);
+
+/// Handler to compute shader for culling shapes on the GPU.
+pub const CULL_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 16021458362840263311);
+
 /// Load the libraries shaders as internal assets.
+///
+/// With the `asset_shaders` feature enabled the shaders are additionally loaded from `assets/shaders/bevy_vector_shapes`
+/// and replace the embedded versions once loaded.
 pub fn load_shaders(app: &mut App) {
     load_internal_asset!(
         app,
@@ -106,20 +166,209 @@ pub fn load_shaders(app: &mut App) {
         "shaders/shapes/rect.wgsl",
         Shader::from_wgsl
     );
+    load_internal_asset!(
+        app,
+        MORPH_HANDLE,
+        "shaders/shapes/morph.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        TRIANGLE_HANDLE,
+        "shaders/shapes/triangle.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(app, CULL_HANDLE, "shaders/cull.wgsl", Shader::from_wgsl);
+
+    #[cfg(feature = "asset_shaders")]
+    load_shader_assets(app);
 }
 
 /// A pair of [`ShapePipelineMaterial`] and [`ShapeData`] to be used for rendering.
 pub type ShapeInstance<T> = (ShapePipelineMaterial, T);
 
-/// Collection of shape data in pairs of [`ShapePipelineMaterial`] and [`ShapeData`].
+/// Collection of extracted shape data in pairs of [`ShapePipelineMaterial`] and [`ShapeData`].
+#[derive(Component, Deref, DerefMut)]
+pub struct ExtractedShapes<T: ShapeData>(pub Vec<ShapeInstance<T>>);
+
+/// Every [`StaticShape`] of a shape type, only extracted on frames where the set of static shapes has changed.
 #[derive(Component, Deref, DerefMut)]
-pub struct ShapeInstances<T: ShapeData>(pub Vec<ShapeInstance<T>>);
+pub struct ExtractedStaticShapes<T: ShapeData>(pub Vec<ShapeInstance<T>>);
+
+/// Whether a retained shape is visible and should be extracted.
+///
+/// Canvas cameras don't take part in Bevy's visibility checks, so shapes drawn to a canvas are only hidden
+/// through their hierarchy rather than being culled by the views of unrelated cameras.
+pub(crate) fn is_shape_visible(
+    visibility: &ComputedVisibility,
+    material: Option<&ShapeMaterial>,
+) -> bool {
+    if material.map_or(false, |material| material.canvas.is_some()) {
+        visibility.is_visible_in_hierarchy()
+    } else {
+        visibility.is_visible()
+    }
+}
+
+/// Instance data of a retained shape with it's [`ComputedOpacity`] applied.
+pub(crate) fn retained_data<T: ShapeComponent>(
+    shape: &T,
+    transform: &GlobalTransform,
+    opacity: Option<&ComputedOpacity>,
+) -> T::Data {
+    let mut data = shape.into_data(transform);
+    if let Some(opacity) = opacity {
+        data.multiply_alpha(opacity.get());
+    }
+    data
+}
+
+/// Instance of a retained shape with it's [`ComputedOpacity`] applied.
+///
+/// Shared by every path that extracts retained shapes, including static and [`DynamicShape`](crate::shapes::DynamicShape)s,
+/// so that they are all drawn alike. [`PaintLayer`]s are applied separately as their settings can change every frame.
+pub(crate) fn retained_instance<T: ShapeComponent>(
+    shape: &T,
+    transform: &GlobalTransform,
+    material: Option<&ShapeMaterial>,
+    render_layers: Option<&RenderLayers>,
+    opacity: Option<&ComputedOpacity>,
+) -> ShapeInstance<T::Data> {
+    (
+        ShapePipelineMaterial::new(material, render_layers),
+        retained_data(shape, transform, opacity),
+    )
+}
+
+/// Instances of the retained shapes extracted last frame, kept so that unchanged shapes are not recomputed.
+///
+/// Shapes are only re-extracted when their shape component, transform, [`ShapeMaterial`], [`RenderLayers`] or [`ComputedOpacity`] have changed,
+/// been inserted or been removed, shapes that are despawned or hidden are dropped from the cache at the end of each extraction.
+pub struct ShapeExtractCache<T: ShapeData> {
+    previous: HashMap<Entity, CachedInstance<T>>,
+    current: HashMap<Entity, CachedInstance<T>>,
+}
+
+/// Instance cached by [`ShapeExtractCache`] along with which of the optional components it was built from.
+///
+/// Removing a component doesn't mark anything as changed, so presence is compared to catch removals.
+type CachedInstance<T> = (ShapeInstance<T>, [bool; 3]);
+
+impl<T: ShapeData> Default for ShapeExtractCache<T> {
+    fn default() -> Self {
+        Self {
+            previous: default(),
+            current: default(),
+        }
+    }
+}
+
+impl<T: ShapeData> ShapeExtractCache<T> {
+    /// Instance of the given retained shape, reused from last frame if nothing has changed since.
+    pub fn instance(
+        &mut self,
+        entity: Entity,
+        shape: Ref<T::Component>,
+        transform: Ref<GlobalTransform>,
+        material: Option<Ref<ShapeMaterial>>,
+        render_layers: Option<Ref<RenderLayers>>,
+        opacity: Option<Ref<ComputedOpacity>>,
+    ) -> ShapeInstance<T> {
+        let changed = shape.is_changed()
+            || transform.is_changed()
+            || material
+                .as_ref()
+                .map_or(false, |material| material.is_changed())
+            || render_layers
+                .as_ref()
+                .map_or(false, |layers| layers.is_changed())
+            || opacity
+                .as_ref()
+                .map_or(false, |opacity| opacity.is_changed());
+
+        let present = [
+            material.is_some(),
+            render_layers.is_some(),
+            opacity.is_some(),
+        ];
+
+        let instance = match self.previous.remove(&entity) {
+            Some((instance, previous)) if !changed && previous == present => instance,
+            _ => retained_instance(
+                &*shape,
+                &transform,
+                material.as_deref(),
+                render_layers.as_deref(),
+                opacity.as_deref(),
+            ),
+        };
+        self.current.insert(entity, (instance.clone(), present));
+        instance
+    }
+
+    /// Drops every shape that wasn't extracted this frame.
+    pub fn finish(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+}
+
+/// Instances of the retained shapes marked with [`StaticShape`], extracted once when the marker is added.
+///
+/// The render world only receives the instances again when a shape has been made static or non-static, see [`ExtractedStaticShapes`].
+pub struct StaticShapeCache<T: ShapeData> {
+    instances: HashMap<Entity, ShapeInstance<T>>,
+}
+
+impl<T: ShapeData> Default for StaticShapeCache<T> {
+    fn default() -> Self {
+        Self {
+            instances: default(),
+        }
+    }
+}
+
+impl<T: ShapeData> StaticShapeCache<T> {
+    /// Drops the shapes that are no longer static and bakes the newly static shapes, returning whether any changed.
+    pub fn update<'a>(
+        &mut self,
+        removed: impl Iterator<Item = Entity>,
+        added: impl Iterator<
+            Item = (
+                Entity,
+                &'a T::Component,
+                &'a GlobalTransform,
+                Option<&'a ShapeMaterial>,
+                Option<&'a RenderLayers>,
+                Option<&'a ComputedOpacity>,
+            ),
+        >,
+    ) -> bool {
+        let mut changed = false;
+        for entity in removed {
+            changed |= self.instances.remove(&entity).is_some();
+        }
+        for (entity, shape, transform, material, render_layers, opacity) in added {
+            let instance = retained_instance(shape, transform, material, render_layers, opacity);
+            self.instances.insert(entity, instance);
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn instances(&self) -> impl Iterator<Item = &ShapeInstance<T>> {
+        self.instances.values()
+    }
+}
 
 /// Trait implemented by each shapes shader data, defines common methods used in the rendering pipeline.
 pub trait ShapeData: Send + Sync + Pod {
     /// Corresponding component representing the given shape.
     type Component: ShapeComponent<Data = Self>;
     /// Vertex layout to be sent to the shader.
+    ///
+    /// Instances are passed to the shader as per instance vertex attributes rather than storage buffers so that shapes work on WebGL2,
+    /// as such the layout should not exceed the 16 attributes available there.
     fn vertex_layout() -> Vec<VertexAttribute>;
     /// Reference to the shader to be used when rendering the shape.
     fn shader() -> ShaderRef;
@@ -129,18 +378,37 @@ pub trait ShapeData: Send + Sync + Pod {
     }
     /// Transform of the shape to be used for z-ordering in 3D.
     fn transform(&self) -> Mat4;
+    /// Radius in world space around the shape's origin that contains the entire shape, used for GPU culling.
+    ///
+    /// Shapes with an infinite radius are never culled.
+    fn bounding_radius(&self) -> f32 {
+        f32::INFINITY
+    }
+    /// Multiplies the alpha of the shape's color, used to apply the [`ComputedOpacity`] of retained shapes.
+    fn multiply_alpha(&mut self, _alpha: f32) {}
+    /// Replaces the shape's color, used to apply the per instance colors of [`ShapeInstances`](crate::shapes::ShapeInstances).
+    fn set_color(&mut self, _color: Color) {}
+    /// Offsets the shape along world z, used to order the shapes of each [`ShapeLayers`] layer.
+    fn offset_z(&mut self, _offset: f32) {}
 }
 
 /// Trait implemented by the corresponding component for each shape type.
 pub trait ShapeComponent: Component + GetTypeRegistration {
     type Data: ShapeData<Component = Self>;
     fn into_data(&self, tf: &GlobalTransform) -> Self::Data;
+    /// Bounds of the shape in local space used to frustum cull retained shapes.
+    ///
+    /// Shapes without bounds are never culled, the bounds are recomputed whenever the component changes.
+    fn aabb(&self) -> Option<Aabb> {
+        None
+    }
 }
 
 /// Determines whether the shape is rendered in the 2D or 3D pipelines.
 #[derive(
     Resource, Copy, Clone, Reflect, FromReflect, Eq, PartialEq, Hash, PartialOrd, Ord, Debug,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShapePipelineType {
     Shape3d,
     Shape2d,
@@ -160,24 +428,169 @@ impl<T: ShapeData> Default for ShapeType<T> {
     }
 }
 
-/// Buffer of instances for a given shape type determined by [`ShapeType`].
-#[derive(Component)]
+/// Batch of instances for a given shape type determined by [`ShapeType`].
+///
+/// Instance data for all batches is stored in the [`ShapeInstanceBuffer`], each batch references a range of bytes within it.
+/// Batches of static shapes instead reference a [`StaticShapeBuffer`] that persists across frames.
+#[derive(Component, Clone)]
 pub struct ShapeDataBuffer {
     view: Entity,
     material: ShapePipelineMaterial,
-    buffer: Buffer,
+    range: Range<u64>,
     distance: f32,
     length: usize,
+    /// Per instance sort keys, only populated when instances are sorted individually, see [`ShapeSort2d`].
+    instance_keys: Arc<[f32]>,
+    /// Buffer holding the batch's instances when it isn't the [`ShapeInstanceBuffer`].
+    buffer: Option<Buffer>,
+}
+
+impl ShapeDataBuffer {
+    /// The GPU buffer holding the batch's instances, if it has been written.
+    pub fn buffer<'a>(&'a self, instance_buffer: &'a ShapeInstanceBuffer) -> Option<&'a Buffer> {
+        self.buffer.as_ref().or_else(|| instance_buffer.buffer())
+    }
+}
+
+/// Resource that determines how shapes are ordered in the 2D pipeline.
+///
+/// Insert this resource to opt into sorting along an axis, for example y-sorting in top-down games.
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub enum ShapeSort2d {
+    /// Shapes are ordered by their z coordinate, each batch of shapes is drawn at the position of it's nearest shape.
+    #[default]
+    Z,
+    /// Shapes are ordered individually by the dot product of their position and the given axis, plus the given offset.
+    ///
+    /// The resulting value is used in place of z, allowing shapes to interleave with sprites that use the same convention.
+    /// Consecutive shapes from the same batch are still drawn with a single draw call.
+    Axis { axis: Vec3, offset: f32 },
+}
+
+impl ShapeSort2d {
+    /// Sort shapes such that shapes lower on the screen are drawn on top, matching sprites with z set to -y plus offset.
+    pub fn y_sort(offset: f32) -> Self {
+        Self::Axis {
+            axis: Vec3::NEG_Y,
+            offset,
+        }
+    }
+
+    /// Key used to order the given shape in the 2D pipeline.
+    pub fn key<T: ShapeData>(&self, instance: &T) -> f32 {
+        match self {
+            Self::Z => instance.distance(),
+            Self::Axis { axis, offset } => {
+                instance.transform().transform_point3(Vec3::ZERO).dot(*axis) + offset
+            }
+        }
+    }
+}
+
+/// Resource that determines how transparent shapes are ordered in the 3D pipeline.
+///
+/// Instances within a batch are always sorted back to front relative to each other.
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub enum ShapeSort3d {
+    /// Each batch is drawn as a single transparent item at the position of it's furthest shape.
+    #[default]
+    Batch,
+    /// Transparent batches are split into chunks spanning at most the given view depth,
+    /// allowing other transparent items such as meshes to be drawn between chunks.
+    ///
+    /// A depth of zero splits every instance into it's own draw call which may be expensive for large numbers of shapes.
+    Split { max_depth: f32 },
+}
+
+/// Resource that enables dithering of shape colors to reduce banding in gradients and soft edges on 8-bit render targets.
+///
+/// Noise is generated per pixel with interleaved gradient noise, a cheap approximation of blue noise.
+/// Only applies to cameras without HDR, HDR cameras are dithered during tonemapping by Bevy's `DebandDither`.
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub struct ShapeDithering {
+    pub enabled: bool,
+}
+
+/// Resource that determines how the components of shape colors are interpreted.
+///
+/// Shape colors are always sent to the GPU as sRGB encoded components, as returned by [`Color::as_rgba_f32`].
+/// The conversion to the linear values that shapes are blended with happens in a single place in the shape shaders.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractResource)]
+pub enum ShapeColorSpace {
+    /// The sRGB encoded components are used as linear values, brighter than the same color on a `Sprite`.
+    ///
+    /// Matches the behaviour of previous versions.
+    #[default]
+    Linear,
+    /// The components are decoded from sRGB to linear, matching `Sprite` and `StandardMaterial` colors.
+    ///
+    /// Gradients, soft edges and thin strokes blend without shifting hue when layered with sprites.
+    Srgb,
+}
+
+/// Resource that simplifies shape shaders for mobile and WebGL targets, inserted by [`ShapePlugin::low_spec`].
+///
+/// When enabled local anti-aliasing, alpha to coverage and dithering are disabled, and lighting, fog, pixel snapping
+/// and clip rects are compiled out of the shaders, shapes using them are drawn as if they were unset.
+/// GPU culling should be left disabled, see [`ShapeGpuCulling`].
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub struct ShapeLowSpec {
+    pub enabled: bool,
+}
+
+/// Resource that replaces shape rendering with a debug visualization.
+///
+/// Useful for profiling why frames with many shapes are slow, large or heavily overlapping shapes are the usual culprits.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractResource)]
+pub enum ShapeDebug {
+    /// Shapes are drawn normally.
+    #[default]
+    None,
+    /// Every fragment of each shape's quad adds a small amount of heat regardless of depth, brighter areas are drawn more often.
+    ///
+    /// Includes fragments later discarded as outside of the shape as they are still shaded.
+    Overdraw,
+    /// Only the outline of each instance's quad is drawn, showing the area shaded for each shape.
+    Bounds,
+}
+
+/// Resource that enables drawing opaque and alpha masked 3D shapes into the depth prepass.
+///
+/// Only affects cameras with a [`DepthPrepass`](bevy::core_pipeline::prepass::DepthPrepass), allowing effects
+/// that read the prepass to treat shapes consistently with meshes. Shapes write depth but not normals.
+#[derive(Resource, Clone, Copy, Debug, Default, ExtractResource)]
+pub struct ShapePrepass {
+    pub enabled: bool,
+}
+
+/// Resource that determines the depth convention used by the 3D pipeline, set with [`ShapePlugin::reverse_z`].
+///
+/// Bevy's core 3D pipeline uses reverse-Z, where depth is cleared to 0.0 and the near plane maps to 1.0.
+/// Disabling reverse Z is only needed for custom render graphs whose depth attachment uses the standard convention.
+/// Both finite and infinite far planes are supported, as are custom orthographic and perspective projections.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+pub struct ShapeDepth {
+    pub reverse_z: bool,
+}
+
+impl Default for ShapeDepth {
+    fn default() -> Self {
+        Self { reverse_z: true }
+    }
 }
 
 bitfield! {
     /// Flags consumed in shape shaders
     pub struct Flags(u32);
     pub u32, from into ThicknessType, _, set_thickness_type: 1, 0;
-    pub u32, from into Alignment, _, set_alignment: 2, 2;
-    pub u32, _, set_hollow: 3, 3;
-    pub u32, from into Cap, _, set_cap: 5, 4;
-    pub u32, _, set_arc: 6, 6;
+    pub u32, from into Alignment, _, set_alignment: 3, 2;
+    pub u32, _, set_hollow: 4, 4;
+    pub u32, from into Cap, _, set_cap: 6, 5;
+    pub u32, _, set_arc: 7, 7;
+    pub u32, _, set_overlay: 8, 8;
+    pub u32, _, set_lit: 9, 9;
+    pub u32, _, set_fog: 10, 10;
+    pub u32, _, set_pixel_snap: 11, 11;
 }
 
 /// Properties attached to a batch of shapes that are needed for pipeline specialization
@@ -186,7 +599,13 @@ pub struct ShapePipelineMaterial {
     render_layers: RenderLayers,
     alpha_mode: AlphaModeOrd,
     disable_laa: bool,
+    alpha_to_coverage: bool,
     texture: Option<Handle<Image>>,
+    texture_array: bool,
+    hook: Option<Handle<ShapeHook>>,
+    stencil: StencilMode,
+    pass: ShapePass,
+    visible_to: Vec<Entity>,
     canvas: Option<Entity>,
     pipeline: ShapePipelineType,
 }
@@ -197,12 +616,44 @@ impl ShapePipelineMaterial {
         Self {
             render_layers: render_layers.cloned().unwrap_or_default(),
             alpha_mode: AlphaModeOrd(material.alpha_mode),
-            disable_laa: material.disable_laa || material.alpha_mode == AlphaMode::Opaque,
+            disable_laa: material.disable_laa
+                || (material.alpha_mode == AlphaMode::Opaque && !material.alpha_to_coverage),
+            alpha_to_coverage: material.alpha_to_coverage,
             canvas: material.canvas,
             pipeline: material.pipeline,
             texture: material.texture,
+            texture_array: material.texture_array,
+            hook: material.hook,
+            stencil: material.stencil,
+            pass: material.pass,
+            visible_to: material.visible_to,
         }
     }
+
+    /// Whether shapes with this material should be drawn by the given view.
+    pub fn is_visible_to(&self, view: Entity) -> bool {
+        self.visible_to.is_empty() || self.visible_to.contains(&view)
+    }
+
+    /// Whether shapes with this material are drawn in the [`ShapePostProcessPhase`].
+    pub fn is_post_process(&self) -> bool {
+        self.pass == ShapePass::PostProcess && self.stencil == StencilMode::None
+    }
+
+    /// Whether shapes with this material are drawn in the `bevy_ui` pass.
+    pub fn is_ui(&self) -> bool {
+        self.pass == ShapePass::Ui && self.stencil == StencilMode::None
+    }
+
+    /// Whether shapes with this material resolve their edges with alpha to coverage.
+    pub fn is_alpha_to_coverage(&self) -> bool {
+        self.alpha_to_coverage && !self.is_transparent()
+    }
+
+    /// Whether shapes with this material are drawn in the transparent phase.
+    pub fn is_transparent(&self) -> bool {
+        !matches!(self.alpha_mode.0, AlphaMode::Opaque | AlphaMode::Mask(_))
+    }
 }
 
 impl From<&ShapeConfig> for ShapePipelineMaterial {
@@ -210,8 +661,15 @@ impl From<&ShapeConfig> for ShapePipelineMaterial {
         Self {
             render_layers: config.render_layers.unwrap_or_default(),
             alpha_mode: AlphaModeOrd(config.alpha_mode),
-            disable_laa: config.disable_laa || config.alpha_mode == AlphaMode::Opaque,
+            disable_laa: config.disable_laa
+                || (config.alpha_mode == AlphaMode::Opaque && !config.alpha_to_coverage),
+            alpha_to_coverage: config.alpha_to_coverage,
             texture: config.texture.clone(),
+            texture_array: config.texture_layer.is_some(),
+            hook: config.hook.clone(),
+            stencil: config.stencil,
+            pass: config.pass,
+            visible_to: config.visible_to.clone(),
             pipeline: config.pipeline,
             canvas: config.canvas,
         }
@@ -250,62 +708,182 @@ impl Ord for AlphaModeOrd {
 ///
 /// Having to do this isn't ideal but with the way the render pipeline is setup for shapes using `visible_entities` is not ideal either.
 /// This may be removed once a better implementation is possible.
+/// Cameras capturing shape layers use the layers of the camera they capture, see [`ShapeLayerCaptures`].
 pub fn extract_render_layers(
     mut commands: Commands,
-    cameras: Extract<Query<(Entity, &RenderLayers), With<Camera>>>,
+    cameras: Extract<
+        Query<(Entity, &RenderLayers, Option<&ShapeLayerCaptureCamera>), With<Camera>>,
+    >,
 ) {
-    for (entity, render_layers) in &cameras {
-        commands.get_or_spawn(entity).insert(*render_layers);
+    for (entity, render_layers, capture) in &cameras {
+        let render_layers = capture.map_or(*render_layers, |capture| capture.layers);
+        commands.get_or_spawn(entity).insert(render_layers);
     }
 }
 
 fn setup_pipeline(app: &mut App) {
-    app.sub_app_mut(RenderApp)
+    // There is no render app when rendering is disabled, such as in headless tests without a GPU
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
         .init_resource::<ShapePipelines>()
         .init_resource::<ShapeTextureBindGroups>()
+        .init_resource::<ShapeViewUniforms>()
+        .init_resource::<ShapeInstanceBuffer>()
+        .init_resource::<ShapeCullBuffers>()
         .add_system(extract_render_layers.in_schedule(ExtractSchedule))
+        .add_system(extract_shape_views.in_schedule(ExtractSchedule))
+        .add_system(prepare_shape_view_uniforms.in_set(RenderSet::Prepare))
+        .add_system(write_shape_instance_buffer.in_set(RenderSet::PrepareFlush))
         .add_system(queue_shape_view_bind_groups.in_set(RenderSet::Queue))
         .add_system(queue_shape_texture_bind_groups.in_set(RenderSet::Queue));
 }
 
+fn setup_pipeline_culling(app: &mut App) {
+    app.init_resource::<ShapeGpuCulling>();
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    // Culling is left permanently disabled in the render world on devices that don't support it
+    if !supports_gpu_culling(render_app.world.resource::<RenderDevice>()) {
+        render_app.init_resource::<ShapeGpuCulling>();
+        return;
+    }
+    render_app
+        .init_resource::<ShapeCullPipeline>()
+        .add_system(queue_shape_culling.in_set(RenderSet::Queue));
+    add_cull_nodes(render_app);
+    app.add_plugin(ExtractResourcePlugin::<ShapeGpuCulling>::default());
+}
+
+fn setup_pipeline_stencil(app: &mut App) {
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
+        .init_resource::<DrawFunctions<ShapeStencilPhase>>()
+        .add_render_command::<ShapeStencilPhase, DrawShapeStencilCommand>()
+        .add_system(extract_stencil_phases.in_schedule(ExtractSchedule))
+        .add_system(sort_phase_system::<ShapeStencilPhase>.in_set(RenderSet::PhaseSort))
+        .add_system(prepare_stencil_textures.in_set(RenderSet::PhaseSort));
+    add_stencil_nodes(render_app);
+}
+
+fn setup_pipeline_post_process(app: &mut App) {
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
+        .init_resource::<DrawFunctions<ShapePostProcessPhase>>()
+        .add_render_command::<ShapePostProcessPhase, DrawShapeCommand>()
+        .add_system(extract_post_process_phases.in_schedule(ExtractSchedule))
+        .add_system(sort_phase_system::<ShapePostProcessPhase>.in_set(RenderSet::PhaseSort));
+    add_post_process_nodes(render_app);
+}
+
+#[cfg(feature = "ui")]
+fn setup_pipeline_ui(app: &mut App) {
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    // UI shapes can only be drawn if the UiPlugin has been added
+    if render_app
+        .world
+        .contains_resource::<DrawFunctions<TransparentUi>>()
+    {
+        render_app.add_render_command::<TransparentUi, DrawShapeCommand>();
+    }
+}
+
 fn setup_pipeline_3d(app: &mut App) {
-    app.sub_app_mut(RenderApp)
+    app.init_resource::<ShapeSort3d>()
+        .init_resource::<ShapePrepass>()
+        .init_resource::<ShapeDepth>()
+        .add_plugin(ExtractResourcePlugin::<ShapeSort3d>::default())
+        .add_plugin(ExtractResourcePlugin::<ShapePrepass>::default())
+        .add_plugin(ExtractResourcePlugin::<ShapeDepth>::default());
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
         .add_render_command::<Opaque3d, DrawShapeCommand>()
         .add_render_command::<Transparent3d, DrawShapeCommand>()
-        .add_render_command::<AlphaMask3d, DrawShapeCommand>();
+        .add_render_command::<AlphaMask3d, DrawShapeCommand>()
+        .add_render_command::<Opaque3d, DrawShapeIndirectCommand>()
+        .add_render_command::<Transparent3d, DrawShapeIndirectCommand>()
+        .add_render_command::<AlphaMask3d, DrawShapeIndirectCommand>()
+        .add_render_command::<Opaque3dPrepass, DrawShapeCommand>()
+        .add_render_command::<AlphaMask3dPrepass, DrawShapeCommand>();
 }
 
 fn setup_pipeline_2d(app: &mut App) {
-    app.sub_app_mut(RenderApp)
-        .add_render_command::<Transparent2d, DrawShapeCommand>();
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
+        .add_render_command::<Transparent2d, DrawShapeCommand>()
+        .add_render_command::<Transparent2d, DrawShapeRangeCommand>()
+        .add_render_command::<Transparent2d, DrawShapeIndirectCommand>();
 }
 
 fn setup_type_pipeline<T: ShapeData>(app: &mut App) {
-    app.sub_app_mut(RenderApp)
-        .init_resource::<ShapePipeline<T>>();
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app.init_resource::<ShapePipeline<T>>();
 }
 
 fn setup_type_pipeline_3d<T: ShapeData>(app: &mut App) {
-    app.sub_app_mut(RenderApp)
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
         .add_system(extract_shapes_3d::<T>.in_schedule(ExtractSchedule))
         .add_system(prepare_shape_buffers_3d::<T>.in_set(RenderSet::Prepare))
-        .add_system(queue_shapes_3d::<T>.in_set(RenderSet::Queue));
+        .add_system(queue_shapes_3d::<T>.in_set(RenderSet::Queue))
+        .add_system(queue_shapes_prepass::<T>.in_set(RenderSet::Queue));
 }
 
 fn setup_type_pipeline_2d<T: ShapeData>(app: &mut App) {
-    app.sub_app_mut(RenderApp)
+    let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app
         .add_system(extract_shapes_2d::<T>.in_schedule(ExtractSchedule))
         .add_system(prepare_shape_buffers_2d::<T>.in_set(RenderSet::Prepare))
         .add_system(queue_shapes_2d::<T>.in_set(RenderSet::Queue));
 }
 
 /// Plugin that sets up the 2d render pipeline for the given [`ShapeComponent`].
-#[derive(Default)]
 pub struct ShapeTypePlugin<T: ShapeComponent>(PhantomData<T>);
 
+impl<T: ShapeComponent> Default for ShapeTypePlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
 impl<T: ShapeComponent> Plugin for ShapeTypePlugin<T> {
     fn build(&self, app: &mut App) {
-        app.register_type::<T>();
+        app.register_type::<T>()
+            .add_system(prepare_shape_hooks::<T::Data>.in_base_set(CoreSet::PostUpdate))
+            .add_system(
+                update_shape_aabbs::<T>
+                    .in_base_set(CoreSet::PostUpdate)
+                    .in_set(VisibilitySystems::CalculateBounds),
+            )
+            .add_system(
+                update_shape_bounds::<T>
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(TransformSystem::TransformPropagate),
+            )
+            .add_system(
+                redraw_changed_canvases::<T>
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(TransformSystem::TransformPropagate)
+                    .before(update_canvases),
+            );
         setup_type_pipeline::<T::Data>(app);
         setup_type_pipeline_2d::<T::Data>(app);
     }
@@ -314,9 +892,14 @@ impl<T: ShapeComponent> Plugin for ShapeTypePlugin<T> {
 /// Plugin that sets up the 3d render pipeline for the given [`ShapeComponent`].
 ///
 /// Requires [`ShapeTypePlugin`] of the same type to have already been built.
-#[derive(Default)]
 pub struct ShapeType3dPlugin<T: ShapeComponent>(PhantomData<T>);
 
+impl<T: ShapeComponent> Default for ShapeType3dPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
 impl<T: ShapeComponent> Plugin for ShapeType3dPlugin<T> {
     fn build(&self, app: &mut App) {
         setup_type_pipeline_3d::<T::Data>(app);
@@ -329,8 +912,50 @@ pub struct ShapeRenderPlugin;
 impl Plugin for ShapeRenderPlugin {
     fn build(&self, app: &mut App) {
         load_shaders(app);
+        app.add_asset::<ShapeHook>()
+            .register_type::<ShapePixelScale>()
+            .register_type::<NoShapeRendering>()
+            .register_type::<ShapeCameraSettings>()
+            .register_type::<ShapeMaterial>()
+            .register_type::<ShapeCommon>()
+            .register_type::<Shape3d>()
+            .register_type::<StaticShape>()
+            .register_type::<Opacity>()
+            .register_type::<ShapeShaderParams>()
+            .register_type::<ShapeConfig>()
+            .register_type::<ShapePipelineType>()
+            .register_type::<ThicknessType>()
+            .register_type::<Alignment>()
+            .register_type::<Cap>()
+            .register_type::<StencilMode>()
+            .register_type::<ShapePass>()
+            .register_type::<MorphTarget>()
+            .register_type::<Option<Rect>>()
+            .register_type::<Option<Entity>>()
+            .register_type::<Option<Handle<Image>>>()
+            .register_type::<Option<Handle<ShapeHook>>>()
+            .register_type::<Vec<Entity>>()
+            .init_resource::<ShapeHookShaders>()
+            .init_resource::<ShapeSort2d>()
+            .init_resource::<ShapeBufferPolicy>()
+            .init_resource::<ShapeDithering>()
+            .init_resource::<ShapeDebug>()
+            .init_resource::<ShapeColorSpace>()
+            .init_resource::<ShapeLowSpec>()
+            .add_plugin(ExtractResourcePlugin::<ShapeHookShaders>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeBufferPolicy>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeDithering>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeDebug>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeColorSpace>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeLowSpec>::default())
+            .add_plugin(ExtractResourcePlugin::<ShapeSort2d>::default());
         setup_pipeline(app);
+        setup_pipeline_culling(app);
         setup_pipeline_2d(app);
+        setup_pipeline_stencil(app);
+        setup_pipeline_post_process(app);
+        #[cfg(feature = "ui")]
+        setup_pipeline_ui(app);
     }
 }
 
@@ -342,3 +967,28 @@ impl Plugin for Shape3dRenderPlugin {
         setup_pipeline_3d(app);
     }
 }
+
+/// Extension trait for [`App`] to register custom shape types with the shape render pipelines.
+pub trait ShapeAppExt {
+    /// Sets up extraction, buffer preparation and pipeline specialization for the given [`ShapeComponent`].
+    ///
+    /// The 2D pipeline is always set up, the 3D pipeline is additionally set up if [`ShapePlugin`] has been added.
+    /// Must be called after adding [`Shape2dPlugin`] or [`ShapePlugin`].
+    ///
+    /// Immediate mode shapes can then be drawn with [`ShapePainter::send`] and retained shapes spawned with [`ShapeBundle::new`].
+    fn add_shape_type<T: ShapeComponent>(&mut self) -> &mut Self;
+}
+
+impl ShapeAppExt for App {
+    fn add_shape_type<T: ShapeComponent>(&mut self) -> &mut Self {
+        assert!(
+            self.is_plugin_added::<ShapeRenderPlugin>(),
+            "Shape2dPlugin or ShapePlugin must be added before registering shape types."
+        );
+        self.add_plugin(ShapeTypePlugin::<T>::default());
+        if self.is_plugin_added::<Shape3dRenderPlugin>() {
+            self.add_plugin(ShapeType3dPlugin::<T>::default());
+        }
+        self
+    }
+}
@@ -1,6 +1,8 @@
 use std::any::TypeId;
 
 use bevy::{
+    core_pipeline::prepass::{DEPTH_PREPASS_FORMAT, NORMAL_PREPASS_FORMAT},
+    ecs::system::SystemParam,
     prelude::*,
     render::{render_resource::*, renderer::RenderDevice, texture::BevyDefault, view::ViewUniform},
     utils::HashMap,
@@ -9,7 +11,14 @@ use bevy::{
 use super::*;
 
 bitflags::bitflags! {
-    #[derive(Eq, PartialEq, Hash, Clone, Copy)]
+    /// Key used to specialize shape pipelines, computed for each batch of shapes from it's view and material every frame.
+    ///
+    /// As the key is recomputed each frame changes to a view's MSAA samples or HDR, or to a material, cause the affected
+    /// pipelines to be specialized again on demand, previously specialized pipelines remain cached for reuse.
+    ///
+    /// The `USER_*` bits are never set by this crate, they can be set per view with [`ShapeViewKey`] and are exposed to
+    /// shape shaders and [`ShapeHook`]s as the shader defs `SHAPE_USER_0` through `SHAPE_USER_3`.
+    #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
     #[repr(transparent)]
     pub struct ShapePipelineKey: u32 {
         const NONE                              = 0;
@@ -17,6 +26,24 @@ bitflags::bitflags! {
         const PIPELINE_2D                       = (1 << 2);
         const LOCAL_AA                          = (1 << 3);
         const TEXTURED                          = (1 << 4);
+        const STENCIL_WRITE                     = (1 << 7);
+        const STENCIL_READ                      = (1 << 8);
+        const POST_PROCESS                      = (1 << 9);
+        const DITHER                            = (1 << 10);
+        const TEXTURE_ARRAY                     = (1 << 11);
+        const USER_0                            = (1 << 12);
+        const USER_1                            = (1 << 13);
+        const USER_2                            = (1 << 14);
+        const USER_3                            = (1 << 15);
+        const USER_RESERVED_BITS                = Self::USER_0.bits() | Self::USER_1.bits() | Self::USER_2.bits() | Self::USER_3.bits();
+        const PREPASS                           = (1 << 16);
+        const PREPASS_NORMAL                    = (1 << 17);
+        const ALPHA_TO_COVERAGE                 = (1 << 18);
+        const STANDARD_Z                        = (1 << 19);
+        const DEBUG_OVERDRAW                    = (1 << 20);
+        const DEBUG_BOUNDS                      = (1 << 21);
+        const SRGB_COLORS                       = (1 << 22);
+        const LOW_SPEC                          = (1 << 23);
         const BLEND_RESERVED_BITS               = Self::BLEND_MASK_BITS << Self::BLEND_SHIFT_BITS;
         const BLEND_OPAQUE                      = (0 << Self::BLEND_SHIFT_BITS);
         const BLEND_ADD                         = (1 << Self::BLEND_SHIFT_BITS);
@@ -46,6 +73,14 @@ impl ShapePipelineKey {
         }
     }
 
+    pub fn from_debug(debug: ShapeDebug) -> Self {
+        match debug {
+            ShapeDebug::None => Self::NONE,
+            ShapeDebug::Overdraw => Self::DEBUG_OVERDRAW,
+            ShapeDebug::Bounds => Self::DEBUG_BOUNDS,
+        }
+    }
+
     pub fn msaa_samples(&self) -> u32 {
         1 << ((self.bits() >> Self::MSAA_SHIFT_BITS) & Self::MSAA_MASK_BITS)
     }
@@ -61,17 +96,103 @@ impl ShapePipelineKey {
         };
         if material.texture.is_some() {
             key |= Self::TEXTURED;
+            if material.texture_array {
+                key |= Self::TEXTURE_ARRAY;
+            }
+        }
+        match material.stencil {
+            StencilMode::None => {}
+            StencilMode::Write(_) => key |= Self::STENCIL_WRITE,
+            StencilMode::Read(_) => key |= Self::STENCIL_READ,
+        }
+        // UI shapes are also drawn to the resolved view target without depth
+        if material.is_post_process() || material.is_ui() {
+            key |= Self::POST_PROCESS;
+        }
+        if material.is_alpha_to_coverage() {
+            key |= Self::ALPHA_TO_COVERAGE;
         }
 
         key
     }
 }
 
+/// Render world resources that contribute to the [`ShapePipelineKey`] of every batch of shapes.
+#[derive(SystemParam)]
+pub struct ShapeKeySettings<'w> {
+    msaa: Res<'w, Msaa>,
+    dithering: Res<'w, ShapeDithering>,
+    debug: Res<'w, ShapeDebug>,
+    color_space: Res<'w, ShapeColorSpace>,
+    low_spec: Res<'w, ShapeLowSpec>,
+}
+
+impl<'w> ShapeKeySettings<'w> {
+    /// Key shared by the 2D and 3D pipelines for a batch with the given material drawn to a view.
+    pub fn key(
+        &self,
+        hdr: bool,
+        material: &ShapePipelineMaterial,
+        view_key: Option<&ShapeViewKey>,
+    ) -> ShapePipelineKey {
+        let mut key = ShapePipelineKey::from_msaa_samples(self.msaa.samples());
+        key |= ShapePipelineKey::from_hdr(hdr);
+        key |= ShapePipelineKey::from_material(material);
+
+        if !material.disable_laa {
+            key |= ShapePipelineKey::LOCAL_AA;
+        }
+        if self.dithering.enabled {
+            key |= ShapePipelineKey::DITHER;
+        }
+        key |= ShapePipelineKey::from_debug(*self.debug);
+        if *self.color_space == ShapeColorSpace::Srgb {
+            key |= ShapePipelineKey::SRGB_COLORS;
+        }
+        if self.low_spec.enabled {
+            key |= ShapePipelineKey::LOW_SPEC;
+        }
+        if let Some(view_key) = view_key {
+            key |= view_key.user_bits();
+        }
+        key
+    }
+}
+
+fn texture_layout(
+    render_device: &RenderDevice,
+    view_dimension: TextureViewDimension,
+) -> BindGroupLayout {
+    render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("shape_texture_layout"),
+    })
+}
+
 #[derive(Resource)]
 pub struct ShapePipelines {
     pub view_layout: BindGroupLayout,
     pub texture_layout: BindGroupLayout,
-    pipeline_cache: HashMap<(ShapePipelineKey, TypeId), CachedRenderPipelineId>,
+    pub texture_array_layout: BindGroupLayout,
+    pipeline_cache:
+        HashMap<(ShapePipelineKey, TypeId, Option<Handle<Shader>>), CachedRenderPipelineId>,
 }
 
 impl FromWorld for ShapePipelines {
@@ -90,60 +211,68 @@ impl FromWorld for ShapePipelines {
                     },
                     count: None,
                 },
-            ],
-            label: Some("shape_view_layout"),
-        });
-        let texture_layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            entries: &[
-                BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Texture {
-                        sample_type: TextureSampleType::Float { filterable: true },
-                        view_dimension: TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
+                // Shape view
                 BindGroupLayoutEntry {
                     binding: 1,
-                    visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: Some(ShapeViewUniform::min_size()),
+                    },
                     count: None,
                 },
             ],
-            label: Some("shape_texture_layout"),
+            label: Some("shape_view_layout"),
         });
+        let texture_2d_layout = texture_layout(render_device, TextureViewDimension::D2);
+        let texture_array_layout = texture_layout(render_device, TextureViewDimension::D2Array);
         Self {
             view_layout,
-            texture_layout,
+            texture_layout: texture_2d_layout,
+            texture_array_layout,
             pipeline_cache: default(),
         }
     }
 }
 
 impl ShapePipelines {
+    /// Get or queue a pipeline for the given shape type and key.
+    ///
+    /// If a hook shader is given it is used in place of the shape's own shader, see [`ShapeHook`].
     pub fn specialize<T: ShapeData>(
         &mut self,
         cache: &PipelineCache,
         pipeline: &ShapePipeline<T>,
         key: ShapePipelineKey,
+        hook: Option<Handle<Shader>>,
     ) -> CachedRenderPipelineId {
         let Self {
             view_layout,
             texture_layout,
+            texture_array_layout,
             pipeline_cache,
         } = self;
+        let texture_layout = match key.contains(ShapePipelineKey::TEXTURE_ARRAY) {
+            true => texture_array_layout,
+            false => texture_layout,
+        };
 
         *pipeline_cache
-            .entry((key.clone(), TypeId::of::<T>()))
+            .entry((key.clone(), TypeId::of::<T>(), hook.clone()))
             .or_insert_with(|| {
-                let descriptor = pipeline.specialize(view_layout, texture_layout, key);
+                let descriptor = pipeline.specialize(view_layout, texture_layout, key, hook);
                 cache.queue_render_pipeline(descriptor)
             })
     }
 }
 
+/// Shader of a shape type, checked against the limits of the render device when created.
+///
+/// There is no separate WebGL2 render path, instances are always passed as per instance vertex attributes
+/// which WebGL2 supports, and every built in shape fits within it's 16 attributes by packing the properties shared
+/// by all shapes into a single attribute, see [`ShapeCommonData`].
+/// Custom shape types exceeding the device's limit only report an error here, their pipelines then fail to be created.
 #[derive(Resource)]
 pub struct ShapePipeline<T: ShapeData> {
     shader: Handle<Shader>,
@@ -152,6 +281,21 @@ pub struct ShapePipeline<T: ShapeData> {
 
 impl<T: ShapeData> FromWorld for ShapePipeline<T> {
     fn from_world(world: &mut World) -> Self {
+        // Instance data is passed entirely through vertex attributes which are limited to 16 on WebGL2
+        let max_attributes = world
+            .resource::<RenderDevice>()
+            .limits()
+            .max_vertex_attributes;
+        let attributes = T::vertex_layout().len() as u32;
+        if attributes > max_attributes {
+            error!(
+                "{} uses {} vertex attributes but the render device only supports {}, pipeline creation will fail.",
+                std::any::type_name::<T>(),
+                attributes,
+                max_attributes
+            );
+        }
+
         let asset_server = world.resource_mut::<AssetServer>();
 
         Self {
@@ -171,6 +315,7 @@ impl<T: ShapeData> ShapePipeline<T> {
         view_layout: &BindGroupLayout,
         texture_layout: &BindGroupLayout,
         key: ShapePipelineKey,
+        hook: Option<Handle<Shader>>,
     ) -> RenderPipelineDescriptor {
         let mut shader_defs = Vec::new();
         let (label, blend, depth_stencil, depth_write_enabled);
@@ -217,6 +362,16 @@ impl<T: ShapeData> ShapePipeline<T> {
             depth_write_enabled = true;
         }
 
+        // Nearer fragments have greater depth with reverse-Z
+        let (depth_compare, prepass_depth_compare) =
+            match key.contains(ShapePipelineKey::STANDARD_Z) {
+                true => {
+                    shader_defs.push("STANDARD_Z".into());
+                    (CompareFunction::Less, CompareFunction::LessEqual)
+                }
+                false => (CompareFunction::Greater, CompareFunction::GreaterEqual),
+            };
+
         if key.contains(ShapePipelineKey::PIPELINE_2D) {
             depth_stencil = None;
             shader_defs.push("PIPELINE_2D".into());
@@ -224,7 +379,7 @@ impl<T: ShapeData> ShapePipeline<T> {
             depth_stencil = Some(DepthStencilState {
                 format: TextureFormat::Depth32Float,
                 depth_write_enabled,
-                depth_compare: CompareFunction::Greater,
+                depth_compare,
                 stencil: StencilState {
                     front: StencilFaceState::IGNORE,
                     back: StencilFaceState::IGNORE,
@@ -240,29 +395,184 @@ impl<T: ShapeData> ShapePipeline<T> {
             shader_defs.push("PIPELINE_3D".into());
         }
 
-        if key.contains(ShapePipelineKey::LOCAL_AA) {
+        // Stencil shapes are drawn in their own pass with a stencil only attachment
+        let mut write_mask = ColorWrites::ALL;
+        let stencil_face = if key.contains(ShapePipelineKey::STENCIL_WRITE) {
+            write_mask = ColorWrites::empty();
+            Some(StencilFaceState {
+                compare: CompareFunction::Equal,
+                fail_op: StencilOperation::Keep,
+                depth_fail_op: StencilOperation::Keep,
+                pass_op: StencilOperation::IncrementClamp,
+            })
+        } else if key.contains(ShapePipelineKey::STENCIL_READ) {
+            Some(StencilFaceState {
+                compare: CompareFunction::LessEqual,
+                fail_op: StencilOperation::Keep,
+                depth_fail_op: StencilOperation::Keep,
+                pass_op: StencilOperation::Keep,
+            })
+        } else {
+            None
+        };
+
+        let depth_stencil = match stencil_face {
+            Some(face) => Some(DepthStencilState {
+                format: SHAPE_STENCIL_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: StencilState {
+                    front: face,
+                    back: face,
+                    read_mask: !0,
+                    write_mask: !0,
+                },
+                bias: DepthBiasState::default(),
+            }),
+            None => depth_stencil,
+        };
+
+        // Post process shapes are drawn to the resolved view target without a depth attachment
+        let (depth_stencil, msaa_samples) = match key.contains(ShapePipelineKey::POST_PROCESS) {
+            true => (None, 1),
+            false => (depth_stencil, key.msaa_samples()),
+        };
+
+        if key.contains(ShapePipelineKey::SRGB_COLORS) {
+            shader_defs.push("SRGB_COLORS".into());
+        }
+
+        if key.contains(ShapePipelineKey::DITHER)
+            && !key.contains(ShapePipelineKey::HDR)
+            && !key.contains(ShapePipelineKey::LOW_SPEC)
+        {
+            shader_defs.push("DITHER".into());
+        }
+
+        for (i, bit) in [
+            ShapePipelineKey::USER_0,
+            ShapePipelineKey::USER_1,
+            ShapePipelineKey::USER_2,
+            ShapePipelineKey::USER_3,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if key.contains(bit) {
+                shader_defs.push(format!("SHAPE_USER_{i}").into());
+            }
+        }
+
+        // Alpha to coverage needs multisampling and a color target, otherwise edges are left aliased as for opaque shapes
+        let low_spec = key.contains(ShapePipelineKey::LOW_SPEC);
+        let alpha_to_coverage = key.contains(ShapePipelineKey::ALPHA_TO_COVERAGE)
+            && msaa_samples > 1
+            && !key.contains(ShapePipelineKey::PREPASS)
+            && !low_spec;
+        let local_aa = key.contains(ShapePipelineKey::LOCAL_AA)
+            && (alpha_to_coverage || !key.contains(ShapePipelineKey::ALPHA_TO_COVERAGE))
+            && !low_spec;
+        if low_spec {
+            shader_defs.push("LOW_SPEC".into());
+        }
+
+        if local_aa {
             shader_defs.push("LOCAL_AA".into());
         } else {
             shader_defs.push("DISABLE_LOCAL_AA".into())
         }
 
+        // Overdraw is accumulated additively for every fragment regardless of depth
+        let (blend, depth_stencil) = match key.contains(ShapePipelineKey::DEBUG_OVERDRAW) {
+            true => {
+                let add = BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                };
+                let depth_stencil = depth_stencil.map(|depth_stencil| DepthStencilState {
+                    depth_write_enabled: false,
+                    depth_compare: CompareFunction::Always,
+                    ..depth_stencil
+                });
+                shader_defs.push("SHAPE_DEBUG".into());
+                shader_defs.push("DEBUG_OVERDRAW".into());
+                (
+                    Some(BlendState {
+                        color: add,
+                        alpha: add,
+                    }),
+                    depth_stencil,
+                )
+            }
+            false => (blend, depth_stencil),
+        };
+        if key.contains(ShapePipelineKey::DEBUG_BOUNDS) {
+            shader_defs.push("SHAPE_DEBUG".into());
+            shader_defs.push("DEBUG_BOUNDS".into());
+        }
+
         let format = match key.contains(ShapePipelineKey::HDR) {
             true => bevy::render::view::ViewTarget::TEXTURE_FORMAT_HDR,
             false => TextureFormat::bevy_default(),
         };
 
+        let targets = vec![Some(ColorTargetState {
+            format,
+            blend,
+            write_mask,
+        })];
+
+        // Prepass shapes only write depth, the normal target is present but left untouched to match the render pass
+        let (label, targets, depth_stencil) = match key.contains(ShapePipelineKey::PREPASS) {
+            true => {
+                let targets = match key.contains(ShapePipelineKey::PREPASS_NORMAL) {
+                    true => vec![Some(ColorTargetState {
+                        format: NORMAL_PREPASS_FORMAT,
+                        blend: None,
+                        write_mask: ColorWrites::empty(),
+                    })],
+                    false => vec![],
+                };
+                let depth_stencil = DepthStencilState {
+                    format: DEPTH_PREPASS_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: prepass_depth_compare,
+                    stencil: StencilState::default(),
+                    bias: DepthBiasState::default(),
+                };
+                (
+                    "prepass_shape_pipeline".into(),
+                    targets,
+                    Some(depth_stencil),
+                )
+            }
+            false => (label, targets, depth_stencil),
+        };
+
         let mut layout = vec![view_layout.clone()];
         if key.contains(ShapePipelineKey::TEXTURED) {
             layout.push(texture_layout.clone());
             shader_defs.push("TEXTURED".into());
         }
+        if key.contains(ShapePipelineKey::TEXTURE_ARRAY) {
+            shader_defs.push("TEXTURE_ARRAY".into());
+        }
+
+        let shader = match hook {
+            Some(hook) => {
+                shader_defs.push("FRAGMENT_HOOK".into());
+                hook
+            }
+            None => self.shader.clone(),
+        };
 
         let mut fragment_defs = shader_defs.clone();
         fragment_defs.push("FRAGMENT".into());
 
         RenderPipelineDescriptor {
             vertex: VertexState {
-                shader: self.shader.clone(),
+                shader: shader.clone(),
                 entry_point: "vertex".into(),
                 shader_defs: shader_defs.clone(),
                 buffers: vec![VertexBufferLayout {
@@ -272,14 +582,10 @@ impl<T: ShapeData> ShapePipeline<T> {
                 }],
             },
             fragment: Some(FragmentState {
-                shader: self.shader.clone(),
+                shader,
                 shader_defs: fragment_defs,
                 entry_point: "fragment".into(),
-                targets: vec![Some(ColorTargetState {
-                    format,
-                    blend,
-                    write_mask: ColorWrites::ALL,
-                })],
+                targets,
             }),
             layout,
             primitive: PrimitiveState {
@@ -293,12 +599,41 @@ impl<T: ShapeData> ShapePipeline<T> {
             },
             depth_stencil,
             multisample: MultisampleState {
-                count: key.msaa_samples(),
+                count: msaa_samples,
                 mask: !0,
-                alpha_to_coverage_enabled: false,
+                alpha_to_coverage_enabled: alpha_to_coverage,
             },
             label: Some(label),
             push_constant_ranges: vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes<T: ShapeData>() -> (&'static str, usize) {
+        (std::any::type_name::<T>(), T::vertex_layout().len())
+    }
+
+    #[test]
+    fn built_in_shapes_fit_in_webgl2_vertex_attributes() {
+        let max_attributes =
+            wgpu::Limits::downlevel_webgl2_defaults().max_vertex_attributes as usize;
+        for (name, count) in [
+            attributes::<DiscData>(),
+            attributes::<LineData>(),
+            attributes::<QuadBezierData>(),
+            attributes::<RectData>(),
+            attributes::<NgonData>(),
+            attributes::<TriangleData>(),
+            attributes::<MorphData>(),
+        ] {
+            assert!(
+                count <= max_attributes,
+                "{name} uses {count} of {max_attributes} vertex attributes"
+            );
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::{
+    core_pipeline::{core_2d, core_3d},
+    prelude::*,
+    render::{
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType},
+        render_phase::{CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem, RenderPhase},
+        render_resource::*,
+        renderer::RenderContext,
+        view::{ExtractedView, ViewTarget},
+        Extract,
+    },
+    utils::FloatOrd,
+};
+
+use crate::render::NoShapeRendering;
+
+/// Name of the [`ShapePostProcessNode`] in the core 2d and 3d render graphs.
+pub const SHAPE_POST_PROCESS_NODE: &str = "shape_post_process_pass";
+
+/// Phase containing all shapes with [`ShapePass::PostProcess`](crate::shapes::ShapePass::PostProcess).
+pub struct ShapePostProcessPhase {
+    pub entity: Entity,
+    pub pipeline: CachedRenderPipelineId,
+    pub draw_function: DrawFunctionId,
+    pub sort_key: FloatOrd,
+}
+
+impl PhaseItem for ShapePostProcessPhase {
+    type SortKey = FloatOrd;
+
+    #[inline]
+    fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Self::SortKey {
+        self.sort_key
+    }
+
+    #[inline]
+    fn draw_function(&self) -> DrawFunctionId {
+        self.draw_function
+    }
+}
+
+impl CachedRenderPipelinePhaseItem for ShapePostProcessPhase {
+    #[inline]
+    fn cached_pipeline(&self) -> CachedRenderPipelineId {
+        self.pipeline
+    }
+}
+
+/// Adds a [`ShapePostProcessPhase`] to each active camera.
+pub fn extract_post_process_phases(
+    mut commands: Commands,
+    cameras: Extract<
+        Query<
+            (Entity, &Camera),
+            (
+                Or<(With<Camera2d>, With<Camera3d>)>,
+                Without<NoShapeRendering>,
+            ),
+        >,
+    >,
+) {
+    for (entity, camera) in &cameras {
+        if camera.is_active {
+            commands
+                .get_or_spawn(entity)
+                .insert(RenderPhase::<ShapePostProcessPhase>::default());
+        }
+    }
+}
+
+/// Render graph node that draws the [`ShapePostProcessPhase`] after tonemapping and anti-aliasing.
+///
+/// Shapes are drawn directly to the post processed view target without multisampling or depth testing.
+pub struct ShapePostProcessNode {
+    query: QueryState<
+        (
+            &'static RenderPhase<ShapePostProcessPhase>,
+            &'static ViewTarget,
+        ),
+        With<ExtractedView>,
+    >,
+}
+
+impl ShapePostProcessNode {
+    pub const IN_VIEW: &'static str = "view";
+
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            query: world.query_filtered(),
+        }
+    }
+}
+
+impl Node for ShapePostProcessNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(Self::IN_VIEW, SlotType::Entity)]
+    }
+
+    fn update(&mut self, world: &mut World) {
+        self.query.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let view_entity = graph.get_input_entity(Self::IN_VIEW)?;
+        let Ok((phase, target)) = self.query.get_manual(world, view_entity) else {
+            return Ok(());
+        };
+
+        if phase.items.is_empty() {
+            return Ok(());
+        }
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("shape_post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target.main_texture(),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        phase.render(&mut render_pass, world, view_entity);
+
+        Ok(())
+    }
+}
+
+/// Adds the [`ShapePostProcessNode`] to the given sub graph after tonemapping and before post processing ends.
+///
+/// If the graph contains an FXAA node the shapes are drawn after it so they are not blurred.
+pub(crate) fn add_post_process_node(
+    render_app: &mut App,
+    sub_graph: &'static str,
+    input: &'static str,
+    tonemapping: &'static str,
+    fxaa: &'static str,
+    end_post_processing: &'static str,
+) {
+    let node = ShapePostProcessNode::new(&mut render_app.world);
+    let mut graph = render_app.world.resource_mut::<RenderGraph>();
+    let Some(graph) = graph.get_sub_graph_mut(sub_graph) else {
+        return;
+    };
+
+    graph.add_node(SHAPE_POST_PROCESS_NODE, node);
+    graph.add_slot_edge(
+        graph.input_node().id,
+        input,
+        SHAPE_POST_PROCESS_NODE,
+        ShapePostProcessNode::IN_VIEW,
+    );
+    graph.add_node_edge(tonemapping, SHAPE_POST_PROCESS_NODE);
+    let _ = graph.try_add_node_edge(fxaa, SHAPE_POST_PROCESS_NODE);
+    graph.add_node_edge(SHAPE_POST_PROCESS_NODE, end_post_processing);
+}
+
+pub(crate) fn add_post_process_nodes(render_app: &mut App) {
+    add_post_process_node(
+        render_app,
+        core_2d::graph::NAME,
+        core_2d::graph::input::VIEW_ENTITY,
+        core_2d::graph::node::TONEMAPPING,
+        core_2d::graph::node::FXAA,
+        core_2d::graph::node::END_MAIN_PASS_POST_PROCESSING,
+    );
+    add_post_process_node(
+        render_app,
+        core_3d::graph::NAME,
+        core_3d::graph::input::VIEW_ENTITY,
+        core_3d::graph::node::TONEMAPPING,
+        core_3d::graph::node::FXAA,
+        core_3d::graph::node::END_MAIN_PASS_POST_PROCESSING,
+    );
+}
@@ -1,3 +1,7 @@
+use std::ops::Range;
+
+#[cfg(feature = "ui")]
+use bevy::ui::TransparentUi;
 use bevy::{
     core_pipeline::core_2d::*,
     prelude::*,
@@ -11,85 +15,318 @@ use bevy::{
     utils::FloatOrd,
 };
 
-use crate::{painter::ShapeStorage, render::*, shapes::Shape3d};
+use crate::{
+    painter::ShapeStorage,
+    render::*,
+    shapes::{
+        ComputedOpacity, DynamicShape, PaintLayer, Shape3d, ShapeInstances, ShapeLayers,
+        StaticShape,
+    },
+};
 
+#[allow(clippy::too_many_arguments)]
 pub fn extract_shapes_2d<T: ShapeData>(
     mut commands: Commands,
     entities: Extract<
         Query<
             (
+                Entity,
+                Ref<T::Component>,
+                Ref<GlobalTransform>,
+                &ComputedVisibility,
+                Option<Ref<ShapeMaterial>>,
+                Option<Ref<RenderLayers>>,
+                Option<Ref<ComputedOpacity>>,
+                Option<&PaintLayer>,
+            ),
+            (Without<Shape3d>, Without<StaticShape>),
+        >,
+    >,
+    static_entities: Extract<
+        Query<
+            (
+                Entity,
                 &T::Component,
                 &GlobalTransform,
+                Option<&ShapeMaterial>,
+                Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+            ),
+            (Without<Shape3d>, Added<StaticShape>),
+        >,
+    >,
+    mut removed_static: Extract<RemovedComponents<StaticShape>>,
+    instanced: Extract<
+        Query<
+            (
+                &ShapeInstances<T::Component>,
+                &GlobalTransform,
                 &ComputedVisibility,
                 Option<&ShapeMaterial>,
                 Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+                Option<&PaintLayer>,
+            ),
+            Without<Shape3d>,
+        >,
+    >,
+    dynamic: Extract<
+        Query<
+            (
+                &DynamicShape,
+                &GlobalTransform,
+                &ComputedVisibility,
+                Option<&ShapeMaterial>,
+                Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+                Option<&PaintLayer>,
             ),
             Without<Shape3d>,
         >,
     >,
     storage: Extract<Res<ShapeStorage>>,
+    layers: Extract<Res<ShapeLayers>>,
+    mut cache: Local<ShapeExtractCache<T>>,
+    mut static_cache: Local<StaticShapeCache<T>>,
 ) {
     let mut instances = entities
         .iter()
-        .filter_map(|(cp, tf, vis, flags, rl)| {
-            if vis.is_visible() {
-                Some((ShapePipelineMaterial::new(flags, rl), cp.into_data(tf)))
-            } else {
-                None
+        .filter_map(|(entity, cp, tf, vis, flags, rl, opacity, layer)| {
+            if !is_shape_visible(vis, flags.as_deref()) {
+                return None;
+            }
+            let mut instance = cache.instance(entity, cp, tf, flags, rl, opacity);
+            match layer {
+                Some(layer) if !layers.apply(&layer.0, &mut instance.1) => None,
+                _ => Some(instance),
             }
         })
         .collect::<Vec<_>>();
+    cache.finish();
+
+    // Static shapes are only sent to the render world when they change, where they are kept in a buffer of their own
+    if static_cache.update(removed_static.iter(), static_entities.iter()) {
+        commands.spawn(ExtractedStaticShapes::<T>(
+            static_cache.instances().cloned().collect(),
+        ));
+    }
+
+    for (shapes, tf, vis, flags, rl, opacity, layer) in &instanced {
+        if !is_shape_visible(vis, flags) {
+            continue;
+        }
+        let material = ShapePipelineMaterial::new(flags, rl);
+        for mut data in shapes.data(tf) {
+            // Opacity is applied after the per instance colors so that it fades every instance alike
+            if let Some(opacity) = opacity {
+                data.multiply_alpha(opacity.get());
+            }
+            if let Some(layer) = layer {
+                if !layers.apply(&layer.0, &mut data) {
+                    break;
+                }
+            }
+            instances.push((material.clone(), data));
+        }
+    }
+
+    for (shape, tf, vis, flags, rl, opacity, layer) in &dynamic {
+        if let Some(shape) = shape.as_shape::<T::Component>() {
+            if is_shape_visible(vis, flags) {
+                let mut instance = retained_instance(shape, tf, flags, rl, opacity);
+                match layer {
+                    Some(layer) if !layers.apply(&layer.0, &mut instance.1) => {}
+                    _ => instances.push(instance),
+                }
+            }
+        }
+    }
 
     if let Some(iter) = storage.get::<T>(ShapePipelineType::Shape2d) {
         instances.extend(iter.cloned());
     }
 
     if !instances.is_empty() {
-        commands.spawn(ShapeInstances::<T>(instances));
+        commands.spawn(ExtractedShapes::<T>(instances));
     }
 }
 
-fn spawn_buffers<T: ShapeData>(
-    commands: &mut Commands,
-    render_device: &RenderDevice,
-    view_entity: Entity,
-    material: ShapePipelineMaterial,
-    instances: &mut Vec<T>,
-) {
-    instances.sort_by_cached_key(|i| FloatOrd(i.distance()));
-
+/// Pushes the instances of a batch with the given function, returning the range of bytes and number of instances to draw.
+fn push_batch<T: ShapeData>(
+    instances: &[T],
+    push: &mut impl FnMut(&[T]) -> Range<u64>,
+) -> (Range<u64>, usize) {
+    let range = push(instances);
     // Workaround for an issue in the implementation of Chromes webgl ANGLE D3D11 backend
+    // The padding instance is pushed right after the batch so it is only drawn, it isn't keyed, sorted or culled
     #[cfg(target_arch = "wasm32")]
     if instances.len() == 1 {
-        instances.push(T::zeroed());
+        return (range.start..push(&[T::zeroed()]).end, 2);
+    }
+    (range, instances.len())
+}
+
+/// Sorts the instances of a material and pushes them with the given function, returning the batches to draw them with.
+///
+/// Each batch is returned along with the range of instances it covers, batches are returned without a view
+/// which is set when they are spawned.
+fn prepare_batches<T: ShapeData>(
+    material: &ShapePipelineMaterial,
+    instances: &mut [T],
+    sort: &ShapeSort2d,
+    mut push: impl FnMut(&[T]) -> Range<u64>,
+) -> Vec<(ShapeDataBuffer, Range<usize>)> {
+    // UI shapes are split into a batch per z value so that each is ordered among UI nodes by it's z
+    if material.is_ui() {
+        instances.sort_by_cached_key(|i| FloatOrd(i.distance()));
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < instances.len() {
+            let distance = instances[start].distance();
+            let end = instances[start..]
+                .iter()
+                .position(|i| i.distance() != distance)
+                .map_or(instances.len(), |length| start + length);
+            let (range, length) = push_batch(&instances[start..end], &mut push);
+            batches.push((
+                ShapeDataBuffer {
+                    view: Entity::PLACEHOLDER,
+                    material: material.clone(),
+                    range,
+                    distance,
+                    length,
+                    instance_keys: Vec::new().into(),
+                    buffer: None,
+                },
+                start..end,
+            ));
+            start = end;
+        }
+        return batches;
     }
 
-    let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("shape_instance_data_buffer"),
-        contents: bytemuck::cast_slice(instances.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+    instances.sort_by_cached_key(|i| FloatOrd(sort.key(i)));
+
+    let instance_keys = match sort {
+        ShapeSort2d::Z => Vec::new().into(),
+        ShapeSort2d::Axis { .. } => instances.iter().map(|i| sort.key(i)).collect(),
+    };
+    let (range, length) = push_batch(instances, &mut push);
+    let batch = ShapeDataBuffer {
+        view: Entity::PLACEHOLDER,
+        material: material.clone(),
+        range,
+        distance: sort.key(&instances[0]),
+        length,
+        instance_keys,
+        buffer: None,
+    };
+    vec![(batch, 0..instances.len())]
+}
+
+/// Spawns the batches of a material for a view, culling them on the GPU if given the [`ShapeCullBuffers`].
+///
+/// Instances are ordered the same for every view, so their bounding radii are pushed for the first view and shared by the rest.
+#[allow(clippy::too_many_arguments)]
+fn spawn_buffers<T: ShapeData>(
+    commands: &mut Commands,
+    instance_buffer: &mut ShapeInstanceBuffer,
+    mut cull_buffers: Option<&mut ShapeCullBuffers>,
+    radii: &mut Option<ShapeCullInstances>,
+    view_entity: Entity,
+    material: &ShapePipelineMaterial,
+    instances: &mut [T],
+    sort: &ShapeSort2d,
+) {
+    let batches = prepare_batches(material, instances, sort, |batch| {
+        instance_buffer.push(batch)
     });
-    commands.spawn((
-        ShapeDataBuffer {
-            view: view_entity,
-            material,
-            buffer,
-            distance: instances[0].distance(),
-            length: instances.len(),
-        },
-        ShapeType::<T>::default(),
-    ));
+    for (batch, range) in batches {
+        // Individually sorted instances are drawn by range so can't be compacted on the GPU
+        let cull = match cull_buffers.as_deref_mut() {
+            Some(cull_buffers) if !material.is_ui() && batch.instance_keys.is_empty() => {
+                let radii = radii.get_or_insert_with(|| {
+                    let radii = instances[range].iter().map(T::bounding_radius);
+                    cull_buffers.push::<T>(&radii.collect::<Vec<_>>())
+                });
+                Some(radii.clone())
+            }
+            _ => None,
+        };
+        let mut entity = commands.spawn((
+            ShapeDataBuffer {
+                view: view_entity,
+                ..batch
+            },
+            ShapeType::<T>::default(),
+        ));
+        if let Some(cull) = cull {
+            entity.insert(cull);
+        }
+    }
+}
+
+/// Filter for views with a phase that the 2D pipeline draws shapes in.
+#[cfg(feature = "ui")]
+type Shape2dPhases = Or<(
+    With<RenderPhase<Transparent2d>>,
+    With<RenderPhase<TransparentUi>>,
+)>;
+#[cfg(not(feature = "ui"))]
+type Shape2dPhases = With<RenderPhase<Transparent2d>>;
+
+type Shape2dViews<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Option<&'static RenderLayers>,
+        Option<&'static RenderPhase<Transparent2d>>,
+    ),
+    (
+        With<ExtractedView>,
+        Shape2dPhases,
+        Without<NoShapeRendering>,
+    ),
+>;
+
+/// Calls the given function for each view that draws shapes with the given material.
+fn for_each_view(
+    views: &Shape2dViews,
+    material: &ShapePipelineMaterial,
+    mut f: impl FnMut(Entity),
+) {
+    for (view_entity, render_layers, main_phase) in views {
+        // Cameras without a 2D main pass, such as 3D cameras, only draw UI shapes
+        if main_phase.is_none() && !material.is_ui() {
+            continue;
+        }
+        if let Some(canvas) = material.canvas {
+            if view_entity == canvas {
+                f(view_entity);
+            }
+            continue;
+        }
+        if let Some(render_layers) = render_layers {
+            if !render_layers.intersects(&material.render_layers) {
+                continue;
+            }
+        }
+        if !material.is_visible_to(view_entity) {
+            continue;
+        }
+        f(view_entity);
+    }
 }
 
 fn compute_visibility<T: ShapeData>(
     commands: &mut Commands,
-    render_device: &RenderDevice,
-    views: &Query<
-        (Entity, Option<&RenderLayers>),
-        (With<ExtractedView>, With<RenderPhase<Transparent2d>>),
-    >,
+    instance_buffer: &mut ShapeInstanceBuffer,
+    mut cull_buffers: Option<&mut ShapeCullBuffers>,
+    views: &Shape2dViews,
     material: &ShapePipelineMaterial,
     mut instances: Vec<T>,
+    sort: &ShapeSort2d,
 ) {
     if instances.is_empty() {
         return;
@@ -100,44 +337,104 @@ fn compute_visibility<T: ShapeData>(
         "Attempting to draw 3D shape in 2D pipeline. Ensure you have the Shape3d component inserted."
     );
 
-    if let Some(canvas) = material.canvas {
-        if let Ok((view_entity, _)) = views.get(canvas) {
-            spawn_buffers(
-                commands,
-                render_device,
-                view_entity,
-                material.clone(),
-                &mut instances,
-            );
-        }
-    } else {
-        for (view_entity, render_layers) in views {
-            if let Some(render_layers) = render_layers {
-                if !render_layers.intersects(&material.render_layers) {
-                    continue;
-                }
-            }
+    let mut radii = None;
+    for_each_view(views, material, |view_entity| {
+        spawn_buffers(
+            commands,
+            instance_buffer,
+            cull_buffers.as_deref_mut(),
+            &mut radii,
+            view_entity,
+            material,
+            &mut instances,
+            sort,
+        );
+    });
+}
+
+/// Batches of static shapes kept across frames, only rebuilt when the static shapes or [`ShapeSort2d`] change.
+pub struct StaticShapeBatches2d<T: ShapeData> {
+    instances: Vec<ShapeInstance<T>>,
+    buffer: StaticShapeBuffer,
+    batches: Vec<ShapeDataBuffer>,
+}
+
+impl<T: ShapeData> Default for StaticShapeBatches2d<T> {
+    fn default() -> Self {
+        Self {
+            instances: Vec::new(),
+            buffer: default(),
+            batches: Vec::new(),
+        }
+    }
+}
+
+impl<T: ShapeData> StaticShapeBatches2d<T> {
+    fn rebuild(&mut self, render_device: &RenderDevice, sort: &ShapeSort2d) {
+        self.instances.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.batches.clear();
+        let mut start = 0;
+        while start < self.instances.len() {
+            let material = &self.instances[start].0;
+            let end = self.instances[start..]
+                .iter()
+                .position(|(next, _)| next != material)
+                .map_or(self.instances.len(), |length| start + length);
+            let mut instances: Vec<T> = self.instances[start..end]
+                .iter()
+                .map(|(_, instance)| *instance)
+                .collect();
+            let buffer = &mut self.buffer;
+            let batches =
+                prepare_batches(material, &mut instances, sort, |batch| buffer.push(batch));
+            self.batches
+                .extend(batches.into_iter().map(|(batch, _)| batch));
+            start = end;
+        }
 
-            spawn_buffers(
-                commands,
-                render_device,
-                view_entity,
-                material.clone(),
-                &mut instances,
-            );
+        self.buffer.write(render_device);
+        for batch in &mut self.batches {
+            batch.buffer = self.buffer.buffer().cloned();
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_shape_buffers_2d<T: ShapeData>(
     mut commands: Commands,
-    mut query: Query<&mut ShapeInstances<T>, Without<Shape3d>>,
+    mut query: Query<&mut ExtractedShapes<T>, Without<Shape3d>>,
+    mut static_query: Query<&mut ExtractedStaticShapes<T>, Without<Shape3d>>,
+    mut static_batches: Local<StaticShapeBatches2d<T>>,
+    mut instance_buffer: ResMut<ShapeInstanceBuffer>,
+    mut cull_buffers: ResMut<ShapeCullBuffers>,
     render_device: Res<RenderDevice>,
-    views: Query<
-        (Entity, Option<&RenderLayers>),
-        (With<ExtractedView>, With<RenderPhase<Transparent2d>>),
-    >,
+    views: Shape2dViews,
+    culling: Res<ShapeGpuCulling>,
+    sort: Res<ShapeSort2d>,
 ) {
+    // Static shapes are only batched and uploaded again when they have changed, otherwise their batches are reused
+    let extracted = static_query
+        .iter_mut()
+        .next()
+        .map(|mut extracted| std::mem::take(&mut extracted.0));
+    if let Some(instances) = extracted {
+        static_batches.instances = instances;
+        static_batches.rebuild(&render_device, &sort);
+    } else if sort.is_changed() && !static_batches.instances.is_empty() {
+        static_batches.rebuild(&render_device, &sort);
+    }
+    for batch in &static_batches.batches {
+        for_each_view(&views, &batch.material, |view_entity| {
+            commands.spawn((
+                ShapeDataBuffer {
+                    view: view_entity,
+                    ..batch.clone()
+                },
+                ShapeType::<T>::default(),
+            ));
+        });
+    }
+
     for mut instance_data in &mut query {
         instance_data.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -150,10 +447,12 @@ pub fn prepare_shape_buffers_2d<T: ShapeData>(
                 } else {
                     compute_visibility(
                         &mut commands,
-                        render_device.as_ref(),
+                        &mut instance_buffer,
+                        culling.enabled.then_some(&mut *cull_buffers),
                         &views,
                         key,
                         instances,
+                        &sort,
                     );
 
                     (next_key, vec![*instance])
@@ -163,10 +462,12 @@ pub fn prepare_shape_buffers_2d<T: ShapeData>(
 
         compute_visibility(
             &mut commands,
-            render_device.as_ref(),
+            &mut instance_buffer,
+            culling.enabled.then_some(&mut *cull_buffers),
             &views,
             key,
             instances,
+            &sort,
         );
     }
 }
@@ -176,34 +477,139 @@ pub fn queue_shapes_2d<T: ShapeData>(
     transparent_2d_draw_functions: Res<DrawFunctions<Transparent2d>>,
     pipeline: Res<ShapePipeline<T>>,
     pipeline_cache: Res<PipelineCache>,
-    msaa: Res<Msaa>,
-    instance_buffers: Query<(Entity, &ShapeDataBuffer), (With<ShapeType<T>>, Without<Shape3d>)>,
+    key_settings: ShapeKeySettings,
+    instance_buffers: Query<
+        (Entity, &ShapeDataBuffer, Option<&ShapeCullInstances>),
+        (With<ShapeType<T>>, Without<Shape3d>),
+    >,
+    hook_shaders: Res<ShapeHookShaders>,
     mut shape_pipelines: ResMut<ShapePipelines>,
-    mut views: Query<(&ExtractedView, &mut RenderPhase<Transparent2d>)>,
+    mut views: Query<(
+        &ExtractedView,
+        Option<&ShapeViewKey>,
+        Option<&ShapeCameraSettings>,
+        Option<&mut RenderPhase<Transparent2d>>,
+        Option<&mut RenderPhase<ShapeStencilPhase>>,
+        Option<&mut RenderPhase<ShapePostProcessPhase>>,
+    )>,
+    stencil_draw_functions: Res<DrawFunctions<ShapeStencilPhase>>,
+    post_process_draw_functions: Res<DrawFunctions<ShapePostProcessPhase>>,
+    #[cfg(feature = "ui")] mut ui_phases: Query<&mut RenderPhase<TransparentUi>>,
+    #[cfg(feature = "ui")] ui_draw_functions: Option<Res<DrawFunctions<TransparentUi>>>,
 ) {
     let draw_function = transparent_2d_draw_functions
         .read()
         .id::<DrawShapeCommand>();
+    let draw_range = transparent_2d_draw_functions
+        .read()
+        .id::<DrawShapeRangeCommand>();
+    let draw_indirect = transparent_2d_draw_functions
+        .read()
+        .id::<DrawShapeIndirectCommand>();
+    let draw_stencil = stencil_draw_functions
+        .read()
+        .id::<DrawShapeStencilCommand>();
+    let draw_post_process = post_process_draw_functions.read().id::<DrawShapeCommand>();
+    #[cfg(feature = "ui")]
+    let draw_ui = ui_draw_functions.map(|functions| functions.read().id::<DrawShapeCommand>());
+
+    for (entity, buffer, culled) in &instance_buffers {
+        let (view, view_key, settings, transparent_phase, stencil_phase, post_process_phase) =
+            views
+                .get_mut(buffer.view)
+                .expect("View entity is gone during queue instances, oh no!");
 
-    for (entity, buffer) in &instance_buffers {
-        let (view, mut transparent_phase) = views
-            .get_mut(buffer.view)
-            .expect("View entity is gone during queue instances, oh no!");
+        // Skip batches in passes the camera has disabled
+        let settings = settings.copied().unwrap_or_default();
+        let enabled = if buffer.material.stencil != StencilMode::None {
+            settings.stencil
+        } else if buffer.material.is_post_process() {
+            settings.post_process
+        } else if buffer.material.is_ui() {
+            settings.ui
+        } else {
+            settings.main
+        };
+        if !enabled {
+            continue;
+        }
+
+        let key =
+            key_settings.key(view.hdr, &buffer.material, view_key) | ShapePipelineKey::PIPELINE_2D;
+
+        let hook = match &buffer.material.hook {
+            Some(hook) => match hook_shaders.get::<T>(hook) {
+                Some(shader) => Some(shader),
+                // The hook's shader has not been generated yet
+                None => continue,
+            },
+            None => None,
+        };
 
-        let mut key = ShapePipelineKey::from_msaa_samples(msaa.samples());
-        key |= ShapePipelineKey::from_hdr(view.hdr);
-        key |= ShapePipelineKey::PIPELINE_2D;
-        key |= ShapePipelineKey::from_material(&buffer.material);
+        let pipeline = shape_pipelines.specialize(&pipeline_cache, pipeline.as_ref(), key, hook);
+        if buffer.material.stencil != StencilMode::None {
+            if let Some(mut stencil_phase) = stencil_phase {
+                stencil_phase.add(ShapeStencilPhase {
+                    entity,
+                    pipeline,
+                    draw_function: draw_stencil,
+                    sort_key: (buffer.material.stencil.order(), FloatOrd(buffer.distance)),
+                });
+            }
+            continue;
+        }
 
-        if !buffer.material.disable_laa {
-            key |= ShapePipelineKey::LOCAL_AA;
+        if buffer.material.is_post_process() {
+            if let Some(mut post_process_phase) = post_process_phase {
+                post_process_phase.add(ShapePostProcessPhase {
+                    entity,
+                    pipeline,
+                    draw_function: draw_post_process,
+                    sort_key: FloatOrd(buffer.distance),
+                });
+            }
+            continue;
         }
 
-        let pipeline = shape_pipelines.specialize(&pipeline_cache, pipeline.as_ref(), key);
+        if buffer.material.is_ui() {
+            #[cfg(feature = "ui")]
+            if let (Ok(mut ui_phase), Some(draw_ui)) = (ui_phases.get_mut(buffer.view), draw_ui) {
+                ui_phase.add(TransparentUi {
+                    sort_key: FloatOrd(buffer.distance),
+                    entity,
+                    pipeline,
+                    draw_function: draw_ui,
+                });
+            }
+            continue;
+        }
+
+        let Some(mut transparent_phase) = transparent_phase else {
+            continue;
+        };
+
+        // When sorting shapes individually add an item per instance, adjacent items are batched together after sorting
+        if !buffer.instance_keys.is_empty() {
+            for (i, key) in buffer.instance_keys.iter().enumerate() {
+                transparent_phase.add(Transparent2d {
+                    entity,
+                    pipeline,
+                    draw_function: draw_range,
+                    sort_key: FloatOrd(*key),
+                    batch_range: Some(i as u32..i as u32 + 1),
+                });
+            }
+            continue;
+        }
+
+        // Batches culled on the GPU are drawn indirectly from their visible instances
         transparent_phase.add(Transparent2d {
             entity,
             pipeline,
-            draw_function,
+            draw_function: match culled {
+                Some(_) => draw_indirect,
+                None => draw_function,
+            },
             sort_key: FloatOrd(buffer.distance),
             batch_range: None,
         });
@@ -1,5 +1,10 @@
+use std::marker::PhantomData;
+
 use bevy::{
-    core_pipeline::core_3d::*,
+    core_pipeline::{
+        core_3d::*,
+        prepass::{AlphaMask3dPrepass, DepthPrepass, NormalPrepass, Opaque3dPrepass},
+    },
     prelude::*,
     render::{
         render_phase::{DrawFunctions, RenderPhase},
@@ -11,41 +16,140 @@ use bevy::{
     utils::FloatOrd,
 };
 
-use crate::{painter::ShapeStorage, render::*, shapes::Shape3d};
+use crate::{
+    painter::ShapeStorage,
+    render::*,
+    shapes::{
+        ComputedOpacity, DynamicShape, PaintLayer, Shape3d, ShapeInstances, ShapeLayers,
+        StaticShape,
+    },
+};
 
+#[allow(clippy::too_many_arguments)]
 pub fn extract_shapes_3d<T: ShapeData>(
     mut commands: Commands,
     entities: Extract<
         Query<
             (
+                Entity,
+                Ref<T::Component>,
+                Ref<GlobalTransform>,
+                &ComputedVisibility,
+                Option<Ref<ShapeMaterial>>,
+                Option<Ref<RenderLayers>>,
+                Option<Ref<ComputedOpacity>>,
+                Option<&PaintLayer>,
+            ),
+            (With<Shape3d>, Without<StaticShape>),
+        >,
+    >,
+    static_entities: Extract<
+        Query<
+            (
+                Entity,
                 &T::Component,
                 &GlobalTransform,
+                Option<&ShapeMaterial>,
+                Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+            ),
+            (With<Shape3d>, Added<StaticShape>),
+        >,
+    >,
+    mut removed_static: Extract<RemovedComponents<StaticShape>>,
+    instanced: Extract<
+        Query<
+            (
+                &ShapeInstances<T::Component>,
+                &GlobalTransform,
+                &ComputedVisibility,
+                Option<&ShapeMaterial>,
+                Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+                Option<&PaintLayer>,
+            ),
+            With<Shape3d>,
+        >,
+    >,
+    dynamic: Extract<
+        Query<
+            (
+                &DynamicShape,
+                &GlobalTransform,
                 &ComputedVisibility,
                 Option<&ShapeMaterial>,
                 Option<&RenderLayers>,
+                Option<&ComputedOpacity>,
+                Option<&PaintLayer>,
             ),
             With<Shape3d>,
         >,
     >,
     storage: Extract<Res<ShapeStorage>>,
+    layers: Extract<Res<ShapeLayers>>,
+    mut cache: Local<ShapeExtractCache<T>>,
+    mut static_cache: Local<StaticShapeCache<T>>,
 ) {
     let mut instances = entities
         .iter()
-        .filter_map(|(cp, tf, vis, flags, rl)| {
-            if vis.is_visible() {
-                Some((ShapePipelineMaterial::new(flags, rl), cp.into_data(tf)))
-            } else {
-                None
+        .filter_map(|(entity, cp, tf, vis, flags, rl, opacity, layer)| {
+            if !is_shape_visible(vis, flags.as_deref()) {
+                return None;
+            }
+            let mut instance = cache.instance(entity, cp, tf, flags, rl, opacity);
+            match layer {
+                Some(layer) if !layers.apply(&layer.0, &mut instance.1) => None,
+                _ => Some(instance),
             }
         })
         .collect::<Vec<_>>();
+    cache.finish();
+
+    // Static shapes are only sent to the render world when they change, where they are kept in a buffer of their own
+    if static_cache.update(removed_static.iter(), static_entities.iter()) {
+        commands.spawn((
+            ExtractedStaticShapes::<T>(static_cache.instances().cloned().collect()),
+            Shape3d,
+        ));
+    }
+
+    for (shapes, tf, vis, flags, rl, opacity, layer) in &instanced {
+        if !is_shape_visible(vis, flags) {
+            continue;
+        }
+        let material = ShapePipelineMaterial::new(flags, rl);
+        for mut data in shapes.data(tf) {
+            // Opacity is applied after the per instance colors so that it fades every instance alike
+            if let Some(opacity) = opacity {
+                data.multiply_alpha(opacity.get());
+            }
+            if let Some(layer) = layer {
+                if !layers.apply(&layer.0, &mut data) {
+                    break;
+                }
+            }
+            instances.push((material.clone(), data));
+        }
+    }
+
+    for (shape, tf, vis, flags, rl, opacity, layer) in &dynamic {
+        if let Some(shape) = shape.as_shape::<T::Component>() {
+            if is_shape_visible(vis, flags) {
+                let mut instance = retained_instance(shape, tf, flags, rl, opacity);
+                match layer {
+                    Some(layer) if !layers.apply(&layer.0, &mut instance.1) => {}
+                    _ => instances.push(instance),
+                }
+            }
+        }
+    }
 
     if let Some(iter) = storage.get::<T>(ShapePipelineType::Shape3d) {
         instances.extend(iter.cloned());
     }
 
     if !instances.is_empty() {
-        commands.spawn((ShapeInstances::<T>(instances), Shape3d));
+        commands.spawn((ExtractedShapes::<T>(instances), Shape3d));
     }
 }
 
@@ -53,50 +157,126 @@ type WithPhases = (
     With<RenderPhase<Opaque3d>>,
     With<RenderPhase<Transparent3d>>,
     With<RenderPhase<AlphaMask3d>>,
+    Without<NoShapeRendering>,
 );
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_buffers<T: ShapeData>(
     commands: &mut Commands,
-    render_device: &RenderDevice,
+    instance_buffer: &mut ShapeInstanceBuffer,
+    mut cull_buffers: Option<&mut ShapeCullBuffers>,
     view_entity: Entity,
     view: &ExtractedView,
     material: ShapePipelineMaterial,
-    instances: &mut Vec<T>,
+    instances: &[T],
+    radii: &[f32],
+    sort: &ShapeSort3d,
 ) {
+    // Sort instances back to front such that they blend correctly within the batch
+    // The order is kept so that the bounding radii computed once for all views can be sorted alike
     let rangefinder = view.rangefinder3d();
-    instances.sort_by_cached_key(|i| FloatOrd(rangefinder.distance(&i.transform())));
+    let depths = instances
+        .iter()
+        .map(|i| rangefinder.distance(&i.transform()))
+        .collect::<Vec<_>>();
+    let mut order = (0..instances.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| FloatOrd(depths[i]));
+    let distances = order.iter().map(|&i| depths[i]).collect::<Vec<_>>();
+    let sorted = order.iter().map(|&i| instances[i]);
+
+    // Split transparent batches into chunks so that other transparent items can be drawn between them
+    let mut chunks = Vec::new();
+    match sort {
+        ShapeSort3d::Split { max_depth } if material.is_transparent() => {
+            let mut start = 0;
+            for (i, distance) in distances.iter().enumerate() {
+                if distance - distances[start] > *max_depth {
+                    chunks.push(start..i);
+                    start = i;
+                }
+            }
+            chunks.push(start..distances.len());
+        }
+        _ => chunks.push(0..instances.len()),
+    }
 
     // Workaround for an issue in the implementation of Chromes webgl ANGLE D3D11 backend
+    // The padding instance is only drawn with the last chunk, it isn't sorted, split or culled
     #[cfg(target_arch = "wasm32")]
-    if instances.len() == 1 {
-        instances.push(T::zeroed());
+    let sorted = sorted.chain((order.len() == 1).then(T::zeroed));
+    let instances = sorted.collect::<Vec<_>>();
+
+    let range = instance_buffer.push(instances.as_slice());
+    let stride = std::mem::size_of::<T>() as u64;
+    for chunk in chunks {
+        let end = match chunk.end == distances.len() {
+            true => instances.len(),
+            false => chunk.end,
+        };
+        let mut entity = commands.spawn((
+            ShapeDataBuffer {
+                view: view_entity,
+                material: material.clone(),
+                range: range.start + chunk.start as u64 * stride..range.start + end as u64 * stride,
+                distance: distances[chunk.start],
+                length: end - chunk.start,
+                instance_keys: Vec::new().into(),
+                buffer: None,
+            },
+            ShapeType::<T>::default(),
+            Shape3d,
+        ));
+        if let Some(cull_buffers) = cull_buffers.as_deref_mut() {
+            let radii = order[chunk].iter().map(|&i| radii[i]).collect::<Vec<_>>();
+            entity.insert(cull_buffers.push::<T>(&radii));
+        }
     }
+}
 
-    let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("shape_instance_data_buffer"),
-        contents: bytemuck::cast_slice(instances.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
-    });
+type Shape3dViews<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static ExtractedView,
+        Option<&'static RenderLayers>,
+    ),
+    WithPhases,
+>;
 
-    commands.spawn((
-        ShapeDataBuffer {
-            view: view_entity,
-            material,
-            buffer,
-            distance: rangefinder.distance(&instances[0].transform()),
-            length: instances.len(),
-        },
-        ShapeType::<T>::default(),
-        Shape3d,
-    ));
+/// Calls the given function for each view that draws shapes with the given material.
+fn for_each_view(
+    views: &Shape3dViews,
+    material: &ShapePipelineMaterial,
+    mut f: impl FnMut(Entity, &ExtractedView),
+) {
+    for (view_entity, view, render_layers) in views {
+        // Shapes targeting a specific camera are only drawn to that camera
+        if let Some(canvas) = material.canvas {
+            if view_entity == canvas {
+                f(view_entity, view);
+            }
+            continue;
+        }
+        let render_layers = render_layers.cloned().unwrap_or_default();
+        if !render_layers.intersects(&material.render_layers) {
+            continue;
+        }
+        if !material.is_visible_to(view_entity) {
+            continue;
+        }
+        f(view_entity, view);
+    }
 }
 
 fn compute_visibility<T: ShapeData>(
     commands: &mut Commands,
-    render_device: &RenderDevice,
-    views: &Query<(Entity, &ExtractedView, Option<&RenderLayers>), WithPhases>,
+    instance_buffer: &mut ShapeInstanceBuffer,
+    mut cull_buffers: Option<&mut ShapeCullBuffers>,
+    views: &Shape3dViews,
     material: &ShapePipelineMaterial,
-    mut instances: Vec<T>,
+    instances: Vec<T>,
+    sort: &ShapeSort3d,
 ) {
     if instances.is_empty() {
         return;
@@ -107,29 +287,128 @@ fn compute_visibility<T: ShapeData>(
         "Attempting to draw 2D shape in 3D pipeline. Ensure you are setting config.pipeline correctly."
     );
 
-    for (view_entity, view, render_layers) in views {
-        let render_layers = render_layers.cloned().unwrap_or_default();
-        if !render_layers.intersects(&material.render_layers) {
-            continue;
-        }
-
+    let radii = match cull_buffers {
+        Some(_) => instances.iter().map(T::bounding_radius).collect(),
+        None => Vec::new(),
+    };
+    for_each_view(views, material, |view_entity, view| {
         spawn_buffers(
             commands,
-            render_device,
+            instance_buffer,
+            cull_buffers.as_deref_mut(),
             view_entity,
             view,
             material.clone(),
-            &mut instances,
-        )
+            &instances,
+            &radii,
+            sort,
+        );
+    });
+}
+
+/// Batches of static shapes kept across frames, only rebuilt when the static shapes change.
+///
+/// Each batch is drawn as a whole at the distance of it's center, it's instances aren't sorted relative to the view.
+pub struct StaticShapeBatches3d<T: ShapeData> {
+    buffer: StaticShapeBuffer,
+    batches: Vec<(ShapeDataBuffer, Vec3)>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: ShapeData> Default for StaticShapeBatches3d<T> {
+    fn default() -> Self {
+        Self {
+            buffer: default(),
+            batches: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ShapeData> StaticShapeBatches3d<T> {
+    fn rebuild(&mut self, render_device: &RenderDevice, instances: &mut [ShapeInstance<T>]) {
+        instances.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.batches.clear();
+        let mut start = 0;
+        while start < instances.len() {
+            let material = &instances[start].0;
+            let end = instances[start..]
+                .iter()
+                .position(|(next, _)| next != material)
+                .map_or(instances.len(), |length| start + length);
+            let batch: Vec<T> = instances[start..end]
+                .iter()
+                .map(|(_, instance)| *instance)
+                .collect();
+            let center = batch
+                .iter()
+                .map(|i| i.transform().transform_point3(Vec3::ZERO))
+                .sum::<Vec3>()
+                / batch.len() as f32;
+
+            // Workaround for an issue in the implementation of Chromes webgl ANGLE D3D11 backend
+            #[cfg(target_arch = "wasm32")]
+            let batch = match batch.len() {
+                1 => vec![batch[0], T::zeroed()],
+                _ => batch,
+            };
+
+            let range = self.buffer.push(batch.as_slice());
+            self.batches.push((
+                ShapeDataBuffer {
+                    view: Entity::PLACEHOLDER,
+                    material: material.clone(),
+                    range,
+                    distance: 0.0,
+                    length: batch.len(),
+                    instance_keys: Vec::new().into(),
+                    buffer: None,
+                },
+                center,
+            ));
+            start = end;
+        }
+
+        self.buffer.write(render_device);
+        for (batch, _) in &mut self.batches {
+            batch.buffer = self.buffer.buffer().cloned();
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_shape_buffers_3d<T: ShapeData>(
     mut commands: Commands,
-    mut query: Query<&mut ShapeInstances<T>, With<Shape3d>>,
+    mut query: Query<&mut ExtractedShapes<T>, With<Shape3d>>,
+    mut static_query: Query<&mut ExtractedStaticShapes<T>, With<Shape3d>>,
+    mut static_batches: Local<StaticShapeBatches3d<T>>,
+    mut instance_buffer: ResMut<ShapeInstanceBuffer>,
+    mut cull_buffers: ResMut<ShapeCullBuffers>,
     render_device: Res<RenderDevice>,
-    views: Query<(Entity, &ExtractedView, Option<&RenderLayers>), WithPhases>,
+    views: Shape3dViews,
+    culling: Res<ShapeGpuCulling>,
+    sort: Res<ShapeSort3d>,
 ) {
+    // Static shapes are only batched and uploaded again when they have changed, otherwise their batches are reused
+    if let Some(mut extracted) = static_query.iter_mut().next() {
+        static_batches.rebuild(&render_device, &mut extracted.0);
+    }
+    for (batch, center) in &static_batches.batches {
+        for_each_view(&views, &batch.material, |view_entity, view| {
+            commands.spawn((
+                ShapeDataBuffer {
+                    view: view_entity,
+                    distance: view
+                        .rangefinder3d()
+                        .distance(&Mat4::from_translation(*center)),
+                    ..batch.clone()
+                },
+                ShapeType::<T>::default(),
+                Shape3d,
+            ));
+        });
+    }
+
     for mut instance_data in &mut query {
         instance_data.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -142,10 +421,12 @@ pub fn prepare_shape_buffers_3d<T: ShapeData>(
                 } else {
                     compute_visibility(
                         &mut commands,
-                        render_device.as_ref(),
+                        &mut instance_buffer,
+                        culling.enabled.then_some(&mut *cull_buffers),
                         &views,
                         key,
                         instances,
+                        &sort,
                     );
 
                     (next_key, vec![*instance])
@@ -155,10 +436,12 @@ pub fn prepare_shape_buffers_3d<T: ShapeData>(
 
         compute_visibility(
             &mut commands,
-            render_device.as_ref(),
+            &mut instance_buffer,
+            culling.enabled.then_some(&mut *cull_buffers),
             &views,
             key,
             instances,
+            &sort,
         );
     }
 }
@@ -170,36 +453,126 @@ pub fn queue_shapes_3d<T: ShapeData>(
     transparent_draw_functions: Res<DrawFunctions<Transparent3d>>,
     pipeline: Res<ShapePipeline<T>>,
     pipeline_cache: Res<PipelineCache>,
-    msaa: Res<Msaa>,
-    shape_buffers: Query<(Entity, &ShapeDataBuffer), (With<ShapeType<T>>, With<Shape3d>)>,
+    key_settings: ShapeKeySettings,
+    depth: Res<ShapeDepth>,
+    shape_buffers: Query<
+        (Entity, &ShapeDataBuffer, Option<&ShapeCullInstances>),
+        (With<ShapeType<T>>, With<Shape3d>),
+    >,
+    hook_shaders: Res<ShapeHookShaders>,
     mut shape_pipelines: ResMut<ShapePipelines>,
     mut views: Query<(
         &ExtractedView,
+        Option<&ShapeViewKey>,
+        Option<&ShapeCameraSettings>,
         &mut RenderPhase<Opaque3d>,
         &mut RenderPhase<AlphaMask3d>,
         &mut RenderPhase<Transparent3d>,
+        Option<&mut RenderPhase<ShapeStencilPhase>>,
+        Option<&mut RenderPhase<ShapePostProcessPhase>>,
     )>,
+    stencil_draw_functions: Res<DrawFunctions<ShapeStencilPhase>>,
+    post_process_draw_functions: Res<DrawFunctions<ShapePostProcessPhase>>,
 ) where
     T: 'static,
 {
     let draw_opaque = opaque_draw_functions.read().id::<DrawShapeCommand>();
     let draw_alpha_mask = alpha_mask_draw_functions.read().id::<DrawShapeCommand>();
     let draw_transparent = transparent_draw_functions.read().id::<DrawShapeCommand>();
+    let draw_opaque_indirect = opaque_draw_functions
+        .read()
+        .id::<DrawShapeIndirectCommand>();
+    let draw_alpha_mask_indirect = alpha_mask_draw_functions
+        .read()
+        .id::<DrawShapeIndirectCommand>();
+    let draw_transparent_indirect = transparent_draw_functions
+        .read()
+        .id::<DrawShapeIndirectCommand>();
+    let draw_stencil = stencil_draw_functions
+        .read()
+        .id::<DrawShapeStencilCommand>();
+    let draw_post_process = post_process_draw_functions.read().id::<DrawShapeCommand>();
 
-    for (entity, buffer) in &shape_buffers {
-        let (view, mut opaque_phase, mut alpha_mask_phase, mut transparent_phase) = views
+    for (entity, buffer, culled) in &shape_buffers {
+        let (
+            view,
+            view_key,
+            settings,
+            mut opaque_phase,
+            mut alpha_mask_phase,
+            mut transparent_phase,
+            stencil_phase,
+            post_process_phase,
+        ) = views
             .get_mut(buffer.view)
             .expect("View entity is gone during queue instances, oh no!");
 
-        let mut key = ShapePipelineKey::from_msaa_samples(msaa.samples());
-        key |= ShapePipelineKey::from_hdr(view.hdr);
-        key |= ShapePipelineKey::from_material(&buffer.material);
+        // Skip batches in passes the camera has disabled
+        let settings = settings.copied().unwrap_or_default();
+        let enabled = if buffer.material.stencil != StencilMode::None {
+            settings.stencil
+        } else if buffer.material.is_post_process() {
+            settings.post_process
+        } else if buffer.material.is_ui() {
+            // UI shapes are only drawn by the 2D pipeline
+            false
+        } else {
+            settings.main
+        };
+        if !enabled {
+            continue;
+        }
 
-        if !buffer.material.disable_laa {
-            key |= ShapePipelineKey::LOCAL_AA;
+        let mut key = key_settings.key(view.hdr, &buffer.material, view_key);
+        if !depth.reverse_z {
+            key |= ShapePipelineKey::STANDARD_Z;
+        }
+
+        let hook = match &buffer.material.hook {
+            Some(hook) => match hook_shaders.get::<T>(hook) {
+                Some(shader) => Some(shader),
+                // The hook's shader has not been generated yet
+                None => continue,
+            },
+            None => None,
+        };
+
+        let pipeline =
+            shape_pipelines.specialize::<T>(&pipeline_cache, pipeline.as_ref(), key, hook);
+        if buffer.material.stencil != StencilMode::None {
+            if let Some(mut stencil_phase) = stencil_phase {
+                stencil_phase.add(ShapeStencilPhase {
+                    entity,
+                    pipeline,
+                    draw_function: draw_stencil,
+                    sort_key: (buffer.material.stencil.order(), FloatOrd(buffer.distance)),
+                });
+            }
+            continue;
+        }
+
+        if buffer.material.is_post_process() {
+            if let Some(mut post_process_phase) = post_process_phase {
+                post_process_phase.add(ShapePostProcessPhase {
+                    entity,
+                    pipeline,
+                    draw_function: draw_post_process,
+                    sort_key: FloatOrd(buffer.distance),
+                });
+            }
+            continue;
         }
 
-        let pipeline = shape_pipelines.specialize::<T>(&pipeline_cache, pipeline.as_ref(), key);
+        // Batches culled on the GPU are drawn indirectly from their visible instances
+        let (draw_opaque, draw_alpha_mask, draw_transparent) = match culled {
+            Some(_) => (
+                draw_opaque_indirect,
+                draw_alpha_mask_indirect,
+                draw_transparent_indirect,
+            ),
+            None => (draw_opaque, draw_alpha_mask, draw_transparent),
+        };
+
         match buffer.material.alpha_mode.0 {
             AlphaMode::Opaque => {
                 opaque_phase.add(Opaque3d {
@@ -228,3 +601,103 @@ pub fn queue_shapes_3d<T: ShapeData>(
         }
     }
 }
+
+/// Queues opaque and alpha masked 3D shapes into the depth prepass of views with a [`DepthPrepass`].
+///
+/// Only runs when [`ShapePrepass`] is enabled, shapes write depth but not normals.
+#[allow(clippy::too_many_arguments)]
+pub fn queue_shapes_prepass<T: ShapeData>(
+    opaque_draw_functions: Res<DrawFunctions<Opaque3dPrepass>>,
+    alpha_mask_draw_functions: Res<DrawFunctions<AlphaMask3dPrepass>>,
+    pipeline: Res<ShapePipeline<T>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    prepass: Res<ShapePrepass>,
+    depth: Res<ShapeDepth>,
+    shape_buffers: Query<(Entity, &ShapeDataBuffer), (With<ShapeType<T>>, With<Shape3d>)>,
+    hook_shaders: Res<ShapeHookShaders>,
+    mut shape_pipelines: ResMut<ShapePipelines>,
+    mut views: Query<
+        (
+            Option<&ShapeViewKey>,
+            Option<&ShapeCameraSettings>,
+            Option<&NormalPrepass>,
+            &mut RenderPhase<Opaque3dPrepass>,
+            &mut RenderPhase<AlphaMask3dPrepass>,
+        ),
+        With<DepthPrepass>,
+    >,
+) {
+    if !prepass.enabled {
+        return;
+    }
+
+    let draw_opaque = opaque_draw_functions.read().id::<DrawShapeCommand>();
+    let draw_alpha_mask = alpha_mask_draw_functions.read().id::<DrawShapeCommand>();
+
+    for (entity, buffer) in &shape_buffers {
+        if buffer.material.stencil != StencilMode::None
+            || buffer.material.is_post_process()
+            || buffer.material.is_ui()
+        {
+            continue;
+        }
+        let Ok((view_key, settings, normal_prepass, mut opaque_phase, mut alpha_mask_phase)) =
+            views.get_mut(buffer.view)
+        else {
+            continue;
+        };
+        if !settings.map_or(true, |settings| settings.prepass) {
+            continue;
+        }
+
+        let mut key = ShapePipelineKey::from_msaa_samples(msaa.samples());
+        key |= ShapePipelineKey::from_material(&buffer.material);
+        key |= ShapePipelineKey::PREPASS;
+        if normal_prepass.is_some() {
+            key |= ShapePipelineKey::PREPASS_NORMAL;
+        }
+        if !buffer.material.disable_laa {
+            key |= ShapePipelineKey::LOCAL_AA;
+        }
+        if !depth.reverse_z {
+            key |= ShapePipelineKey::STANDARD_Z;
+        }
+        if let Some(view_key) = view_key {
+            key |= view_key.user_bits();
+        }
+
+        let hook = match &buffer.material.hook {
+            Some(hook) => match hook_shaders.get::<T>(hook) {
+                Some(shader) => Some(shader),
+                // The hook's shader has not been generated yet
+                None => continue,
+            },
+            None => None,
+        };
+
+        match buffer.material.alpha_mode.0 {
+            AlphaMode::Opaque => {
+                let pipeline =
+                    shape_pipelines.specialize::<T>(&pipeline_cache, pipeline.as_ref(), key, hook);
+                opaque_phase.add(Opaque3dPrepass {
+                    entity,
+                    draw_function: draw_opaque,
+                    pipeline_id: pipeline,
+                    distance: buffer.distance,
+                });
+            }
+            AlphaMode::Mask(_) => {
+                let pipeline =
+                    shape_pipelines.specialize::<T>(&pipeline_cache, pipeline.as_ref(), key, hook);
+                alpha_mask_phase.add(AlphaMask3dPrepass {
+                    entity,
+                    draw_function: draw_alpha_mask,
+                    pipeline_id: pipeline,
+                    distance: buffer.distance,
+                });
+            }
+            _ => {}
+        }
+    }
+}
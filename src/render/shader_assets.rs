@@ -0,0 +1,67 @@
+use bevy::{asset::HandleId, prelude::*};
+
+use crate::render::*;
+
+/// Path within the asset folder that shape shaders are loaded from when the `asset_shaders` feature is enabled.
+pub const SHADER_ASSET_DIR: &str = "shaders/bevy_vector_shapes";
+
+/// Shaders loaded from the asset folder paired with the internal handle they replace.
+///
+/// The embedded shaders are always loaded first, any file missing from [`SHADER_ASSET_DIR`] leaves the embedded version in place.
+/// Copies of `src/render/shaders` placed in the asset folder can then be edited and hot-reloaded,
+/// which requires bevy's `filesystem_watcher` feature and [`AssetPlugin::watch_for_changes`].
+#[derive(Resource, Default)]
+pub struct ShapeShaderAssets {
+    shaders: Vec<(Handle<Shader>, HandleId)>,
+}
+
+fn shader_paths() -> [(HandleId, &'static str); 10] {
+    [
+        (BINDINGS_HANDLE.id(), "bindings.wgsl"),
+        (FUNCTIONS_HANDLE.id(), "functions.wgsl"),
+        (DISC_HANDLE.id(), "shapes/disc.wgsl"),
+        (LINE_HANDLE.id(), "shapes/line.wgsl"),
+        (QUAD_BEZIER_HANDLE.id(), "shapes/quad_bezier.wgsl"),
+        (NGON_HANDLE.id(), "shapes/ngon.wgsl"),
+        (RECT_HANDLE.id(), "shapes/rect.wgsl"),
+        (MORPH_HANDLE.id(), "shapes/morph.wgsl"),
+        (TRIANGLE_HANDLE.id(), "shapes/triangle.wgsl"),
+        (CULL_HANDLE.id(), "cull.wgsl"),
+    ]
+}
+
+/// Begin loading each shape shader from the asset folder.
+pub(crate) fn load_shader_assets(app: &mut App) {
+    let asset_server = app.world.resource::<AssetServer>();
+    let shaders = shader_paths()
+        .into_iter()
+        .map(|(id, path)| (asset_server.load(format!("{SHADER_ASSET_DIR}/{path}")), id))
+        .collect();
+
+    app.insert_resource(ShapeShaderAssets { shaders })
+        .add_system(mirror_shader_assets);
+}
+
+/// Copies each loaded or modified shader over the internal handle it replaces so pipelines are re-specialized.
+pub fn mirror_shader_assets(
+    mut events: EventReader<AssetEvent<Shader>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    shader_assets: Res<ShapeShaderAssets>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        let Some((_, id)) = shader_assets
+            .shaders
+            .iter()
+            .find(|(loaded, _)| loaded == handle)
+        else {
+            continue;
+        };
+        let Some(shader) = shaders.get(handle).cloned() else {
+            continue;
+        };
+        shaders.set_untracked(*id, shader);
+    }
+}
@@ -26,11 +26,51 @@ struct View {
 @group(0) @binding(0)
 var<uniform> view: View;
 
+struct ShapeDirectionalLight {
+    // Direction towards the light
+    direction: vec3<f32>,
+    // Color premultiplied by illuminance and exposure
+    color: vec4<f32>,
+};
+
+struct ShapeFog {
+    // Color of the fog with alpha as it's intensity
+    color: vec4<f32>,
+    // 0: Off, 1: Linear, 2: Exponential, 3: ExponentialSquared, 4: Atmospheric
+    mode: u32,
+    // Linear start and end or density in x, or atmospheric extinction
+    be: vec3<f32>,
+    // Atmospheric inscattering
+    bi: vec3<f32>,
+};
+
+struct ShapeView {
+    // Physical pixels per logical pixel
+    pixel_scale: f32,
+    // Ambient light color premultiplied by brightness
+    ambient: vec4<f32>,
+    directional_count: u32,
+    directional: array<ShapeDirectionalLight, 4>,
+    fog: ShapeFog,
+    // Seconds since startup, wraps every hour to maintain precision
+    time: f32,
+    // Seconds since the previous frame
+    delta_time: f32,
+};
+
+@group(0) @binding(1)
+var<uniform> shape_view: ShapeView;
+
 #ifdef TEXTURED
 #ifdef FRAGMENT
 
+#ifdef TEXTURE_ARRAY
+@group(1) @binding(0)
+var image: texture_2d_array<f32>;
+#else
 @group(1) @binding(0)
 var image: texture_2d<f32>;
+#endif
 
 @group(1) @binding(1)
 var image_sampler: sampler;
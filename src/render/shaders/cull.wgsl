@@ -0,0 +1,70 @@
+#define_import_path bevy_vector_shapes::cull
+
+struct CullParams {
+    // Size of a single instance in words
+    stride: u32,
+    // Number of instances in the batch
+    count: u32,
+    // Offset of the batch within the instance and visible buffers in words
+    offset: u32,
+    // Index of the batch's first radius
+    radii: u32,
+    // Index of the batch's draw arguments
+    indirect: u32,
+    // Frustum planes of the view as normal and distance
+    planes: array<vec4<f32>, 6>,
+};
+
+struct DrawIndirect {
+    vertex_count: u32,
+    instance_count: atomic<u32>,
+    first_vertex: u32,
+    first_instance: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: CullParams;
+
+@group(0) @binding(1)
+var<storage, read> instances: array<u32>;
+
+@group(0) @binding(2)
+var<storage, read> radii: array<f32>;
+
+@group(0) @binding(3)
+var<storage, read_write> visible: array<u32>;
+
+@group(0) @binding(4)
+var<storage, read_write> indirect: array<DrawIndirect>;
+
+@compute @workgroup_size(64)
+fn cull(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+
+    // The translation of each instance is stored in the last column of it's transform
+    let base = params.offset + index * params.stride;
+    let center = vec3<f32>(
+        bitcast<f32>(instances[base + 12u]),
+        bitcast<f32>(instances[base + 13u]),
+        bitcast<f32>(instances[base + 14u])
+    );
+
+    // Test the bounding sphere of the instance against each plane of the frustum
+    let radius = radii[params.radii + index];
+    for (var i = 0u; i < 6u; i++) {
+        let plane = params.planes[i];
+        if dot(plane.xyz, center) + plane.w < -radius {
+            return;
+        }
+    }
+
+    // Append the instance to the batch's visible instances and increase the instance count of it's draw
+    let slot = atomicAdd(&indirect[params.indirect].instance_count, 1u);
+    let visible_base = params.offset + slot * params.stride;
+    for (var i = 0u; i < params.stride; i++) {
+        visible[visible_base + i] = instances[base + i];
+    }
+}
@@ -31,7 +31,7 @@ fn get_thickness_pixels(thickness: f32, thickness_type: u32, pixels_per_u: f32)
             return thickness * pixels_per_u;
         }
         case 1u: { // PIXELS
-            return thickness;
+            return thickness * shape_view.pixel_scale;
         }
         case 2u: { // SCREEN
             return min(view.viewport.z, view.viewport.w) * (thickness / 100.);
@@ -65,16 +65,129 @@ fn calculate_thickness(thickness_data: ThicknessData, uv_scale: f32, flags: u32)
     }
 }
 
+// Whether the view uses an orthographic projection, perspective projections always have a 0 in their bottom right element
+fn is_orthographic() -> bool {
+    return view.projection[3].w == 1.0;
+}
+
 fn p_to_camera_dir(p: vec3<f32>) -> vec3<f32> {
 #ifdef PIPELINE_2D
     return transpose(view.inverse_view)[2].xyz;
 #endif
 
 #ifdef PIPELINE_3D
+    // Orthographic projections view along a single direction regardless of position
+    if is_orthographic() {
+        return transpose(view.inverse_view)[2].xyz;
+    }
     return normalize(view.world_position - p);
 #endif
 }
 
+// Offset a world position towards the camera by the given depth bias without changing it's screen position
+fn apply_depth_bias(world_pos: vec3<f32>, depth_bias: f32) -> vec3<f32> {
+    return world_pos + p_to_camera_dir(world_pos) * depth_bias;
+}
+
+// Round the thickness of pixel snapped shapes to a whole number of pixels
+fn snap_thickness(thickness_data: ThicknessData, flags: u32) -> ThicknessData {
+    var out = thickness_data;
+    if f_pixel_snap(flags) > 0u {
+        out.thickness_p = max(round(out.thickness_p), 1.0);
+    }
+    return out;
+}
+
+// Translate the clip position of pixel snapped shapes such that their origin lies on the device pixel grid
+// Origins are snapped to pixel centers for odd thicknesses and pixel corners otherwise, so that edges fall between pixels
+fn apply_pixel_snap(clip_pos: vec4<f32>, origin: vec3<f32>, thickness_p: f32, flags: u32) -> vec4<f32> {
+    if f_pixel_snap(flags) == 0u {
+        return clip_pos;
+    }
+
+    let origin_clip = view.view_proj * vec4<f32>(origin, 1.0);
+    let pixel = (origin_clip.xy / origin_clip.w * 0.5 + 0.5) * view.viewport.zw;
+    let half_pixel = select(0.0, 0.5, u32(round(thickness_p)) % 2u == 1u);
+    let snapped = round(pixel - half_pixel) + half_pixel;
+    let offset = (snapped - pixel) / view.viewport.zw * 2.0;
+    return vec4<f32>(clip_pos.xy + offset * clip_pos.w, clip_pos.zw);
+}
+
+// Move overlay shapes onto the near plane such that they pass the depth test against all geometry
+fn apply_overlay(clip_pos: vec4<f32>, flags: u32) -> vec4<f32> {
+    if f_overlay(flags) > 0u {
+#ifdef STANDARD_Z
+        return vec4<f32>(clip_pos.xy, 0.0, clip_pos.w);
+#else
+        return vec4<f32>(clip_pos.xy, clip_pos.w, clip_pos.w);
+#endif
+    }
+    return clip_pos;
+}
+
+// Convert an instance color to the linear color that shapes are blended with, see ShapeColorSpace
+// Intensity scales the linear color without affecting alpha
+fn decode_color(color: vec4<f32>, intensity: f32) -> vec4<f32> {
+#ifdef SRGB_COLORS
+    let rgb = color.rgb;
+    let linear = select(pow((rgb + 0.055) / 1.055, vec3<f32>(2.4)), rgb / 12.92, rgb <= vec3<f32>(0.04045));
+#else
+    let linear = color.rgb;
+#endif
+    return vec4<f32>(linear * intensity, color.a);
+}
+
+// Shade lit shapes with the view's ambient and directional lights, shapes are lit from both sides
+fn apply_lighting(color: vec4<f32>, normal: vec3<f32>, flags: u32) -> vec4<f32> {
+    if f_lit(flags) == 0u {
+        return color;
+    }
+
+    var light = shape_view.ambient.rgb;
+    for (var i = 0u; i < shape_view.directional_count; i++) {
+        let directional = shape_view.directional[i];
+        light += directional.color.rgb * abs(dot(normal, directional.direction));
+    }
+    return vec4<f32>(color.rgb * light, color.a);
+}
+
+// Blend shapes with fog towards the view's fog color based on their distance from the camera
+// Matches the falloff modes of Bevy's FogSettings, directional light scattering is not supported
+fn apply_fog(color: vec4<f32>, world_pos: vec3<f32>, flags: u32) -> vec4<f32> {
+    if f_fog(flags) == 0u {
+        return color;
+    }
+
+    let fog = shape_view.fog;
+    let distance = length(world_pos - view.world_position);
+    var intensity = 0.0;
+    switch fog.mode {
+        // Linear
+        case 1u: {
+            intensity = 1.0 - clamp((fog.be.y - distance) / (fog.be.y - fog.be.x), 0.0, 1.0);
+        }
+        // Exponential
+        case 2u: {
+            intensity = 1.0 - 1.0 / exp(distance * fog.be.x);
+        }
+        // ExponentialSquared
+        case 3u: {
+            intensity = 1.0 - 1.0 / exp(pow(distance * fog.be.x, 2.0));
+        }
+        // Atmospheric
+        case 4u: {
+            let extinction = 1.0 - 1.0 / exp(distance * fog.be);
+            let inscattering = 1.0 - 1.0 / exp(distance * fog.bi);
+            return vec4<f32>(color.rgb * (1.0 - extinction) + fog.color.rgb * inscattering * fog.color.a, color.a);
+        }
+        // Off
+        default: {
+            return color;
+        }
+    }
+    return vec4<f32>(mix(color.rgb, fog.color.rgb, intensity * fog.color.a), color.a);
+}
+
 // Rotate the given 2d vector on the z axis by the given angle
 fn rotate_vec_a(v: vec2<f32>, a: f32) -> vec2<f32> {
     var point = vec2<f32>(cos(a), sin(a));
@@ -88,10 +201,14 @@ fn rotate_vec_a(v: vec2<f32>, a: f32) -> vec2<f32> {
 // bitfield! {
 //     pub struct Flags(u32);
 //     pub u32, from into ThicknessType, _, set_thickness_type: 1, 0;
-//     pub u32, from into Alignment, _, set_alignment: 2, 2;
-//     pub u32, _, set_hollow: 3, 3;
-//     pub u32, from into Cap, _, set_cap: 5, 4;
-//     pub u32, _, set_arc: 6, 6;
+//     pub u32, from into Alignment, _, set_alignment: 3, 2;
+//     pub u32, _, set_hollow: 4, 4;
+//     pub u32, from into Cap, _, set_cap: 6, 5;
+//     pub u32, _, set_arc: 7, 7;
+//     pub u32, _, set_overlay: 8, 8;
+//     pub u32, _, set_lit: 9, 9;
+//     pub u32, _, set_fog: 10, 10;
+//     pub u32, _, set_pixel_snap: 11, 11;
 // }
 
 fn f_thickness_type(flags: u32) -> u32 {
@@ -99,19 +216,48 @@ fn f_thickness_type(flags: u32) -> u32 {
 }
 
 fn f_alignment(flags: u32) -> u32 {
-    return (flags >> 2u) & 1u;
+    return (flags >> 2u) & 3u;
 }
 
 fn f_hollow(flags: u32) -> u32 {
-    return (flags >> 3u) & 1u;
+    return (flags >> 4u) & 1u;
 }
 
 fn f_cap(flags: u32) -> u32 {
-    return (flags >> 4u) & 3u;
+    return (flags >> 5u) & 3u;
 }
 
 fn f_arc(flags: u32) -> u32 {
-    return (flags >> 6u) & 1u;
+    return (flags >> 7u) & 1u;
+}
+
+fn f_overlay(flags: u32) -> u32 {
+    return (flags >> 8u) & 1u;
+}
+
+// Optional per shape features are compiled out of low spec shaders by treating their flags as unset
+fn f_lit(flags: u32) -> u32 {
+#ifdef LOW_SPEC
+    return 0u;
+#else
+    return (flags >> 9u) & 1u;
+#endif
+}
+
+fn f_fog(flags: u32) -> u32 {
+#ifdef LOW_SPEC
+    return 0u;
+#else
+    return (flags >> 10u) & 1u;
+#endif
+}
+
+fn f_pixel_snap(flags: u32) -> u32 {
+#ifdef LOW_SPEC
+    return 0u;
+#else
+    return (flags >> 11u) & 1u;
+#endif
 }
 
 #ifdef LOCAL_AA
@@ -179,6 +325,17 @@ fn get_basis_vectors_from_up(matrix: mat4x4<f32>, origin: vec3<f32>, y_basis: ve
         case 1u: {
             z_basis = p_to_camera_dir(origin);
         }
+        // Alignment::AxisBillboard
+        case 2u: {
+            // Project the direction to the camera onto the plane perpendicular to our y basis
+            var to_camera = p_to_camera_dir(origin);
+            var projected = to_camera - dot(to_camera, y_basis) * y_basis;
+            if length(projected) < 0.0001 {
+                z_basis = normalize(matrix[2].xyz);
+            } else {
+                z_basis = normalize(projected);
+            }
+        }
     }
 
     // The x basis is then calculated as the cross product of the y and z basis
@@ -200,20 +357,24 @@ struct VertexData {
     clip_pos: vec4<f32>,
     local_pos: vec2<f32>,
     uv_ratio: vec2<f32>,
-    scale: vec2<f32>
+    scale: vec2<f32>,
+    normal: vec3<f32>,
+    world_pos: vec3<f32>
 };
 
 // Calculate the full set of vertex data shared betwen each shape type
-fn get_vertex_data(matrix: mat4x4<f32>, vertex: vec2<f32>, thickness: f32, flags: u32) -> VertexData {
+fn get_vertex_data(matrix: mat4x4<f32>, vertex: vec2<f32>, thickness: f32, flags: u32, depth_bias: f32) -> VertexData {
     var out: VertexData;
 
     // Transform the origin into world space
     var origin = (matrix * vec4<f32>(0.0, 0.0, 0.0, 1.0)).xyz;
     var basis_vectors = get_basis_vectors(matrix, origin, flags);
+    out.normal = basis_vectors[2];
 
     // Get thickness data at our origin given our up vector
     var thickness_type = f_thickness_type(flags);
     out.thickness_data = get_thickness_data(thickness, thickness_type, origin, basis_vectors[1]);
+    out.thickness_data = snap_thickness(out.thickness_data, flags);
 
     // Calculate the local position of our vertex by scaling it
     out.scale = get_scale(matrix);
@@ -229,32 +390,108 @@ fn get_vertex_data(matrix: mat4x4<f32>, vertex: vec2<f32>, thickness: f32, flags
 
     // Rotate the position based on our basis vectors and add the world position offset
     var world_pos = origin + (padded_pos.x * basis_vectors[0]) + (padded_pos.y * basis_vectors[1]);
+    world_pos = apply_depth_bias(world_pos, depth_bias);
+    out.world_pos = world_pos;
 
-    // Transform to clip space
-    out.clip_pos = view.view_proj * vec4<f32>(world_pos, 1.0);
+    // Transform to clip space, filled shapes have no edges of a given thickness so snap to pixel corners
+    var snap_thickness_p = select(0.0, out.thickness_data.thickness_p, f_hollow(flags) > 0u);
+    out.clip_pos = apply_pixel_snap(view.view_proj * vec4<f32>(world_pos, 1.0), origin, snap_thickness_p, flags);
+    out.clip_pos = apply_overlay(out.clip_pos, flags);
     return out;
 }
 
 fn get_texture_uv(vertex: vec2<f32>) -> vec2<f32> {
-    return (vertex + 1.0) / 2.0;
+    // Texture coordinates have their origin at the top left with y pointing down
+    return vec2<f32>(vertex.x + 1.0, 1.0 - vertex.y) / 2.0;
+}
+
+// Convert a clip rect from logical to physical pixels to be compared against the fragment position
+fn get_clip_bounds(clip: vec4<f32>) -> vec4<f32> {
+    return clip * shape_view.pixel_scale;
 }
 
 #ifdef FRAGMENT
+#ifdef SHAPE_DEBUG
+// Whether the fragment should be drawn with the debug color, quad is the position within the instance's quad from -1 to 1
+fn is_debug_fragment(quad: vec2<f32>) -> bool {
+#ifdef DEBUG_BOUNDS
+    // Fragments within roughly a pixel of the quad's edge
+    return any(abs(quad) > 1.0 - fwidth(quad) * 1.5);
+#else
+    return true;
+#endif
+}
+
+fn debug_color() -> vec4<f32> {
+#ifdef DEBUG_BOUNDS
+    return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+#else
+    // Heat added by each fragment, roughly 10 overlapping fragments saturate to white
+    return vec4<f32>(0.2, 0.1, 0.05, 1.0);
+#endif
+}
+#endif
+
+// Interleaved gradient noise, a cheap per pixel approximation of blue noise in the range 0 to 1
+fn interleaved_gradient_noise(pos: vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(pos, vec2<f32>(0.06711056, 0.00583715))));
+}
+
+// Offset the color by up to half an 8-bit step, noise is applied in approximate sRGB space so steps are even
+fn dither(color: vec4<f32>, frag_coord: vec2<f32>) -> vec4<f32> {
+    let noise = vec3<f32>(
+        interleaved_gradient_noise(frag_coord),
+        interleaved_gradient_noise(frag_coord + vec2<f32>(17.0, 31.0)),
+        interleaved_gradient_noise(frag_coord + vec2<f32>(59.0, 83.0)),
+    );
+    let srgb = pow(max(color.rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / 2.2)) + (noise - 0.5) / 255.0;
+    return vec4<f32>(pow(max(srgb, vec3<f32>(0.0)), vec3<f32>(2.2)), color.a);
+}
+
+// Input passed to the get_color function of a ShapeHook
+struct ShapeHookInput {
+    // Color of the shape with alpha masked to the shape
+    color: vec4<f32>,
+    // Position within the shape in uv space
+    uv: vec2<f32>,
+    // User data of the shape instance
+    user_data: vec4<f32>,
+};
+
 // Transform our color output to respect the alpha mode set for our shape and combine with our texture if any
-fn color_output(color: vec4<f32>, f: FragmentInput) -> vec4<f32> {
+fn color_output(in_color: vec4<f32>, f: FragmentInput) -> vec4<f32> {
+#ifndef LOW_SPEC
+    // Discard fragments outside the shape's clip rect
+    if any(f.frag_coord.xy < f.clip.xy) || any(f.frag_coord.xy >= f.clip.zw) {
+        discard;
+    }
+#endif
+
+    var color = in_color;
+
+#ifdef FRAGMENT_HOOK
+    // get_color is provided by the ShapeHook appended to this shader
+    color = get_color(ShapeHookInput(color, f.uv, f.user_data));
+#endif
+
 #ifdef BLEND_MULTIPLY
-    var color = vec4<f32>(color.rgb * color.a, color.a);
+    color = vec4<f32>(color.rgb * color.a, color.a);
 #endif
 #ifdef BLEND_ADD
-    var color = vec4<f32>(color.rgb * color.a, 0.0);
-#endif
-#ifdef BLEND_ALPHA
-    var color = color;
+    color = vec4<f32>(color.rgb * color.a, 0.0);
 #endif
 
 #ifdef TEXTURED
+#ifdef TEXTURE_ARRAY
+    color = color * textureSample(image, image_sampler, f.texture_uv, f.texture_layer);
+#else
     color = color * textureSample(image, image_sampler, f.texture_uv);
 #endif
+#endif
+
+#ifdef DITHER
+    color = dither(color, f.frag_coord.xy);
+#endif
 
     return color;
 }
@@ -1,3 +1,5 @@
+#define_import_path bevy_vector_shapes::disc
+
 #import bevy_vector_shapes::bindings
 
 struct Vertex {
@@ -9,11 +11,14 @@ struct Vertex {
 
     @location(4) color: vec4<f32>,  
     @location(5) thickness: f32,
-    @location(6) flags: u32,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(6) style: vec4<u32>,
+    @location(7) clip: vec4<f32>,
+    @location(8) user_data: vec4<f32>,
   
-    @location(7) radius: f32,
-    @location(8) start_angle: f32, 
-    @location(9) end_angle: f32,
+    @location(9) radius: f32,
+    @location(10) start_angle: f32, 
+    @location(11) end_angle: f32,
 };
 
 struct VertexOutput {
@@ -24,8 +29,14 @@ struct VertexOutput {
     @location(3) angle: f32,
     @location(4) delta: f32,
     @location(5) cap: u32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(6) texture_uv: vec2<f32>,
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
 #endif
 };
 
@@ -45,18 +56,18 @@ fn vertex(v: Vertex) -> VertexOutput {
         v.matrix_3
     );
 
-    var vertex_data = get_vertex_data(matrix, vertex.xy * v.radius, v.thickness, v.flags);
+    var vertex_data = get_vertex_data(matrix, vertex.xy * v.radius, v.thickness, v.style.x, bitcast<f32>(v.style.y));
 
     // Multiply the world space position by the view projection matrix to convert to our clip position
     out.clip_position = vertex_data.clip_pos;
     out.uv = vertex.xy * vertex_data.uv_ratio;
-    out.thickness = calculate_thickness(vertex_data.thickness_data, v.radius, v.flags);
+    out.thickness = calculate_thickness(vertex_data.thickness_data, v.radius, v.style.x);
 
     // Extract cap type from flags
-    out.cap = f_cap(v.flags);
+    out.cap = f_cap(v.style.x);
 
     // Setup angles for the fragment shader if we are an arc
-    var arc = f_arc(v.flags);
+    var arc = f_arc(v.style.x);
     if arc > 0u {
         // Transform our angles such that 0 points towards y up
         var delta = (v.end_angle - v.start_angle) / 2.0;
@@ -70,22 +81,36 @@ fn vertex(v: Vertex) -> VertexOutput {
         out.delta = PI;
     }
 
-    out.color = v.color;
+    out.color = apply_lighting(decode_color(v.color, bitcast<f32>(v.style.w)), vertex_data.normal, v.style.x);
+    out.color = apply_fog(out.color, vertex_data.world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
 #ifdef TEXTURED
     out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_layer = v.style.z;
 #endif
     return out;
 }
 
 struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) thickness: f32,
     @location(3) angle: f32,
     @location(4) delta: f32,
     @location(5) cap: u32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(6) texture_uv: vec2<f32>,
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
 #endif
 };
 
@@ -93,6 +118,14 @@ struct FragmentInput {
 #ifdef FRAGMENT
 @fragment
 fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
     // Mask representing whether this fragment falls within the shape
     var in_shape = f.color.a;
 
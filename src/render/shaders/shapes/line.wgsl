@@ -1,3 +1,5 @@
+#define_import_path bevy_vector_shapes::line
+
 #import bevy_vector_shapes::bindings
 
 struct Vertex {
@@ -9,10 +11,13 @@ struct Vertex {
 
     @location(4) color: vec4<f32>,
     @location(5) thickness: f32,
-    @location(6) flags: u32,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(6) style: vec4<u32>,
+    @location(7) clip: vec4<f32>,
+    @location(8) user_data: vec4<f32>,
 
-    @location(7) start: vec3<f32>,
-    @location(8) end: vec3<f32>,
+    @location(9) start: vec3<f32>,
+    @location(10) end: vec3<f32>,
 };
 
 #import bevy_vector_shapes::functions
@@ -22,8 +27,14 @@ struct VertexOutput {
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) cap_ratio: f32,
+    @location(3) @interpolate(flat) clip: vec4<f32>,
+    @location(4) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(3) texture_uv: vec2<f32>,
+    @location(5) texture_uv: vec2<f32>,
+    @location(6) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(7) quad: vec2<f32>,
 #endif
 };
 
@@ -62,26 +73,27 @@ fn vertex(v: Vertex) -> VertexOutput {
     var origin = select(world_end, world_start, vertex.y < 0.0);
 
     // Calculate the remainder of our basis vectors
-    var basis_vectors = get_basis_vectors_from_up(matrix, origin, y_basis, v.flags);
+    var basis_vectors = get_basis_vectors_from_up(matrix, origin, y_basis, v.style.x);
 
     // Calculate thickness data
-    var thickness_type = f_thickness_type(v.flags);
+    var thickness_type = f_thickness_type(v.style.x);
     var thickness_data = get_thickness_data(v.thickness, thickness_type, origin, basis_vectors[1]);
 
     let scale = vec3<f32>(length(matrix[0].xyz), length(matrix[1].xyz), length(matrix[2].xyz));
 
     // If our thickness in pixels is less than 1, clamp to 1 and reduce the alpha instead
-    var out_color = v.color;
+    var out_color = decode_color(v.color, bitcast<f32>(v.style.w));
     if thickness_data.thickness_p * max(scale.x, scale.y) < 1.0 {
         out_color.a = out_color.a * thickness_data.thickness_p;
         thickness_data.thickness_p = 1.;
     }
+    thickness_data = snap_thickness(thickness_data, v.style.x);
 
     // Calculate thickness and radius in world units
     var thickness = thickness_data.thickness_p / thickness_data.pixels_per_u;
     var radius = thickness / 2.0;
 
-    var cap_type = f_cap(v.flags);
+    var cap_type = f_cap(v.style.x);
     var cap_length = 0.0;
 
     // If we have caps increase the cap length to our radius
@@ -110,24 +122,40 @@ fn vertex(v: Vertex) -> VertexOutput {
 
     // Determine final world position by offsetting by the origin we chose and rotating by our basis vectors
     var world_pos = origin + local_offset.x * basis_vectors[0] + local_offset.y * basis_vectors[1];
+    world_pos = apply_depth_bias(world_pos, bitcast<f32>(v.style.y));
 
     // Multiply the world space position by the view projection matrix to convert to our clip position
-    out.clip_position = view.view_proj * vec4<f32>(world_pos, 1.0);
+    out.clip_position = apply_pixel_snap(view.view_proj * vec4<f32>(world_pos, 1.0), origin, thickness_data.thickness_p, v.style.x);
+    out.clip_position = apply_overlay(out.clip_position, v.style.x);
     out.uv = vertex.xy * uv_ratio;
 
-    out.color = out_color;
+    out.color = apply_lighting(out_color, basis_vectors[2], v.style.x);
+    out.color = apply_fog(out.color, world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
 #ifdef TEXTURED
     out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_layer = v.style.z;
 #endif
     return out;
 }
 
 struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) cap_ratio: f32,
+    @location(3) @interpolate(flat) clip: vec4<f32>,
+    @location(4) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(3) texture_uv: vec2<f32>,
+    @location(5) texture_uv: vec2<f32>,
+    @location(6) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(7) quad: vec2<f32>,
 #endif
 };
 
@@ -135,6 +163,14 @@ struct FragmentInput {
 #ifdef FRAGMENT
 @fragment
 fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
     var in_shape = f.color.a;
 
     // If we have rounded caps mask them
@@ -0,0 +1,205 @@
+#define_import_path bevy_vector_shapes::morph
+
+#import bevy_vector_shapes::bindings
+
+struct Vertex {
+    @builtin(vertex_index) index: u32,
+    @location(0) matrix_0: vec4<f32>,
+    @location(1) matrix_1: vec4<f32>,
+    @location(2) matrix_2: vec4<f32>,
+    @location(3) matrix_3: vec4<f32>,
+
+    @location(4) color: vec4<f32>,
+    @location(5) thickness: f32,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(6) style: vec4<u32>,
+    @location(7) clip: vec4<f32>,
+    @location(8) user_data: vec4<f32>,
+
+    @location(9) from_params: vec4<f32>,
+    @location(10) to_params: vec4<f32>,
+    @location(11) kinds: vec2<u32>,
+    @location(12) progress: f32,
+};
+
+#import bevy_vector_shapes::functions
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) thickness: f32,
+    @location(3) @interpolate(flat) from_params: vec4<f32>,
+    @location(4) @interpolate(flat) to_params: vec4<f32>,
+    @location(5) @interpolate(flat) kinds: vec2<u32>,
+    @location(6) @interpolate(flat) progress: f32,
+    @location(7) @interpolate(flat) clip: vec4<f32>,
+    @location(8) @interpolate(flat) user_data: vec4<f32>,
+#ifdef TEXTURED
+    @location(9) texture_uv: vec2<f32>,
+    @location(10) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(11) quad: vec2<f32>,
+#endif
+};
+
+const MORPH_CIRCLE: u32 = 0u;
+const MORPH_RECTANGLE: u32 = 1u;
+
+// Half extents of a primitive in world space
+fn morph_extents(kind: u32, params: vec4<f32>) -> vec2<f32> {
+    if kind == MORPH_CIRCLE {
+        return vec2<f32>(params.x);
+    } else if kind == MORPH_RECTANGLE {
+        return params.xy;
+    }
+    return vec2<f32>(params.y);
+}
+
+// Convert the lengths within a primitive's parameters from world to uv space
+fn morph_uv_params(kind: u32, params: vec4<f32>, scale: f32) -> vec4<f32> {
+    if kind == MORPH_CIRCLE || kind == MORPH_RECTANGLE {
+        return params / scale;
+    }
+    // The side count of a polygon is not a length
+    return vec4<f32>(params.x, params.yzw / scale);
+}
+
+@vertex
+fn vertex(v: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Vertex positions for a basic quad
+    let vertex = get_quad_vertex(v);
+
+    // Reconstruct our transformation matrix
+    let matrix = mat4x4<f32>(
+        v.matrix_0,
+        v.matrix_1,
+        v.matrix_2,
+        v.matrix_3
+    );
+
+    // Our quad must cover both primitives
+    var extents = max(morph_extents(v.kinds.x, v.from_params), morph_extents(v.kinds.y, v.to_params));
+    var longest_side = max(extents.x, extents.y);
+
+    var vertex_data = get_vertex_data(matrix, vertex.xy * extents, v.thickness, v.style.x, bitcast<f32>(v.style.y));
+    out.clip_position = vertex_data.clip_pos;
+
+    // Scale our uv space such that the longest half extent is of length 1
+    out.uv = vertex.xy * extents / longest_side * vertex_data.uv_ratio;
+    out.thickness = calculate_thickness(vertex_data.thickness_data, longest_side, v.style.x);
+
+    out.from_params = morph_uv_params(v.kinds.x, v.from_params, longest_side);
+    out.to_params = morph_uv_params(v.kinds.y, v.to_params, longest_side);
+    out.kinds = v.kinds;
+    out.progress = v.progress;
+
+    out.color = apply_lighting(decode_color(v.color, bitcast<f32>(v.style.w)), vertex_data.normal, v.style.x);
+    out.color = apply_fog(out.color, vertex_data.world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
+#ifdef TEXTURED
+    out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_layer = v.style.z;
+#endif
+    return out;
+}
+
+struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) thickness: f32,
+    @location(3) @interpolate(flat) from_params: vec4<f32>,
+    @location(4) @interpolate(flat) to_params: vec4<f32>,
+    @location(5) @interpolate(flat) kinds: vec2<u32>,
+    @location(6) @interpolate(flat) progress: f32,
+    @location(7) @interpolate(flat) clip: vec4<f32>,
+    @location(8) @interpolate(flat) user_data: vec4<f32>,
+#ifdef TEXTURED
+    @location(9) texture_uv: vec2<f32>,
+    @location(10) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(11) quad: vec2<f32>,
+#endif
+};
+
+fn circleSDF(pos: vec2<f32>, radius: f32) -> f32 {
+    return length(pos) - radius;
+}
+
+// Distance to a rectangle with the given half size and corner rounding, see rect.wgsl for details
+fn roundedRectSDF(pos: vec2<f32>, half_size: vec2<f32>, corner_radius: f32) -> f32 {
+    var radius = min(corner_radius, min(half_size.x, half_size.y));
+    var to_corner = abs(pos) - (half_size - radius);
+    var outside_to_edge = max(vec2<f32>(0.), to_corner);
+    var inside_length = min(0., max(to_corner.x, to_corner.y));
+    return length(outside_to_edge) + inside_length - radius;
+}
+
+// Distance to a regular polygon with the given radius and corner rounding, see ngon.wgsl for details
+fn roundedNgonSDF(pos: vec2<f32>, sides: f32, radius: f32, roundness: f32) -> f32 {
+    var central_angle = TAU / sides;
+    var apothem = cos(central_angle / 2.) * radius;
+    var rounding = min(roundness, apothem);
+
+    // Shrink the polygon by the rounding radius so that the rounded polygon keeps it's size
+    var inner_apothem = apothem - rounding;
+    var half_side_length = tan(central_angle / 2.) * inner_apothem;
+
+    var pos = pos.yx;
+    var angle = atan2(pos.y, pos.x);
+    var nearest_angle = central_angle * floor((angle + 0.5 * central_angle) / central_angle);
+    var nearest_vertex = vec2<f32>(cos(nearest_angle), sin(nearest_angle));
+    pos = mat2x2<f32>(nearest_vertex.x, -nearest_vertex.y, nearest_vertex.y, nearest_vertex.x) * pos;
+    var nearest_point = vec2<f32>(inner_apothem, clamp(pos.y, -half_side_length, half_side_length));
+    return length(pos - nearest_point) * sign(pos.x - inner_apothem) - rounding;
+}
+
+fn primitiveSDF(pos: vec2<f32>, kind: u32, params: vec4<f32>) -> f32 {
+    if kind == MORPH_CIRCLE {
+        return circleSDF(pos, params.x);
+    } else if kind == MORPH_RECTANGLE {
+        return roundedRectSDF(pos, params.xy, params.z);
+    }
+    return roundedNgonSDF(pos, params.x, params.y, params.z);
+}
+
+// Due to https://github.com/gfx-rs/naga/issues/1743 this cannot be compiled into the vertex shader on web
+#ifdef FRAGMENT
+@fragment
+fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
+    // Mask representing whether this fragment falls within the shape
+    var in_shape = f.color.a;
+
+    // Blend between the distance fields of both primitives
+    var from_dist = primitiveSDF(f.uv, f.kinds.x, f.from_params);
+    var to_dist = primitiveSDF(f.uv, f.kinds.y, f.to_params);
+    var dist = mix(from_dist, to_dist, clamp(f.progress, 0.0, 1.0));
+
+    // Cut off points outside the shape or within the hollow area
+    in_shape *= step_aa(-f.thickness, dist) * step_aa(dist, 0.);
+
+    // Discard fragments no longer in the shape
+    if in_shape < 0.0001 {
+        discard;
+    }
+
+    return color_output(vec4<f32>(f.color.rgb, in_shape), f);
+}
+#endif
@@ -1,3 +1,5 @@
+#define_import_path bevy_vector_shapes::ngon
+
 #import bevy_vector_shapes::bindings
 
 struct Vertex {
@@ -9,11 +11,14 @@ struct Vertex {
 
     @location(4) color: vec4<f32>,  
     @location(5) thickness: f32,
-    @location(6) flags: u32,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(6) style: vec4<u32>,
+    @location(7) clip: vec4<f32>,
+    @location(8) user_data: vec4<f32>,
   
-    @location(7) sides: f32,
-    @location(8) radius: f32,
-    @location(9) roundness: f32
+    @location(9) sides: f32,
+    @location(10) radius: f32,
+    @location(11) roundness: f32
 };
 
 #import bevy_vector_shapes::functions
@@ -26,8 +31,14 @@ struct VertexOutput {
     @location(3) central_angle: f32,
     @location(4) half_side_length: f32,
     @location(5) roundness: f32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(6) texture_uv: vec2<f32>,
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
 #endif
 };
 
@@ -47,7 +58,7 @@ fn vertex(v: Vertex) -> VertexOutput {
     );
 
     // Calculate vertex data shared between most shapes
-    var vertex_data = get_vertex_data(matrix, vertex.xy * v.radius, v.thickness, v.flags);
+    var vertex_data = get_vertex_data(matrix, vertex.xy * v.radius, v.thickness, v.style.x, bitcast<f32>(v.style.y));
     out.clip_position = vertex_data.clip_pos;
 
     // Here we precompute several values related to our polygon
@@ -70,29 +81,43 @@ fn vertex(v: Vertex) -> VertexOutput {
     // We want 1 unit in uv space to be the length of the apothem of our polygon 
     // so scale world to uv space using the world space apothem
     out.uv = vertex_data.local_pos / (apothem * vertex_data.scale) * vertex_data.uv_ratio;
-    out.thickness = calculate_thickness(vertex_data.thickness_data, apothem, v.flags);
+    out.thickness = calculate_thickness(vertex_data.thickness_data, apothem, v.style.x);
     out.roundness = min(v.roundness / apothem, 1.0);
 
     // Scale our half side length to match our uv space of 1 unit per apothem
     // Precalculate our scaling by the inverse of roundness for our sdf
     out.half_side_length = half_side_length / unit_apothem * (1.0 - out.roundness);
 
-    out.color = v.color;
+    out.color = apply_lighting(decode_color(v.color, bitcast<f32>(v.style.w)), vertex_data.normal, v.style.x);
+    out.color = apply_fog(out.color, vertex_data.world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
 #ifdef TEXTURED
     out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_layer = v.style.z;
 #endif
     return out;
 }
 
 struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) thickness: f32,
     @location(3) central_angle: f32,
     @location(4) half_side_length: f32,
     @location(5) roundness: f32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(6) texture_uv: vec2<f32>,
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
 #endif
 };
 
@@ -128,6 +153,14 @@ fn ngonSDF(pos: vec2<f32>, central_angle: f32, half_side_length: f32, apothem: f
 #ifdef FRAGMENT
 @fragment
 fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
     // Mask representing whether this fragment falls within the shape
     var in_shape = f.color.a;
 
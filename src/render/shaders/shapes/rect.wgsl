@@ -1,3 +1,5 @@
+#define_import_path bevy_vector_shapes::rect
+
 #import bevy_vector_shapes::bindings
 
 struct Vertex {
@@ -9,10 +11,14 @@ struct Vertex {
 
     @location(4) color: vec4<f32>,  
     @location(5) thickness: f32,
-    @location(6) flags: u32,
-
-    @location(7) size: vec2<f32>,
-    @location(8) corner_radii: vec4<f32>,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(6) style: vec4<u32>,
+    @location(7) clip: vec4<f32>,
+    @location(8) user_data: vec4<f32>,
+
+    @location(9) size: vec2<f32>,
+    @location(10) corner_radii: vec4<f32>,
+    @location(11) texture_rect: vec4<f32>,
 };
 
 #import bevy_vector_shapes::functions
@@ -24,8 +30,14 @@ struct VertexOutput {
     @location(2) size: vec2<f32>,
     @location(3) corner_radii: vec4<f32>,
     @location(4) thickness: f32,
+    @location(5) @interpolate(flat) clip: vec4<f32>,
+    @location(6) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(5) texture_uv: vec2<f32>,
+    @location(7) texture_uv: vec2<f32>,
+    @location(8) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(9) quad: vec2<f32>,
 #endif
 };
 
@@ -46,32 +58,46 @@ fn vertex(v: Vertex) -> VertexOutput {
     // Shortest of the two side lengths for the rectangle
     var shortest_side = min(v.size.x, v.size.y);
 
-    var vertex_data = get_vertex_data(matrix, vertex.xy * v.size / 2.0, v.thickness, v.flags);
+    var vertex_data = get_vertex_data(matrix, vertex.xy * v.size / 2.0, v.thickness, v.style.x, bitcast<f32>(v.style.y));
     out.clip_position = vertex_data.clip_pos;
 
     // Our vertex outputs should all be in uv space so scale our uv space such that the shortest side is of length 1
     out.size = v.size / shortest_side;
     out.uv = vertex.xy * out.size * vertex_data.uv_ratio;
-    out.thickness = calculate_thickness(vertex_data.thickness_data, shortest_side / 2.0, v.flags);
+    out.thickness = calculate_thickness(vertex_data.thickness_data, shortest_side / 2.0, v.style.x);
 
     // Our corner radii cannot be more than half the shortest side so cap them
     out.corner_radii = 2.0 * min(v.corner_radii / shortest_side, vec4<f32>(0.5));
 
-    out.color = v.color;
+    out.color = apply_lighting(decode_color(v.color, bitcast<f32>(v.style.w)), vertex_data.normal, v.style.x);
+    out.color = apply_fog(out.color, vertex_data.world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
 #ifdef TEXTURED
-    out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_uv = mix(v.texture_rect.xy, v.texture_rect.zw, get_texture_uv(vertex.xy));
+    out.texture_layer = v.style.z;
 #endif
     return out;
 }
 
 struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) size: vec2<f32>,
     @location(3) corner_radii: vec4<f32>,
     @location(4) thickness: f32,
+    @location(5) @interpolate(flat) clip: vec4<f32>,
+    @location(6) @interpolate(flat) user_data: vec4<f32>,
 #ifdef TEXTURED
-    @location(5) texture_uv: vec2<f32>,
+    @location(7) texture_uv: vec2<f32>,
+    @location(8) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(9) quad: vec2<f32>,
 #endif
 };
 
@@ -107,6 +133,14 @@ fn quadrant(uv: vec2<f32>) -> i32 {
 #ifdef FRAGMENT
 @fragment
 fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
     // Mask representing whether this fragment falls within the shape
     var in_shape = f.color.a;
 
@@ -0,0 +1,157 @@
+#define_import_path bevy_vector_shapes::triangle
+
+#import bevy_vector_shapes::bindings
+
+struct Vertex {
+    @builtin(vertex_index) index: u32,
+    @location(0) matrix_0: vec4<f32>,
+    @location(1) matrix_1: vec4<f32>,
+    @location(2) matrix_2: vec4<f32>,
+    @location(3) matrix_3: vec4<f32>,
+
+    @location(4) color: vec4<f32>,
+    // Flags, depth bias, texture layer and intensity, floats are stored as bits
+    @location(5) style: vec4<u32>,
+    @location(6) clip: vec4<f32>,
+    @location(7) user_data: vec4<f32>,
+
+    @location(8) a: vec2<f32>,
+    @location(9) b: vec2<f32>,
+    @location(10) c: vec2<f32>,
+    @location(11) aa_edges: u32,
+};
+
+#import bevy_vector_shapes::functions
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) @interpolate(flat) a: vec2<f32>,
+    @location(3) @interpolate(flat) b: vec2<f32>,
+    @location(4) @interpolate(flat) c: vec2<f32>,
+    @location(5) @interpolate(flat) aa_edges: u32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
+#ifdef TEXTURED
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
+#endif
+};
+
+@vertex
+fn vertex(v: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Vertex positions for a basic quad
+    let vertex = get_quad_vertex(v);
+
+    // Reconstruct our transformation matrix
+    var matrix = mat4x4<f32>(
+        v.matrix_0,
+        v.matrix_1,
+        v.matrix_2,
+        v.matrix_3
+    );
+
+    // The quad covers the bounds of the triangle so move the origin to the center of the bounds
+    let bounds_min = min(min(v.a, v.b), v.c);
+    let bounds_max = max(max(v.a, v.b), v.c);
+    let center = (bounds_min + bounds_max) / 2.0;
+    let half_size = max((bounds_max - bounds_min) / 2.0, vec2<f32>(0.0001));
+    matrix[3] = matrix * vec4<f32>(center, 0.0, 1.0);
+
+    // Triangles have no thickness so pass a thickness of 1 to get the pixels per unit for padding
+    var vertex_data = get_vertex_data(matrix, vertex.xy * half_size, 1.0, v.style.x, bitcast<f32>(v.style.y));
+    out.clip_position = vertex_data.clip_pos;
+
+    // Our local y axis is flipped relative to the transform so flip our uv back to match the corners
+    out.uv = center + vertex.xy * half_size * vertex_data.uv_ratio * vec2<f32>(1.0, -1.0);
+    out.a = v.a;
+    out.b = v.b;
+    out.c = v.c;
+    out.aa_edges = v.aa_edges;
+
+    out.color = apply_lighting(decode_color(v.color, bitcast<f32>(v.style.w)), vertex_data.normal, v.style.x);
+    out.color = apply_fog(out.color, vertex_data.world_pos, v.style.x);
+    out.clip = get_clip_bounds(v.clip);
+    out.user_data = v.user_data;
+#ifdef SHAPE_DEBUG
+    out.quad = vertex.xy;
+#endif
+#ifdef TEXTURED
+    out.texture_uv = get_texture_uv(vertex.xy);
+    out.texture_layer = v.style.z;
+#endif
+    return out;
+}
+
+struct FragmentInput {
+    @builtin(position) frag_coord: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) @interpolate(flat) a: vec2<f32>,
+    @location(3) @interpolate(flat) b: vec2<f32>,
+    @location(4) @interpolate(flat) c: vec2<f32>,
+    @location(5) @interpolate(flat) aa_edges: u32,
+    @location(6) @interpolate(flat) clip: vec4<f32>,
+    @location(7) @interpolate(flat) user_data: vec4<f32>,
+#ifdef TEXTURED
+    @location(8) texture_uv: vec2<f32>,
+    @location(9) @interpolate(flat) texture_layer: u32,
+#endif
+#ifdef SHAPE_DEBUG
+    @location(10) quad: vec2<f32>,
+#endif
+};
+
+// 2D cross product of two vectors
+fn cross_2d(a: vec2<f32>, b: vec2<f32>) -> f32 {
+    return a.x * b.y - a.y * b.x;
+}
+
+// Signed distance from a point to the line through an edge, positive on the side of the triangle's interior
+fn edge_distance(pos: vec2<f32>, start: vec2<f32>, end: vec2<f32>, winding: f32) -> f32 {
+    let edge = end - start;
+    return winding * cross_2d(edge, pos - start) / max(length(edge), 0.0001);
+}
+
+// Due to https://github.com/gfx-rs/naga/issues/1743 this cannot be compiled into the vertex shader on web
+#ifdef FRAGMENT
+// Mask for a single edge, edges without anti-aliasing are cut off exactly so that neighbouring triangles don't overlap
+fn edge_mask(dist: f32, aa: bool) -> f32 {
+    // Partial derivatives must be taken in uniform control flow so compute both masks and select between them
+    return select(step(0.0, dist), step_aa(0.0, dist), aa);
+}
+
+@fragment
+fn fragment(f: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef SHAPE_DEBUG
+    // Debug visualization selected by the ShapeDebug resource replaces the shape
+    if !is_debug_fragment(f.quad) {
+        discard;
+    }
+    return debug_color();
+#endif
+
+    // Mask representing whether this fragment falls within the shape
+    var in_shape = f.color.a;
+
+    // Triangles may be wound either way so orient each edge's distance towards the interior
+    let winding = sign(cross_2d(f.b - f.a, f.c - f.a));
+
+    in_shape *= edge_mask(edge_distance(f.uv, f.a, f.b, winding), (f.aa_edges & 1u) != 0u);
+    in_shape *= edge_mask(edge_distance(f.uv, f.b, f.c, winding), (f.aa_edges & 2u) != 0u);
+    in_shape *= edge_mask(edge_distance(f.uv, f.c, f.a, winding), (f.aa_edges & 4u) != 0u);
+
+    // Discard fragments no longer in the shape
+    if in_shape < 0.0001 {
+        discard;
+    }
+
+    return color_output(vec4<f32>(f.color.rgb, in_shape), f);
+}
+#endif
@@ -0,0 +1,273 @@
+use bevy::{
+    core_pipeline::{core_2d, core_3d},
+    ecs::system::{lifetimeless::Read, SystemParamItem},
+    prelude::*,
+    render::{
+        camera::ExtractedCamera,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType},
+        render_phase::{
+            CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem, RenderCommand,
+            RenderCommandResult, RenderPhase, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice},
+        texture::{CachedTexture, TextureCache},
+        view::{ExtractedView, ViewTarget},
+        Extract,
+    },
+    utils::FloatOrd,
+};
+
+use crate::render::*;
+
+/// Texture format used for the stencil attachment of [`ShapeStencilNode`].
+pub const SHAPE_STENCIL_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
+
+/// Name of the [`ShapeStencilNode`] in the core 2d and 3d render graphs.
+pub const SHAPE_STENCIL_NODE: &str = "shape_stencil_pass";
+
+/// Phase containing all shapes with a [`StencilMode`] other than [`StencilMode::None`].
+pub struct ShapeStencilPhase {
+    pub entity: Entity,
+    pub pipeline: CachedRenderPipelineId,
+    pub draw_function: DrawFunctionId,
+    /// Stencil order as determined by [`StencilMode::order`] followed by distance.
+    pub sort_key: (u32, FloatOrd),
+}
+
+impl PhaseItem for ShapeStencilPhase {
+    type SortKey = (u32, FloatOrd);
+
+    #[inline]
+    fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    #[inline]
+    fn sort_key(&self) -> Self::SortKey {
+        self.sort_key
+    }
+
+    #[inline]
+    fn draw_function(&self) -> DrawFunctionId {
+        self.draw_function
+    }
+}
+
+impl CachedRenderPipelinePhaseItem for ShapeStencilPhase {
+    #[inline]
+    fn cached_pipeline(&self) -> CachedRenderPipelineId {
+        self.pipeline
+    }
+}
+
+/// Depth stencil texture used by a view's [`ShapeStencilNode`].
+#[derive(Component)]
+pub struct ShapeStencilTexture {
+    pub texture: CachedTexture,
+}
+
+/// Adds a [`ShapeStencilPhase`] to each active camera.
+pub fn extract_stencil_phases(
+    mut commands: Commands,
+    cameras: Extract<
+        Query<
+            (Entity, &Camera),
+            (
+                Or<(With<Camera2d>, With<Camera3d>)>,
+                Without<NoShapeRendering>,
+            ),
+        >,
+    >,
+) {
+    for (entity, camera) in &cameras {
+        if camera.is_active {
+            commands
+                .get_or_spawn(entity)
+                .insert(RenderPhase::<ShapeStencilPhase>::default());
+        }
+    }
+}
+
+/// Creates a [`ShapeStencilTexture`] for each view with stencil shapes queued.
+pub fn prepare_stencil_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedCamera, &RenderPhase<ShapeStencilPhase>)>,
+) {
+    for (entity, camera, phase) in &views {
+        if phase.items.is_empty() {
+            continue;
+        }
+        let Some(size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("shape_stencil_texture"),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: msaa.samples(),
+                dimension: TextureDimension::D2,
+                format: SHAPE_STENCIL_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+        commands
+            .entity(entity)
+            .insert(ShapeStencilTexture { texture });
+    }
+}
+
+/// Render graph node that draws the [`ShapeStencilPhase`] after the main pass with a dedicated stencil attachment.
+///
+/// Stencil shapes are drawn on top of the main pass and are not depth tested against the scene.
+pub struct ShapeStencilNode {
+    query: QueryState<
+        (
+            &'static RenderPhase<ShapeStencilPhase>,
+            &'static ViewTarget,
+            &'static ShapeStencilTexture,
+        ),
+        With<ExtractedView>,
+    >,
+}
+
+impl ShapeStencilNode {
+    pub const IN_VIEW: &'static str = "view";
+
+    pub fn new(world: &mut World) -> Self {
+        Self {
+            query: world.query_filtered(),
+        }
+    }
+}
+
+impl Node for ShapeStencilNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(Self::IN_VIEW, SlotType::Entity)]
+    }
+
+    fn update(&mut self, world: &mut World) {
+        self.query.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let view_entity = graph.get_input_entity(Self::IN_VIEW)?;
+        let Ok((phase, target, stencil)) = self.query.get_manual(world, view_entity) else {
+            return Ok(());
+        };
+
+        if phase.items.is_empty() {
+            return Ok(());
+        }
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("shape_stencil_pass"),
+            color_attachments: &[Some(target.get_color_attachment(Operations {
+                load: LoadOp::Load,
+                store: true,
+            }))],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &stencil.texture.default_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(0.0),
+                    store: false,
+                }),
+                stencil_ops: Some(Operations {
+                    load: LoadOp::Clear(0),
+                    store: false,
+                }),
+            }),
+        });
+
+        phase.render(&mut render_pass, world, view_entity);
+
+        Ok(())
+    }
+}
+
+/// Sets the stencil reference value for a shape batch according to it's [`StencilMode`].
+pub struct SetShapeStencilReference;
+
+impl<P: PhaseItem> RenderCommand<P> for SetShapeStencilReference {
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = Read<ShapeDataBuffer>;
+    type Param = ();
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        shape_buffer: &'w ShapeDataBuffer,
+        _param: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        pass.set_stencil_reference(shape_buffer.material.stencil.reference());
+        RenderCommandResult::Success
+    }
+}
+
+pub type DrawShapeStencilCommand = (
+    SetItemPipeline,
+    SetShapeViewBindGroup<0>,
+    SetShapeTextureBindGroup<1>,
+    SetShapeStencilReference,
+    DrawShape,
+);
+
+/// Adds the [`ShapeStencilNode`] to the given sub graph between the main pass and tonemapping.
+pub(crate) fn add_stencil_node(
+    render_app: &mut App,
+    sub_graph: &'static str,
+    input: &'static str,
+    main_pass: &'static str,
+    tonemapping: &'static str,
+) {
+    let node = ShapeStencilNode::new(&mut render_app.world);
+    let mut graph = render_app.world.resource_mut::<RenderGraph>();
+    let Some(graph) = graph.get_sub_graph_mut(sub_graph) else {
+        return;
+    };
+
+    graph.add_node(SHAPE_STENCIL_NODE, node);
+    graph.add_slot_edge(
+        graph.input_node().id,
+        input,
+        SHAPE_STENCIL_NODE,
+        ShapeStencilNode::IN_VIEW,
+    );
+    graph.add_node_edge(main_pass, SHAPE_STENCIL_NODE);
+    graph.add_node_edge(SHAPE_STENCIL_NODE, tonemapping);
+}
+
+pub(crate) fn add_stencil_nodes(render_app: &mut App) {
+    add_stencil_node(
+        render_app,
+        core_2d::graph::NAME,
+        core_2d::graph::input::VIEW_ENTITY,
+        core_2d::graph::node::MAIN_PASS,
+        core_2d::graph::node::TONEMAPPING,
+    );
+    add_stencil_node(
+        render_app,
+        core_3d::graph::NAME,
+        core_3d::graph::input::VIEW_ENTITY,
+        core_3d::graph::node::MAIN_PASS,
+        core_3d::graph::node::TONEMAPPING,
+    );
+}
@@ -0,0 +1,237 @@
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    render::{
+        render_resource::{
+            Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+        },
+        texture::ImageSampler,
+    },
+    utils::{HashMap, HashSet},
+};
+
+/// Combines the given images into a single array texture with one layer per image, in order.
+///
+/// Shapes drawn with the resulting texture and a [`ShapeConfig::texture_layer`](crate::prelude::ShapeConfig::texture_layer)
+/// are batched together regardless of which layer they sample, avoiding a draw call per image.
+/// Images drawn with [`RectPainter::image`](crate::prelude::RectPainter::image) are packed into arrays automatically
+/// by [`ShapeImageArrays`], this is only needed to build arrays by hand, such as for retained shapes.
+///
+/// Returns `None` if no images are given or they don't all share the same size and format.
+/// Images with more than one mip level or layer are not supported.
+pub fn create_texture_array(images: &[&Image]) -> Option<Image> {
+    let first = images.first()?;
+    let descriptor = &first.texture_descriptor;
+    if images.iter().any(|image| {
+        image.texture_descriptor.size != descriptor.size
+            || image.texture_descriptor.format != descriptor.format
+            || image.texture_descriptor.mip_level_count != 1
+            || image.texture_descriptor.size.depth_or_array_layers != 1
+    }) {
+        return None;
+    }
+
+    let data = images
+        .iter()
+        .flat_map(|image| image.data.iter().copied())
+        .collect();
+    let mut array = Image::new(
+        Extent3d {
+            depth_or_array_layers: images.len() as u32,
+            ..descriptor.size
+        },
+        TextureDimension::D2,
+        data,
+        descriptor.format,
+    );
+    array.sampler_descriptor = first.sampler_descriptor.clone();
+    array.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        ..default()
+    });
+    Some(array)
+}
+
+/// Texture array images are packed into, along with the size and format shared by every layer.
+struct ImageArray {
+    handle: Handle<Image>,
+    size: Extent3d,
+    format: TextureFormat,
+    layers: u32,
+}
+
+/// Resource that packs images drawn with [`RectPainter::image`](crate::prelude::RectPainter::image) into texture arrays,
+/// such that distinct images of the same size and format are drawn in a single batch.
+///
+/// Images are packed at the end of the frame they are first drawn in, until then they are drawn on their own.
+/// Packed images are copied into their layer again whenever they are modified.
+/// Images with more than one mip level or layer, or with a custom sampler, are never packed.
+#[derive(Resource, Default)]
+pub struct ShapeImageArrays {
+    /// Array and layer of each packed image.
+    layers: HashMap<HandleId, (Handle<Image>, u32)>,
+    /// Images drawn since they were last packed in the order they were first drawn,
+    /// weak such that drawing an image doesn't keep it loaded.
+    pending: Vec<Handle<Image>>,
+    /// Images that can't be packed.
+    unpackable: HashSet<HandleId>,
+    arrays: Vec<ImageArray>,
+}
+
+impl ShapeImageArrays {
+    /// Maximum number of images packed into each array, the minimum number of array layers supported by WebGPU.
+    pub const MAX_LAYERS: u32 = 256;
+
+    /// Array and layer the image has been packed into, queueing the image to be packed if it hasn't been yet.
+    pub fn layer(&mut self, image: &Handle<Image>) -> Option<(Handle<Image>, u32)> {
+        if let Some((array, layer)) = self.layers.get(&image.id()) {
+            return Some((array.clone(), *layer));
+        }
+        if !self.unpackable.contains(&image.id()) && !self.pending.contains(image) {
+            self.pending.push(image.clone_weak());
+        }
+        None
+    }
+}
+
+fn is_packable(image: &Image) -> bool {
+    let descriptor = &image.texture_descriptor;
+    descriptor.mip_level_count == 1
+        && descriptor.size.depth_or_array_layers == 1
+        && image.texture_view_descriptor.is_none()
+        && matches!(image.sampler_descriptor, ImageSampler::Default)
+}
+
+/// Packs the images queued by [`ShapeImageArrays::layer`] into arrays and copies modified images into their layers.
+pub fn pack_shape_images(
+    mut arrays: ResMut<ShapeImageArrays>,
+    mut images: ResMut<Assets<Image>>,
+    mut events: EventReader<AssetEvent<Image>>,
+) {
+    let arrays = arrays.as_mut();
+    for event in events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        let Some((array, layer)) = arrays.layers.get(&handle.id()).cloned() else {
+            continue;
+        };
+        let data = images
+            .get(handle)
+            .filter(|image| {
+                let descriptor = &image.texture_descriptor;
+                is_packable(image)
+                    && arrays.arrays.iter().any(|packed| {
+                        packed.handle == array
+                            && packed.size == descriptor.size
+                            && packed.format == descriptor.format
+                    })
+            })
+            .map(|image| image.data.clone());
+        if let (Some(data), Some(target)) = (data, images.get_mut(&array)) {
+            let start = data.len() * layer as usize;
+            target.data[start..start + data.len()].copy_from_slice(&data);
+        } else {
+            // Images that no longer match their array are packed again the next time they are drawn
+            arrays.layers.remove(&handle.id());
+        }
+    }
+
+    for image in std::mem::take(&mut arrays.pending) {
+        let Some(source) = images.get(&image) else {
+            // Images that haven't loaded yet are queued again when drawn
+            continue;
+        };
+        if !is_packable(source) {
+            arrays.unpackable.insert(image.id());
+            continue;
+        }
+
+        let (size, format) = (
+            source.texture_descriptor.size,
+            source.texture_descriptor.format,
+        );
+        let existing = arrays.arrays.iter_mut().find(|array| {
+            array.size == size
+                && array.format == format
+                && array.layers < ShapeImageArrays::MAX_LAYERS
+        });
+        let (handle, layer) = match existing {
+            Some(array) => {
+                let data = source.data.clone();
+                let Some(target) = images.get_mut(&array.handle) else {
+                    continue;
+                };
+                target.data.extend(data);
+                target.texture_descriptor.size.depth_or_array_layers += 1;
+                array.layers += 1;
+                (array.handle.clone(), array.layers - 1)
+            }
+            None => {
+                let Some(array) = create_texture_array(&[source]) else {
+                    arrays.unpackable.insert(image.id());
+                    continue;
+                };
+                let handle = images.add(array);
+                arrays.arrays.push(ImageArray {
+                    handle: handle.clone(),
+                    size,
+                    format,
+                    layers: 1,
+                });
+                (handle, 0)
+            }
+        };
+        arrays.layers.insert(image.id(), (handle, layer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(size: u32, value: u8) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[value; 4],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn images_of_the_same_size_share_an_array() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .init_resource::<ShapeImageArrays>()
+            .add_system(pack_shape_images);
+
+        let mut images = app.world.resource_mut::<Assets<Image>>();
+        let handles = [
+            images.add(image(2, 1)),
+            images.add(image(2, 2)),
+            images.add(image(4, 3)),
+        ];
+
+        let mut arrays = app.world.resource_mut::<ShapeImageArrays>();
+        assert!(handles.iter().all(|handle| arrays.layer(handle).is_none()));
+        app.update();
+
+        let mut arrays = app.world.resource_mut::<ShapeImageArrays>();
+        let layers = handles.map(|handle| arrays.layer(&handle).unwrap());
+        assert_eq!(layers[0].0, layers[1].0);
+        assert_ne!(layers[0].0, layers[2].0);
+        assert_eq!([layers[0].1, layers[1].1, layers[2].1], [0, 1, 0]);
+
+        let images = app.world.resource::<Assets<Image>>();
+        let array = images.get(&layers[1].0).unwrap();
+        assert_eq!(array.texture_descriptor.size.depth_or_array_layers, 2);
+        assert_eq!(array.data[16..], [2; 16]);
+    }
+}
@@ -0,0 +1,271 @@
+// The field checks generated by deriving `ShaderType` are reported as unused by newer compilers
+#![allow(dead_code)]
+
+#[cfg(feature = "ui")]
+use bevy::ui::DefaultCameraView;
+use bevy::{
+    pbr::{FogFalloff, FogSettings},
+    prelude::*,
+    render::{
+        camera::NormalizedRenderTarget,
+        render_resource::{DynamicUniformBuffer, ShaderType},
+        renderer::{RenderDevice, RenderQueue},
+        Extract,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::render::ShapePipelineKey;
+
+/// Component that overrides the number of physical pixels per logical pixel for shapes drawn by a camera.
+///
+/// By default this is taken from the scale factor of the camera's render target,
+/// such that shapes with [`ThicknessType::Pixels`] have the same logical thickness regardless of the display's scale factor.
+#[derive(Component, Clone, Copy, Debug, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct ShapePixelScale(pub f32);
+
+impl Default for ShapePixelScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Component that adds user defined bits to the [`ShapePipelineKey`] of every shape drawn by a camera.
+///
+/// Only bits within [`ShapePipelineKey::USER_RESERVED_BITS`] are used, allowing custom shape shaders and hooks to
+/// provide per camera variants. Changing the key re-specializes the affected pipelines.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ShapeViewKey(pub ShapePipelineKey);
+
+impl ShapeViewKey {
+    /// The user bits of the key.
+    pub fn user_bits(&self) -> ShapePipelineKey {
+        self.0.intersection(ShapePipelineKey::USER_RESERVED_BITS)
+    }
+}
+
+/// Marker component for cameras that should not draw any shapes.
+///
+/// Useful for cameras such as minimaps or reflection probes where shapes are not needed, skipping all per view shape work.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct NoShapeRendering;
+
+/// Component that controls which shape passes are drawn by a camera, cameras without it draw all passes.
+#[derive(Component, Clone, Copy, Debug, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct ShapeCameraSettings {
+    /// Draw shapes in the camera's main pass.
+    pub main: bool,
+    /// Draw shapes with a [`StencilMode`](crate::shapes::StencilMode) in the stencil pass.
+    pub stencil: bool,
+    /// Draw shapes with [`ShapePass::PostProcess`](crate::shapes::ShapePass::PostProcess) after post processing.
+    pub post_process: bool,
+    /// Draw shapes with [`ShapePass::Ui`](crate::shapes::ShapePass::Ui) in the UI pass.
+    pub ui: bool,
+    /// Draw 3D shapes into the depth prepass, see [`ShapePrepass`](crate::render::ShapePrepass).
+    pub prepass: bool,
+}
+
+impl Default for ShapeCameraSettings {
+    fn default() -> Self {
+        Self {
+            main: true,
+            stencil: true,
+            post_process: true,
+            ui: true,
+            prepass: true,
+        }
+    }
+}
+
+/// Maximum number of directional lights that affect lit shapes.
+pub const MAX_SHAPE_DIRECTIONAL_LIGHTS: usize = 4;
+
+/// Exposure matching that used by Bevy's PBR lighting, equivalent to an EV100 of 9.7.
+fn exposure() -> f32 {
+    1.0 / (2.0f32.powf(9.7) * 1.2)
+}
+
+/// Directional light data used to shade lit shapes.
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+pub struct ShapeDirectionalLight {
+    /// Direction towards the light.
+    pub direction: Vec3,
+    /// Color premultiplied by illuminance and exposure.
+    pub color: Vec4,
+}
+
+/// Fog data matching the camera's [`FogSettings`] used to fade shapes with distance.
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+pub struct ShapeFog {
+    pub color: Vec4,
+    /// 0: Off, 1: Linear, 2: Exponential, 3: ExponentialSquared, 4: Atmospheric.
+    pub mode: u32,
+    pub be: Vec3,
+    pub bi: Vec3,
+}
+
+impl From<&FogSettings> for ShapeFog {
+    fn from(settings: &FogSettings) -> Self {
+        let color = Vec4::from(settings.color.as_linear_rgba_f32());
+        let (mode, be, bi) = match settings.falloff {
+            FogFalloff::Linear { start, end } => (1, Vec3::new(start, end, 0.0), Vec3::ZERO),
+            FogFalloff::Exponential { density } => (2, Vec3::new(density, 0.0, 0.0), Vec3::ZERO),
+            FogFalloff::ExponentialSquared { density } => {
+                (3, Vec3::new(density, 0.0, 0.0), Vec3::ZERO)
+            }
+            FogFalloff::Atmospheric {
+                extinction,
+                inscattering,
+            } => (4, extinction, inscattering),
+        };
+        Self {
+            color,
+            mode,
+            be,
+            bi,
+        }
+    }
+}
+
+/// Per view data used by shape shaders, resolved for each camera during extraction.
+#[derive(Component, ShaderType, Clone, Copy, Debug)]
+pub struct ShapeViewUniform {
+    /// Physical pixels per logical pixel.
+    pub pixel_scale: f32,
+    /// Ambient light color premultiplied by brightness.
+    pub ambient: Vec4,
+    pub directional_count: u32,
+    pub directional: [ShapeDirectionalLight; MAX_SHAPE_DIRECTIONAL_LIGHTS],
+    pub fog: ShapeFog,
+    /// Seconds since startup, wrapped to maintain precision, see [`Time::elapsed_seconds_wrapped`].
+    pub time: f32,
+    /// Seconds since the previous frame.
+    pub delta_time: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct ShapeViewUniforms {
+    pub uniforms: DynamicUniformBuffer<ShapeViewUniform>,
+}
+
+#[derive(Component)]
+pub struct ShapeViewUniformOffset {
+    pub offset: u32,
+}
+
+/// Scale factor of the window the camera renders to, images are always rendered at a scale factor of 1.
+pub(crate) fn target_scale_factor(
+    camera: &Camera,
+    primary_window: Option<Entity>,
+    windows: &Query<&Window>,
+) -> Option<f32> {
+    match camera.target.normalize(primary_window)? {
+        NormalizedRenderTarget::Window(window) => windows
+            .get(window.entity())
+            .ok()
+            .map(|window| window.resolution.scale_factor() as f32),
+        NormalizedRenderTarget::Image(_) => Some(1.0),
+    }
+}
+
+/// Extracts a [`ShapeViewUniform`] for each active camera.
+#[allow(clippy::too_many_arguments)]
+pub fn extract_shape_views(
+    mut commands: Commands,
+    cameras: Extract<
+        Query<(
+            Entity,
+            &Camera,
+            Option<&ShapePixelScale>,
+            Option<&FogSettings>,
+            Option<&ShapeViewKey>,
+            Option<&ShapeCameraSettings>,
+            Option<&NoShapeRendering>,
+        )>,
+    >,
+    directional_lights: Extract<Query<(&DirectionalLight, &GlobalTransform, &ComputedVisibility)>>,
+    primary_window: Extract<Query<Entity, With<PrimaryWindow>>>,
+    windows: Extract<Query<&Window>>,
+    ambient_light: Extract<Option<Res<AmbientLight>>>,
+    time: Extract<Res<Time>>,
+) {
+    let ambient = ambient_light.as_ref().map_or(Vec4::ZERO, |ambient| {
+        Vec4::from(ambient.color.as_linear_rgba_f32()) * ambient.brightness
+    });
+
+    let mut directional = [ShapeDirectionalLight::default(); MAX_SHAPE_DIRECTIONAL_LIGHTS];
+    let mut directional_count = 0;
+    for (light, transform, visibility) in &directional_lights {
+        if !visibility.is_visible() || directional_count == MAX_SHAPE_DIRECTIONAL_LIGHTS {
+            continue;
+        }
+        directional[directional_count] = ShapeDirectionalLight {
+            direction: transform.back(),
+            color: Vec4::from(light.color.as_linear_rgba_f32()) * light.illuminance * exposure(),
+        };
+        directional_count += 1;
+    }
+
+    let primary_window = primary_window.get_single().ok();
+    for (entity, camera, pixel_scale, fog, view_key, settings, no_shapes) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+        if no_shapes.is_some() {
+            commands.get_or_spawn(entity).insert(NoShapeRendering);
+            continue;
+        }
+
+        let pixel_scale = pixel_scale
+            .map(|scale| scale.0)
+            .or_else(|| target_scale_factor(camera, primary_window, &windows))
+            .unwrap_or(1.0);
+        commands.get_or_spawn(entity).insert(ShapeViewUniform {
+            pixel_scale,
+            ambient,
+            directional_count: directional_count as u32,
+            directional,
+            fog: fog.map(ShapeFog::from).unwrap_or_default(),
+            time: time.elapsed_seconds_wrapped(),
+            delta_time: time.delta_seconds(),
+        });
+        if let Some(view_key) = view_key {
+            commands.get_or_spawn(entity).insert(*view_key);
+        }
+        if let Some(settings) = settings {
+            commands.get_or_spawn(entity).insert(*settings);
+        }
+    }
+}
+
+/// Writes each [`ShapeViewUniform`] into [`ShapeViewUniforms`].
+pub fn prepare_shape_view_uniforms(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut shape_view_uniforms: ResMut<ShapeViewUniforms>,
+    views: Query<(Entity, &ShapeViewUniform)>,
+    #[cfg(feature = "ui")] ui_views: Query<&DefaultCameraView>,
+) {
+    shape_view_uniforms.uniforms.clear();
+    for (entity, uniform) in &views {
+        let offset = shape_view_uniforms.uniforms.push(*uniform);
+        commands
+            .entity(entity)
+            .insert(ShapeViewUniformOffset { offset });
+        // The UI pass draws with the camera's default UI view, which shares the camera's shape uniform
+        #[cfg(feature = "ui")]
+        if let Ok(ui_view) = ui_views.get(entity) {
+            commands
+                .entity(ui_view.0)
+                .insert(ShapeViewUniformOffset { offset });
+        }
+    }
+
+    shape_view_uniforms
+        .uniforms
+        .write_buffer(&render_device, &render_queue);
+}
@@ -0,0 +1,268 @@
+use bevy::{
+    prelude::*,
+    render::{view::RenderLayers, Extract, RenderApp},
+    sprite::Anchor,
+};
+
+use crate::{
+    prelude::*,
+    render::{is_shape_visible, ExtractedShapes, ShapeData, ShapePipelineMaterial},
+};
+
+/// Font drawn from an image containing a grid of equally sized glyphs, used by [`BitmapText`].
+///
+/// Glyphs are laid out left to right and top to bottom in order of their codepoint, starting from `first`.
+/// Pixel fonts should use an image with a nearest neighbour sampler to stay crisp.
+#[derive(Clone, Debug)]
+pub struct BitmapFont {
+    pub image: Handle<Image>,
+    /// Size of each glyph in pixels, only their aspect ratio affects the size text is drawn at.
+    pub glyph_size: Vec2,
+    /// Number of columns and rows of glyphs in the image.
+    pub grid: UVec2,
+    /// Character of the glyph in the top left of the image.
+    pub first: char,
+    /// Space between glyphs and lines in pixels.
+    pub spacing: Vec2,
+}
+
+impl BitmapFont {
+    /// Font of a grid of glyphs starting with a space, as in the common layout of printable ASCII characters.
+    pub fn new(image: Handle<Image>, glyph_size: Vec2, grid: UVec2) -> Self {
+        Self {
+            image,
+            glyph_size,
+            grid,
+            first: ' ',
+            spacing: Vec2::ZERO,
+        }
+    }
+
+    /// Part of the image containing the glyph for the given character in normalized texture coordinates.
+    pub fn glyph_rect(&self, c: char) -> Option<Rect> {
+        let index = (c as u32).checked_sub(self.first as u32)?;
+        if index >= self.grid.x * self.grid.y {
+            return None;
+        }
+        Some(sprite_frame_rect(self.grid, index))
+    }
+
+    /// Size of each glyph when drawn with the given line height.
+    pub fn glyph_extent(&self, height: f32) -> Vec2 {
+        Vec2::new(height * self.glyph_size.x / self.glyph_size.y, height)
+    }
+
+    /// Size of the given text when drawn with the given line height.
+    pub fn measure(&self, text: &str, height: f32) -> Vec2 {
+        let advance = self.advance(height);
+        let (columns, lines) = text.lines().fold((0, 0), |(columns, lines), line| {
+            (columns.max(line.chars().count()), lines + 1)
+        });
+        if lines == 0 {
+            return Vec2::ZERO;
+        }
+        Vec2::new(columns as f32, lines as f32) * advance - (advance - self.glyph_extent(height))
+    }
+
+    /// Distance between the centers of adjacent glyphs and lines.
+    fn advance(&self, height: f32) -> Vec2 {
+        let scale = height / self.glyph_size.y;
+        self.glyph_extent(height) + self.spacing * scale
+    }
+
+    /// Center and texture rect of each visible glyph of the text, relative to the anchor point of the text block.
+    pub fn layout<'a>(
+        &'a self,
+        text: &'a str,
+        height: f32,
+        anchor: &Anchor,
+    ) -> impl Iterator<Item = (Vec2, Rect)> + 'a {
+        let size = self.measure(text, height);
+        let advance = self.advance(height);
+        let extent = self.glyph_extent(height);
+        let anchor = anchor.as_vec();
+        let top_left = Vec2::new(-(anchor.x + 0.5) * size.x, (0.5 - anchor.y) * size.y);
+        text.lines().enumerate().flat_map(move |(row, line)| {
+            line.chars().enumerate().filter_map(move |(column, c)| {
+                if c.is_whitespace() {
+                    return None;
+                }
+                let rect = self.glyph_rect(c)?;
+                let offset = Vec2::new(column as f32, -(row as f32)) * advance;
+                let center = top_left + offset + Vec2::new(extent.x, -extent.y) / 2.0;
+                Some((center, rect))
+            })
+        })
+    }
+}
+
+/// Component that draws a string with a [`BitmapFont`] as a textured rectangle per glyph.
+///
+/// Glyphs are extracted to the rectangle pipeline, so all glyphs sharing a font are drawn in a single instanced batch.
+/// Lighter weight than rich text for HUDs in a retro style, but limited to monospaced fonts without kerning.
+/// Spawn with [`BitmapTextSpawner::bitmap_text`] or alongside a [`ShapeMaterial`] and spatial components.
+#[derive(Component, Clone, Debug)]
+pub struct BitmapText {
+    pub text: String,
+    pub font: BitmapFont,
+    /// Height of each line in world units.
+    pub height: f32,
+    /// Color multiplied with the font's image.
+    pub color: Color,
+    /// Point of the text block placed at the entity's origin.
+    pub anchor: Anchor,
+}
+
+impl BitmapText {
+    pub fn new(
+        config: &ShapeConfig,
+        font: &BitmapFont,
+        text: impl Into<String>,
+        height: f32,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            font: font.clone(),
+            height,
+            color: config.color,
+            anchor: Anchor::Center,
+        }
+    }
+
+    /// Instance data of each glyph given the entity's transform.
+    fn data(&self, tf: &GlobalTransform) -> impl Iterator<Item = RectData> + '_ {
+        let mut config = ShapeConfig {
+            color: self.color,
+            ..ShapeConfig::default_2d()
+        };
+        let transform = tf.compute_transform();
+        let extent = self.font.glyph_extent(self.height);
+        self.font
+            .layout(&self.text, self.height, &self.anchor)
+            .map(move |(center, rect)| {
+                config.transform =
+                    transform.mul_transform(Transform::from_translation(center.extend(0.0)));
+                config.texture_rect = Some(rect);
+                RectData::new(&config, extent)
+            })
+    }
+}
+
+/// Config for a glyph of a bitmap font drawn by the painter or spawned as part of a [`BitmapText`].
+fn glyph_config(config: &ShapeConfig, font: &BitmapFont) -> ShapeConfig {
+    let mut config = config.clone();
+    config.texture = Some(font.image.clone());
+    config.texture_layer = None;
+    config.hollow = false;
+    config.corner_radii = Vec4::ZERO;
+    config
+}
+
+/// Extension trait for [`ShapePainter`] to draw text with a [`BitmapFont`].
+pub trait BitmapTextPainter {
+    /// Draws the text centered on the painter's transform with the painter's color, each line is `height` world units tall.
+    fn bitmap_text(&mut self, font: &BitmapFont, text: &str, height: f32) -> &mut Self;
+}
+
+impl<'w, 's> BitmapTextPainter for ShapePainter<'w, 's> {
+    fn bitmap_text(&mut self, font: &BitmapFont, text: &str, height: f32) -> &mut Self {
+        let mut config = glyph_config(self.config(), font);
+        let extent = font.glyph_extent(height);
+        for (center, rect) in font.layout(text, height, &Anchor::Center) {
+            config.transform = self
+                .transform
+                .mul_transform(Transform::from_translation(center.extend(0.0)));
+            config.texture_rect = Some(rect);
+            self.send_with_config(&config, RectData::new(&config, extent));
+        }
+        self
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to spawn [`BitmapText`] entities.
+pub trait BitmapTextSpawner<'w, 's> {
+    /// Spawns an entity drawing the text centered on the config's transform with the config's color.
+    fn bitmap_text(
+        &mut self,
+        font: &BitmapFont,
+        text: impl Into<String>,
+        height: f32,
+    ) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> BitmapTextSpawner<'w, 's> for T {
+    fn bitmap_text(
+        &mut self,
+        font: &BitmapFont,
+        text: impl Into<String>,
+        height: f32,
+    ) -> ShapeEntityCommands<'w, 's, '_> {
+        let config = glyph_config(self.config(), font);
+        self.spawn_shape((
+            SpatialBundle::from_transform(config.transform),
+            ShapeMaterial::from(&config),
+            config.render_layers.unwrap_or_default(),
+            BitmapText::new(&config, font, text, height),
+        ))
+    }
+}
+
+/// Extracts the glyphs of each visible [`BitmapText`] into the rectangle pipeline.
+#[allow(clippy::type_complexity)]
+pub fn extract_bitmap_texts(
+    mut commands: Commands,
+    texts: Extract<
+        Query<(
+            &BitmapText,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&ShapeMaterial>,
+            Option<&RenderLayers>,
+            Option<&ComputedOpacity>,
+            Option<&Shape3d>,
+        )>,
+    >,
+) {
+    let mut instances_2d = Vec::new();
+    let mut instances_3d = Vec::new();
+    for (text, tf, vis, material, layers, opacity, shape_3d) in &texts {
+        if !is_shape_visible(vis, material) {
+            continue;
+        }
+        // The font's image is always used, so that changing the font doesn't require updating the material
+        let material = ShapeMaterial {
+            texture: Some(text.font.image.clone()),
+            texture_array: false,
+            ..material.cloned().unwrap_or_default()
+        };
+        let material = ShapePipelineMaterial::new(Some(&material), layers);
+        let alpha = opacity.map_or(1.0, ComputedOpacity::get);
+        let instances = match shape_3d {
+            Some(_) => &mut instances_3d,
+            None => &mut instances_2d,
+        };
+        instances.extend(text.data(tf).map(|mut data| {
+            data.multiply_alpha(alpha);
+            (material.clone(), data)
+        }));
+    }
+
+    if !instances_2d.is_empty() {
+        commands.spawn(ExtractedShapes::<RectData>(instances_2d));
+    }
+    if !instances_3d.is_empty() {
+        commands.spawn((ExtractedShapes::<RectData>(instances_3d), Shape3d));
+    }
+}
+
+/// Plugin that draws [`BitmapText`]s, added by [`Shape2dPlugin`].
+pub struct BitmapTextPlugin;
+
+impl Plugin for BitmapTextPlugin {
+    fn build(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_system(extract_bitmap_texts.in_schedule(ExtractSchedule));
+    }
+}
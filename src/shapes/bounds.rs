@@ -0,0 +1,119 @@
+use bevy::{ecs::system::SystemParam, math::Vec3A, prelude::*, render::primitives::Aabb};
+
+use crate::render::ShapeComponent;
+
+/// Component containing the bounds of a retained shape, kept up to date by [`update_shape_bounds`].
+///
+/// Shapes whose bounds can't be known ahead of time, such as lines with [`ThicknessType::Pixels`](crate::shapes::ThicknessType::Pixels),
+/// don't have bounds. Use [`ShapeBoundsQuery`] to find shapes within a region.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ShapeBounds {
+    /// Bounds of the shape in it's local xy plane.
+    pub local: Rect,
+    /// Axis aligned bounds of the shape in world space.
+    pub world: Aabb,
+}
+
+impl ShapeBounds {
+    /// Bounds of the shape in the world xy plane, as used by 2D cameras.
+    pub fn world_rect(&self) -> Rect {
+        Rect::from_corners(self.world.min().truncate(), self.world.max().truncate())
+    }
+}
+
+/// Transforms a local [`Aabb`] into an axis aligned box containing it in world space.
+fn world_aabb(aabb: &Aabb, tf: &GlobalTransform) -> Aabb {
+    let affine = tf.affine();
+    let matrix = affine.matrix3;
+    Aabb {
+        center: affine.transform_point3a(aabb.center),
+        half_extents: matrix.x_axis.abs() * aabb.half_extents.x
+            + matrix.y_axis.abs() * aabb.half_extents.y
+            + matrix.z_axis.abs() * aabb.half_extents.z,
+    }
+}
+
+/// Keeps the [`ShapeBounds`] of retained shapes up to date as their shape or transform changes.
+pub fn update_shape_bounds<T: ShapeComponent>(
+    mut commands: Commands,
+    mut shapes: Query<
+        (Entity, &T, &GlobalTransform, Option<&mut ShapeBounds>),
+        Or<(Changed<T>, Changed<GlobalTransform>)>,
+    >,
+) {
+    for (entity, shape, tf, bounds) in &mut shapes {
+        let Some(aabb) = shape.aabb() else {
+            if bounds.is_some() {
+                commands.entity(entity).remove::<ShapeBounds>();
+            }
+            continue;
+        };
+
+        let new_bounds = ShapeBounds {
+            local: Rect::from_corners(aabb.min().truncate(), aabb.max().truncate()),
+            world: world_aabb(&aabb, tf),
+        };
+        match bounds {
+            Some(mut bounds) => *bounds = new_bounds,
+            None => {
+                commands.entity(entity).insert(new_bounds);
+            }
+        }
+    }
+}
+
+/// System param for spatial queries against the [`ShapeBounds`] of retained shapes.
+///
+/// Queries test bounds only, combine them with [`HitTestShape`](crate::shapes::HitTestShape) for exact results.
+#[derive(SystemParam)]
+pub struct ShapeBoundsQuery<'w, 's> {
+    bounds: Query<'w, 's, (Entity, &'static ShapeBounds)>,
+}
+
+impl<'w, 's> ShapeBoundsQuery<'w, 's> {
+    /// Shapes whose bounds overlap the given rect in the world xy plane, such as a marquee selection.
+    pub fn intersecting_rect(&self, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        self.bounds
+            .iter()
+            .filter(move |(_, bounds)| !bounds.world_rect().intersect(rect).is_empty())
+            .map(|(entity, _)| entity)
+    }
+
+    /// Shapes whose bounds lie entirely within the given rect in the world xy plane.
+    pub fn within_rect(&self, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        self.bounds
+            .iter()
+            .filter(move |(_, bounds)| rect.union(bounds.world_rect()) == rect)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Shapes whose bounds overlap the given world space box.
+    pub fn intersecting_aabb(&self, aabb: Aabb) -> impl Iterator<Item = Entity> + '_ {
+        self.bounds
+            .iter()
+            .filter(move |(_, bounds)| {
+                let distance = (bounds.world.center - aabb.center).abs();
+                distance
+                    .cmple(bounds.world.half_extents + aabb.half_extents)
+                    .all()
+            })
+            .map(|(entity, _)| entity)
+    }
+
+    /// Shapes whose bounds contain the given world space point.
+    pub fn containing_point(&self, point: Vec3) -> impl Iterator<Item = Entity> + '_ {
+        let point = Vec3A::from(point);
+        self.bounds
+            .iter()
+            .filter(move |(_, bounds)| {
+                let distance = (bounds.world.center - point).abs();
+                distance.cmple(bounds.world.half_extents).all()
+            })
+            .map(|(entity, _)| entity)
+    }
+
+    /// Bounds of the given shape, if it has any.
+    pub fn get(&self, entity: Entity) -> Option<&ShapeBounds> {
+        self.bounds.get(entity).ok().map(|(_, bounds)| bounds)
+    }
+}
@@ -2,7 +2,7 @@ use bevy::{
     core::{Pod, Zeroable},
     prelude::*,
     reflect::Reflect,
-    render::render_resource::ShaderRef,
+    render::{primitives::Aabb, render_resource::ShaderRef},
 };
 use wgpu::vertex_attr_array;
 
@@ -10,16 +10,29 @@ use crate::{
     prelude::*,
     render::{Flags, ShapeComponent, ShapeData, DISC_HANDLE},
 };
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 /// Component containing the data for drawing a disc.
 ///
 /// Discs include both arcs and circles
-#[derive(Component, Reflect)]
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Disc {
     pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub thickness: f32,
     pub thickness_type: ThicknessType,
     pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
     pub hollow: bool,
     /// Cap type for an arc, only supports None or Round
     pub cap: Cap,
@@ -27,6 +40,7 @@ pub struct Disc {
     pub arc: bool,
 
     /// External radius of the disc
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub radius: f32,
     /// Starting angle for an arc
     pub start_angle: f32,
@@ -48,6 +62,7 @@ impl Disc {
             thickness: config.thickness,
             thickness_type: config.thickness_type,
             alignment: config.alignment,
+            common: ShapeCommon::from(config),
             hollow: config.hollow,
             cap,
             arc,
@@ -83,13 +98,21 @@ impl ShapeComponent for Disc {
 
             color: self.color.as_rgba_f32(),
             thickness: self.thickness,
-            flags: flags.0,
+            common: self.common.data(flags),
 
             radius: self.radius,
             start_angle: self.start_angle,
             end_angle: self.end_angle,
         }
     }
+
+    fn aabb(&self) -> Option<Aabb> {
+        Some(flat_aabb(
+            Vec2::splat(self.radius),
+            self.alignment,
+            self.common.depth_bias,
+        ))
+    }
 }
 
 impl Default for Disc {
@@ -99,6 +122,7 @@ impl Default for Disc {
             thickness: 1.0,
             thickness_type: default(),
             alignment: default(),
+            common: default(),
             hollow: false,
             cap: Cap::None,
             arc: false,
@@ -112,13 +136,14 @@ impl Default for Disc {
 
 /// Raw data sent to the disc shader to draw a disc
 #[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct DiscData {
     transform: [[f32; 4]; 4],
 
     color: [f32; 4],
     thickness: f32,
-    flags: u32,
+    common: ShapeCommonData,
 
     radius: f32,
     start_angle: f32,
@@ -138,7 +163,7 @@ impl DiscData {
 
             color: config.color.as_rgba_f32(),
             thickness: config.thickness,
-            flags: flags.0,
+            common: ShapeCommon::from(config).data(flags),
 
             radius,
 
@@ -160,7 +185,7 @@ impl DiscData {
 
             color: config.color.as_rgba_f32(),
             thickness: config.thickness,
-            flags: flags.0,
+            common: ShapeCommon::from(config).data(flags),
 
             radius,
 
@@ -168,6 +193,23 @@ impl DiscData {
             end_angle,
         }
     }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> Disc {
+        // Circles are stored with matching start and end angles
+        let arc = self.start_angle != self.end_angle;
+        Disc {
+            color: Color::from(self.color),
+            ..Disc::new(
+                config,
+                self.radius,
+                arc,
+                self.start_angle,
+                self.end_angle,
+                config.cap,
+            )
+        }
+    }
 }
 
 impl ShapeData for DiscData {
@@ -182,10 +224,12 @@ impl ShapeData for DiscData {
 
             4 => Float32x4,
             5 => Float32,
-            6 => Uint32,
-            7 => Float32,
-            8 => Float32,
+            6 => Uint32x4,
+            7 => Float32x4,
+            8 => Float32x4,
             9 => Float32,
+            10 => Float32,
+            11 => Float32,
         ]
         .to_vec()
     }
@@ -197,6 +241,22 @@ impl ShapeData for DiscData {
     fn transform(&self) -> Mat4 {
         Mat4::from_cols_array_2d(&self.transform)
     }
+
+    fn bounding_radius(&self) -> f32 {
+        self.radius * max_scale(&self.transform()) + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
 }
 
 /// Extension trait for [`ShapePainter`] to enable it to draw disc type shapes.
@@ -0,0 +1,152 @@
+use std::any::Any;
+
+use bevy::{
+    prelude::*,
+    render::{
+        primitives::Aabb,
+        view::{NoFrustumCulling, VisibilitySystems},
+    },
+};
+
+use crate::{
+    prelude::*,
+    render::ShapeComponent,
+    shapes::{apply_shape_bounds, UnboundedShape},
+};
+
+/// Component holding any of the built-in shape types, allowing an entity to change which shape it draws at runtime.
+///
+/// Replacing the variant switches the shape without removing and inserting components, each variant is
+/// extracted to the pipeline of it's shape type. Spawn it alongside a [`ShapeBundle`]'s material and spatial components
+/// in place of a shape component, the entity's [`Aabb`] is kept up to date by [`DynamicShapePlugin`].
+/// Each variant is extracted like the retained shape it wraps, taking on the entity's [`Opacity`] and [`PaintLayer`].
+/// Dynamic shapes aren't given [`ShapeBounds`] and can't be made static with [`StaticShape`].
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DynamicShape {
+    Line(Line),
+    QuadBezier(QuadBezier),
+    Disc(Disc),
+    Rectangle(Rectangle),
+    RegularPolygon(RegularPolygon),
+    Morph(ShapeMorph),
+    Triangle(Triangle),
+}
+
+impl Default for DynamicShape {
+    fn default() -> Self {
+        Self::Disc(default())
+    }
+}
+
+impl DynamicShape {
+    /// The current shape if it is of the given type.
+    pub fn as_shape<T: ShapeComponent>(&self) -> Option<&T> {
+        let shape: &dyn Any = match self {
+            Self::Line(shape) => shape,
+            Self::QuadBezier(shape) => shape,
+            Self::Disc(shape) => shape,
+            Self::Rectangle(shape) => shape,
+            Self::RegularPolygon(shape) => shape,
+            Self::Morph(shape) => shape,
+            Self::Triangle(shape) => shape,
+        };
+        shape.downcast_ref()
+    }
+
+    /// Bounds of the current shape, see [`ShapeComponent::aabb`].
+    pub fn aabb(&self) -> Option<Aabb> {
+        match self {
+            Self::Line(shape) => shape.aabb(),
+            Self::QuadBezier(shape) => shape.aabb(),
+            Self::Disc(shape) => shape.aabb(),
+            Self::Rectangle(shape) => shape.aabb(),
+            Self::RegularPolygon(shape) => shape.aabb(),
+            Self::Morph(shape) => shape.aabb(),
+            Self::Triangle(shape) => shape.aabb(),
+        }
+    }
+}
+
+impl From<Line> for DynamicShape {
+    fn from(shape: Line) -> Self {
+        Self::Line(shape)
+    }
+}
+
+impl From<QuadBezier> for DynamicShape {
+    fn from(shape: QuadBezier) -> Self {
+        Self::QuadBezier(shape)
+    }
+}
+
+impl From<Disc> for DynamicShape {
+    fn from(shape: Disc) -> Self {
+        Self::Disc(shape)
+    }
+}
+
+impl From<Rectangle> for DynamicShape {
+    fn from(shape: Rectangle) -> Self {
+        Self::Rectangle(shape)
+    }
+}
+
+impl From<RegularPolygon> for DynamicShape {
+    fn from(shape: RegularPolygon) -> Self {
+        Self::RegularPolygon(shape)
+    }
+}
+
+impl From<ShapeMorph> for DynamicShape {
+    fn from(shape: ShapeMorph) -> Self {
+        Self::Morph(shape)
+    }
+}
+
+impl From<Triangle> for DynamicShape {
+    fn from(shape: Triangle) -> Self {
+        Self::Triangle(shape)
+    }
+}
+
+/// Keeps the [`Aabb`] of entities with a [`DynamicShape`] up to date as the shape changes.
+pub fn update_dynamic_shape_aabbs(
+    mut commands: Commands,
+    mut shapes: Query<
+        (
+            Entity,
+            &DynamicShape,
+            Option<&mut Aabb>,
+            Option<&UnboundedShape>,
+        ),
+        (
+            Changed<DynamicShape>,
+            Or<(Without<NoFrustumCulling>, With<UnboundedShape>)>,
+        ),
+    >,
+) {
+    for (entity, shape, aabb, unbounded) in &mut shapes {
+        apply_shape_bounds(
+            &mut commands,
+            entity,
+            shape.aabb(),
+            aabb,
+            unbounded.is_some(),
+        );
+    }
+}
+
+/// Plugin that sets up [`DynamicShape`] components, added by [`Shape2dPlugin`].
+pub struct DynamicShapePlugin;
+
+impl Plugin for DynamicShapePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DynamicShape>().add_system(
+            update_dynamic_shape_aabbs
+                .in_base_set(CoreSet::PostUpdate)
+                .in_set(VisibilitySystems::CalculateBounds),
+        );
+    }
+}
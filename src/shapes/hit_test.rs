@@ -0,0 +1,622 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{math::Affine3A, prelude::*};
+
+use crate::prelude::*;
+
+/// Trait implemented by shape components to test points against the area the shape covers when drawn.
+///
+/// Tests mirror the distance fields used by the shaders, so they take into account thickness, hollowness,
+/// caps and corner rounding. Thickness is always interpreted in world units as [`ThicknessType::Pixels`]
+/// and [`ThicknessType::Screen`] depend on the camera. Billboarded shapes are likewise tested as if they were flat
+/// by the point tests, use [`HitTestShape::raycast`] to orient them towards a camera.
+pub trait HitTestShape {
+    /// Whether the given world space point falls within the shape drawn with the given transform.
+    ///
+    /// Flat shapes project the point onto their plane before testing it.
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool;
+
+    /// Closest world space point covered by the shape drawn with the given transform.
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3;
+
+    /// Whether the given 2D point, at the depth of the shape's origin, falls within the shape.
+    fn contains_point_2d(&self, tf: &GlobalTransform, point: Vec2) -> bool {
+        self.contains_point(tf, point.extend(tf.translation().z))
+    }
+
+    /// Closest 2D point covered by the shape, for points at the depth of the shape's origin.
+    fn closest_point_2d(&self, tf: &GlobalTransform, point: Vec2) -> Vec2 {
+        self.closest_point(tf, point.extend(tf.translation().z))
+            .truncate()
+    }
+
+    /// Nearest intersection of a ray cast from the given camera with the shape drawn with the given transform.
+    ///
+    /// Billboarded shapes are oriented towards the camera as they are when drawn by it, such that rays from
+    /// [`Camera::viewport_to_world`] can be used to select in-world shapes with the mouse.
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit>;
+}
+
+/// Intersection of a ray with a shape, returned by [`HitTestShape::raycast`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeHit {
+    /// Distance along the ray to the intersection.
+    pub distance: f32,
+    /// Intersection point in world space.
+    pub point: Vec3,
+    /// Normal of the shape at the intersection, facing the origin of the ray.
+    pub normal: Vec3,
+}
+
+/// Direction from a world position towards the camera, as used to orient billboards in the shaders.
+fn to_camera(camera: &Camera, camera_tf: &GlobalTransform, position: Vec3) -> Vec3 {
+    // Orthographic projections view along a single direction regardless of position
+    if camera.projection_matrix().w_axis.w == 1.0 {
+        camera_tf.back()
+    } else {
+        (camera_tf.translation() - position).normalize_or_zero()
+    }
+}
+
+/// Z basis of a shape with the given origin and y basis, calculated for it's alignment as in the shaders.
+fn aligned_normal(
+    alignment: Alignment,
+    tf: &GlobalTransform,
+    origin: Vec3,
+    up: Vec3,
+    camera: &Camera,
+    camera_tf: &GlobalTransform,
+) -> Vec3 {
+    match alignment {
+        Alignment::Flat => tf.back(),
+        Alignment::Billboard => to_camera(camera, camera_tf, origin),
+        Alignment::AxisBillboard => {
+            // Project the direction to the camera onto the plane perpendicular to our y basis
+            let to_camera = to_camera(camera, camera_tf, origin);
+            let projected = to_camera - to_camera.dot(up) * up;
+            if projected.length() < 0.0001 {
+                tf.back()
+            } else {
+                projected.normalize()
+            }
+        }
+    }
+}
+
+/// Transform from the local space of a flat shape to world space, built from it's basis vectors as in the shaders.
+fn shape_affine(tf: &GlobalTransform, normal: Vec3) -> Affine3A {
+    let matrix = tf.affine().matrix3;
+    let x_axis = Vec3::from(matrix.y_axis).cross(normal).normalize_or_zero();
+    let y_axis = x_axis.cross(normal);
+    Affine3A::from_mat3_translation(
+        Mat3::from_cols(
+            x_axis * matrix.x_axis.length(),
+            y_axis * matrix.y_axis.length(),
+            normal,
+        ),
+        tf.translation(),
+    )
+}
+
+/// Point in the local xy plane of a flat shape with the given alignment, as seen from the given camera.
+///
+/// Matches the coordinates used by the shaders, such that texture coordinates can be derived from it.
+#[cfg(feature = "picking")]
+pub(crate) fn local_point(
+    tf: &GlobalTransform,
+    alignment: Alignment,
+    point: Vec3,
+    camera: &Camera,
+    camera_tf: &GlobalTransform,
+) -> Vec2 {
+    let up = tf.up();
+    let normal = aligned_normal(alignment, tf, tf.translation(), up, camera, camera_tf);
+    to_local(&shape_affine(tf, normal), point)
+}
+
+fn to_local(affine: &Affine3A, point: Vec3) -> Vec2 {
+    affine.inverse().transform_point3(point).truncate()
+}
+
+/// Distance along a ray to the plane with the given origin and normal.
+pub(crate) fn intersect_plane(ray: Ray, origin: Vec3, normal: Vec3) -> Option<f32> {
+    let denom = ray.direction.dot(normal);
+    if denom.abs() <= f32::EPSILON {
+        return None;
+    }
+    let distance = (origin - ray.origin).dot(normal) / denom;
+    (distance >= 0.0).then_some(distance)
+}
+
+/// Intersection of a ray with a plane if the intersection point passes the given test.
+fn plane_hit(
+    ray: Ray,
+    origin: Vec3,
+    normal: Vec3,
+    contains: impl FnOnce(Vec3) -> bool,
+) -> Option<ShapeHit> {
+    let distance = intersect_plane(ray, origin, normal)?;
+    let point = ray.get_point(distance);
+    contains(point).then(|| ShapeHit {
+        distance,
+        point,
+        normal: if normal.dot(ray.direction) > 0.0 {
+            -normal
+        } else {
+            normal
+        },
+    })
+}
+
+fn flat_contains(tf: &GlobalTransform, point: Vec3, sdf: impl Fn(Vec2) -> f32) -> bool {
+    sdf(to_local(&shape_affine(tf, tf.back()), point)) <= 0.0
+}
+
+fn flat_closest(tf: &GlobalTransform, point: Vec3, sdf: impl Fn(Vec2) -> f32) -> Vec3 {
+    let affine = shape_affine(tf, tf.back());
+    let local = closest_local_point(sdf, to_local(&affine, point));
+    affine.transform_point3(local.extend(0.0))
+}
+
+fn flat_raycast(
+    tf: &GlobalTransform,
+    alignment: Alignment,
+    ray: Ray,
+    camera: &Camera,
+    camera_tf: &GlobalTransform,
+    sdf: impl Fn(Vec2) -> f32,
+) -> Option<ShapeHit> {
+    let origin = tf.translation();
+    let normal = aligned_normal(alignment, tf, origin, tf.up(), camera, camera_tf);
+    let affine = shape_affine(tf, normal);
+    plane_hit(ray, origin, normal, |point| {
+        sdf(to_local(&affine, point)) <= 0.0
+    })
+}
+
+/// Intersection of a ray with the strip a line between the given points is drawn on.
+fn stroke_raycast(
+    tf: &GlobalTransform,
+    alignment: Alignment,
+    (start, end): (Vec3, Vec3),
+    ray: Ray,
+    (camera, camera_tf): (&Camera, &GlobalTransform),
+    contains: impl FnOnce(Vec3) -> bool,
+) -> Option<ShapeHit> {
+    // Lines are drawn on the plane containing their direction and the x basis of their alignment
+    let up = (start - end).normalize_or_zero();
+    let origin = start.lerp(end, 0.5);
+    let z_axis = aligned_normal(alignment, tf, origin, up, camera, camera_tf);
+    let x_axis = up.cross(z_axis).normalize_or_zero();
+    plane_hit(ray, origin, x_axis.cross(up), contains)
+}
+
+/// Steps a local point outside of a flat shape onto it's edge by following the gradient of it's distance field.
+fn closest_local_point(sdf: impl Fn(Vec2) -> f32, mut point: Vec2) -> Vec2 {
+    for _ in 0..4 {
+        let dist = sdf(point);
+        if dist <= 0.0 {
+            break;
+        }
+        let h = 1e-3 * (dist + point.length()).max(1e-3);
+        let gradient = Vec2::new(
+            sdf(point + Vec2::X * h) - sdf(point - Vec2::X * h),
+            sdf(point + Vec2::Y * h) - sdf(point - Vec2::Y * h),
+        )
+        .normalize_or_zero();
+        point -= gradient * dist;
+    }
+    point
+}
+
+/// Distance to a filled shape with the given distance field, or to a band of the given thickness along it's edge if hollow.
+fn outline_sdf(dist: f32, hollow: bool, thickness: f32) -> f32 {
+    if hollow {
+        dist.max(-dist - thickness)
+    } else {
+        dist
+    }
+}
+
+fn rect_sdf(point: Vec2, half_size: Vec2, corner_radius: f32) -> f32 {
+    let radius = corner_radius.min(half_size.x.min(half_size.y)).max(0.0);
+    let to_corner = point.abs() - (half_size - radius);
+    let outside_to_edge = to_corner.max(Vec2::ZERO);
+    let inside_length = to_corner.x.max(to_corner.y).min(0.0);
+    outside_to_edge.length() + inside_length - radius
+}
+
+fn ngon_sdf(point: Vec2, sides: f32, radius: f32, roundness: f32) -> f32 {
+    let central_angle = TAU / sides;
+    let apothem = (central_angle / 2.0).cos() * radius;
+    let rounding = roundness.min(apothem);
+
+    // Shrink the polygon by the rounding radius so that the rounded polygon keeps it's size
+    let inner_apothem = apothem - rounding;
+    let half_side_length = (central_angle / 2.0).tan() * inner_apothem;
+
+    // Polygons point up, so measure angles from positive y
+    let point = Vec2::new(point.y, point.x);
+    let angle = point.y.atan2(point.x);
+    let nearest_angle = central_angle * ((angle + 0.5 * central_angle) / central_angle).floor();
+    let nearest_vertex = Vec2::from_angle(-nearest_angle);
+    let point = nearest_vertex.rotate(point);
+    let nearest_point = Vec2::new(
+        inner_apothem,
+        point.y.clamp(-half_side_length, half_side_length),
+    );
+    (point - nearest_point).length() * (point.x - inner_apothem).signum() - rounding
+}
+
+impl Disc {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        let delta = (self.end_angle - self.start_angle) / 2.0;
+        if !self.arc || delta >= PI {
+            return outline_sdf(point.length() - self.radius, self.hollow, self.thickness);
+        }
+
+        // Filled arcs reach all the way to the center
+        let thickness = if self.hollow {
+            self.thickness
+        } else {
+            self.radius
+        };
+
+        // Rotate such that the arc is centered around positive x as in the shader
+        let center_angle = self.start_angle - PI / 2.0 + delta;
+        let point = Vec2::from_angle(-center_angle).rotate(point);
+        let angle = point.y.atan2(point.x);
+        let end = Vec2::from_angle(delta.max(0.0) * angle.signum());
+
+        if self.cap == Cap::Round {
+            // Rounded arcs cover everything within half their thickness of their center line
+            let center_radius = self.radius - thickness / 2.0;
+            let dist = if angle.abs() <= delta {
+                (point.length() - center_radius).abs()
+            } else {
+                point.distance(end * center_radius)
+            };
+            dist - thickness / 2.0
+        } else if angle.abs() <= delta {
+            outline_sdf(point.length() - self.radius, true, thickness)
+        } else {
+            // Distance to the straight edge at the nearest end of the arc
+            let along = point.dot(end).clamp(self.radius - thickness, self.radius);
+            point.distance(end * along)
+        }
+    }
+}
+
+impl HitTestShape for Disc {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        flat_contains(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        flat_closest(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let sdf = |p| self.local_sdf(p);
+        flat_raycast(tf, self.alignment, ray, camera, camera_tf, sdf)
+    }
+}
+
+impl Rectangle {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        // Corner radii are ordered top right, top left, bottom left, bottom right
+        let corner_radius = match (point.x >= 0.0, point.y >= 0.0) {
+            (true, true) => self.corner_radii.x,
+            (false, true) => self.corner_radii.y,
+            (false, false) => self.corner_radii.z,
+            (true, false) => self.corner_radii.w,
+        };
+        let dist = rect_sdf(point, self.size / 2.0, corner_radius);
+        outline_sdf(dist, self.hollow, self.thickness)
+    }
+}
+
+impl HitTestShape for Rectangle {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        flat_contains(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        flat_closest(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let sdf = |p| self.local_sdf(p);
+        flat_raycast(tf, self.alignment, ray, camera, camera_tf, sdf)
+    }
+}
+
+impl RegularPolygon {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        let dist = ngon_sdf(point, self.sides, self.radius, self.roundness);
+        outline_sdf(dist, self.hollow, self.thickness)
+    }
+}
+
+impl HitTestShape for RegularPolygon {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        flat_contains(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        flat_closest(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let sdf = |p| self.local_sdf(p);
+        flat_raycast(tf, self.alignment, ray, camera, camera_tf, sdf)
+    }
+}
+
+impl Triangle {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        // Triangle points aren't flipped like the local space of the other shaders, so flip the point back
+        let point = Vec2::new(point.x, -point.y);
+        let [a, b, c] = self.points;
+
+        // Barycentric coordinates are all positive inside the triangle regardless of it's winding
+        let area = (b - a).perp_dot(c - a);
+        let u = (c - b).perp_dot(point - b) / area;
+        let v = (a - c).perp_dot(point - c) / area;
+        let inside = u >= 0.0 && v >= 0.0 && u + v <= 1.0;
+
+        let edge_distance = |start: Vec2, end: Vec2| {
+            let edge = end - start;
+            let t = (point - start).dot(edge) / edge.length_squared().max(f32::EPSILON);
+            point.distance(start + edge * t.clamp(0.0, 1.0))
+        };
+        let dist = edge_distance(a, b)
+            .min(edge_distance(b, c))
+            .min(edge_distance(c, a));
+        if inside {
+            -dist
+        } else {
+            dist
+        }
+    }
+}
+
+impl HitTestShape for Triangle {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        flat_contains(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        flat_closest(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let sdf = |p| self.local_sdf(p);
+        flat_raycast(tf, self.alignment, ray, camera, camera_tf, sdf)
+    }
+}
+
+impl MorphTarget {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        match *self {
+            MorphTarget::Circle { radius } => point.length() - radius,
+            MorphTarget::Rectangle {
+                size,
+                corner_radius,
+            } => rect_sdf(point, size / 2.0, corner_radius),
+            MorphTarget::Ngon {
+                sides,
+                radius,
+                roundness,
+            } => ngon_sdf(point, sides, radius, roundness),
+        }
+    }
+}
+
+impl ShapeMorph {
+    fn local_sdf(&self, point: Vec2) -> f32 {
+        let from = self.from.local_sdf(point);
+        let to = self.to.local_sdf(point);
+        let dist = from + (to - from) * self.progress.clamp(0.0, 1.0);
+        outline_sdf(dist, self.hollow, self.thickness)
+    }
+}
+
+impl HitTestShape for ShapeMorph {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        flat_contains(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        flat_closest(tf, point, |p| self.local_sdf(p))
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let sdf = |p| self.local_sdf(p);
+        flat_raycast(tf, self.alignment, ray, camera, camera_tf, sdf)
+    }
+}
+
+/// Closest point to `point` within a stroke of the given thickness passing through `nearest`,
+/// the nearest point along the stroke's center, along with whether `point` falls inside the stroke.
+///
+/// `end` is the outward direction of the stroke if `nearest` is one of it's end points.
+fn stroke_hit(
+    point: Vec3,
+    nearest: Vec3,
+    end: Option<Vec3>,
+    cap: Cap,
+    thickness: f32,
+) -> (bool, Vec3) {
+    let radius = thickness / 2.0;
+    let offset = point - nearest;
+    match end {
+        // Square and absent caps cut the stroke off at a fixed distance past it's end
+        Some(axis) if cap != Cap::Round => {
+            let cap_length = if cap == Cap::Square { radius } else { 0.0 };
+            let along = offset.dot(axis);
+            let across = offset - axis * along;
+            let contains = along <= cap_length && across.length() <= radius;
+            let closest = nearest + axis * along.min(cap_length) + across.clamp_length_max(radius);
+            (contains, closest)
+        }
+        _ => (
+            offset.length() <= radius,
+            nearest + offset.clamp_length_max(radius),
+        ),
+    }
+}
+
+impl Line {
+    fn hit(&self, tf: &GlobalTransform, point: Vec3) -> (bool, Vec3) {
+        // Lines are drawn in world space with their thickness scaled by the transform's x scale
+        let thickness = self.thickness * tf.affine().matrix3.x_axis.length();
+        let start = tf.transform_point(self.start);
+        let end = tf.transform_point(self.end);
+        let axis = (end - start).normalize_or_zero();
+        let t = (point - start).dot(end - start) / (end - start).length_squared().max(f32::EPSILON);
+
+        let (nearest, end) = if t <= 0.0 {
+            (start, Some(-axis))
+        } else if t >= 1.0 {
+            (end, Some(axis))
+        } else {
+            (start.lerp(end, t), None)
+        };
+        stroke_hit(point, nearest, end, self.cap, thickness)
+    }
+}
+
+impl HitTestShape for Line {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        self.hit(tf, point).0
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        self.hit(tf, point).1
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let points = (tf.transform_point(self.start), tf.transform_point(self.end));
+        stroke_raycast(
+            tf,
+            self.alignment,
+            points,
+            ray,
+            (camera, camera_tf),
+            |point| self.contains_point(tf, point),
+        )
+    }
+}
+
+impl QuadBezier {
+    fn hit(&self, tf: &GlobalTransform, point: Vec3) -> (bool, Vec3) {
+        // Affine transforms of a bezier are the bezier of the transformed control points
+        let start = tf.transform_point(self.start);
+        let control = tf.transform_point(self.control);
+        let end = tf.transform_point(self.end);
+
+        let position = |t: f32| start.lerp(control, t).lerp(control.lerp(end, t), t);
+        let derivative = |t: f32| 2.0 * ((control - start) * (1.0 - t) + (end - control) * t);
+        let second_derivative = 2.0 * (end - 2.0 * control + start);
+
+        // Find a rough parameter for the nearest point by sampling then refine it with newton's method
+        const SAMPLES: usize = 16;
+        let mut t = (0..=SAMPLES)
+            .map(|i| i as f32 / SAMPLES as f32)
+            .min_by(|a, b| {
+                point
+                    .distance_squared(position(*a))
+                    .total_cmp(&point.distance_squared(position(*b)))
+            })
+            .unwrap_or(0.0);
+        for _ in 0..4 {
+            let to_point = position(t) - point;
+            let slope = derivative(t).length_squared() + to_point.dot(second_derivative);
+            if slope.abs() <= f32::EPSILON {
+                break;
+            }
+            t = (t - to_point.dot(derivative(t)) / slope).clamp(0.0, 1.0);
+        }
+
+        let end = if t <= 0.0 {
+            Some(-derivative(0.0).normalize_or_zero())
+        } else if t >= 1.0 {
+            Some(derivative(1.0).normalize_or_zero())
+        } else {
+            None
+        };
+        let thickness = self.thickness * tf.affine().matrix3.x_axis.length();
+        stroke_hit(point, position(t), end, self.cap, thickness)
+    }
+}
+
+impl HitTestShape for QuadBezier {
+    fn contains_point(&self, tf: &GlobalTransform, point: Vec3) -> bool {
+        self.hit(tf, point).0
+    }
+
+    fn closest_point(&self, tf: &GlobalTransform, point: Vec3) -> Vec3 {
+        self.hit(tf, point).1
+    }
+
+    fn raycast(
+        &self,
+        tf: &GlobalTransform,
+        ray: Ray,
+        camera: &Camera,
+        camera_tf: &GlobalTransform,
+    ) -> Option<ShapeHit> {
+        let points = (tf.transform_point(self.start), tf.transform_point(self.end));
+        stroke_raycast(
+            tf,
+            self.alignment,
+            points,
+            ray,
+            (camera, camera_tf),
+            |point| self.contains_point(tf, point),
+        )
+    }
+}
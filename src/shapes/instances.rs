@@ -0,0 +1,105 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{
+    prelude::*,
+    render::{ShapeComponent, ShapeData},
+};
+
+/// Transform and color of a single instance within [`ShapeInstances`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShapeInstanceProps {
+    /// Transform of the instance relative to the entity's [`GlobalTransform`].
+    pub transform: Transform,
+    /// Color of the instance, uses the shape's color when unset.
+    pub color: Option<Color>,
+}
+
+impl ShapeInstanceProps {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            transform,
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl From<Transform> for ShapeInstanceProps {
+    fn from(transform: Transform) -> Self {
+        Self::new(transform)
+    }
+}
+
+/// Component that draws a single shape definition once for each of it's instances.
+///
+/// All instances are extracted together from one entity, allowing large numbers of identical shapes such as markers
+/// to be drawn without an entity each. Instances share the entity's [`ShapeMaterial`], visibility, render layers,
+/// [`Opacity`] and [`PaintLayer`] but are not individually culled, hit tested or given [`ShapeBounds`].
+#[derive(Component, Clone, Debug)]
+pub struct ShapeInstances<T: ShapeComponent> {
+    pub shape: T,
+    pub instances: Vec<ShapeInstanceProps>,
+}
+
+impl<T: ShapeComponent> ShapeInstances<T> {
+    pub fn new(
+        shape: T,
+        instances: impl IntoIterator<Item = impl Into<ShapeInstanceProps>>,
+    ) -> Self {
+        Self {
+            shape,
+            instances: instances.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Shape data for each instance given the entity's transform.
+    pub fn data<'a>(&'a self, tf: &'a GlobalTransform) -> impl Iterator<Item = T::Data> + 'a {
+        self.instances.iter().map(move |instance| {
+            let mut data = self.shape.into_data(&tf.mul_transform(instance.transform));
+            if let Some(color) = instance.color {
+                data.set_color(color);
+            }
+            data
+        })
+    }
+}
+
+/// Bundle that draws a shape at many transforms from a single entity, see [`ShapeInstances`].
+#[derive(Bundle)]
+pub struct ShapeInstancesBundle<T: ShapeComponent> {
+    pub spatial_bundle: SpatialBundle,
+    pub shape: ShapeMaterial,
+    pub render_layers: RenderLayers,
+    pub instances: ShapeInstances<T>,
+}
+
+impl<T: ShapeComponent> ShapeInstancesBundle<T> {
+    pub fn new(
+        config: &ShapeConfig,
+        component: T,
+        instances: impl IntoIterator<Item = impl Into<ShapeInstanceProps>>,
+    ) -> Self {
+        let ShapeBundle {
+            spatial_bundle,
+            shape,
+            render_layers,
+            shape_type,
+            ..
+        } = ShapeBundle::new(config, component);
+        Self {
+            spatial_bundle,
+            shape,
+            render_layers,
+            instances: ShapeInstances::new(shape_type, instances),
+        }
+    }
+
+    /// Inserts the [`Shape3d`] marker component so that the entity is picked up by the associated pipeline.
+    pub fn insert_3d(self) -> (Self, Shape3d) {
+        (self, Shape3d)
+    }
+}
@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::render::ShapeData;
+
+/// Settings of a named layer in [`ShapeLayers`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeLayerSettings {
+    /// Order of the layer, each step offsets it's shapes by [`ShapeLayers::ORDER_STEP`] along world z.
+    pub order: i32,
+    /// Whether the layer's shapes are drawn.
+    pub visible: bool,
+    /// Multiplier applied to the alpha of the layer's shapes.
+    pub opacity: f32,
+}
+
+impl Default for ShapeLayerSettings {
+    fn default() -> Self {
+        Self {
+            order: 0,
+            visible: true,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Resource defining named layers that shapes can be assigned to, such as categories of debug overlays.
+///
+/// Painter calls are assigned a layer with [`ShapeConfig::paint_layer`](crate::prelude::ShapeConfig::paint_layer)
+/// and retained shapes with the [`PaintLayer`] component, which is inserted when spawning with that config.
+/// Changing a layer's settings hides, fades or reorders all of it's shapes at once, layers that haven't been
+/// defined use the default settings. Intended for 2D, orders offset shapes along world z.
+/// Layers apply to [`ShapeInstances`](crate::shapes::ShapeInstances) and [`DynamicShape`](crate::shapes::DynamicShape)s as they do
+/// to other retained shapes, except for static shapes which are baked without their layer.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ShapeLayers {
+    layers: HashMap<Cow<'static, str>, ShapeLayerSettings>,
+}
+
+impl ShapeLayers {
+    /// Distance along world z between consecutive layer orders.
+    pub const ORDER_STEP: f32 = 1.0;
+
+    /// Defines or replaces the settings of the given layer.
+    pub fn insert(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        settings: ShapeLayerSettings,
+    ) -> &mut Self {
+        self.layers.insert(name.into(), settings);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ShapeLayerSettings> {
+        self.layers.get(name)
+    }
+
+    /// Settings of the given layer, defining it with the default settings if it doesn't exist.
+    pub fn get_mut(&mut self, name: impl Into<Cow<'static, str>>) -> &mut ShapeLayerSettings {
+        self.layers.entry(name.into()).or_default()
+    }
+
+    pub fn set_visible(&mut self, name: impl Into<Cow<'static, str>>, visible: bool) {
+        self.get_mut(name).visible = visible;
+    }
+
+    /// Toggles the visibility of the given layer, returning whether it is now visible.
+    pub fn toggle(&mut self, name: impl Into<Cow<'static, str>>) -> bool {
+        let settings = self.get_mut(name);
+        settings.visible = !settings.visible;
+        settings.visible
+    }
+
+    /// Applies the settings of the given layer to the shape data, returning false if the layer is hidden.
+    pub fn apply<T: ShapeData>(&self, name: &str, data: &mut T) -> bool {
+        let Some(settings) = self.get(name) else {
+            return true;
+        };
+        if !settings.visible {
+            return false;
+        }
+        if settings.opacity != 1.0 {
+            data.multiply_alpha(settings.opacity);
+        }
+        if settings.order != 0 {
+            data.offset_z(settings.order as f32 * Self::ORDER_STEP);
+        }
+        true
+    }
+}
+
+/// Component assigning a retained shape to a named layer of [`ShapeLayers`].
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PaintLayer(pub Cow<'static, str>);
@@ -2,7 +2,7 @@ use bevy::{
     core::{Pod, Zeroable},
     prelude::*,
     reflect::{FromReflect, Reflect},
-    render::render_resource::ShaderRef,
+    render::{primitives::Aabb, render_resource::ShaderRef},
 };
 use wgpu::vertex_attr_array;
 
@@ -10,14 +10,27 @@ use crate::{
     prelude::*,
     render::{Flags, ShapeComponent, ShapeData, LINE_HANDLE},
 };
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 /// Component containing the data for drawing a line.
-#[derive(Component, Reflect)]
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Line {
     pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub thickness: f32,
     pub thickness_type: ThicknessType,
     pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
     pub cap: Cap,
 
     /// Position to draw the start of the line in world space relative to it's transform.
@@ -33,6 +46,7 @@ impl Line {
             thickness: config.thickness,
             thickness_type: config.thickness_type,
             alignment: config.alignment,
+            common: ShapeCommon::from(config),
             cap: config.cap,
 
             start,
@@ -48,6 +62,7 @@ impl Default for Line {
             thickness: 1.0,
             thickness_type: default(),
             alignment: default(),
+            common: default(),
             cap: default(),
 
             start: default(),
@@ -70,23 +85,36 @@ impl ShapeComponent for Line {
 
             color: self.color.as_rgba_f32(),
             thickness: self.thickness,
-            flags: flags.0,
+            common: self.common.data(flags),
 
             start: self.start,
             end: self.end,
         }
     }
+
+    fn aabb(&self) -> Option<Aabb> {
+        // Thickness is only known in world units, other thickness types can't be bounded ahead of time
+        if self.thickness_type != ThicknessType::World {
+            return None;
+        }
+        Some(padded_aabb(
+            self.start.min(self.end),
+            self.start.max(self.end),
+            self.thickness / 2.0 + self.common.depth_bias.abs(),
+        ))
+    }
 }
 
 /// Raw data sent to the line shader to draw a line
 #[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct LineData {
     transform: [[f32; 4]; 4],
 
     color: [f32; 4],
     thickness: f32,
-    flags: u32,
+    common: ShapeCommonData,
 
     start: Vec3,
     end: Vec3,
@@ -104,12 +132,20 @@ impl LineData {
 
             color: config.color.as_rgba_f32(),
             thickness: config.thickness,
-            flags: flags.0,
+            common: ShapeCommon::from(config).data(flags),
 
             start,
             end,
         }
     }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> Line {
+        Line {
+            color: Color::from(self.color),
+            ..Line::new(config, self.start, self.end)
+        }
+    }
 }
 
 impl ShapeData for LineData {
@@ -124,9 +160,11 @@ impl ShapeData for LineData {
 
             4 => Float32x4,
             5 => Float32,
-            6 => Uint32,
-            7 => Float32x3,
-            8 => Float32x3,
+            6 => Uint32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x3,
+            10 => Float32x3,
         ]
         .to_vec()
     }
@@ -138,6 +176,28 @@ impl ShapeData for LineData {
     fn transform(&self) -> Mat4 {
         Mat4::from_cols_array_2d(&self.transform)
     }
+
+    fn bounding_radius(&self) -> f32 {
+        if self.common.flags() & 0b11 != ThicknessType::World as u32 {
+            return f32::INFINITY;
+        }
+        let extent = self.start.length().max(self.end.length());
+        extent * max_scale(&self.transform())
+            + self.thickness / 2.0
+            + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
 }
 
 /// Extension trait for [`ShapePainter`] to enable it to draw lines.
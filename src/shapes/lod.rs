@@ -0,0 +1,128 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::prelude::*;
+
+/// Component that simplifies a retained shape while it is further than the given distance from every active camera.
+///
+/// Intended for 3D scenes containing many shapes, distances are measured from each camera's position.
+#[derive(Component, Clone, Debug)]
+pub struct ShapeLod {
+    /// Distance in world units from the nearest camera beyond which the shape is simplified.
+    pub distance: f32,
+    /// Disable local anti-aliasing while simplified, edges become aliased but are cheaper to shade.
+    pub disable_laa: bool,
+    /// Draw a [`RegularPolygon`] as a [`Disc`] of similar size while simplified, as it's sides are indistinguishable at a distance.
+    pub ngon_as_disc: bool,
+    simplified: bool,
+    disable_laa_base: bool,
+}
+
+impl ShapeLod {
+    pub fn new(distance: f32) -> Self {
+        Self {
+            distance,
+            disable_laa: true,
+            ngon_as_disc: true,
+            simplified: false,
+            disable_laa_base: false,
+        }
+    }
+
+    /// Whether the shape is currently simplified.
+    pub fn is_simplified(&self) -> bool {
+        self.simplified
+    }
+}
+
+/// Regular polygon that has been swapped for a [`Disc`] by it's [`ShapeLod`], restored once the shape is no longer simplified.
+#[derive(Component)]
+pub struct LodPolygon(pub RegularPolygon);
+
+/// Disc drawn in place of a polygon while simplified, sized between the polygon's apothem and radius.
+fn lod_disc(polygon: &RegularPolygon) -> Disc {
+    let apothem = polygon.radius * (std::f32::consts::PI / polygon.sides.max(3.0)).cos();
+    Disc {
+        color: polygon.color,
+        thickness: polygon.thickness,
+        thickness_type: polygon.thickness_type,
+        alignment: polygon.alignment,
+        common: polygon.common.clone(),
+        hollow: polygon.hollow,
+        radius: (apothem + polygon.radius) / 2.0,
+        ..default()
+    }
+}
+
+/// Simplifies or restores shapes with a [`ShapeLod`] based on their distance to the nearest active camera.
+pub fn update_shape_lods(
+    mut commands: Commands,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    mut shapes: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut ShapeLod,
+        Option<&mut ShapeMaterial>,
+        Option<&mut RegularPolygon>,
+        Option<&mut LodPolygon>,
+    )>,
+) {
+    let cameras: Vec<Vec3> = cameras
+        .iter()
+        .filter(|(_, camera)| camera.is_active)
+        .map(|(tf, _)| tf.translation())
+        .collect();
+    if cameras.is_empty() {
+        return;
+    }
+
+    for (entity, tf, mut lod, material, polygon, stashed) in &mut shapes {
+        let position = tf.translation();
+        let simplify = cameras
+            .iter()
+            .all(|camera| camera.distance_squared(position) > lod.distance * lod.distance);
+        if simplify == lod.simplified {
+            continue;
+        }
+        lod.simplified = simplify;
+
+        if let Some(mut material) = material {
+            if simplify {
+                lod.disable_laa_base = material.disable_laa;
+                material.disable_laa |= lod.disable_laa;
+            } else {
+                material.disable_laa = lod.disable_laa_base;
+            }
+        }
+
+        match (simplify, polygon, stashed) {
+            (true, Some(mut polygon), None) if lod.ngon_as_disc => {
+                let polygon = std::mem::take(&mut *polygon);
+                commands
+                    .entity(entity)
+                    .remove::<RegularPolygon>()
+                    .insert((lod_disc(&polygon), LodPolygon(polygon)));
+            }
+            (false, _, Some(mut stashed)) => {
+                let polygon = std::mem::take(&mut stashed.0);
+                commands
+                    .entity(entity)
+                    .remove::<(Disc, LodPolygon)>()
+                    .insert(polygon);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Plugin that updates shapes with a [`ShapeLod`], added by [`Shape2dPlugin`].
+pub struct ShapeLodPlugin;
+
+impl Plugin for ShapeLodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            update_shape_lods
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
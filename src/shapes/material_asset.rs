@@ -0,0 +1,119 @@
+use bevy::{asset::HandleId, prelude::*, reflect::TypeUuid, utils::HashSet};
+
+use crate::prelude::*;
+
+/// Asset describing the appearance of any number of retained shapes, whose geometry stays in their shape components.
+///
+/// Shape entities reference the asset by inserting a [`Handle<ShapeMaterialAsset>`], their shape component and
+/// [`ShapeMaterial`] are updated whenever the handle is inserted or the asset is modified, so editing the asset restyles
+/// every shape using it. Shapes sharing an asset share a render configuration and are batched together.
+/// The asset is distinct from the [`ShapeMaterial`] component, which holds the resolved render configuration of a single shape.
+#[derive(TypeUuid, Clone, Debug)]
+#[uuid = "0d5a7c3e-96b1-4f28-8e4d-2a17c9b6f053"]
+pub struct ShapeMaterialAsset {
+    /// Color used to fill the shape, or it's outline when stroked.
+    pub color: Color,
+    /// If set the shape is hollow, drawing only an outline of the given thickness.
+    pub stroke: Option<f32>,
+    pub thickness_type: ThicknessType,
+    /// Blend mode to use when rendering, see [`ShapeMaterial::alpha_mode`].
+    pub alpha_mode: AlphaMode,
+    /// Texture to apply to the shape, color is determined as color * sample.
+    pub texture: Option<Handle<Image>>,
+    pub disable_laa: bool,
+}
+
+impl Default for ShapeMaterialAsset {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            stroke: None,
+            thickness_type: default(),
+            alpha_mode: AlphaMode::Blend,
+            texture: None,
+            disable_laa: false,
+        }
+    }
+}
+
+impl ShapeMaterialAsset {
+    /// Style applied to the shape components of entities using the asset.
+    pub fn style(&self) -> ShapeStyle {
+        ShapeStyle {
+            color: Some(self.color),
+            thickness: self.stroke,
+            thickness_type: Some(self.thickness_type),
+            hollow: Some(self.stroke.is_some()),
+            cap: None,
+        }
+    }
+
+    /// Applies the asset's render configuration to the given material.
+    pub fn apply_to_material(&self, material: &mut ShapeMaterial) {
+        material.alpha_mode = self.alpha_mode;
+        material.texture = self.texture.clone();
+        material.disable_laa = self.disable_laa;
+    }
+}
+
+/// Ids of the [`ShapeMaterialAsset`]s created or modified since the system last ran.
+fn modified_assets(events: &mut EventReader<AssetEvent<ShapeMaterialAsset>>) -> HashSet<HandleId> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle.id()),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect()
+}
+
+/// Applies each [`ShapeMaterialAsset`] to the shapes of the given type using it when the handle is inserted or the asset is modified.
+pub fn apply_shape_material_assets<T: StyledShape>(
+    mut events: EventReader<AssetEvent<ShapeMaterialAsset>>,
+    assets: Res<Assets<ShapeMaterialAsset>>,
+    mut shapes: Query<(&mut T, Ref<Handle<ShapeMaterialAsset>>)>,
+) {
+    let modified = modified_assets(&mut events);
+    for (mut shape, handle) in &mut shapes {
+        if !handle.is_changed() && !modified.contains(&handle.id()) {
+            continue;
+        }
+        if let Some(asset) = assets.get(&*handle) {
+            shape.apply_style(&asset.style());
+        }
+    }
+}
+
+/// Applies each [`ShapeMaterialAsset`] to the [`ShapeMaterial`] of entities using it when the handle is inserted or the asset is modified.
+pub fn apply_material_assets_to_materials(
+    mut events: EventReader<AssetEvent<ShapeMaterialAsset>>,
+    assets: Res<Assets<ShapeMaterialAsset>>,
+    mut materials: Query<(&mut ShapeMaterial, Ref<Handle<ShapeMaterialAsset>>)>,
+) {
+    let modified = modified_assets(&mut events);
+    for (mut material, handle) in &mut materials {
+        if !handle.is_changed() && !modified.contains(&handle.id()) {
+            continue;
+        }
+        if let Some(asset) = assets.get(&*handle) {
+            asset.apply_to_material(&mut material);
+        }
+    }
+}
+
+/// Plugin that sets up [`ShapeMaterialAsset`]s for the built-in shape types, added by [`Shape2dPlugin`].
+pub struct ShapeMaterialAssetPlugin;
+
+impl Plugin for ShapeMaterialAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ShapeMaterialAsset>().add_systems((
+            apply_material_assets_to_materials,
+            apply_shape_material_assets::<Line>,
+            apply_shape_material_assets::<QuadBezier>,
+            apply_shape_material_assets::<Disc>,
+            apply_shape_material_assets::<Rectangle>,
+            apply_shape_material_assets::<RegularPolygon>,
+            apply_shape_material_assets::<ShapeMorph>,
+        ));
+    }
+}
@@ -1,6 +1,22 @@
-use bevy::prelude::*;
+use bevy::{
+    core::{Pod, Zeroable},
+    ecs::{
+        entity::{EntityMap, MapEntities, MapEntitiesError},
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+    render::{
+        primitives::Aabb,
+        view::{NoFrustumCulling, RenderLayers},
+    },
+};
 
-use crate::{prelude::*, render::ShapePipelineType};
+use crate::{
+    prelude::*,
+    render::{Flags, ShapeComponent, ShapeHook, ShapePipelineType},
+};
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 mod disc;
 pub use disc::*;
@@ -17,19 +33,104 @@ pub use rectangle::*;
 mod regular_polygon;
 pub use regular_polygon::*;
 
+mod morph;
+pub use morph::*;
+
+mod triangle;
+pub use triangle::*;
+
+mod style;
+pub use style::*;
+
+mod lod;
+pub use lod::*;
+
+mod opacity;
+pub use opacity::*;
+
+mod hit_test;
+pub use hit_test::*;
+
+mod bounds;
+pub use bounds::*;
+
+mod shader_params;
+pub use shader_params::*;
+
+mod instances;
+pub use instances::*;
+
+mod query;
+pub use query::*;
+
+mod dynamic;
+pub use dynamic::*;
+
+mod svg;
+pub use svg::*;
+
+mod theme;
+pub use theme::*;
+
+mod material_asset;
+pub use material_asset::*;
+
+mod z_order;
+pub use z_order::*;
+
+mod layers;
+pub use layers::*;
+
+#[cfg(feature = "ui")]
+mod ui_node;
+#[cfg(feature = "ui")]
+pub use ui_node::*;
+
+mod bitmap_text;
+pub use bitmap_text::*;
+
+#[cfg(feature = "lyon")]
+mod tessellation;
+#[cfg(feature = "lyon")]
+pub use tessellation::*;
+
+#[cfg(feature = "text")]
+mod text;
+#[cfg(feature = "text")]
+pub use text::*;
+
 /// Component that holds data related to a shape to be used during rendering,
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default, MapEntities)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapeMaterial {
     /// Alpha mode to use when rendering, Opaque, Blend, Add and Multiply are explicitly supported.
+    #[cfg_attr(feature = "serde", serde(with = "AlphaModeDef"))]
     pub alpha_mode: AlphaMode,
     /// Forcibly disable local anti-aliasing.
     pub disable_laa: bool,
+    /// Resolve anti-aliased edges of opaque and alpha masked shapes with alpha to coverage when MSAA is enabled.
+    pub alpha_to_coverage: bool,
     /// Target pipeline draw the shape.
     pub pipeline: ShapePipelineType,
-    /// [`Canvas`] to draw the shape to.
+    /// [`Canvas`] or camera to draw the shape to, bypassing render layers.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub canvas: Option<Entity>,
     /// Texture to apply to the shape.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub texture: Option<Handle<Image>>,
+    /// Whether the texture is an array texture, the layer is taken from the shape component.
+    pub texture_array: bool,
+    /// Fragment shader hook to apply to the shape, see [`ShapeHook`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hook: Option<Handle<ShapeHook>>,
+    /// How the shape interacts with the stencil buffer, see [`StencilMode`].
+    pub stencil: StencilMode,
+    /// Render pass in which the shape is drawn, see [`ShapePass`].
+    pub pass: ShapePass,
+    /// Cameras that may draw the shape, if empty the shape is drawn by all cameras.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub visible_to: Vec<Entity>,
 }
 
 impl Default for ShapeMaterial {
@@ -37,38 +138,319 @@ impl Default for ShapeMaterial {
         Self {
             alpha_mode: AlphaMode::Blend,
             disable_laa: false,
+            alpha_to_coverage: false,
             pipeline: ShapePipelineType::Shape2d,
             texture: None,
+            texture_array: false,
+            hook: None,
+            stencil: default(),
+            pass: default(),
+            visible_to: Vec::new(),
             canvas: None,
         }
     }
 }
 
+impl From<&ShapeConfig> for ShapeMaterial {
+    fn from(config: &ShapeConfig) -> Self {
+        Self {
+            alpha_mode: config.alpha_mode,
+            disable_laa: config.disable_laa,
+            alpha_to_coverage: config.alpha_to_coverage,
+            pipeline: config.pipeline,
+            canvas: config.canvas,
+            texture: config.texture.clone(),
+            texture_array: config.texture_layer.is_some(),
+            hook: config.hook.clone(),
+            stencil: config.stencil,
+            pass: config.pass,
+            visible_to: config.visible_to.clone(),
+        }
+    }
+}
+
+/// Properties shared by every built-in shape component, written into each shape's instance data by the same helper.
+#[derive(Clone, Reflect, FromReflect)]
+#[reflect(Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ShapeCommon {
+    /// Distance in world units to offset the shape towards the camera.
+    pub depth_bias: f32,
+    /// Whether to draw the shape on top of other geometry by ignoring the depth test, only affects 3D.
+    pub overlay: bool,
+    /// Whether to shade the shape using the scene's directional and ambient lights, intended for 3D.
+    pub lit: bool,
+    /// Whether the shape is affected by the camera's `FogSettings`, intended for 3D.
+    pub fog: bool,
+    /// Whether to snap the shape's position and pixel thickness to the device pixel grid.
+    pub pixel_snap: bool,
+    /// Layer of the texture array to sample from, only used when the shape's material has `texture_array` set.
+    pub texture_layer: u32,
+    /// Rect in logical pixels relative to the top left of the render target outside of which the shape is clipped.
+    pub clip: Option<Rect>,
+    /// Arbitrary data passed to shader hooks and custom shaders, ignored by the built-in shaders.
+    pub user_data: Vec4,
+    /// Multiplier applied to the shape's color after decoding, separate from alpha, values above 1.0 drive bloom.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
+    pub intensity: f32,
+}
+
+impl Default for ShapeCommon {
+    fn default() -> Self {
+        Self {
+            depth_bias: 0.0,
+            overlay: false,
+            lit: false,
+            fog: false,
+            pixel_snap: false,
+            texture_layer: 0,
+            clip: None,
+            user_data: Vec4::ZERO,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl From<&ShapeConfig> for ShapeCommon {
+    fn from(config: &ShapeConfig) -> Self {
+        Self {
+            depth_bias: config.depth_bias,
+            overlay: config.overlay,
+            lit: config.lit,
+            fog: config.fog,
+            pixel_snap: config.pixel_snap,
+            texture_layer: config.texture_layer.unwrap_or(0),
+            clip: config.clip,
+            user_data: config.user_data,
+            intensity: config.intensity,
+        }
+    }
+}
+
+impl ShapeCommon {
+    /// Sets the flags of the common properties on the given shape flags and packs both into instance data.
+    pub(crate) fn data(&self, mut flags: Flags) -> ShapeCommonData {
+        flags.set_overlay(self.overlay as u32);
+        flags.set_lit(self.lit as u32);
+        flags.set_fog(self.fog as u32);
+        flags.set_pixel_snap(self.pixel_snap as u32);
+
+        ShapeCommonData {
+            style: [
+                flags.0,
+                self.depth_bias.to_bits(),
+                self.texture_layer,
+                self.intensity.to_bits(),
+            ],
+            clip: clip_bounds(self.clip),
+            user_data: self.user_data.into(),
+        }
+    }
+}
+
+/// Raw data of a [`ShapeCommon`] along with the shape's flags, laid out at the same position in every shape's instance data.
+///
+/// The flags, depth bias, texture layer and intensity are packed into a single `vec4<u32>` attribute,
+/// with the floats stored as their bits, to keep every built in shape within the 16 vertex attributes of WebGL2.
+#[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct ShapeCommonData {
+    style: [u32; 4],
+    clip: [f32; 4],
+    user_data: [f32; 4],
+}
+
+impl ShapeCommonData {
+    pub(crate) fn flags(&self) -> u32 {
+        self.style[0]
+    }
+
+    pub(crate) fn depth_bias(&self) -> f32 {
+        f32::from_bits(self.style[1])
+    }
+}
+
+/// Serializable mirror of [`AlphaMode`], which does not implement serde's traits itself.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "AlphaMode")]
+pub(crate) enum AlphaModeDef {
+    Opaque,
+    Mask(f32),
+    Blend,
+    Premultiplied,
+    Add,
+    Multiply,
+}
+
+impl MapEntities for ShapeMaterial {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        // Canvases and cameras outside of the scene being loaded keep referring to the existing entity
+        if let Some(canvas) = self.canvas.and_then(|canvas| entity_map.get(canvas).ok()) {
+            self.canvas = Some(canvas);
+        }
+        for camera in &mut self.visible_to {
+            if let Ok(mapped) = entity_map.get(*camera) {
+                *camera = mapped;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bounds of the given clip rect as passed to the shape shaders, shapes without a clip rect are unbounded.
+pub(crate) fn clip_bounds(clip: Option<Rect>) -> [f32; 4] {
+    match clip {
+        Some(rect) => [rect.min.x, rect.min.y, rect.max.x, rect.max.y],
+        None => [f32::MIN, f32::MIN, f32::MAX, f32::MAX],
+    }
+}
+
+/// Largest scale along any axis of the given transform.
+pub(crate) fn max_scale(transform: &Mat4) -> f32 {
+    transform
+        .x_axis
+        .truncate()
+        .length()
+        .max(transform.y_axis.truncate().length())
+        .max(transform.z_axis.truncate().length())
+}
+
+/// Creates an [`Aabb`] from the given bounds expanded by padding on all sides.
+pub(crate) fn padded_aabb(min: Vec3, max: Vec3, padding: f32) -> Aabb {
+    Aabb::from_min_max(min - padding, max + padding)
+}
+
+/// Creates an [`Aabb`] for a shape lying in it's local xy plane with the given half extents.
+///
+/// Billboarded shapes may rotate freely so their bounds are expanded to cover any orientation.
+pub(crate) fn flat_aabb(half_extents: Vec2, alignment: Alignment, depth_bias: f32) -> Aabb {
+    let half_extents = match alignment {
+        Alignment::Flat => half_extents.extend(0.0),
+        Alignment::Billboard => Vec3::splat(half_extents.length()),
+        Alignment::AxisBillboard => half_extents.extend(half_extents.x),
+    };
+    padded_aabb(-half_extents, half_extents, depth_bias.abs())
+}
+
+/// Marker inserted along with [`NoFrustumCulling`] on retained shapes that can't be bounded ahead of time.
+///
+/// Distinguishes shapes that opted out of culling themselves from those that are culled again once they can be bounded.
+#[derive(Component, Default)]
+pub struct UnboundedShape;
+
+/// Inserts the given bounds as the entity's [`Aabb`], or disables frustum culling for it if the shape is unbounded.
+pub(crate) fn apply_shape_bounds(
+    commands: &mut Commands,
+    entity: Entity,
+    bounds: Option<Aabb>,
+    aabb: Option<Mut<Aabb>>,
+    unbounded: bool,
+) {
+    match (bounds, aabb) {
+        (Some(bounds), Some(mut aabb)) => *aabb = bounds,
+        (Some(bounds), None) => {
+            commands.entity(entity).insert(bounds);
+        }
+        (None, _) => {
+            commands
+                .entity(entity)
+                .remove::<Aabb>()
+                .insert((NoFrustumCulling, UnboundedShape));
+            return;
+        }
+    }
+    if unbounded {
+        commands
+            .entity(entity)
+            .remove::<(NoFrustumCulling, UnboundedShape)>();
+    }
+}
+
+/// Keeps the [`Aabb`] of retained shapes up to date so they are frustum culled by Bevy's visibility systems.
+///
+/// Shapes without bounds have their [`Aabb`] replaced by [`NoFrustumCulling`] until they can be bounded again.
+pub fn update_shape_aabbs<T: ShapeComponent>(
+    mut commands: Commands,
+    mut shapes: Query<
+        (Entity, &T, Option<&mut Aabb>, Option<&UnboundedShape>),
+        (
+            Changed<T>,
+            Or<(Without<NoFrustumCulling>, With<UnboundedShape>)>,
+        ),
+    >,
+) {
+    for (entity, shape, aabb, unbounded) in &mut shapes {
+        apply_shape_bounds(
+            &mut commands,
+            entity,
+            shape.aabb(),
+            aabb,
+            unbounded.is_some(),
+        );
+    }
+}
+
 /// Marker component for entities that should be drawn by the 3D pipeline.
-#[derive(Component)]
+#[derive(Component, Default, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shape3d;
 
+/// Marker component for retained shapes that will never change after being spawned.
+///
+/// Static shapes are extracted once when the marker is added and uploaded to a buffer of their own, which is only rebuilt
+/// when a shape is made static or non-static. They are otherwise skipped by the per frame extraction and batching,
+/// greatly reducing CPU time for large sets of decorative shapes.
+/// Changes to a static shape's components, transform or visibility are ignored, including frustum culling,
+/// to update a static shape remove and re-insert the marker.
+///
+/// Static shapes are never culled on the GPU. In 3D each batch of static shapes is ordered by it's center
+/// and it's instances aren't sorted back to front, so transparent static shapes may blend in the wrong order.
+#[derive(Component, Default, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StaticShape;
+
 /// Bundle that is required to render a shape.
 ///
 /// Shape specific methods will additionally add the component representing the corresponding shape.
+/// The bundle's [`Aabb`] is kept up to date as the shape changes, see [`ShapeComponent::aabb`].
+/// Shapes that can't be bounded start with an empty [`Aabb`] that is swapped for [`NoFrustumCulling`] before visibility is checked.
+/// Hiding the entity or any of it's ancestors with [`Visibility`] hides the shape and skips it's extraction.
+///
+/// Bundles can be adjusted with chainable `with_*` methods when only a few properties differ from the config:
+/// ```ignore
+/// commands.spawn(
+///     ShapeBundle::circle(&config, 1.0)
+///         .with_color(Color::RED)
+///         .with_translation(Vec3::X),
+/// );
+/// ```
 #[derive(Bundle)]
-pub struct ShapeBundle<T: Component> {
+pub struct ShapeBundle<T: ShapeComponent> {
     pub spatial_bundle: SpatialBundle,
+    pub aabb: Aabb,
     pub shape: ShapeMaterial,
+    pub render_layers: RenderLayers,
     pub shape_type: T,
 }
 
-impl<T: Component> ShapeBundle<T> {
+impl<T: ShapeComponent> ShapeBundle<T> {
     pub fn new(config: &ShapeConfig, component: T) -> Self {
         Self {
             spatial_bundle: SpatialBundle::from_transform(config.transform),
-            shape: ShapeMaterial {
-                alpha_mode: config.alpha_mode,
-                disable_laa: config.disable_laa,
-                pipeline: config.pipeline,
-                canvas: config.canvas,
-                texture: config.texture.clone(),
-            },
+            aabb: component.aabb().unwrap_or_default(),
+            shape: ShapeMaterial::from(config),
+            // Entities without render layers are on the default layer so this is equivalent to leaving them out
+            render_layers: config.render_layers.unwrap_or_default(),
             shape_type: component,
         }
     }
@@ -77,15 +459,98 @@ impl<T: Component> ShapeBundle<T> {
     pub fn insert_3d(self) -> (Self, Shape3d) {
         (self, Shape3d)
     }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.spatial_bundle.transform = transform;
+        self
+    }
+
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.spatial_bundle.transform.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.spatial_bundle.transform.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.spatial_bundle.transform.scale = scale;
+        self
+    }
+
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.spatial_bundle.visibility = visibility;
+        self
+    }
+
+    pub fn with_render_layers(mut self, render_layers: RenderLayers) -> Self {
+        self.render_layers = render_layers;
+        self
+    }
+
+    /// Draws the shape to the given [`Canvas`], see [`ShapeConfig::set_canvas`].
+    ///
+    /// Changes to the shape mark the canvas to be redrawn, so it can use [`CanvasMode::OnDemand`].
+    pub fn with_canvas(mut self, canvas: Entity) -> Self {
+        self.shape.pipeline = ShapePipelineType::Shape2d;
+        self.shape.canvas = Some(canvas);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.shape.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Modifies the shape component directly, for properties without a dedicated method.
+    pub fn with_shape(mut self, f: impl FnOnce(&mut T)) -> Self {
+        f(&mut self.shape_type);
+        self.aabb = self.shape_type.aabb().unwrap_or_default();
+        self
+    }
+}
+
+impl<T: ShapeComponent + StyledShape> ShapeBundle<T> {
+    /// Applies each property set in the given [`ShapeStyle`] to the shape.
+    pub fn with_style(self, style: &ShapeStyle) -> Self {
+        self.with_shape(|shape| shape.apply_style(style))
+    }
+
+    pub fn with_color(self, color: Color) -> Self {
+        self.with_style(&ShapeStyle {
+            color: Some(color),
+            ..default()
+        })
+    }
+
+    pub fn with_thickness(self, thickness: f32) -> Self {
+        self.with_style(&ShapeStyle {
+            thickness: Some(thickness),
+            ..default()
+        })
+    }
+
+    pub fn with_hollow(self, hollow: bool) -> Self {
+        self.with_style(&ShapeStyle {
+            hollow: Some(hollow),
+            ..default()
+        })
+    }
 }
 
 /// Defines the way in which the thickness value of shape is interpreted.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThicknessType {
     /// 1.0 thickness corresponds to 1.0 world unit.
     #[default]
     World,
-    /// 1.0 thickness corresponds to 1 pixel.
+    /// 1.0 thickness corresponds to 1 logical pixel.
+    ///
+    /// Accounts for the camera's projection including orthographic scaling modes and zoom,
+    /// as well as the scale factor of the render target which can be overridden with [`ShapePixelScale`].
     Pixels,
     /// 1.0 thickness corresponds to 1% of the screen size along the shortest axis.
     Screen,
@@ -99,6 +564,7 @@ impl From<ThicknessType> for u32 {
 
 /// Defines the way in which caps will be rendered on a supported shape.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cap {
     /// No caps
     None,
@@ -117,6 +583,7 @@ impl From<Cap> for u32 {
 
 /// Defines how a shape will orient itself in relation to it's transform and the camera
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Alignment {
     /// Shapes will respect the rotation in their transform.
     #[default]
@@ -124,6 +591,9 @@ pub enum Alignment {
     /// Shapes will always orient themselves towards the camera.
     /// Note that lines rotate around their direction while all other shapes will fully face the camera at all times.
     Billboard,
+    /// Shapes will rotate towards the camera around the y axis of their transform only.
+    /// Useful for health bars and markers that should face the camera without pitching with it.
+    AxisBillboard,
 }
 
 impl From<Alignment> for u32 {
@@ -131,3 +601,68 @@ impl From<Alignment> for u32 {
         value as u32
     }
 }
+
+/// Defines the render pass in which a shape is drawn.
+///
+/// To draw all shapes in a given pass set the `pass` of the `base_config` passed to [`Shape2dPlugin`](crate::Shape2dPlugin) or [`ShapePlugin`](crate::ShapePlugin).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Reflect, FromReflect,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShapePass {
+    /// Shapes are drawn in the camera's main pass alongside the rest of the scene.
+    #[default]
+    Main,
+    /// Shapes are drawn after tonemapping and anti-aliasing, guaranteeing they appear above all post processing.
+    ///
+    /// Shapes in this pass are drawn in order of distance without depth testing or MSAA and are not affected by bloom or tonemapping.
+    /// Shapes with a [`StencilMode`] other than [`StencilMode::None`] are always drawn in the stencil pass.
+    PostProcess,
+    /// Shapes are drawn in the camera's `bevy_ui` pass, positioned in UI coordinates and ordered among UI nodes by their z translation.
+    ///
+    /// UI coordinates are logical pixels with the origin at the top left of the render target and y pointing down.
+    /// Only supported by the 2D pipeline and requires the `ui` feature and the `UiPlugin` to be added before the shape plugin, retained shapes should be spawned with
+    /// [`NoFrustumCulling`] as they are not positioned relative to the camera.
+    /// Shapes with a [`StencilMode`] other than [`StencilMode::None`] are always drawn in the stencil pass.
+    Ui,
+}
+
+/// Defines how a shape interacts with the stencil buffer, allowing shapes to be clipped to the area covered by other shapes.
+///
+/// Shapes with a stencil mode other than [`StencilMode::None`] are drawn in a separate pass after the main pass
+/// and are not depth tested against the scene.
+/// Masks at each level are drawn before any shapes reading them, masks at higher levels are nested within lower levels.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Reflect, FromReflect,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StencilMode {
+    /// Shapes are drawn normally.
+    #[default]
+    None,
+    /// Shapes write to the mask at the given level, only where the mask at the previous level has been written.
+    /// Mask shapes do not write color, levels start at 1.
+    Write(u8),
+    /// Shapes are only drawn where the mask at the given level or higher has been written.
+    Read(u8),
+}
+
+impl StencilMode {
+    /// Stencil reference value used when drawing the shape.
+    pub fn reference(&self) -> u32 {
+        match self {
+            StencilMode::None => 0,
+            StencilMode::Write(level) => level.saturating_sub(1) as u32,
+            StencilMode::Read(level) => *level as u32,
+        }
+    }
+
+    /// Order in which shapes are drawn such that masks are written before they are read.
+    pub fn order(&self) -> u32 {
+        match self {
+            StencilMode::None => 0,
+            StencilMode::Write(level) => *level as u32 * 2,
+            StencilMode::Read(level) => *level as u32 * 2 + 1,
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use bevy::{
+    core::{Pod, Zeroable},
+    prelude::*,
+    reflect::{FromReflect, Reflect},
+    render::{primitives::Aabb, render_resource::ShaderRef},
+};
+use wgpu::vertex_attr_array;
+
+use crate::{
+    prelude::*,
+    render::{Flags, ShapeComponent, ShapeData, MORPH_HANDLE},
+};
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
+
+/// Primitive shape that a [`ShapeMorph`] blends from or to, all lengths are in world units.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MorphTarget {
+    Circle {
+        radius: f32,
+    },
+    Rectangle {
+        size: Vec2,
+        /// Corner rounding radius for all corners.
+        corner_radius: f32,
+    },
+    Ngon {
+        /// Number of sides, non-integer values may have unexpected results.
+        sides: f32,
+        /// Radius to the tip of a corner.
+        radius: f32,
+        /// Corner rounding radius for all corners.
+        roundness: f32,
+    },
+}
+
+impl Default for MorphTarget {
+    fn default() -> Self {
+        MorphTarget::Circle { radius: 1.0 }
+    }
+}
+
+impl MorphTarget {
+    /// Identifier of the primitive used by the morph shader.
+    fn kind(&self) -> u32 {
+        match self {
+            MorphTarget::Circle { .. } => 0,
+            MorphTarget::Rectangle { .. } => 1,
+            MorphTarget::Ngon { .. } => 2,
+        }
+    }
+
+    /// Parameters of the primitive as interpreted by the morph shader.
+    fn params(&self) -> [f32; 4] {
+        match *self {
+            MorphTarget::Circle { radius } => [radius, 0.0, 0.0, 0.0],
+            MorphTarget::Rectangle {
+                size,
+                corner_radius,
+            } => [size.x / 2.0, size.y / 2.0, corner_radius, 0.0],
+            MorphTarget::Ngon {
+                sides,
+                radius,
+                roundness,
+            } => [sides, radius, roundness, 0.0],
+        }
+    }
+
+    /// Primitive described by the given identifier and parameters, the inverse of [`MorphTarget::kind`] and [`MorphTarget::params`].
+    fn from_params(kind: u32, params: [f32; 4]) -> Self {
+        match kind {
+            0 => MorphTarget::Circle { radius: params[0] },
+            1 => MorphTarget::Rectangle {
+                size: Vec2::new(params[0], params[1]) * 2.0,
+                corner_radius: params[2],
+            },
+            _ => MorphTarget::Ngon {
+                sides: params[0],
+                radius: params[1],
+                roundness: params[2],
+            },
+        }
+    }
+
+    /// Half extents of the primitive in it's local xy plane.
+    pub fn half_extents(&self) -> Vec2 {
+        match *self {
+            MorphTarget::Circle { radius } => Vec2::splat(radius),
+            MorphTarget::Rectangle { size, .. } => size / 2.0,
+            MorphTarget::Ngon { radius, .. } => Vec2::splat(radius),
+        }
+    }
+}
+
+/// Half extents covering both primitives of a morph.
+fn morph_half_extents(from: &MorphTarget, to: &MorphTarget) -> Vec2 {
+    from.half_extents().max(to.half_extents())
+}
+
+/// Component containing the data for drawing a shape that blends between two [`MorphTarget`]s.
+///
+/// The signed distance fields of both primitives are interpolated by `progress` in a dedicated shader,
+/// animate `progress` with [`AnimateMorph`] for smooth transitions such as a circle turning into a rounded rectangle.
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ShapeMorph {
+    pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
+    pub thickness: f32,
+    pub thickness_type: ThicknessType,
+    pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
+    pub hollow: bool,
+
+    /// Primitive drawn when progress is 0.0.
+    pub from: MorphTarget,
+    /// Primitive drawn when progress is 1.0.
+    pub to: MorphTarget,
+    /// How far the shape has morphed from `from` to `to`.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0, max = 1.0))]
+    pub progress: f32,
+}
+
+impl ShapeMorph {
+    pub fn new(config: &ShapeConfig, from: MorphTarget, to: MorphTarget, progress: f32) -> Self {
+        Self {
+            color: config.color,
+            thickness: config.thickness,
+            thickness_type: config.thickness_type,
+            alignment: config.alignment,
+            common: ShapeCommon::from(config),
+            hollow: config.hollow,
+
+            from,
+            to,
+            progress,
+        }
+    }
+}
+
+impl ShapeComponent for ShapeMorph {
+    type Data = MorphData;
+
+    fn into_data(&self, tf: &GlobalTransform) -> MorphData {
+        let mut flags = Flags(0);
+        flags.set_thickness_type(self.thickness_type);
+        flags.set_alignment(self.alignment);
+        flags.set_hollow(self.hollow as u32);
+
+        MorphData {
+            transform: tf.compute_matrix().to_cols_array_2d(),
+
+            color: self.color.as_rgba_f32(),
+            thickness: self.thickness,
+            common: self.common.data(flags),
+
+            from: self.from.params(),
+            to: self.to.params(),
+            kinds: [self.from.kind(), self.to.kind()],
+            progress: self.progress,
+        }
+    }
+
+    fn aabb(&self) -> Option<Aabb> {
+        Some(flat_aabb(
+            morph_half_extents(&self.from, &self.to),
+            self.alignment,
+            self.common.depth_bias,
+        ))
+    }
+}
+
+impl Default for ShapeMorph {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            thickness: 1.0,
+            thickness_type: default(),
+            alignment: default(),
+            common: default(),
+            hollow: false,
+
+            from: default(),
+            to: default(),
+            progress: 0.0,
+        }
+    }
+}
+
+/// Raw data sent to the morph shader to draw a shape morphing between two primitives
+#[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct MorphData {
+    transform: [[f32; 4]; 4],
+
+    color: [f32; 4],
+    thickness: f32,
+    common: ShapeCommonData,
+
+    from: [f32; 4],
+    to: [f32; 4],
+    kinds: [u32; 2],
+    progress: f32,
+}
+
+impl MorphData {
+    pub fn new(
+        config: &ShapeConfig,
+        from: MorphTarget,
+        to: MorphTarget,
+        progress: f32,
+    ) -> MorphData {
+        let mut flags = Flags(0);
+        flags.set_thickness_type(config.thickness_type);
+        flags.set_alignment(config.alignment);
+        flags.set_hollow(config.hollow as u32);
+
+        MorphData {
+            transform: config.transform.compute_matrix().to_cols_array_2d(),
+
+            color: config.color.as_rgba_f32(),
+            thickness: config.thickness,
+            common: ShapeCommon::from(config).data(flags),
+
+            from: from.params(),
+            to: to.params(),
+            kinds: [from.kind(), to.kind()],
+            progress,
+        }
+    }
+
+    /// Half extents covering both primitives in local space.
+    fn half_extents(&self) -> Vec2 {
+        let extents = |kind: u32, params: [f32; 4]| match kind {
+            0 => Vec2::splat(params[0]),
+            1 => Vec2::new(params[0], params[1]),
+            _ => Vec2::splat(params[1]),
+        };
+        extents(self.kinds[0], self.from).max(extents(self.kinds[1], self.to))
+    }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> ShapeMorph {
+        ShapeMorph {
+            color: Color::from(self.color),
+            ..ShapeMorph::new(
+                config,
+                MorphTarget::from_params(self.kinds[0], self.from),
+                MorphTarget::from_params(self.kinds[1], self.to),
+                self.progress,
+            )
+        }
+    }
+}
+
+impl ShapeData for MorphData {
+    type Component = ShapeMorph;
+
+    fn vertex_layout() -> Vec<wgpu::VertexAttribute> {
+        vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+
+            4 => Float32x4,
+            5 => Float32,
+            6 => Uint32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x4,
+            10 => Float32x4,
+            11 => Uint32x2,
+            12 => Float32
+        ]
+        .to_vec()
+    }
+
+    fn shader() -> ShaderRef {
+        MORPH_HANDLE.typed::<Shader>().into()
+    }
+
+    fn transform(&self) -> Mat4 {
+        Mat4::from_cols_array_2d(&self.transform)
+    }
+
+    fn bounding_radius(&self) -> f32 {
+        self.half_extents().length() * max_scale(&self.transform()) + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to enable it to draw morphing shapes.
+pub trait MorphPainter {
+    fn morph(&mut self, from: MorphTarget, to: MorphTarget, progress: f32) -> &mut Self;
+}
+
+impl<'w, 's> MorphPainter for ShapePainter<'w, 's> {
+    fn morph(&mut self, from: MorphTarget, to: MorphTarget, progress: f32) -> &mut Self {
+        self.send(MorphData::new(self.config(), from, to, progress))
+    }
+}
+
+/// Extension trait for [`ShapeBundle`] to enable creation of morphing shape bundles.
+pub trait MorphBundle {
+    fn morph(config: &ShapeConfig, from: MorphTarget, to: MorphTarget, progress: f32) -> Self;
+}
+
+impl MorphBundle for ShapeBundle<ShapeMorph> {
+    fn morph(config: &ShapeConfig, from: MorphTarget, to: MorphTarget, progress: f32) -> Self {
+        Self::new(config, ShapeMorph::new(config, from, to, progress))
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to enable spawning of morphing shape entities.
+pub trait MorphSpawner<'w, 's> {
+    fn morph(
+        &mut self,
+        from: MorphTarget,
+        to: MorphTarget,
+        progress: f32,
+    ) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> MorphSpawner<'w, 's> for T {
+    fn morph(
+        &mut self,
+        from: MorphTarget,
+        to: MorphTarget,
+        progress: f32,
+    ) -> ShapeEntityCommands<'w, 's, '_> {
+        self.spawn_shape(ShapeBundle::morph(self.config(), from, to, progress))
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Component that multiplies the alpha of the entity's shape and all shapes below it in the hierarchy.
+///
+/// Opacities of nested entities are multiplied together, allowing an entire subtree of shapes such as a HUD
+/// spawned with [`ShapeChildBuilder`](crate::painter::ShapeChildBuilder) to be faded with a single value.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Opacity(pub f32);
+
+impl Default for Opacity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Opacity of an entity after multiplying the [`Opacity`] of it's ancestors, maintained by [`propagate_opacity`].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ComputedOpacity(f32);
+
+impl ComputedOpacity {
+    pub fn get(&self) -> f32 {
+        self.0
+    }
+}
+
+/// Propagates [`Opacity`] down the hierarchy into the [`ComputedOpacity`] of each descendant.
+///
+/// Entities that are no longer below an [`Opacity`] are reset to fully opaque.
+pub fn propagate_opacity(
+    mut commands: Commands,
+    opacities: Query<&Opacity>,
+    roots: Query<Entity, With<Opacity>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    mut computed: Query<(Entity, &mut ComputedOpacity)>,
+) {
+    let mut values = HashMap::new();
+    for root in &roots {
+        // Descendants of another entity with opacity are visited when walking down from that entity
+        let mut ancestor = root;
+        let mut nested = false;
+        while let Ok(parent) = parents.get(ancestor) {
+            ancestor = parent.get();
+            if opacities.contains(ancestor) {
+                nested = true;
+                break;
+            }
+        }
+        if nested {
+            continue;
+        }
+
+        let mut stack = vec![(root, 1.0)];
+        while let Some((entity, inherited)) = stack.pop() {
+            let value = inherited * opacities.get(entity).map_or(1.0, |opacity| opacity.0);
+            values.insert(entity, value);
+            if let Ok(children) = children.get(entity) {
+                stack.extend(children.iter().map(|child| (*child, value)));
+            }
+        }
+    }
+
+    for (entity, mut opacity) in &mut computed {
+        let value = values.remove(&entity).unwrap_or(1.0);
+        // Only write changes so that unchanged shapes aren't re-extracted
+        if opacity.0 != value {
+            opacity.0 = value;
+        }
+    }
+    for (entity, value) in values {
+        commands.entity(entity).insert(ComputedOpacity(value));
+    }
+}
+
+/// Plugin that propagates [`Opacity`] to retained shapes, added by [`Shape2dPlugin`](crate::Shape2dPlugin).
+pub struct ShapeOpacityPlugin;
+
+impl Plugin for ShapeOpacityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(propagate_opacity.in_base_set(CoreSet::PostUpdate));
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::prelude::*;
+
+/// Mutable references to the fields shared by the built-in shape types, see [`ShapeQuery`].
+pub struct ShapeFieldsMut<'a> {
+    pub color: &'a mut Color,
+    pub thickness: &'a mut f32,
+    pub thickness_type: &'a mut ThicknessType,
+    pub alignment: &'a mut Alignment,
+    pub common: &'a mut ShapeCommon,
+    /// Set for shapes that can be hollow, not lines or bezier curves.
+    pub hollow: Option<&'a mut bool>,
+    /// Set for shapes with caps, lines, bezier curves and discs.
+    pub cap: Option<&'a mut Cap>,
+}
+
+/// Trait implemented by shape components that expose their common fields to [`ShapeQuery`].
+pub trait CommonShapeFields: Component {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_>;
+}
+
+/// System param that accesses the fields shared by every built-in shape type at once.
+///
+/// Avoids writing a query per shape type when changing the style of many different shapes, for example:
+/// ```ignore
+/// fn tint(mut shapes: ShapeQuery, selected: Query<Entity, With<Selected>>) {
+///     for entity in &selected {
+///         shapes.for_each_descendant_mut(entity, |_, fields| *fields.color = Color::RED);
+///     }
+/// }
+/// ```
+/// Accessing a shape's fields marks it's component as changed.
+#[derive(SystemParam)]
+pub struct ShapeQuery<'w, 's> {
+    lines: Query<'w, 's, (Entity, &'static mut Line)>,
+    beziers: Query<'w, 's, (Entity, &'static mut QuadBezier)>,
+    discs: Query<'w, 's, (Entity, &'static mut Disc)>,
+    rectangles: Query<'w, 's, (Entity, &'static mut Rectangle)>,
+    polygons: Query<'w, 's, (Entity, &'static mut RegularPolygon)>,
+    morphs: Query<'w, 's, (Entity, &'static mut ShapeMorph)>,
+    children: Query<'w, 's, &'static Children>,
+}
+
+fn for_each_shape<T: CommonShapeFields>(
+    query: &mut Query<(Entity, &mut T)>,
+    f: &mut impl FnMut(Entity, ShapeFieldsMut),
+) {
+    for (entity, mut shape) in query {
+        f(entity, shape.fields_mut());
+    }
+}
+
+fn get_shape<T: CommonShapeFields>(
+    query: &mut Query<(Entity, &mut T)>,
+    entity: Entity,
+    f: &mut impl FnMut(Entity, ShapeFieldsMut),
+) -> bool {
+    match query.get_mut(entity) {
+        Ok((entity, mut shape)) => {
+            f(entity, shape.fields_mut());
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+impl<'w, 's> ShapeQuery<'w, 's> {
+    /// Calls the given function with the fields of every shape.
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(Entity, ShapeFieldsMut)) {
+        for_each_shape(&mut self.lines, &mut f);
+        for_each_shape(&mut self.beziers, &mut f);
+        for_each_shape(&mut self.discs, &mut f);
+        for_each_shape(&mut self.rectangles, &mut f);
+        for_each_shape(&mut self.polygons, &mut f);
+        for_each_shape(&mut self.morphs, &mut f);
+    }
+
+    /// Calls the given function with the fields of each shape on the given entity, returns false if it has none.
+    pub fn get_mut(&mut self, entity: Entity, mut f: impl FnMut(Entity, ShapeFieldsMut)) -> bool {
+        // Not short circuited as an entity may have several shape components
+        let mut found = get_shape(&mut self.lines, entity, &mut f);
+        found |= get_shape(&mut self.beziers, entity, &mut f);
+        found |= get_shape(&mut self.discs, entity, &mut f);
+        found |= get_shape(&mut self.rectangles, entity, &mut f);
+        found |= get_shape(&mut self.polygons, entity, &mut f);
+        found |= get_shape(&mut self.morphs, entity, &mut f);
+        found
+    }
+
+    /// Calls the given function with the fields of every shape on the given entity and it's descendants.
+    pub fn for_each_descendant_mut(
+        &mut self,
+        root: Entity,
+        mut f: impl FnMut(Entity, ShapeFieldsMut),
+    ) {
+        let mut stack = vec![root];
+        while let Some(entity) = stack.pop() {
+            self.get_mut(entity, &mut f);
+            if let Ok(children) = self.children.get(entity) {
+                stack.extend(children.iter().copied());
+            }
+        }
+    }
+}
+
+impl CommonShapeFields for Line {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: None,
+            cap: Some(&mut self.cap),
+        }
+    }
+}
+
+impl CommonShapeFields for QuadBezier {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: None,
+            cap: Some(&mut self.cap),
+        }
+    }
+}
+
+impl CommonShapeFields for Disc {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: Some(&mut self.hollow),
+            cap: Some(&mut self.cap),
+        }
+    }
+}
+
+impl CommonShapeFields for Rectangle {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: Some(&mut self.hollow),
+            cap: None,
+        }
+    }
+}
+
+impl CommonShapeFields for RegularPolygon {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: Some(&mut self.hollow),
+            cap: None,
+        }
+    }
+}
+
+impl CommonShapeFields for ShapeMorph {
+    fn fields_mut(&mut self) -> ShapeFieldsMut<'_> {
+        ShapeFieldsMut {
+            color: &mut self.color,
+            thickness: &mut self.thickness,
+            thickness_type: &mut self.thickness_type,
+            alignment: &mut self.alignment,
+            common: &mut self.common,
+            hollow: Some(&mut self.hollow),
+            cap: None,
+        }
+    }
+}
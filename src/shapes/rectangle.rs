@@ -2,7 +2,7 @@ use bevy::{
     core::{Pod, Zeroable},
     prelude::*,
     reflect::{FromReflect, Reflect},
-    render::render_resource::ShaderRef,
+    render::{primitives::Aabb, render_resource::ShaderRef},
 };
 use wgpu::vertex_attr_array;
 
@@ -10,19 +10,42 @@ use crate::{
     prelude::*,
     render::{Flags, ShapeComponent, ShapeData, RECT_HANDLE},
 };
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 /// Component containing the data for drawing a rectangle.
-#[derive(Component, Reflect)]
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Rectangle {
     pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub thickness: f32,
     pub thickness_type: ThicknessType,
     pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
+    /// Part of the texture drawn in normalized texture coordinates with the origin at the top left, the whole texture if unset.
+    pub texture_rect: Option<Rect>,
+    /// Number of frames in each row and column when the texture is a sprite sheet, overrides `texture_rect` if set.
+    pub frame_grid: Option<UVec2>,
+    /// Frame of the sprite sheet to draw, frames are numbered left to right and top to bottom.
+    ///
+    /// Animate with [`AnimateFrames`].
+    pub frame_index: u32,
     pub hollow: bool,
 
     /// Size of the rectangle on the x and y axis.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub size: Vec2,
     /// Corner rounding radius for each corner in world units.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub corner_radii: Vec4,
 }
 
@@ -33,6 +56,10 @@ impl Rectangle {
             thickness: config.thickness,
             thickness_type: config.thickness_type,
             alignment: config.alignment,
+            common: ShapeCommon::from(config),
+            texture_rect: config.texture_rect,
+            frame_grid: None,
+            frame_index: 0,
             hollow: config.hollow,
 
             size,
@@ -55,12 +82,23 @@ impl ShapeComponent for Rectangle {
 
             color: self.color.as_rgba_f32(),
             thickness: self.thickness,
-            flags: flags.0,
+            common: self.common.data(flags),
 
             size: self.size.into(),
             corner_radii: self.corner_radii.into(),
+            texture_rect: texture_rect(self.frame_grid.map_or(self.texture_rect, |grid| {
+                Some(sprite_frame_rect(grid, self.frame_index))
+            })),
         }
     }
+
+    fn aabb(&self) -> Option<Aabb> {
+        Some(flat_aabb(
+            self.size / 2.0,
+            self.alignment,
+            self.common.depth_bias,
+        ))
+    }
 }
 
 impl Default for Rectangle {
@@ -70,6 +108,10 @@ impl Default for Rectangle {
             thickness: 1.0,
             thickness_type: default(),
             alignment: default(),
+            common: default(),
+            texture_rect: None,
+            frame_grid: None,
+            frame_index: 0,
             hollow: false,
 
             size: Vec2::ONE,
@@ -80,16 +122,37 @@ impl Default for Rectangle {
 
 /// Raw data sent to the rectangle shader to draw a rectangle
 #[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct RectData {
     transform: [[f32; 4]; 4],
 
     color: [f32; 4],
     thickness: f32,
-    flags: u32,
+    common: ShapeCommonData,
 
     size: [f32; 2],
     corner_radii: [f32; 4],
+    texture_rect: [f32; 4],
+}
+
+/// Part of a sprite sheet with the given number of columns and rows containing a frame, in normalized texture coordinates.
+///
+/// Frames are numbered left to right and top to bottom, wrapping around once the last frame is reached.
+pub fn sprite_frame_rect(grid: UVec2, index: u32) -> Rect {
+    let grid = grid.max(UVec2::ONE);
+    let index = index % (grid.x * grid.y);
+    let cell = UVec2::new(index % grid.x, index / grid.x).as_vec2();
+    let grid = grid.as_vec2();
+    Rect::from_corners(cell / grid, (cell + 1.0) / grid)
+}
+
+/// Texture rect as min and max corners, covering the whole texture if unset.
+fn texture_rect(rect: Option<Rect>) -> [f32; 4] {
+    match rect {
+        Some(rect) => [rect.min.x, rect.min.y, rect.max.x, rect.max.y],
+        None => [0.0, 0.0, 1.0, 1.0],
+    }
 }
 
 impl RectData {
@@ -104,10 +167,20 @@ impl RectData {
 
             color: config.color.as_rgba_f32(),
             thickness: config.thickness,
-            flags: flags.0,
+            common: ShapeCommon::from(config).data(flags),
 
             size: size.into(),
             corner_radii: config.corner_radii.into(),
+            texture_rect: texture_rect(config.texture_rect),
+        }
+    }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> Rectangle {
+        Rectangle {
+            color: Color::from(self.color),
+            corner_radii: Vec4::from(self.corner_radii),
+            ..Rectangle::new(config, Vec2::from(self.size))
         }
     }
 }
@@ -124,9 +197,12 @@ impl ShapeData for RectData {
 
             4 => Float32x4,
             5 => Float32,
-            6 => Uint32,
-            7 => Float32x2,
-            8 => Float32x4
+            6 => Uint32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x2,
+            10 => Float32x4,
+            11 => Float32x4
         ]
         .to_vec()
     }
@@ -138,13 +214,46 @@ impl ShapeData for RectData {
     fn transform(&self) -> Mat4 {
         Mat4::from_cols_array_2d(&self.transform)
     }
+
+    fn bounding_radius(&self) -> f32 {
+        Vec2::from(self.size).length() / 2.0 * max_scale(&self.transform())
+            + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
 }
 
 /// Extension trait for [`ShapePainter`] to enable it to draw rectangles.
 pub trait RectPainter {
     fn rect(&mut self, size: Vec2) -> &mut Self;
 
+    /// Draws the given image, once packed into a texture array by [`ShapeImageArrays`](crate::render::ShapeImageArrays)
+    /// it's batched with other images of the same size and format.
     fn image(&mut self, image: Handle<Image>, size: Vec2) -> &mut Self;
+
+    /// Draws the given layer of an array texture, see [`create_texture_array`](crate::render::create_texture_array).
+    fn image_layer(&mut self, array: Handle<Image>, layer: u32, size: Vec2) -> &mut Self;
+
+    /// Draws a frame of a sprite sheet with `grid` columns and rows of frames, see [`sprite_frame_rect`].
+    ///
+    /// Pair with [`AnimateFrames::frame`] to draw animated sprites.
+    fn image_frame(
+        &mut self,
+        image: Handle<Image>,
+        size: Vec2,
+        grid: UVec2,
+        frame: u32,
+    ) -> &mut Self;
 }
 
 impl<'w, 's> RectPainter for ShapePainter<'w, 's> {
@@ -153,8 +262,37 @@ impl<'w, 's> RectPainter for ShapePainter<'w, 's> {
     }
 
     fn image(&mut self, image: Handle<Image>, size: Vec2) -> &mut Self {
+        if let Some((array, layer)) = self.packed_image(&image) {
+            return self.image_layer(array, layer, size);
+        }
+        let mut config = self.config().clone();
+        config.texture = Some(image);
+        config.texture_layer = None;
+        config.color = Color::WHITE;
+        config.hollow = false;
+        self.send_with_config(&config, RectData::new(&config, size))
+    }
+
+    fn image_layer(&mut self, array: Handle<Image>, layer: u32, size: Vec2) -> &mut Self {
+        let mut config = self.config().clone();
+        config.texture = Some(array);
+        config.texture_layer = Some(layer);
+        config.color = Color::WHITE;
+        config.hollow = false;
+        self.send_with_config(&config, RectData::new(&config, size))
+    }
+
+    fn image_frame(
+        &mut self,
+        image: Handle<Image>,
+        size: Vec2,
+        grid: UVec2,
+        frame: u32,
+    ) -> &mut Self {
         let mut config = self.config().clone();
         config.texture = Some(image);
+        config.texture_layer = None;
+        config.texture_rect = Some(sprite_frame_rect(grid, frame));
         config.color = Color::WHITE;
         config.hollow = false;
         self.send_with_config(&config, RectData::new(&config, size))
@@ -2,7 +2,7 @@ use bevy::{
     core::{Pod, Zeroable},
     prelude::*,
     reflect::{FromReflect, Reflect},
-    render::render_resource::ShaderRef,
+    render::{primitives::Aabb, render_resource::ShaderRef},
 };
 use wgpu::vertex_attr_array;
 
@@ -10,21 +10,37 @@ use crate::{
     prelude::*,
     render::{Flags, ShapeComponent, ShapeData, NGON_HANDLE},
 };
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
 
 /// Component containing the data for drawing a regular polygon.
-#[derive(Component, Reflect)]
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RegularPolygon {
     pub color: Color,
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub thickness: f32,
     pub thickness_type: ThicknessType,
     pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
     pub hollow: bool,
 
     /// Number of sides, non-integer values may have unexpected results.
+    #[cfg_attr(feature = "inspector", inspector(min = 3.0))]
     pub sides: f32,
     /// Radius to the tip of a corner.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub radius: f32,
     /// Corner rounding radius for all corner in world units.
+    #[cfg_attr(feature = "inspector", inspector(min = 0.0))]
     pub roundness: f32,
 }
 
@@ -35,6 +51,7 @@ impl RegularPolygon {
             thickness: config.thickness,
             thickness_type: config.thickness_type,
             alignment: config.alignment,
+            common: ShapeCommon::from(config),
             hollow: config.hollow,
 
             sides,
@@ -58,13 +75,21 @@ impl ShapeComponent for RegularPolygon {
 
             color: self.color.as_rgba_f32(),
             thickness: self.thickness,
-            flags: flags.0,
+            common: self.common.data(flags),
 
             sides: self.sides,
             radius: self.radius,
             roundness: self.roundness,
         }
     }
+
+    fn aabb(&self) -> Option<Aabb> {
+        Some(flat_aabb(
+            Vec2::splat(self.radius),
+            self.alignment,
+            self.common.depth_bias,
+        ))
+    }
 }
 
 impl Default for RegularPolygon {
@@ -74,6 +99,7 @@ impl Default for RegularPolygon {
             thickness: 1.0,
             thickness_type: default(),
             alignment: default(),
+            common: default(),
             hollow: false,
 
             sides: 3.0,
@@ -85,13 +111,14 @@ impl Default for RegularPolygon {
 
 /// Raw data sent to the regular polygon shader to draw a regular polygon
 #[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct NgonData {
     transform: [[f32; 4]; 4],
 
     color: [f32; 4],
     thickness: f32,
-    flags: u32,
+    common: ShapeCommonData,
 
     sides: f32,
     radius: f32,
@@ -110,13 +137,22 @@ impl NgonData {
 
             color: config.color.as_rgba_f32(),
             thickness: config.thickness,
-            flags: flags.0,
+            common: ShapeCommon::from(config).data(flags),
 
             sides,
             radius,
             roundness: config.roundness,
         }
     }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> RegularPolygon {
+        RegularPolygon {
+            color: Color::from(self.color),
+            roundness: self.roundness,
+            ..RegularPolygon::new(config, self.sides, self.radius)
+        }
+    }
 }
 
 impl ShapeData for NgonData {
@@ -131,10 +167,12 @@ impl ShapeData for NgonData {
 
             4 => Float32x4,
             5 => Float32,
-            6 => Uint32,
-            7 => Float32,
-            8 => Float32,
-            9 => Float32
+            6 => Uint32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32,
+            10 => Float32,
+            11 => Float32
         ]
         .to_vec()
     }
@@ -146,6 +184,22 @@ impl ShapeData for NgonData {
     fn transform(&self) -> Mat4 {
         Mat4::from_cols_array_2d(&self.transform)
     }
+
+    fn bounding_radius(&self) -> f32 {
+        self.radius * max_scale(&self.transform()) + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
 }
 
 /// Extension trait for [`ShapePainter`] to enable it to draw regular polygons.
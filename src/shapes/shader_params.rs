@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Component whose value is written to the `user_data` of the entity's shape whenever it changes.
+///
+/// Allows retained shapes driving [`ShapeHook`]s or custom shaders to be animated by mutating a component,
+/// rather than reaching into each shape type's fields.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeShaderParams(pub Vec4);
+
+/// Trait implemented by shape components that pass user data to their shaders.
+pub trait ShaderParamsShape: Component {
+    fn set_user_data(&mut self, user_data: Vec4);
+}
+
+/// Copies changed [`ShapeShaderParams`] into the user data of shapes of the given type.
+pub fn apply_shader_params<T: ShaderParamsShape>(
+    mut shapes: Query<(&mut T, &ShapeShaderParams), Changed<ShapeShaderParams>>,
+) {
+    for (mut shape, params) in &mut shapes {
+        shape.set_user_data(params.0);
+    }
+}
+
+/// Plugin that applies [`ShapeShaderParams`] to the built-in shape types, added by [`Shape2dPlugin`].
+pub struct ShapeShaderParamsPlugin;
+
+impl Plugin for ShapeShaderParamsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                apply_shader_params::<Line>,
+                apply_shader_params::<QuadBezier>,
+                apply_shader_params::<Disc>,
+                apply_shader_params::<Rectangle>,
+                apply_shader_params::<RegularPolygon>,
+                apply_shader_params::<ShapeMorph>,
+            )
+                .in_base_set(CoreSet::PostUpdate),
+        );
+    }
+}
+
+impl ShaderParamsShape for Line {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
+
+impl ShaderParamsShape for QuadBezier {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
+
+impl ShaderParamsShape for Disc {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
+
+impl ShaderParamsShape for Rectangle {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
+
+impl ShaderParamsShape for RegularPolygon {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
+
+impl ShaderParamsShape for ShapeMorph {
+    fn set_user_data(&mut self, user_data: Vec4) {
+        self.common.user_data = user_data;
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashSet};
+
+use crate::prelude::*;
+
+/// Asset containing a set of properties shared by any number of retained shapes.
+///
+/// Shape entities reference a style by inserting a [`Handle<ShapeStyle>`], each property that is set overrides
+/// the corresponding property of the shape whenever the handle is inserted or the asset is modified,
+/// allowing every shape referencing the style to be restyled at once.
+/// Properties a shape type doesn't support, such as caps on rectangles, are ignored.
+#[derive(TypeUuid, Clone, Debug, Default, PartialEq)]
+#[uuid = "9b3f6e2a-41c8-4d57-a0e3-6c1d8f2b75e4"]
+pub struct ShapeStyle {
+    pub color: Option<Color>,
+    pub thickness: Option<f32>,
+    pub thickness_type: Option<ThicknessType>,
+    pub hollow: Option<bool>,
+    pub cap: Option<Cap>,
+}
+
+impl ShapeStyle {
+    /// Applies the style to the given config, so that immediate mode shapes can share styles with retained shapes.
+    pub fn apply_to_config(&self, config: &mut ShapeConfig) {
+        if let Some(color) = self.color {
+            config.color = color;
+        }
+        if let Some(thickness) = self.thickness {
+            config.thickness = thickness;
+        }
+        if let Some(thickness_type) = self.thickness_type {
+            config.thickness_type = thickness_type;
+        }
+        if let Some(hollow) = self.hollow {
+            config.hollow = hollow;
+        }
+        if let Some(cap) = self.cap {
+            config.cap = cap;
+        }
+    }
+}
+
+/// Trait implemented by shape components that can be styled by a [`ShapeStyle`].
+pub trait StyledShape: Component {
+    fn apply_style(&mut self, style: &ShapeStyle);
+}
+
+/// Applies each [`ShapeStyle`] to the shapes of the given type referencing it when the handle is inserted or the style is modified.
+pub fn apply_shape_styles<T: StyledShape>(
+    mut events: EventReader<AssetEvent<ShapeStyle>>,
+    styles: Res<Assets<ShapeStyle>>,
+    mut shapes: Query<(&mut T, Ref<Handle<ShapeStyle>>)>,
+) {
+    let modified: HashSet<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle.id()),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (mut shape, handle) in &mut shapes {
+        if !handle.is_changed() && !modified.contains(&handle.id()) {
+            continue;
+        }
+        if let Some(style) = styles.get(&*handle) {
+            shape.apply_style(style);
+        }
+    }
+}
+
+/// Plugin that sets up [`ShapeStyle`] assets for the built-in shape types, added by [`Shape2dPlugin`].
+pub struct ShapeStylePlugin;
+
+impl Plugin for ShapeStylePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ShapeStyle>().add_systems((
+            apply_shape_styles::<Line>,
+            apply_shape_styles::<QuadBezier>,
+            apply_shape_styles::<Disc>,
+            apply_shape_styles::<Rectangle>,
+            apply_shape_styles::<RegularPolygon>,
+            apply_shape_styles::<ShapeMorph>,
+        ));
+    }
+}
+
+impl StyledShape for Line {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.cap = style.cap.unwrap_or(self.cap);
+    }
+}
+
+impl StyledShape for QuadBezier {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.cap = style.cap.unwrap_or(self.cap);
+    }
+}
+
+impl StyledShape for Disc {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.hollow = style.hollow.unwrap_or(self.hollow);
+        self.cap = style.cap.unwrap_or(self.cap);
+    }
+}
+
+impl StyledShape for Rectangle {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.hollow = style.hollow.unwrap_or(self.hollow);
+    }
+}
+
+impl StyledShape for RegularPolygon {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.hollow = style.hollow.unwrap_or(self.hollow);
+    }
+}
+
+impl StyledShape for ShapeMorph {
+    fn apply_style(&mut self, style: &ShapeStyle) {
+        self.color = style.color.unwrap_or(self.color);
+        self.thickness = style.thickness.unwrap_or(self.thickness);
+        self.thickness_type = style.thickness_type.unwrap_or(self.thickness_type);
+        self.hollow = style.hollow.unwrap_or(self.hollow);
+    }
+}
@@ -0,0 +1,561 @@
+use std::{
+    f32::consts::{PI, TAU},
+    fmt::Write,
+    path::PathBuf,
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*, render::view::RenderLayers};
+
+use crate::{prelude::*, render::ShapeData};
+
+/// Trait implemented by shape components that can be exported as an SVG element.
+///
+/// Elements are in the shape's local space with y pointing up, see [`ShapeSvgExporter`] for exporting entities.
+/// Thickness is always interpreted in world units and intensity is ignored, morphs are exported as the
+/// target they are closest to and rounded hollow polygons are approximated.
+pub trait SvgShape {
+    fn to_svg_element(&self) -> String;
+}
+
+/// Color attributes for filling or stroking an element.
+fn paint(attribute: &str, color: Color) -> String {
+    let [r, g, b, a] = color.as_rgba_f32();
+    let [r, g, b] = [r, g, b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!(r#"{attribute}="rgb({r},{g},{b})" {attribute}-opacity="{a}""#)
+}
+
+/// Attributes for drawing the outline of a shape, inset by half the thickness as in the shaders.
+fn stroke(color: Color, thickness: f32) -> String {
+    format!(
+        r#"fill="none" {} stroke-width="{thickness}""#,
+        paint("stroke", color)
+    )
+}
+
+fn linecap(cap: Cap) -> &'static str {
+    match cap {
+        Cap::None => "butt",
+        Cap::Square => "square",
+        Cap::Round => "round",
+    }
+}
+
+/// Point on a disc of the given radius at an angle measured clockwise from positive y, as used by arcs.
+pub(crate) fn clock_point(angle: f32, radius: f32) -> Vec2 {
+    Vec2::new(angle.sin(), angle.cos()) * radius
+}
+
+impl SvgShape for Line {
+    fn to_svg_element(&self) -> String {
+        format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-width="{}" stroke-linecap="{}"/>"#,
+            self.start.x,
+            self.start.y,
+            self.end.x,
+            self.end.y,
+            paint("stroke", self.color),
+            self.thickness,
+            linecap(self.cap)
+        )
+    }
+}
+
+impl SvgShape for QuadBezier {
+    fn to_svg_element(&self) -> String {
+        format!(
+            r#"<path d="M {} {} Q {} {} {} {}" fill="none" {} stroke-width="{}" stroke-linecap="{}"/>"#,
+            self.start.x,
+            self.start.y,
+            self.control.x,
+            self.control.y,
+            self.end.x,
+            self.end.y,
+            paint("stroke", self.color),
+            self.thickness,
+            linecap(self.cap)
+        )
+    }
+}
+
+impl SvgShape for Disc {
+    fn to_svg_element(&self) -> String {
+        let sweep = self.end_angle - self.start_angle;
+        if !self.arc || sweep >= TAU {
+            return if self.hollow {
+                format!(
+                    r#"<circle r="{}" {}/>"#,
+                    (self.radius - self.thickness / 2.0).max(0.0),
+                    stroke(self.color, self.thickness)
+                )
+            } else {
+                format!(
+                    r#"<circle r="{}" {}/>"#,
+                    self.radius,
+                    paint("fill", self.color)
+                )
+            };
+        }
+
+        // Angles increase clockwise, which is the negative direction with y pointing up
+        let large_arc = (sweep > PI) as u8;
+        if self.hollow {
+            let radius = (self.radius - self.thickness / 2.0).max(0.0);
+            let start = clock_point(self.start_angle, radius);
+            let end = clock_point(self.end_angle, radius);
+            format!(
+                r#"<path d="M {} {} A {radius} {radius} 0 {large_arc} 0 {} {}" {} stroke-linecap="{}"/>"#,
+                start.x,
+                start.y,
+                end.x,
+                end.y,
+                stroke(self.color, self.thickness),
+                linecap(self.cap)
+            )
+        } else {
+            let radius = self.radius;
+            let start = clock_point(self.start_angle, radius);
+            let end = clock_point(self.end_angle, radius);
+            format!(
+                r#"<path d="M 0 0 L {} {} A {radius} {radius} 0 {large_arc} 0 {} {} Z" {}/>"#,
+                start.x,
+                start.y,
+                end.x,
+                end.y,
+                paint("fill", self.color)
+            )
+        }
+    }
+}
+
+impl SvgShape for Rectangle {
+    fn to_svg_element(&self) -> String {
+        let inset = if self.hollow {
+            self.thickness / 2.0
+        } else {
+            0.0
+        };
+        let half = (self.size / 2.0 - inset).max(Vec2::ZERO);
+        let max_radius = half.x.min(half.y);
+        // Corner radii are ordered top right, top left, bottom left, bottom right in the shader's space, whose y is flipped
+        let [br, bl, tl, tr] = self
+            .corner_radii
+            .to_array()
+            .map(|radius| (radius - inset).clamp(0.0, max_radius));
+
+        let mut d = String::new();
+        let _ = write!(d, "M {} {}", -half.x + bl, -half.y);
+        let _ = write!(d, " L {} {}", half.x - br, -half.y);
+        let _ = write!(d, " A {br} {br} 0 0 1 {} {}", half.x, -half.y + br);
+        let _ = write!(d, " L {} {}", half.x, half.y - tr);
+        let _ = write!(d, " A {tr} {tr} 0 0 1 {} {}", half.x - tr, half.y);
+        let _ = write!(d, " L {} {}", -half.x + tl, half.y);
+        let _ = write!(d, " A {tl} {tl} 0 0 1 {} {}", -half.x, half.y - tl);
+        let _ = write!(d, " L {} {}", -half.x, -half.y + bl);
+        let _ = write!(d, " A {bl} {bl} 0 0 1 {} {} Z", -half.x + bl, -half.y);
+
+        let paint = if self.hollow {
+            stroke(self.color, self.thickness)
+        } else {
+            paint("fill", self.color)
+        };
+        format!(r#"<path d="{d}" {paint}/>"#)
+    }
+}
+
+impl SvgShape for RegularPolygon {
+    fn to_svg_element(&self) -> String {
+        let sides = self.sides.max(3.0);
+        let central_angle = TAU / sides;
+        let apothem = (central_angle / 2.0).cos() * self.radius;
+        let rounding = self.roundness.clamp(0.0, apothem);
+        // Rounded polygons are the polygon shrunk by the rounding radius then outlined with round joins
+        let inset = if self.hollow {
+            self.thickness / 2.0
+        } else {
+            rounding
+        };
+        let radius = (self.radius * (apothem - inset) / apothem).max(0.0);
+        let points = (0..sides as u32)
+            .map(|i| {
+                // Edges are centered on positive y in the shader's space, whose y is flipped, so vertices are offset by half a side
+                let angle = (i as f32 + 0.5) * central_angle;
+                let vertex = Vec2::new(angle.sin(), -angle.cos()) * radius;
+                format!("{},{}", vertex.x, vertex.y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if self.hollow {
+            let join = if rounding > 0.0 { "round" } else { "miter" };
+            format!(
+                r#"<polygon points="{points}" {} stroke-linejoin="{join}"/>"#,
+                stroke(self.color, self.thickness)
+            )
+        } else if rounding > 0.0 {
+            format!(
+                r#"<polygon points="{points}" {} {} stroke-width="{}" stroke-linejoin="round"/>"#,
+                paint("fill", self.color),
+                paint("stroke", self.color),
+                rounding * 2.0
+            )
+        } else {
+            format!(
+                r#"<polygon points="{points}" {}/>"#,
+                paint("fill", self.color)
+            )
+        }
+    }
+}
+
+impl SvgShape for ShapeMorph {
+    fn to_svg_element(&self) -> String {
+        let target = if self.progress < 0.5 {
+            self.from
+        } else {
+            self.to
+        };
+        match target {
+            MorphTarget::Circle { radius } => Disc {
+                color: self.color,
+                thickness: self.thickness,
+                hollow: self.hollow,
+                radius,
+                ..default()
+            }
+            .to_svg_element(),
+            MorphTarget::Rectangle {
+                size,
+                corner_radius,
+            } => Rectangle {
+                color: self.color,
+                thickness: self.thickness,
+                hollow: self.hollow,
+                size,
+                corner_radii: Vec4::splat(corner_radius),
+                ..default()
+            }
+            .to_svg_element(),
+            MorphTarget::Ngon {
+                sides,
+                radius,
+                roundness,
+            } => RegularPolygon {
+                color: self.color,
+                thickness: self.thickness,
+                hollow: self.hollow,
+                sides,
+                radius,
+                roundness,
+                ..default()
+            }
+            .to_svg_element(),
+        }
+    }
+}
+
+impl SvgShape for Triangle {
+    fn to_svg_element(&self) -> String {
+        let [a, b, c] = self.points;
+        format!(
+            r#"<polygon points="{},{} {},{} {},{}" {}/>"#,
+            a.x,
+            a.y,
+            b.x,
+            b.y,
+            c.x,
+            c.y,
+            paint("fill", self.color)
+        )
+    }
+}
+
+/// Exports a recorded painter call along with the transform it was drawn with.
+fn painted_element(command: &PainterCommand) -> (Mat4, String) {
+    let config = &command.config;
+    match &command.shape {
+        PaintedShape::Line(data) => (data.transform(), data.component(config).to_svg_element()),
+        PaintedShape::QuadBezier(data) => {
+            (data.transform(), data.component(config).to_svg_element())
+        }
+        PaintedShape::Disc(data) => (data.transform(), data.component(config).to_svg_element()),
+        PaintedShape::Rectangle(data) => {
+            (data.transform(), data.component(config).to_svg_element())
+        }
+        PaintedShape::RegularPolygon(data) => {
+            (data.transform(), data.component(config).to_svg_element())
+        }
+        PaintedShape::Morph(data) => (data.transform(), data.component(config).to_svg_element()),
+        PaintedShape::Triangle(data) => (data.transform(), data.component(config).to_svg_element()),
+    }
+}
+
+/// Builds an SVG document covering the given rect of the world xy plane from elements and their transforms.
+fn svg_document(view: Rect, mut elements: Vec<(Mat4, String)>) -> String {
+    elements.sort_by(|(a, _), (b, _)| a.w_axis.z.total_cmp(&b.w_axis.z));
+
+    let size = view.size();
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        view.min.x, -view.max.y, size.x, size.y, size.x, size.y
+    );
+    svg.push('\n');
+    for (matrix, element) in elements {
+        // SVG's y points down, so flip the shape's transform vertically
+        let (x, y, t) = (matrix.x_axis, matrix.y_axis, matrix.w_axis);
+        let _ = writeln!(
+            svg,
+            r#"  <g transform="matrix({} {} {} {} {} {})">{element}</g>"#,
+            x.x, -x.y, y.x, -y.y, t.x, -t.y
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// System param that exports retained shapes to SVG documents.
+///
+/// Shapes are projected onto the world xy plane as seen by a 2D camera and ordered by their z translation,
+/// hidden shapes are skipped.
+#[derive(SystemParam)]
+pub struct ShapeSvgExporter<'w, 's> {
+    lines: Query<'w, 's, &'static Line>,
+    beziers: Query<'w, 's, &'static QuadBezier>,
+    discs: Query<'w, 's, &'static Disc>,
+    rectangles: Query<'w, 's, &'static Rectangle>,
+    polygons: Query<'w, 's, &'static RegularPolygon>,
+    morphs: Query<'w, 's, &'static ShapeMorph>,
+    triangles: Query<'w, 's, &'static Triangle>,
+    shapes: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static GlobalTransform,
+            &'static ComputedVisibility,
+            Option<&'static ShapeMaterial>,
+        ),
+    >,
+    canvases: Query<'w, 's, (&'static Canvas, &'static GlobalTransform)>,
+}
+
+impl<'w, 's> ShapeSvgExporter<'w, 's> {
+    fn element(&self, entity: Entity) -> Option<String> {
+        self.lines
+            .get(entity)
+            .ok()
+            .map(SvgShape::to_svg_element)
+            .or_else(|| self.beziers.get(entity).ok().map(SvgShape::to_svg_element))
+            .or_else(|| self.discs.get(entity).ok().map(SvgShape::to_svg_element))
+            .or_else(|| {
+                self.rectangles
+                    .get(entity)
+                    .ok()
+                    .map(SvgShape::to_svg_element)
+            })
+            .or_else(|| self.polygons.get(entity).ok().map(SvgShape::to_svg_element))
+            .or_else(|| self.morphs.get(entity).ok().map(SvgShape::to_svg_element))
+            .or_else(|| {
+                self.triangles
+                    .get(entity)
+                    .ok()
+                    .map(SvgShape::to_svg_element)
+            })
+    }
+
+    /// Exports the given shape entities to an SVG document covering the given rect of the world xy plane.
+    pub fn export(&self, entities: impl IntoIterator<Item = Entity>, view: Rect) -> String {
+        let elements = entities
+            .into_iter()
+            .filter_map(|entity| self.shapes.get(entity).ok())
+            .filter(|(_, _, visibility, _)| visibility.is_visible_in_hierarchy())
+            .filter_map(|(entity, tf, ..)| Some((tf.compute_matrix(), self.element(entity)?)))
+            .collect();
+        svg_document(view, elements)
+    }
+
+    /// Exports every shape drawn to the given [`Canvas`] to an SVG document the size of the canvas.
+    pub fn export_canvas(&self, canvas: Entity) -> Option<String> {
+        let (config, tf) = self.canvases.get(canvas).ok()?;
+        let size = Vec2::new(config.width as f32, config.height as f32);
+        let view = Rect::from_center_size(tf.translation().truncate(), size);
+        let entities = self
+            .shapes
+            .iter()
+            .filter(|(_, _, _, material)| {
+                material.and_then(|material| material.canvas) == Some(canvas)
+            })
+            .map(|(entity, ..)| entity);
+        Some(self.export(entities, view))
+    }
+}
+
+/// Pending capture of the shapes drawn to a camera during a single frame.
+struct SvgFrameRequest {
+    camera: Entity,
+    path: Option<PathBuf>,
+}
+
+/// Resource that captures a single frame of [`ShapePainter`] output as SVG documents, inserted by [`Shape2dPlugin`].
+///
+/// Captures requested during a frame record every shape drawn by a painter over the whole of the next frame,
+/// each capture is sent as a [`SvgFrameCaptured`] event at the end of that frame and optionally written to a file.
+/// Intended for exporting data visualizations as vector graphics for print, see [`SvgShape`] for the limitations of the export.
+#[derive(Resource, Default)]
+pub struct ShapeSvgCapture {
+    requests: Vec<SvgFrameRequest>,
+    capturing: Vec<SvgFrameRequest>,
+    commands: Vec<PainterCommand>,
+}
+
+impl ShapeSvgCapture {
+    /// Captures the shapes drawn to the given camera or [`Canvas`] in the next frame.
+    ///
+    /// The document covers the area seen by the camera's [`OrthographicProjection`], cameras with other projections are skipped.
+    pub fn capture(&mut self, camera: Entity) {
+        self.requests.push(SvgFrameRequest { camera, path: None });
+    }
+
+    /// Captures the shapes drawn to the given camera or [`Canvas`] in the next frame and writes the document to the given path.
+    pub fn capture_to_file(&mut self, camera: Entity, path: impl Into<PathBuf>) {
+        self.requests.push(SvgFrameRequest {
+            camera,
+            path: Some(path.into()),
+        });
+    }
+
+    /// Whether painter calls are being recorded for a capture this frame.
+    pub fn is_capturing(&self) -> bool {
+        !self.capturing.is_empty()
+    }
+
+    /// Records the given shape if capturing.
+    pub fn record<T: ShapeData>(&mut self, config: &ShapeConfig, data: &T) {
+        if !self.is_capturing() {
+            return;
+        }
+        if let Some(shape) = PaintedShape::from_data(data) {
+            self.commands.push(PainterCommand {
+                config: config.clone(),
+                shape,
+            });
+        }
+    }
+
+    /// Records a previously recorded command if capturing, used when replaying commands.
+    pub fn record_command(&mut self, command: &PainterCommand) {
+        if self.is_capturing() {
+            self.commands.push(command.clone());
+        }
+    }
+}
+
+/// Event sent at the end of the frame captured by [`ShapeSvgCapture`].
+pub struct SvgFrameCaptured {
+    /// Camera or [`Canvas`] the shapes were drawn to.
+    pub camera: Entity,
+    /// SVG document containing the captured shapes.
+    pub svg: String,
+}
+
+/// Starts recording the captures requested during the previous frame.
+pub fn begin_svg_captures(mut capture: ResMut<ShapeSvgCapture>) {
+    let capture = &mut *capture;
+    capture.commands.clear();
+    capture.capturing = std::mem::take(&mut capture.requests);
+}
+
+/// Exports the shapes recorded for each capture, only including shapes the camera would draw.
+pub fn finish_svg_captures(
+    mut capture: ResMut<ShapeSvgCapture>,
+    cameras: Query<(
+        &GlobalTransform,
+        Option<&OrthographicProjection>,
+        Option<&RenderLayers>,
+    )>,
+    mut events: EventWriter<SvgFrameCaptured>,
+) {
+    if !capture.is_capturing() {
+        return;
+    }
+    let commands = std::mem::take(&mut capture.commands);
+    for SvgFrameRequest { camera, path } in std::mem::take(&mut capture.capturing) {
+        let Ok((tf, Some(projection), render_layers)) = cameras.get(camera) else {
+            warn!("Skipping SVG capture of {camera:?}, only entities with an orthographic camera can be captured");
+            continue;
+        };
+        let center = tf.translation().truncate();
+        let view = Rect::from_corners(projection.area.min + center, projection.area.max + center);
+
+        // Mirror the filtering used when queueing shapes for each view
+        let elements = commands
+            .iter()
+            .filter(|command| match command.config.canvas {
+                Some(canvas) => canvas == camera,
+                None => {
+                    let layers = command.config.render_layers.unwrap_or_default();
+                    render_layers.map_or(true, |render_layers| render_layers.intersects(&layers))
+                        && (command.config.visible_to.is_empty()
+                            || command.config.visible_to.contains(&camera))
+                }
+            })
+            .map(painted_element)
+            .collect();
+        let svg = svg_document(view, elements);
+
+        if let Some(path) = path {
+            if let Err(error) = std::fs::write(&path, &svg) {
+                error!("Failed to write SVG capture to {}: {error}", path.display());
+            }
+        }
+        events.send(SvgFrameCaptured { camera, svg });
+    }
+}
+
+/// Plugin that sets up [`ShapeSvgCapture`], added by [`Shape2dPlugin`].
+pub struct ShapeSvgCapturePlugin;
+
+impl Plugin for ShapeSvgCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeSvgCapture>()
+            .add_event::<SvgFrameCaptured>()
+            .add_system(begin_svg_captures.in_base_set(CoreSet::First))
+            .add_system(finish_svg_captures.in_base_set(CoreSet::Last));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon_points(element: &str) -> Vec<Vec2> {
+        let start = element.find("points=\"").unwrap() + "points=\"".len();
+        let end = start + element[start..].find('"').unwrap();
+        element[start..end]
+            .split(' ')
+            .map(|point| {
+                let (x, y) = point.split_once(',').unwrap();
+                Vec2::new(x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn odd_sided_polygon_points_up() {
+        let element = RegularPolygon {
+            sides: 3.0,
+            radius: 2.0,
+            ..default()
+        }
+        .to_svg_element();
+        let points = polygon_points(&element);
+        assert_eq!(points.len(), 3);
+
+        // A single vertex at the top and a flat edge along the bottom, as drawn by the shader
+        let top = points
+            .iter()
+            .filter(|p| p.distance(Vec2::new(0.0, 2.0)) < 1e-4);
+        assert_eq!(top.count(), 1);
+        let bottom = points.iter().filter(|p| (p.y + 1.0).abs() < 1e-4);
+        assert_eq!(bottom.count(), 2);
+    }
+}
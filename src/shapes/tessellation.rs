@@ -0,0 +1,243 @@
+use bevy::{
+    prelude::*,
+    render::{view::RenderLayers, Extract, RenderApp},
+    utils::HashMap,
+};
+use lyon::{
+    path::Path,
+    tessellation::{
+        BuffersBuilder, FillOptions, FillRule, FillTessellator, FillVertex, LineCap, StrokeOptions,
+        StrokeTessellator, StrokeVertex, TessellationError, VertexBuffers,
+    },
+};
+
+use crate::{
+    prelude::*,
+    render::{is_shape_visible, retained_data, ExtractedShapes, ShapePipelineMaterial},
+};
+
+/// Tessellates the given path into triangles using the color, thickness, cap and hollow properties of the config.
+///
+/// Filled paths are tessellated with the non-zero fill rule, hollow paths are stroked with a line width of the config's thickness in world units.
+/// Edges shared between triangles are not anti-aliased so that the triangles meet without seams.
+pub fn tessellate_path(
+    config: &ShapeConfig,
+    path: &Path,
+    tolerance: f32,
+) -> Result<Vec<Triangle>, TessellationError> {
+    let mut buffers: VertexBuffers<Vec2, u32> = VertexBuffers::new();
+    if config.hollow {
+        let cap = match config.cap {
+            Cap::None => LineCap::Butt,
+            Cap::Square => LineCap::Square,
+            Cap::Round => LineCap::Round,
+        };
+        let options = StrokeOptions::tolerance(tolerance)
+            .with_line_width(config.thickness)
+            .with_line_cap(cap);
+        StrokeTessellator::new().tessellate_path(
+            path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| {
+                Vec2::from_array(vertex.position().to_array())
+            }),
+        )?;
+    } else {
+        FillTessellator::new().tessellate_path(
+            path,
+            &FillOptions::tolerance(tolerance).with_fill_rule(FillRule::NonZero),
+            &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| {
+                Vec2::from_array(vertex.position().to_array())
+            }),
+        )?;
+    }
+
+    // Edges used by a single triangle lie on the outline of the path and are the only ones anti-aliased
+    let edge = |a: u32, b: u32| (a.min(b), a.max(b));
+    let mut edge_counts = HashMap::new();
+    for indices in buffers.indices.chunks_exact(3) {
+        for i in 0..3 {
+            *edge_counts
+                .entry(edge(indices[i], indices[(i + 1) % 3]))
+                .or_insert(0) += 1;
+        }
+    }
+
+    Ok(buffers
+        .indices
+        .chunks_exact(3)
+        .map(|indices| {
+            let mut triangle = Triangle::new(
+                config,
+                buffers.vertices[indices[0] as usize],
+                buffers.vertices[indices[1] as usize],
+                buffers.vertices[indices[2] as usize],
+            );
+            triangle.aa_edges = (0..3)
+                .filter(|&i| edge_counts[&edge(indices[i], indices[(i + 1) % 3])] == 1)
+                .fold(0, |edges, i| edges | 1 << i);
+            triangle
+        })
+        .collect())
+}
+
+/// Component containing a [`lyon`] path tessellated into triangles, requires the `lyon` feature.
+///
+/// Covers outlines the analytic shapes can't such as arbitrary curves, glyphs and self intersecting polygons.
+/// The path is only tessellated when the component is created, the resulting triangles can be restyled through [`TessellatedPath::triangles_mut`].
+#[derive(Component, Clone)]
+pub struct TessellatedPath {
+    path: Path,
+    triangles: Vec<Triangle>,
+}
+
+impl TessellatedPath {
+    /// Maximum distance in world units between curves of the path and the triangles approximating them.
+    pub const DEFAULT_TOLERANCE: f32 = FillOptions::DEFAULT_TOLERANCE;
+
+    pub fn new(config: &ShapeConfig, path: Path) -> Self {
+        Self::with_tolerance(config, path, Self::DEFAULT_TOLERANCE)
+    }
+
+    /// Tessellates the path with the given tolerance, see [`TessellatedPath::DEFAULT_TOLERANCE`].
+    ///
+    /// Paths that fail to tessellate are logged and left empty.
+    pub fn with_tolerance(config: &ShapeConfig, path: Path, tolerance: f32) -> Self {
+        let triangles = tessellate_path(config, &path, tolerance).unwrap_or_else(|error| {
+            warn!("Failed to tessellate path: {error:?}");
+            Vec::new()
+        });
+        Self { path, triangles }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        &self.triangles
+    }
+
+    pub fn triangles_mut(&mut self) -> &mut [Triangle] {
+        &mut self.triangles
+    }
+}
+
+/// Bundle of the components needed to draw a retained [`TessellatedPath`].
+#[derive(Bundle)]
+pub struct TessellatedPathBundle {
+    pub spatial_bundle: SpatialBundle,
+    pub shape: ShapeMaterial,
+    pub render_layers: RenderLayers,
+    pub path: TessellatedPath,
+}
+
+impl TessellatedPathBundle {
+    pub fn new(config: &ShapeConfig, path: Path) -> Self {
+        Self {
+            spatial_bundle: SpatialBundle::from_transform(config.transform),
+            shape: ShapeMaterial::from(config),
+            render_layers: config.render_layers.unwrap_or_default(),
+            path: TessellatedPath::new(config, path),
+        }
+    }
+
+    /// Inserts the [`Shape3d`] marker component so that the entity is picked up by the associated pipeline.
+    pub fn insert_3d(self) -> (Self, Shape3d) {
+        (self, Shape3d)
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to enable it to draw [`lyon`] paths.
+pub trait LyonPathPainter {
+    fn lyon_path(&mut self, path: &Path) -> &mut Self;
+}
+
+impl<'w, 's> LyonPathPainter for ShapePainter<'w, 's> {
+    fn lyon_path(&mut self, path: &Path) -> &mut Self {
+        let config = self.config().clone();
+        match tessellate_path(&config, path, TessellatedPath::DEFAULT_TOLERANCE) {
+            Ok(triangles) => {
+                for triangle in triangles {
+                    self.send(TriangleData::with_edges(
+                        &config,
+                        triangle.points,
+                        triangle.aa_edges,
+                    ));
+                }
+            }
+            Err(error) => warn!("Failed to tessellate path: {error:?}"),
+        }
+        self
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to enable spawning of [`TessellatedPath`] entities.
+pub trait LyonPathSpawner<'w, 's> {
+    fn lyon_path(&mut self, path: &Path) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> LyonPathSpawner<'w, 's> for T {
+    fn lyon_path(&mut self, path: &Path) -> ShapeEntityCommands<'w, 's, '_> {
+        self.spawn_shape(TessellatedPathBundle::new(self.config(), path.clone()))
+    }
+}
+
+/// Extracts the triangles of each visible [`TessellatedPath`] into the triangle pipelines.
+pub fn extract_tessellated_paths(
+    mut commands: Commands,
+    paths: Extract<
+        Query<(
+            &TessellatedPath,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&ShapeMaterial>,
+            Option<&RenderLayers>,
+            Option<&ComputedOpacity>,
+            Option<&PaintLayer>,
+            Option<&Shape3d>,
+        )>,
+    >,
+    layers: Extract<Res<ShapeLayers>>,
+) {
+    let mut instances_2d = Vec::new();
+    let mut instances_3d = Vec::new();
+    for (path, tf, vis, flags, rl, opacity, layer, shape_3d) in &paths {
+        if !is_shape_visible(vis, flags) {
+            continue;
+        }
+        let material = ShapePipelineMaterial::new(flags, rl);
+        let instances = match shape_3d {
+            Some(_) => &mut instances_3d,
+            None => &mut instances_2d,
+        };
+        for triangle in &path.triangles {
+            let mut data = retained_data(triangle, tf, opacity);
+            if let Some(layer) = layer {
+                if !layers.apply(&layer.0, &mut data) {
+                    break;
+                }
+            }
+            instances.push((material.clone(), data));
+        }
+    }
+
+    if !instances_2d.is_empty() {
+        commands.spawn(ExtractedShapes::<TriangleData>(instances_2d));
+    }
+    if !instances_3d.is_empty() {
+        commands.spawn((ExtractedShapes::<TriangleData>(instances_3d), Shape3d));
+    }
+}
+
+/// Plugin that draws retained [`TessellatedPath`]s, added by [`Shape2dPlugin`] when the `lyon` feature is enabled.
+pub struct TessellatedPathPlugin;
+
+impl Plugin for TessellatedPathPlugin {
+    fn build(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_system(extract_tessellated_paths.in_schedule(ExtractSchedule));
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::{prelude::*, utils::HashMap};
+use cosmic_text::{
+    Align, Attrs, AttrsList, Buffer, BufferLine, CacheKey, Color as TextColor, Command, Family,
+    FontSystem, Metrics, Style as FontStyle, SwashCache, Weight,
+};
+use lyon::{geom::point, path::Path};
+
+use crate::prelude::*;
+
+/// Style of a span of rich text drawn with [`RichTextPainter::text_rich`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RichTextStyle {
+    /// Name of the font family, the default sans serif family is used if unset.
+    pub family: Option<String>,
+    /// Font weight, 400 is regular and 700 is bold.
+    pub weight: u16,
+    pub italic: bool,
+    /// Color of the span, the painter's color is used if unset.
+    pub color: Option<Color>,
+}
+
+impl Default for RichTextStyle {
+    fn default() -> Self {
+        Self {
+            family: None,
+            weight: 400,
+            italic: false,
+            color: None,
+        }
+    }
+}
+
+impl RichTextStyle {
+    fn attrs(&self) -> Attrs {
+        let mut attrs = Attrs::new()
+            .weight(Weight(self.weight))
+            .style(match self.italic {
+                true => FontStyle::Italic,
+                false => FontStyle::Normal,
+            });
+        if let Some(family) = &self.family {
+            attrs = attrs.family(Family::Name(family));
+        }
+        if let Some(color) = self.color {
+            let [r, g, b, a] = color.as_rgba_u8();
+            attrs = attrs.color(TextColor::rgba(r, g, b, a));
+        }
+        attrs
+    }
+}
+
+/// Horizontal alignment of each line of rich text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RichTextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches wrapped lines to fill the width of the text.
+    Justified,
+}
+
+/// Layout of rich text drawn with [`RichTextPainter::text_rich`], lengths are in world units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RichTextLayout {
+    pub font_size: f32,
+    /// Distance between the baselines of consecutive lines.
+    pub line_height: f32,
+    /// Width to wrap lines to, lines are only broken at newlines if unset.
+    pub width: Option<f32>,
+    pub align: RichTextAlign,
+}
+
+impl Default for RichTextLayout {
+    fn default() -> Self {
+        Self {
+            font_size: 16.0,
+            line_height: 20.0,
+            width: None,
+            align: default(),
+        }
+    }
+}
+
+/// Triangles of a glyph's outline along with their anti-aliased edges.
+type GlyphTriangles = Vec<([Vec2; 3], u32)>;
+
+/// Resource containing the fonts used to shape rich text and a cache of tessellated glyphs, requires the `text` feature.
+///
+/// Initialized with the system's fonts by [`ShapeTextPlugin`], further fonts can be loaded with [`ShapeFonts::load_font_data`].
+#[derive(Resource)]
+pub struct ShapeFonts {
+    font_system: FontSystem,
+    swash_cache: SwashCache,
+    glyphs: HashMap<CacheKey, GlyphTriangles>,
+}
+
+impl Default for ShapeFonts {
+    fn default() -> Self {
+        Self {
+            font_system: FontSystem::new(),
+            swash_cache: SwashCache::new(),
+            glyphs: HashMap::default(),
+        }
+    }
+}
+
+impl ShapeFonts {
+    /// Loads a font from the contents of a TrueType or OpenType file.
+    pub fn load_font_data(&mut self, data: Vec<u8>) {
+        self.font_system.db_mut().load_font_data(data);
+    }
+
+    pub fn font_system_mut(&mut self) -> &mut FontSystem {
+        &mut self.font_system
+    }
+
+    /// Shapes and lays out the given spans, splitting them into lines at each newline.
+    fn layout(&mut self, spans: &[(&str, RichTextStyle)], layout: &RichTextLayout) -> Buffer {
+        let align = match layout.align {
+            RichTextAlign::Left => Align::Left,
+            RichTextAlign::Center => Align::Center,
+            RichTextAlign::Right => Align::Right,
+            RichTextAlign::Justified => Align::Justified,
+        };
+
+        let mut lines = vec![(String::new(), AttrsList::new(Attrs::new()))];
+        for (text, style) in spans {
+            let attrs = style.attrs();
+            for (i, part) in text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push((String::new(), AttrsList::new(Attrs::new())));
+                }
+                let (line, attrs_list) = lines.last_mut().unwrap();
+                let start = line.len();
+                line.push_str(part);
+                attrs_list.add_span(start..line.len(), attrs);
+            }
+        }
+
+        let metrics = Metrics::new(layout.font_size, layout.line_height);
+        let mut buffer = Buffer::new(&mut self.font_system, metrics);
+        buffer.set_size(
+            &mut self.font_system,
+            layout.width.unwrap_or(f32::MAX),
+            f32::MAX,
+        );
+        buffer.lines = lines
+            .into_iter()
+            .map(|(text, attrs_list)| {
+                let mut line = BufferLine::new(text, attrs_list);
+                line.set_align(Some(align));
+                line
+            })
+            .collect();
+        buffer.shape_until_scroll(&mut self.font_system);
+        buffer
+    }
+
+    /// Triangles of the given glyph in it's local space with y pointing up, tessellated on first use.
+    fn glyph_triangles(&mut self, key: CacheKey) -> &GlyphTriangles {
+        let Self {
+            font_system,
+            swash_cache,
+            glyphs,
+        } = self;
+        glyphs.entry(key).or_insert_with(|| {
+            let Some(commands) = swash_cache.get_outline_commands(font_system, key) else {
+                return Vec::new();
+            };
+            let path = outline_path(commands);
+            tessellate_path(
+                &ShapeConfig::default_2d(),
+                &path,
+                TessellatedPath::DEFAULT_TOLERANCE,
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .map(|triangle| (triangle.points, triangle.aa_edges))
+            .collect()
+        })
+    }
+}
+
+/// Converts the outline of a glyph to a lyon path.
+fn outline_path(commands: &[Command]) -> Path {
+    let mut builder = Path::builder();
+    let mut open = false;
+    for command in commands {
+        match *command {
+            Command::MoveTo(p) => {
+                if open {
+                    builder.end(false);
+                }
+                builder.begin(point(p.x, p.y));
+                open = true;
+            }
+            Command::LineTo(p) => {
+                builder.line_to(point(p.x, p.y));
+            }
+            Command::QuadTo(control, p) => {
+                builder.quadratic_bezier_to(point(control.x, control.y), point(p.x, p.y));
+            }
+            Command::CurveTo(control_1, control_2, p) => {
+                builder.cubic_bezier_to(
+                    point(control_1.x, control_1.y),
+                    point(control_2.x, control_2.y),
+                    point(p.x, p.y),
+                );
+            }
+            Command::Close => {
+                builder.end(true);
+                open = false;
+            }
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+    builder.build()
+}
+
+/// Extension trait for [`ShapePainter`] to enable it to draw rich text, requires the `text` feature.
+pub trait RichTextPainter {
+    /// Draws spans of text with their own font family, weight, style and color, shaped by `cosmic-text`.
+    ///
+    /// The top left of the text is placed at the painter's transform with lines running down along negative y.
+    /// Glyph outlines are tessellated into triangles and drawn through the triangle pipeline, each glyph is only
+    /// tessellated once per font size and cached in the [`ShapeFonts`].
+    fn text_rich(
+        &mut self,
+        fonts: &mut ShapeFonts,
+        spans: &[(&str, RichTextStyle)],
+        layout: &RichTextLayout,
+    ) -> &mut Self;
+}
+
+impl<'w, 's> RichTextPainter for ShapePainter<'w, 's> {
+    fn text_rich(
+        &mut self,
+        fonts: &mut ShapeFonts,
+        spans: &[(&str, RichTextStyle)],
+        layout: &RichTextLayout,
+    ) -> &mut Self {
+        let buffer = fonts.layout(spans, layout);
+        let glyphs: Vec<_> = buffer
+            .layout_runs()
+            .flat_map(|run| {
+                run.glyphs.iter().map(move |glyph| {
+                    // Layout runs point y down
+                    let origin = Vec2::new(glyph.x_int as f32, -(run.line_y + glyph.y_int as f32));
+                    (glyph.cache_key, origin, glyph.color_opt)
+                })
+            })
+            .collect();
+
+        let color = self.color;
+        for (key, origin, glyph_color) in glyphs {
+            self.color = glyph_color.map_or(color, |c| Color::rgba_u8(c.r(), c.g(), c.b(), c.a()));
+            for &(points, edges) in fonts.glyph_triangles(key) {
+                let data =
+                    TriangleData::with_edges(self.config(), points.map(|p| p + origin), edges);
+                self.send(data);
+            }
+        }
+        self.color = color;
+        self
+    }
+}
+
+/// Plugin that sets up the [`ShapeFonts`] resource, added by [`Shape2dPlugin`] when the `text` feature is enabled.
+pub struct ShapeTextPlugin;
+
+impl Plugin for ShapeTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeFonts>();
+    }
+}
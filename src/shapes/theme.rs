@@ -0,0 +1,120 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::prelude::*;
+
+/// Component providing a [`ShapeStyle`] that is inherited by the entity's shape and all shapes below it in the hierarchy.
+///
+/// Themes of nested entities are merged, with properties set by the nearer theme taking precedence,
+/// allowing a whole retained hierarchy to be restyled after it has been spawned.
+/// Shapes with their own [`Handle<ShapeStyle>`] are left to that style.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ShapeTheme(pub ShapeStyle);
+
+/// Style of an entity after merging the [`ShapeTheme`] of it's ancestors, maintained by [`propagate_shape_themes`].
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ComputedShapeTheme(ShapeStyle);
+
+impl ComputedShapeTheme {
+    pub fn get(&self) -> &ShapeStyle {
+        &self.0
+    }
+}
+
+/// Returns the inherited style with each property set by the given theme overridden.
+fn merge_theme(inherited: &ShapeStyle, theme: Option<&ShapeTheme>) -> ShapeStyle {
+    let Some(ShapeTheme(theme)) = theme else {
+        return inherited.clone();
+    };
+    ShapeStyle {
+        color: theme.color.or(inherited.color),
+        thickness: theme.thickness.or(inherited.thickness),
+        thickness_type: theme.thickness_type.or(inherited.thickness_type),
+        hollow: theme.hollow.or(inherited.hollow),
+        cap: theme.cap.or(inherited.cap),
+    }
+}
+
+/// Propagates [`ShapeTheme`] down the hierarchy into the [`ComputedShapeTheme`] of each descendant.
+///
+/// Entities that are no longer below a [`ShapeTheme`] are reset to an empty style, leaving their shapes as they are.
+pub fn propagate_shape_themes(
+    mut commands: Commands,
+    themes: Query<&ShapeTheme>,
+    roots: Query<Entity, With<ShapeTheme>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    mut computed: Query<(Entity, &mut ComputedShapeTheme)>,
+) {
+    let mut values = HashMap::new();
+    for root in &roots {
+        // Descendants of another entity with a theme are visited when walking down from that entity
+        let mut ancestor = root;
+        let mut nested = false;
+        while let Ok(parent) = parents.get(ancestor) {
+            ancestor = parent.get();
+            if themes.contains(ancestor) {
+                nested = true;
+                break;
+            }
+        }
+        if nested {
+            continue;
+        }
+
+        let mut stack = vec![(root, ShapeStyle::default())];
+        while let Some((entity, inherited)) = stack.pop() {
+            let value = merge_theme(&inherited, themes.get(entity).ok());
+            if let Ok(children) = children.get(entity) {
+                stack.extend(children.iter().map(|child| (*child, value.clone())));
+            }
+            values.insert(entity, value);
+        }
+    }
+
+    for (entity, mut theme) in &mut computed {
+        let value = values.remove(&entity).unwrap_or_default();
+        // Only write changes so that shapes aren't restyled every frame
+        if theme.0 != value {
+            theme.0 = value;
+        }
+    }
+    for (entity, value) in values {
+        commands.entity(entity).insert(ComputedShapeTheme(value));
+    }
+}
+
+/// Applies the [`ComputedShapeTheme`] to shapes of the given type when it changes or the shape is inserted.
+pub fn apply_shape_themes<T: StyledShape>(
+    mut shapes: Query<
+        (&mut T, &ComputedShapeTheme),
+        (
+            Or<(Changed<ComputedShapeTheme>, Added<T>)>,
+            Without<Handle<ShapeStyle>>,
+        ),
+    >,
+) {
+    for (mut shape, theme) in &mut shapes {
+        shape.apply_style(&theme.0);
+    }
+}
+
+/// Plugin that propagates [`ShapeTheme`] to the built-in shape types, added by [`Shape2dPlugin`](crate::Shape2dPlugin).
+pub struct ShapeThemePlugin;
+
+impl Plugin for ShapeThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(propagate_shape_themes.in_base_set(CoreSet::PostUpdate))
+            .add_systems(
+                (
+                    apply_shape_themes::<Line>,
+                    apply_shape_themes::<QuadBezier>,
+                    apply_shape_themes::<Disc>,
+                    apply_shape_themes::<Rectangle>,
+                    apply_shape_themes::<RegularPolygon>,
+                    apply_shape_themes::<ShapeMorph>,
+                )
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(propagate_shape_themes),
+            );
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::{
+    core::{Pod, Zeroable},
+    math::Vec3A,
+    prelude::*,
+    reflect::{FromReflect, Reflect},
+    render::{primitives::Aabb, render_resource::ShaderRef},
+};
+use wgpu::vertex_attr_array;
+
+use crate::{
+    prelude::*,
+    render::{Flags, ShapeComponent, ShapeData, TRIANGLE_HANDLE},
+};
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::prelude::{InspectorOptions, ReflectInspectorOptions};
+
+/// Component containing the data for drawing a filled triangle.
+///
+/// Triangles are the building block of tessellated paths, which leave the edges shared between
+/// adjacent triangles aliased so that they meet without seams, see [`Triangle::aa_edges`].
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "inspector",
+    derive(InspectorOptions),
+    reflect(InspectorOptions)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Triangle {
+    pub color: Color,
+    pub alignment: Alignment,
+    /// Properties shared by every shape type, see [`ShapeCommon`].
+    pub common: ShapeCommon,
+
+    /// Corners of the triangle in world space relative to it's transform.
+    pub points: [Vec2; 3],
+    /// Bitmask of the edges to anti-alias, made up of [`Triangle::EDGE_AB`], [`Triangle::EDGE_BC`] and [`Triangle::EDGE_CA`].
+    #[cfg_attr(feature = "inspector", inspector(min = 0, max = Triangle::EDGE_ALL))]
+    pub aa_edges: u32,
+}
+
+impl Triangle {
+    /// Edge from the first to the second point.
+    pub const EDGE_AB: u32 = 0b001;
+    /// Edge from the second to the third point.
+    pub const EDGE_BC: u32 = 0b010;
+    /// Edge from the third to the first point.
+    pub const EDGE_CA: u32 = 0b100;
+    /// Every edge of the triangle.
+    pub const EDGE_ALL: u32 = 0b111;
+
+    pub fn new(config: &ShapeConfig, a: Vec2, b: Vec2, c: Vec2) -> Self {
+        Self {
+            color: config.color,
+            alignment: config.alignment,
+            common: ShapeCommon::from(config),
+
+            points: [a, b, c],
+            aa_edges: Self::EDGE_ALL,
+        }
+    }
+}
+
+impl Default for Triangle {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            alignment: default(),
+            common: default(),
+
+            points: [
+                Vec2::new(0.0, 1.0),
+                Vec2::new(-1.0, -1.0),
+                Vec2::new(1.0, -1.0),
+            ],
+            aa_edges: Self::EDGE_ALL,
+        }
+    }
+}
+
+impl ShapeComponent for Triangle {
+    type Data = TriangleData;
+
+    fn into_data(&self, tf: &GlobalTransform) -> TriangleData {
+        let mut flags = Flags(0);
+        flags.set_alignment(self.alignment);
+
+        TriangleData {
+            transform: tf.compute_matrix().to_cols_array_2d(),
+
+            color: self.color.as_rgba_f32(),
+            common: self.common.data(flags),
+
+            a: self.points[0],
+            b: self.points[1],
+            c: self.points[2],
+            aa_edges: self.aa_edges,
+        }
+    }
+
+    fn aabb(&self) -> Option<Aabb> {
+        let [a, b, c] = self.points;
+        let (min, max) = (a.min(b).min(c), a.max(b).max(c));
+        let mut aabb = flat_aabb((max - min) / 2.0, self.alignment, self.common.depth_bias);
+        aabb.center += Vec3A::from(((min + max) / 2.0).extend(0.0));
+        Some(aabb)
+    }
+}
+
+/// Raw data sent to the triangle shader to draw a triangle
+#[derive(Clone, Copy, Reflect, FromReflect, Pod, Zeroable, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct TriangleData {
+    transform: [[f32; 4]; 4],
+
+    color: [f32; 4],
+    common: ShapeCommonData,
+
+    a: Vec2,
+    b: Vec2,
+    c: Vec2,
+    aa_edges: u32,
+}
+
+impl TriangleData {
+    pub fn new(config: &ShapeConfig, a: Vec2, b: Vec2, c: Vec2) -> TriangleData {
+        Self::with_edges(config, [a, b, c], Triangle::EDGE_ALL)
+    }
+
+    /// Triangle that only anti-aliases the given edges, see [`Triangle::aa_edges`].
+    pub fn with_edges(config: &ShapeConfig, points: [Vec2; 3], aa_edges: u32) -> TriangleData {
+        let mut flags = Flags(0);
+        flags.set_alignment(config.alignment);
+
+        TriangleData {
+            transform: config.transform.compute_matrix().to_cols_array_2d(),
+
+            color: config.color.as_rgba_f32(),
+            common: ShapeCommon::from(config).data(flags),
+
+            a: points[0],
+            b: points[1],
+            c: points[2],
+            aa_edges,
+        }
+    }
+
+    /// Rebuilds the component the data was created from, properties not stored in the data are taken from the given config.
+    pub(crate) fn component(&self, config: &ShapeConfig) -> Triangle {
+        Triangle {
+            color: Color::from(self.color),
+            aa_edges: self.aa_edges,
+            ..Triangle::new(config, self.a, self.b, self.c)
+        }
+    }
+}
+
+impl ShapeData for TriangleData {
+    type Component = Triangle;
+
+    fn vertex_layout() -> Vec<wgpu::VertexAttribute> {
+        vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+
+            4 => Float32x4,
+            5 => Uint32x4,
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x2,
+            9 => Float32x2,
+            10 => Float32x2,
+            11 => Uint32,
+        ]
+        .to_vec()
+    }
+
+    fn shader() -> ShaderRef {
+        TRIANGLE_HANDLE.typed::<Shader>().into()
+    }
+
+    fn transform(&self) -> Mat4 {
+        Mat4::from_cols_array_2d(&self.transform)
+    }
+
+    fn bounding_radius(&self) -> f32 {
+        let extent = self.a.length().max(self.b.length()).max(self.c.length());
+        extent * max_scale(&self.transform()) + self.common.depth_bias().abs()
+    }
+
+    fn multiply_alpha(&mut self, alpha: f32) {
+        self.color[3] *= alpha;
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color.as_rgba_f32();
+    }
+
+    fn offset_z(&mut self, offset: f32) {
+        self.transform[3][2] += offset;
+    }
+}
+
+/// Extension trait for [`ShapePainter`] to enable it to draw triangles.
+pub trait TrianglePainter {
+    fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) -> &mut Self;
+}
+
+impl<'w, 's> TrianglePainter for ShapePainter<'w, 's> {
+    fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) -> &mut Self {
+        self.send(TriangleData::new(self.config(), a, b, c))
+    }
+}
+
+/// Extension trait for [`ShapeBundle`] to enable creation of triangle bundles.
+pub trait TriangleBundle {
+    fn triangle(config: &ShapeConfig, a: Vec2, b: Vec2, c: Vec2) -> Self;
+}
+
+impl TriangleBundle for ShapeBundle<Triangle> {
+    fn triangle(config: &ShapeConfig, a: Vec2, b: Vec2, c: Vec2) -> Self {
+        Self::new(config, Triangle::new(config, a, b, c))
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to enable spawning of triangle entities.
+pub trait TriangleSpawner<'w, 's> {
+    fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> TriangleSpawner<'w, 's> for T {
+    fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) -> ShapeEntityCommands<'w, 's, '_> {
+        self.spawn_shape(ShapeBundle::triangle(self.config(), a, b, c))
+    }
+}
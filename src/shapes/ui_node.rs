@@ -0,0 +1,175 @@
+use bevy::{
+    prelude::*,
+    reflect::{FromReflect, Reflect},
+    render::{Extract, RenderApp},
+    ui::CalculatedClip,
+};
+
+use crate::{
+    prelude::*,
+    render::{ExtractedShapes, ShapeData, ShapePipelineMaterial},
+};
+
+/// Component that draws a shape sized to the computed layout of a `bevy_ui` node in the UI pass.
+///
+/// The shape follows the node's size, position, clip and visibility, allowing rounded panels, rings and dividers
+/// to replace the background color of a `NodeBundle`. Shapes are styled by the node's [`ShapeNodeStyle`] and drawn
+/// at the node's z translation, see [`ShapePass::Ui`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+pub enum ShapeNode {
+    /// Rectangle covering the node.
+    Rect {
+        /// Corner rounding radius for each corner in logical pixels, ordered top right, top left, bottom left and bottom right.
+        corner_radii: Vec4,
+    },
+    /// Disc inscribed in the node.
+    Disc,
+    /// Line through the center of the node along it's longer axis, as thick as the node's shorter axis.
+    Line,
+}
+
+impl Default for ShapeNode {
+    fn default() -> Self {
+        ShapeNode::Rect {
+            corner_radii: Vec4::ZERO,
+        }
+    }
+}
+
+impl ShapeNode {
+    /// Rectangle covering the node with the same rounding radius on every corner.
+    pub fn rounded(radius: f32) -> Self {
+        ShapeNode::Rect {
+            corner_radii: Vec4::splat(radius),
+        }
+    }
+}
+
+/// Component containing the style of a [`ShapeNode`], nodes without one are drawn as filled white shapes.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(Component, Default)]
+pub struct ShapeNodeStyle {
+    pub color: Color,
+    /// Thickness of the outline of hollow shapes in logical pixels.
+    pub thickness: f32,
+    /// Whether to only draw the outline of rectangles and discs, lines are unaffected.
+    pub hollow: bool,
+    /// Cap type of lines, the line is shortened so that the caps stay within the node.
+    pub cap: Cap,
+}
+
+impl Default for ShapeNodeStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            thickness: 1.0,
+            hollow: false,
+            cap: Cap::None,
+        }
+    }
+}
+
+/// Bundle of the components needed to lay out and draw a [`ShapeNode`].
+#[derive(Bundle, Clone, Default)]
+pub struct ShapeNodeBundle {
+    pub node: NodeBundle,
+    pub shape: ShapeNode,
+    pub shape_style: ShapeNodeStyle,
+}
+
+/// Extracts each visible [`ShapeNode`] into the UI pass of the 2D shape pipeline.
+pub fn extract_shape_nodes(
+    mut commands: Commands,
+    nodes: Extract<
+        Query<(
+            &Node,
+            &ShapeNode,
+            Option<&ShapeNodeStyle>,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&CalculatedClip>,
+            Option<&ComputedOpacity>,
+        )>,
+    >,
+) {
+    let mut rects = Vec::new();
+    let mut discs = Vec::new();
+    let mut lines = Vec::new();
+    for (node, shape, style, tf, vis, clip, opacity) in &nodes {
+        let size = node.size();
+        if !vis.is_visible() || size.min_element() <= 0.0 {
+            continue;
+        }
+
+        let style = style.copied().unwrap_or_default();
+        let mut config = ShapeConfig {
+            transform: tf.compute_transform(),
+            color: style.color,
+            thickness: style.thickness,
+            hollow: style.hollow,
+            cap: style.cap,
+            clip: clip.map(|clip| clip.clip),
+            pass: ShapePass::Ui,
+            ..ShapeConfig::default_2d()
+        };
+        let material = ShapePipelineMaterial::from(&config);
+        let alpha = opacity.map_or(1.0, ComputedOpacity::get);
+
+        match *shape {
+            ShapeNode::Rect { corner_radii } => {
+                config.corner_radii = corner_radii;
+                let mut data = RectData::new(&config, size);
+                data.multiply_alpha(alpha);
+                rects.push((material, data));
+            }
+            ShapeNode::Disc => {
+                let mut data = DiscData::circle(&config, size.min_element() / 2.0);
+                data.multiply_alpha(alpha);
+                discs.push((material, data));
+            }
+            ShapeNode::Line => {
+                let thickness = size.min_element();
+                config.thickness = thickness;
+                config.hollow = false;
+                // Caps extend past the ends of the line by half it's thickness
+                let inset = match config.cap {
+                    Cap::None => 0.0,
+                    Cap::Square | Cap::Round => thickness / 2.0,
+                };
+                let end = if size.x >= size.y {
+                    Vec3::X * (size.x / 2.0 - inset)
+                } else {
+                    Vec3::Y * (size.y / 2.0 - inset)
+                };
+                let mut data = LineData::new(&config, -end, end);
+                data.multiply_alpha(alpha);
+                lines.push((material, data));
+            }
+        }
+    }
+
+    if !rects.is_empty() {
+        commands.spawn(ExtractedShapes::<RectData>(rects));
+    }
+    if !discs.is_empty() {
+        commands.spawn(ExtractedShapes::<DiscData>(discs));
+    }
+    if !lines.is_empty() {
+        commands.spawn(ExtractedShapes::<LineData>(lines));
+    }
+}
+
+/// Plugin that draws [`ShapeNode`]s, added by [`Shape2dPlugin`].
+pub struct ShapeNodePlugin;
+
+impl Plugin for ShapeNodePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ShapeNode>()
+            .register_type::<ShapeNodeStyle>();
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_system(extract_shape_nodes.in_schedule(ExtractSchedule));
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+
+/// Component that layers an entity and it's descendants among their siblings, such as the parts of a composed widget.
+///
+/// Shapes are offset along world z by [`LocalZOrder::STEP`] per order after transforms are propagated,
+/// orders of nested entities are summed so that a whole subtree is layered together.
+/// Higher orders are drawn on top in 2D, the entity's [`Transform`] is left untouched.
+#[derive(
+    Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Reflect, FromReflect,
+)]
+#[reflect(Component, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalZOrder(pub i32);
+
+impl LocalZOrder {
+    /// Distance along world z between consecutive orders.
+    pub const STEP: f32 = 0.001;
+}
+
+/// Offset along world z currently applied to an entity's [`GlobalTransform`] by [`apply_local_z_order`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct AppliedZOrder(f32);
+
+/// Offsets the [`GlobalTransform`] of entities with a [`LocalZOrder`] and their descendants.
+///
+/// Offsets are reapplied whenever transform propagation overwrites a [`GlobalTransform`] and adjusted when orders change,
+/// entities that are no longer below a [`LocalZOrder`] have their offset removed.
+pub fn apply_local_z_order(
+    mut commands: Commands,
+    orders: Query<&LocalZOrder>,
+    roots: Query<Entity, With<LocalZOrder>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    applied: Query<Entity, With<AppliedZOrder>>,
+    mut transforms: Query<(&mut GlobalTransform, Option<&mut AppliedZOrder>)>,
+) {
+    let mut offsets = HashMap::new();
+    for root in &roots {
+        // Descendants of another entity with an order are visited when walking down from that entity
+        let mut ancestor = root;
+        let mut nested = false;
+        while let Ok(parent) = parents.get(ancestor) {
+            ancestor = parent.get();
+            if orders.contains(ancestor) {
+                nested = true;
+                break;
+            }
+        }
+        if nested {
+            continue;
+        }
+
+        let mut stack = vec![(root, 0)];
+        while let Some((entity, inherited)) = stack.pop() {
+            let order = inherited + orders.get(entity).map_or(0, |order| order.0);
+            offsets.insert(entity, order);
+            if let Ok(children) = children.get(entity) {
+                stack.extend(children.iter().map(|child| (*child, order)));
+            }
+        }
+    }
+
+    for entity in &applied {
+        offsets.entry(entity).or_insert(0);
+    }
+
+    for (entity, order) in offsets {
+        let Ok((mut global, applied)) = transforms.get_mut(entity) else {
+            continue;
+        };
+        let offset = order as f32 * LocalZOrder::STEP;
+        // Propagation writes the transform without our offset, otherwise it still contains the previous one
+        let current = match &applied {
+            Some(applied) if !global.is_changed() => applied.0,
+            _ => 0.0,
+        };
+        if offset != current {
+            *global = GlobalTransform::from_translation(Vec3::Z * (offset - current)) * *global;
+        }
+        match applied {
+            Some(mut applied) if applied.0 != offset => applied.0 = offset,
+            None if offset != 0.0 => {
+                commands.entity(entity).insert(AppliedZOrder(offset));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Plugin that applies [`LocalZOrder`], added by [`Shape2dPlugin`](crate::Shape2dPlugin).
+pub struct LocalZOrderPlugin;
+
+impl Plugin for LocalZOrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LocalZOrder>().add_system(
+            apply_local_z_order
+                .in_base_set(CoreSet::PostUpdate)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
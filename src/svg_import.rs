@@ -0,0 +1,715 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    math::Affine2,
+    prelude::*,
+    utils::BoxedFuture,
+};
+use lyon::path::{
+    builder::SvgPathBuilder,
+    math::{point, vector, Angle},
+    ArcFlags, Path,
+};
+use roxmltree::Node;
+
+use crate::{prefab::ShapePrefab, prelude::*};
+
+/// Loads [`ShapePrefab`] assets from SVG files, requires the `svg_import` feature.
+///
+/// See [`svg_to_prefab`] for the supported subset of SVG.
+#[derive(Default)]
+pub struct ShapeSvgLoader;
+
+impl AssetLoader for ShapeSvgLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let prefab = svg_to_prefab(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(prefab));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["svg"]
+    }
+}
+
+/// Converts an SVG document into a [`ShapePrefab`] with a node for each group and shape.
+///
+/// Circles, lines and rectangles with uniform corner radii are converted to the matching shape,
+/// ellipses, polylines, polygons and paths are tessellated into triangles.
+/// Fills, strokes, opacity, line caps and transforms are supported, as are presentation attributes set through the `style` attribute.
+/// Gradients, patterns, text, images, clipping, masking and `<use>` elements are ignored.
+///
+/// One SVG user unit becomes one world unit with y pointing up, the prefab is centered on the document's `viewBox`.
+/// Shapes are ordered by offsetting each along z by [`LocalZOrder::STEP`] in document order.
+pub fn svg_to_prefab(text: &str) -> Result<ShapePrefab, roxmltree::Error> {
+    let document = roxmltree::Document::parse(text)?;
+    let root = document.root_element();
+
+    // Documents without a view box are centered on the area given by their width and height
+    let center = match numbers(root.attribute("viewBox").unwrap_or_default())[..] {
+        [x, y, width, height] => Vec2::new(x + width / 2.0, y + height / 2.0),
+        _ => {
+            Vec2::new(
+                root.attribute("width").and_then(length).unwrap_or(0.0),
+                root.attribute("height").and_then(length).unwrap_or(0.0),
+            ) / 2.0
+        }
+    };
+
+    let mut importer = SvgImporter { order: 0 };
+    let mut children = Vec::new();
+    importer.import_children(root, &SvgStyle::default().inherit(root), &mut children);
+
+    Ok(ShapePrefab {
+        transform: Transform::from_xyz(-center.x, center.y, 0.0),
+        shape: None,
+        children,
+    })
+}
+
+/// Presentation attributes inherited by each element from it's ancestors.
+#[derive(Clone)]
+struct SvgStyle {
+    fill: Option<Color>,
+    stroke: Option<Color>,
+    stroke_width: f32,
+    cap: Cap,
+    opacity: f32,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            fill: Some(Color::BLACK),
+            stroke: None,
+            stroke_width: 1.0,
+            cap: Cap::None,
+            opacity: 1.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+        }
+    }
+}
+
+impl SvgStyle {
+    /// Style of the given element, inheriting any attributes it doesn't set from this style.
+    fn inherit(&self, node: Node) -> Self {
+        let mut style = self.clone();
+        if let Some(fill) = property(node, "fill").and_then(color) {
+            style.fill = fill;
+        }
+        if let Some(stroke) = property(node, "stroke").and_then(color) {
+            style.stroke = stroke;
+        }
+        if let Some(width) = property(node, "stroke-width").and_then(length) {
+            style.stroke_width = width;
+        }
+        match property(node, "stroke-linecap") {
+            Some("butt") => style.cap = Cap::None,
+            Some("square") => style.cap = Cap::Square,
+            Some("round") => style.cap = Cap::Round,
+            _ => {}
+        }
+        if let Some(opacity) = property(node, "opacity").and_then(length) {
+            style.opacity *= opacity.clamp(0.0, 1.0);
+        }
+        if let Some(opacity) = property(node, "fill-opacity").and_then(length) {
+            style.fill_opacity = opacity.clamp(0.0, 1.0);
+        }
+        if let Some(opacity) = property(node, "stroke-opacity").and_then(length) {
+            style.stroke_opacity = opacity.clamp(0.0, 1.0);
+        }
+        style
+    }
+
+    fn fill_color(&self) -> Option<Color> {
+        let mut color = self.fill?;
+        color.set_a(color.a() * self.fill_opacity * self.opacity);
+        Some(color)
+    }
+
+    fn stroke_color(&self) -> Option<Color> {
+        let mut color = self.stroke?;
+        if self.stroke_width <= 0.0 {
+            return None;
+        }
+        color.set_a(color.a() * self.stroke_opacity * self.opacity);
+        Some(color)
+    }
+}
+
+/// Value of a presentation attribute, declarations in the `style` attribute take precedence.
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute("style")
+        .and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == name).then(|| value.trim())
+            })
+        })
+        .or_else(|| node.attribute(name))
+}
+
+/// Parses a length in user units, units other than `px` are treated as user units.
+fn length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E' || c == '%')
+        .unwrap_or(value.len());
+    value[..end].trim().parse().ok()
+}
+
+/// Parses a color, returning `Some(None)` for `none` and colors that can't be drawn such as gradients.
+fn color(value: &str) -> Option<Option<Color>> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        return Color::hex(hex).ok().map(Some);
+    }
+    if let Some(channels) = value
+        .strip_prefix("rgb(")
+        .and_then(|value| value.strip_suffix(')'))
+    {
+        let channels: Vec<f32> = channels
+            .split(',')
+            .filter_map(|channel| match channel.trim().strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok().map(|p| p / 100.0),
+                None => channel.trim().parse::<f32>().ok().map(|c| c / 255.0),
+            })
+            .collect();
+        return match channels[..] {
+            [r, g, b] => Some(Some(Color::rgb(r, g, b))),
+            _ => None,
+        };
+    }
+    match value {
+        "inherit" => None,
+        "none" | "transparent" => Some(None),
+        _ if value.starts_with("url(") => Some(None),
+        "black" | "currentColor" => Some(Some(Color::BLACK)),
+        "white" => Some(Some(Color::WHITE)),
+        "red" => Some(Some(Color::RED)),
+        "green" => Some(Some(Color::rgb_u8(0, 128, 0))),
+        "lime" => Some(Some(Color::GREEN)),
+        "blue" => Some(Some(Color::BLUE)),
+        "yellow" => Some(Some(Color::YELLOW)),
+        "cyan" | "aqua" => Some(Some(Color::CYAN)),
+        "magenta" | "fuchsia" => Some(Some(Color::FUCHSIA)),
+        "gray" | "grey" => Some(Some(Color::GRAY)),
+        "orange" => Some(Some(Color::ORANGE)),
+        "purple" => Some(Some(Color::PURPLE)),
+        _ => None,
+    }
+}
+
+/// Parses a list of numbers separated by whitespace or commas.
+fn numbers(value: &str) -> Vec<f32> {
+    let mut tokens = PathTokens::new(value);
+    std::iter::from_fn(|| tokens.number()).collect()
+}
+
+/// Parses the `transform` attribute of an element into an affine transform in SVG space.
+fn transform(node: Node) -> Affine2 {
+    let Some(value) = node.attribute("transform") else {
+        return Affine2::IDENTITY;
+    };
+    value
+        .split(')')
+        .filter_map(|function| {
+            let (name, args) = function.split_once('(')?;
+            let args = numbers(args);
+            let arg = |i: usize| args.get(i).copied();
+            let name = name.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            Some(match (name.trim_end(), args.len()) {
+                ("matrix", 6) => Affine2::from_cols_array(&[
+                    args[0], args[1], args[2], args[3], args[4], args[5],
+                ]),
+                ("translate", 1 | 2) => {
+                    Affine2::from_translation(Vec2::new(args[0], arg(1).unwrap_or(0.0)))
+                }
+                ("scale", 1 | 2) => {
+                    Affine2::from_scale(Vec2::new(args[0], arg(1).unwrap_or(args[0])))
+                }
+                ("rotate", 1) => Affine2::from_angle(args[0].to_radians()),
+                ("rotate", 3) => {
+                    let pivot = Vec2::new(args[1], args[2]);
+                    Affine2::from_translation(pivot)
+                        * Affine2::from_angle(args[0].to_radians())
+                        * Affine2::from_translation(-pivot)
+                }
+                ("skewX", 1) => {
+                    Affine2::from_cols_array(&[1.0, 0.0, args[0].to_radians().tan(), 1.0, 0.0, 0.0])
+                }
+                ("skewY", 1) => {
+                    Affine2::from_cols_array(&[1.0, args[0].to_radians().tan(), 0.0, 1.0, 0.0, 0.0])
+                }
+                _ => return None,
+            })
+        })
+        .fold(Affine2::IDENTITY, |transform, next| transform * next)
+}
+
+/// Converts an affine transform in SVG space, where y points down, into a transform with y pointing up.
+///
+/// Skew can't be represented by a [`Transform`] and is dropped.
+fn to_transform(affine: Affine2, z: f32) -> Transform {
+    let flip = Affine2::from_scale(Vec2::new(1.0, -1.0));
+    let affine = flip * affine * flip;
+    Transform::from_matrix(Mat4::from_cols(
+        affine.matrix2.x_axis.extend(0.0).extend(0.0),
+        affine.matrix2.y_axis.extend(0.0).extend(0.0),
+        Vec4::Z,
+        affine.translation.extend(z).extend(1.0),
+    ))
+}
+
+/// Tokenizer for path data and number lists.
+struct PathTokens<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> PathTokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len()
+            - rest
+                .trim_start_matches(|c: char| c.is_whitespace() || c == ',')
+                .len();
+    }
+
+    /// Next command letter, if the next token is a command.
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = self.text[self.pos..].chars().next()?;
+        if c.is_ascii_alphabetic() {
+            self.pos += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the next token is a number, such that the previous command is repeated.
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        self.text[self.pos..]
+            .starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        let mut end = start;
+        if matches!(bytes.get(end), Some(b'-' | b'+')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&byte) = bytes.get(end) {
+            match byte {
+                b'0'..=b'9' => end += 1,
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                    end += 1;
+                }
+                b'e' | b'E' => {
+                    end += 1;
+                    if matches!(bytes.get(end), Some(b'-' | b'+')) {
+                        end += 1;
+                    }
+                    while bytes.get(end).map_or(false, u8::is_ascii_digit) {
+                        end += 1;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+        let number = self.text[start..end].parse().ok()?;
+        self.pos = end;
+        Some(number)
+    }
+
+    /// Arc flags may be written without separators, such as `a1 1 0 01 5 5`.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.text[self.pos..].chars().next()? {
+            '0' => false,
+            '1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<Vec2> {
+        Some(Vec2::new(self.number()?, self.number()?))
+    }
+
+    /// Control point and end point of a quadratic or smooth cubic segment.
+    fn points(&mut self) -> Option<(Vec2, Vec2)> {
+        Some((self.point()?, self.point()?))
+    }
+
+    /// Control points and end point of a cubic segment.
+    fn curve(&mut self) -> Option<(Vec2, Vec2, Vec2)> {
+        Some((self.point()?, self.point()?, self.point()?))
+    }
+
+    /// Radii, rotation, large arc and sweep flags and end point of an arc segment.
+    fn arc(&mut self) -> Option<(Vec2, f32, bool, bool, Vec2)> {
+        Some((
+            self.point()?,
+            self.number()?,
+            self.flag()?,
+            self.flag()?,
+            self.point()?,
+        ))
+    }
+}
+
+/// Parses SVG path data into a [`Path`], stopping at the first malformed segment.
+fn path_data(data: &str) -> Path {
+    let mut builder = Path::svg_builder();
+    let mut tokens = PathTokens::new(data);
+    let to_point = |p: Vec2| point(p.x, p.y);
+    let to_vector = |p: Vec2| vector(p.x, p.y);
+
+    let mut started = false;
+    'commands: while let Some(mut command) = tokens.command() {
+        loop {
+            if !started && !matches!(command, 'M' | 'm') {
+                break 'commands;
+            }
+            let segment = match command {
+                'M' => tokens.point().map(|p| {
+                    builder.move_to(to_point(p));
+                    // Coordinates following a move are implicit lines
+                    command = 'L';
+                }),
+                'm' => tokens.point().map(|p| {
+                    builder.relative_move_to(to_vector(p));
+                    command = 'l';
+                }),
+                'L' => tokens.point().map(|p| {
+                    builder.line_to(to_point(p));
+                }),
+                'l' => tokens.point().map(|p| {
+                    builder.relative_line_to(to_vector(p));
+                }),
+                'H' => tokens.number().map(|x| {
+                    builder.horizontal_line_to(x);
+                }),
+                'h' => tokens.number().map(|x| {
+                    builder.relative_horizontal_line_to(x);
+                }),
+                'V' => tokens.number().map(|y| {
+                    builder.vertical_line_to(y);
+                }),
+                'v' => tokens.number().map(|y| {
+                    builder.relative_vertical_line_to(y);
+                }),
+                'Q' => tokens.points().map(|(ctrl, to)| {
+                    builder.quadratic_bezier_to(to_point(ctrl), to_point(to));
+                }),
+                'q' => tokens.points().map(|(ctrl, to)| {
+                    builder.relative_quadratic_bezier_to(to_vector(ctrl), to_vector(to));
+                }),
+                'T' => tokens.point().map(|to| {
+                    builder.smooth_quadratic_bezier_to(to_point(to));
+                }),
+                't' => tokens.point().map(|to| {
+                    builder.smooth_relative_quadratic_bezier_to(to_vector(to));
+                }),
+                'C' => tokens.curve().map(|(ctrl1, ctrl2, to)| {
+                    builder.cubic_bezier_to(to_point(ctrl1), to_point(ctrl2), to_point(to));
+                }),
+                'c' => tokens.curve().map(|(ctrl1, ctrl2, to)| {
+                    builder.relative_cubic_bezier_to(
+                        to_vector(ctrl1),
+                        to_vector(ctrl2),
+                        to_vector(to),
+                    );
+                }),
+                'S' => tokens.points().map(|(ctrl, to)| {
+                    builder.smooth_cubic_bezier_to(to_point(ctrl), to_point(to));
+                }),
+                's' => tokens.points().map(|(ctrl, to)| {
+                    builder.smooth_relative_cubic_bezier_to(to_vector(ctrl), to_vector(to));
+                }),
+                'A' | 'a' => tokens.arc().map(|(radii, rotation, large_arc, sweep, to)| {
+                    let flags = ArcFlags { large_arc, sweep };
+                    let rotation = Angle::degrees(rotation);
+                    if command == 'A' {
+                        builder.arc_to(to_vector(radii), rotation, flags, to_point(to));
+                    } else {
+                        builder.relative_arc_to(to_vector(radii), rotation, flags, to_vector(to));
+                    }
+                }),
+                'Z' | 'z' => {
+                    builder.close();
+                    break;
+                }
+                _ => None,
+            };
+            if segment.is_none() {
+                break 'commands;
+            }
+            started = true;
+            if !tokens.has_number() {
+                break;
+            }
+        }
+    }
+    builder.build()
+}
+
+/// Path of an ellipse centered on the given point.
+fn ellipse_path(center: Vec2, radii: Vec2) -> Path {
+    let mut builder = Path::svg_builder();
+    let flags = ArcFlags {
+        large_arc: false,
+        sweep: true,
+    };
+    builder.move_to(point(center.x + radii.x, center.y));
+    builder.arc_to(
+        vector(radii.x, radii.y),
+        Angle::zero(),
+        flags,
+        point(center.x - radii.x, center.y),
+    );
+    builder.arc_to(
+        vector(radii.x, radii.y),
+        Angle::zero(),
+        flags,
+        point(center.x + radii.x, center.y),
+    );
+    builder.close();
+    builder.build()
+}
+
+/// Path through the points of a `<polyline>` or `<polygon>`.
+fn polyline_path(points: &str, close: bool) -> Path {
+    let mut builder = Path::svg_builder();
+    let points = numbers(points);
+    let mut points = points.chunks_exact(2);
+    if let Some(start) = points.next() {
+        builder.move_to(point(start[0], start[1]));
+        for p in points {
+            builder.line_to(point(p[0], p[1]));
+        }
+        if close {
+            builder.close();
+        }
+    }
+    builder.build()
+}
+
+/// State carried through the document while importing.
+struct SvgImporter {
+    /// Number of shapes imported so far, used to order shapes along z.
+    order: u32,
+}
+
+impl SvgImporter {
+    fn import_children(&mut self, node: Node, style: &SvgStyle, nodes: &mut Vec<ShapePrefab>) {
+        for child in node.children().filter(Node::is_element) {
+            self.import_element(child, style, nodes);
+        }
+    }
+
+    fn import_element(
+        &mut self,
+        node: Node,
+        parent_style: &SvgStyle,
+        nodes: &mut Vec<ShapePrefab>,
+    ) {
+        if property(node, "display") == Some("none") {
+            return;
+        }
+        let style = parent_style.inherit(node);
+        let attr = |name: &str| node.attribute(name).and_then(length).unwrap_or(0.0);
+        let element_transform = transform(node);
+
+        match node.tag_name().name() {
+            "g" | "svg" | "a" | "switch" => {
+                let mut children = Vec::new();
+                self.import_children(node, &style, &mut children);
+                if !children.is_empty() {
+                    nodes.push(ShapePrefab {
+                        transform: to_transform(element_transform, 0.0),
+                        shape: None,
+                        children,
+                    });
+                }
+            }
+            "rect" => {
+                let size = Vec2::new(attr("width"), attr("height"));
+                if size.cmple(Vec2::ZERO).any() {
+                    return;
+                }
+                // Elliptical corners can't be represented so the smaller radius is used for both
+                let radius = match (node.attribute("rx"), node.attribute("ry")) {
+                    (Some(_), Some(_)) => attr("rx").min(attr("ry")),
+                    (Some(_), None) => attr("rx"),
+                    (None, Some(_)) => attr("ry"),
+                    (None, None) => 0.0,
+                }
+                .clamp(0.0, size.min_element() / 2.0);
+                let transform = element_transform
+                    * Affine2::from_translation(Vec2::new(attr("x"), attr("y")) + size / 2.0);
+
+                let rect = |color, hollow, grow: f32| Rectangle {
+                    color,
+                    hollow,
+                    thickness: style.stroke_width,
+                    size: size + grow,
+                    corner_radii: Vec4::splat(if radius > 0.0 {
+                        radius + grow / 2.0
+                    } else {
+                        0.0
+                    }),
+                    ..default()
+                };
+                if let Some(color) = style.fill_color() {
+                    self.push_shape(nodes, transform, rect(color, false, 0.0).into());
+                }
+                if let Some(color) = style.stroke_color() {
+                    let stroke = rect(color, true, style.stroke_width);
+                    self.push_shape(nodes, transform, stroke.into());
+                }
+            }
+            "circle" => {
+                let radius = attr("r");
+                if radius <= 0.0 {
+                    return;
+                }
+                let transform = element_transform
+                    * Affine2::from_translation(Vec2::new(attr("cx"), attr("cy")));
+
+                if let Some(color) = style.fill_color() {
+                    let disc = Disc {
+                        color,
+                        radius,
+                        ..default()
+                    };
+                    self.push_shape(nodes, transform, disc.into());
+                }
+                if let Some(color) = style.stroke_color() {
+                    // Hollow discs are drawn inside their radius while SVG strokes are centered on it
+                    let ring = Disc {
+                        color,
+                        hollow: true,
+                        thickness: style.stroke_width,
+                        radius: radius + style.stroke_width / 2.0,
+                        ..default()
+                    };
+                    self.push_shape(nodes, transform, ring.into());
+                }
+            }
+            "line" => {
+                if let Some(color) = style.stroke_color() {
+                    let line = Line {
+                        color,
+                        thickness: style.stroke_width,
+                        cap: style.cap,
+                        start: Vec3::new(attr("x1"), -attr("y1"), 0.0),
+                        end: Vec3::new(attr("x2"), -attr("y2"), 0.0),
+                        ..default()
+                    };
+                    self.push_shape(nodes, element_transform, line.into());
+                }
+            }
+            "ellipse" => {
+                let center = Vec2::new(attr("cx"), attr("cy"));
+                let radii = Vec2::new(attr("rx"), attr("ry"));
+                if radii.cmpgt(Vec2::ZERO).all() {
+                    let path = ellipse_path(center, radii);
+                    self.push_path(nodes, element_transform, &path, &style);
+                }
+            }
+            "polyline" | "polygon" => {
+                if let Some(points) = node.attribute("points") {
+                    let path = polyline_path(points, node.tag_name().name() == "polygon");
+                    self.push_path(nodes, element_transform, &path, &style);
+                }
+            }
+            "path" => {
+                if let Some(data) = node.attribute("d") {
+                    self.push_path(nodes, element_transform, &path_data(data), &style);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds a node drawing the given shape, ordered in front of every shape added before it.
+    fn push_shape(
+        &mut self,
+        nodes: &mut Vec<ShapePrefab>,
+        transform: Affine2,
+        shape: DynamicShape,
+    ) {
+        self.order += 1;
+        nodes.push(ShapePrefab {
+            transform: to_transform(transform, self.order as f32 * LocalZOrder::STEP),
+            shape: Some(shape),
+            children: Vec::new(),
+        });
+    }
+
+    /// Adds nodes for the fill and stroke of a path, each containing the triangles of it's tessellation.
+    fn push_path(
+        &mut self,
+        nodes: &mut Vec<ShapePrefab>,
+        transform: Affine2,
+        path: &Path,
+        style: &SvgStyle,
+    ) {
+        let mut config = ShapeConfig::default_2d();
+        config.thickness = style.stroke_width;
+        config.cap = style.cap;
+
+        let layers = [(style.fill_color(), false), (style.stroke_color(), true)];
+        for (color, hollow) in layers {
+            let Some(color) = color else {
+                continue;
+            };
+            config.color = color;
+            config.hollow = hollow;
+            let Ok(triangles) = tessellate_path(&config, path, TessellatedPath::DEFAULT_TOLERANCE)
+            else {
+                continue;
+            };
+
+            // Paths are tessellated in SVG space so flip each triangle to point y up
+            let children = triangles
+                .into_iter()
+                .map(|mut triangle| {
+                    triangle.points = triangle.points.map(|p| Vec2::new(p.x, -p.y));
+                    ShapePrefab {
+                        shape: Some(triangle.into()),
+                        ..default()
+                    }
+                })
+                .collect();
+            self.order += 1;
+            nodes.push(ShapePrefab {
+                transform: to_transform(transform, self.order as f32 * LocalZOrder::STEP),
+                shape: None,
+                children,
+            });
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{animation::ShapeAnimation, prelude::*, render::ShapePipelineType};
+
+/// Asset describing layers of shapes whose properties change over time, loaded from `.anim.ron` or `.anim.json` files,
+/// requires the `vector_animation` feature.
+///
+/// Each layer draws a single shape, it's keyframes set any of the layer's transform, color, thickness, radius
+/// and morph progress at a point in time. Each property is interpolated between the keyframes that set it, eased by the
+/// later keyframe's easing, and holds it's first and last values before and after them:
+/// ```ron
+/// (
+///     layers: [
+///         (
+///             shape: Disc((radius: 1.0, color: Rgba(red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0))),
+///             keyframes: [
+///                 (time: 0.0, radius: Some(0.5)),
+///                 (time: 1.0, radius: Some(2.0), color: Some(Rgba(red: 0.0, green: 0.0, blue: 1.0, alpha: 1.0)), easing: CubicOut),
+///             ],
+///         ),
+///     ],
+/// )
+/// ```
+/// Play animations with [`VectorAnimationSpawner::spawn_vector_animation`].
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Default)]
+#[uuid = "9b1f6e2c-3d48-4a7e-8c25-e07a4d6b1f93"]
+#[serde(default)]
+pub struct VectorAnimation {
+    pub layers: Vec<VectorAnimationLayer>,
+}
+
+impl VectorAnimation {
+    /// Time in seconds of the last keyframe of any layer.
+    pub fn duration(&self) -> f32 {
+        self.layers
+            .iter()
+            .flat_map(|layer| &layer.keyframes)
+            .fold(0.0, |duration, keyframe| duration.max(keyframe.time))
+    }
+}
+
+/// Shape drawn by a [`VectorAnimation`] along with the keyframes animating it.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct VectorAnimationLayer {
+    /// Shape as it is drawn before any keyframe has set it's properties.
+    pub shape: DynamicShape,
+    /// Transform relative to the animation's root before any keyframe has set it.
+    pub transform: Transform,
+    /// Keyframes in order of their time.
+    pub keyframes: Vec<VectorKeyframe>,
+}
+
+/// Values of the properties of a [`VectorAnimationLayer`] at a point in time, unset properties aren't affected.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct VectorKeyframe {
+    /// Time in seconds since the start of the animation.
+    pub time: f32,
+    /// Easing applied when interpolating from the previous keyframe to this one.
+    pub easing: Easing,
+    pub transform: Option<Transform>,
+    pub color: Option<Color>,
+    pub thickness: Option<f32>,
+    /// Radius of a [`Disc`] or [`RegularPolygon`].
+    pub radius: Option<f32>,
+    /// Progress of a [`ShapeMorph`].
+    pub morph_progress: Option<f32>,
+}
+
+impl VectorAnimationLayer {
+    /// Interpolates the property returned by `get` at the given time, if any keyframe sets it.
+    fn sample<V: Copy>(
+        &self,
+        time: f32,
+        get: impl Fn(&VectorKeyframe) -> Option<V>,
+        lerp: impl Fn(V, V, f32) -> V,
+    ) -> Option<V> {
+        let mut previous: Option<(f32, V)> = None;
+        for keyframe in &self.keyframes {
+            let Some(value) = get(keyframe) else {
+                continue;
+            };
+            if keyframe.time <= time {
+                previous = Some((keyframe.time, value));
+                continue;
+            }
+            let Some((start, previous)) = previous else {
+                return Some(value);
+            };
+            let t = (time - start) / (keyframe.time - start);
+            return Some(lerp(previous, value, keyframe.easing.ease(t)));
+        }
+        previous.map(|(_, value)| value)
+    }
+
+    /// Applies the layer's properties at the given time to it's shape and transform.
+    fn apply(&self, time: f32, shape: &mut DynamicShape, transform: &mut Transform) {
+        *transform = self
+            .sample(time, |k| k.transform, lerp_transform)
+            .unwrap_or(self.transform);
+        if let Some(color) = self.sample(time, |k| k.color, AnimateColor::lerp) {
+            *shape_color(shape) = color;
+        }
+        if let Some(thickness) = self.sample(time, |k| k.thickness, AnimateThickness::lerp) {
+            if let Some(value) = shape_thickness(shape) {
+                *value = thickness;
+            }
+        }
+        if let Some(radius) = self.sample(time, |k| k.radius, AnimateRadius::lerp) {
+            match shape {
+                DynamicShape::Disc(disc) => disc.radius = radius,
+                DynamicShape::RegularPolygon(ngon) => ngon.radius = radius,
+                _ => {}
+            }
+        }
+        if let Some(progress) = self.sample(time, |k| k.morph_progress, AnimateMorph::lerp) {
+            if let DynamicShape::Morph(morph) = shape {
+                morph.progress = progress;
+            }
+        }
+    }
+}
+
+fn lerp_transform(start: Transform, end: Transform, t: f32) -> Transform {
+    Transform {
+        translation: start.translation.lerp(end.translation, t),
+        rotation: start.rotation.slerp(end.rotation, t),
+        scale: start.scale.lerp(end.scale, t),
+    }
+}
+
+fn shape_color(shape: &mut DynamicShape) -> &mut Color {
+    match shape {
+        DynamicShape::Line(shape) => &mut shape.color,
+        DynamicShape::QuadBezier(shape) => &mut shape.color,
+        DynamicShape::Disc(shape) => &mut shape.color,
+        DynamicShape::Rectangle(shape) => &mut shape.color,
+        DynamicShape::RegularPolygon(shape) => &mut shape.color,
+        DynamicShape::Morph(shape) => &mut shape.color,
+        DynamicShape::Triangle(shape) => &mut shape.color,
+    }
+}
+
+fn shape_thickness(shape: &mut DynamicShape) -> Option<&mut f32> {
+    match shape {
+        DynamicShape::Line(shape) => Some(&mut shape.thickness),
+        DynamicShape::QuadBezier(shape) => Some(&mut shape.thickness),
+        DynamicShape::Disc(shape) => Some(&mut shape.thickness),
+        DynamicShape::Rectangle(shape) => Some(&mut shape.thickness),
+        DynamicShape::RegularPolygon(shape) => Some(&mut shape.thickness),
+        DynamicShape::Morph(shape) => Some(&mut shape.thickness),
+        DynamicShape::Triangle(_) => None,
+    }
+}
+
+/// Loads [`VectorAnimation`] assets from RON or JSON files with the `.anim.ron` or `.anim.json` extensions.
+#[derive(Default)]
+pub struct VectorAnimationLoader;
+
+impl AssetLoader for VectorAnimationLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let animation: VectorAnimation =
+                match load_context.path().extension().and_then(|ext| ext.to_str()) {
+                    Some("json") => serde_json::from_slice(bytes)?,
+                    _ => ron::de::from_bytes(bytes)?,
+                };
+            load_context.set_default_asset(LoadedAsset::new(animation));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron", "anim.json"]
+    }
+}
+
+/// Component playing a [`VectorAnimation`] on the root entity it was spawned with, each layer is spawned as a child entity
+/// with a [`DynamicShape`].
+///
+/// Layers are spawned once the asset has loaded and respawned whenever the asset is modified.
+#[derive(Component)]
+pub struct VectorAnimationPlayer {
+    /// Config whose material and render settings are used for every layer.
+    pub config: ShapeConfig,
+    /// Multiplier applied to the time the animation advances by each frame.
+    pub speed: f32,
+    /// Whether the animation restarts once it reaches it's end.
+    pub looping: bool,
+    pub paused: bool,
+    time: f32,
+    duration: f32,
+    layers: Vec<Entity>,
+}
+
+impl VectorAnimationPlayer {
+    pub fn new(config: ShapeConfig) -> Self {
+        Self {
+            config,
+            speed: 1.0,
+            looping: false,
+            paused: false,
+            time: 0.0,
+            duration: 0.0,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Time in seconds since the start of the animation.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jumps to the given time in seconds, applied to the layers on the next update.
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.max(0.0);
+    }
+
+    /// Whether a non-looping animation has loaded and reached it's end.
+    pub fn finished(&self) -> bool {
+        !self.looping && !self.layers.is_empty() && self.time >= self.duration
+    }
+}
+
+/// Extension trait for [`ShapeSpawner`] to play [`VectorAnimation`] assets using the spawner's config.
+pub trait VectorAnimationSpawner<'w, 's>: ShapeSpawner<'w, 's> {
+    /// Spawns an entity at the config's transform that plays the animation once it has loaded.
+    fn spawn_vector_animation(
+        &mut self,
+        animation: Handle<VectorAnimation>,
+    ) -> ShapeEntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, T: ShapeSpawner<'w, 's>> VectorAnimationSpawner<'w, 's> for T {
+    fn spawn_vector_animation(
+        &mut self,
+        animation: Handle<VectorAnimation>,
+    ) -> ShapeEntityCommands<'w, 's, '_> {
+        let config = self.config().clone();
+        self.spawn_shape((
+            SpatialBundle::from_transform(config.transform),
+            animation,
+            VectorAnimationPlayer::new(config),
+        ))
+    }
+}
+
+/// Spawns the layers of each [`VectorAnimationPlayer`] once it's animation has loaded, respawning them when the animation is modified.
+pub fn spawn_vector_animations(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<VectorAnimation>>,
+    animations: Res<Assets<VectorAnimation>>,
+    mut players: Query<(
+        Entity,
+        Ref<Handle<VectorAnimation>>,
+        &mut VectorAnimationPlayer,
+    )>,
+) {
+    let modified: HashSet<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle.id()),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (entity, handle, mut player) in &mut players {
+        if !player.layers.is_empty() && !handle.is_changed() && !modified.contains(&handle.id()) {
+            continue;
+        }
+        let Some(animation) = animations.get(&*handle) else {
+            continue;
+        };
+
+        for layer in player.layers.drain(..) {
+            commands.entity(layer).despawn_recursive();
+        }
+        let config = &player.config;
+        let material = ShapeMaterial::from(config);
+        let render_layers = config.render_layers.unwrap_or_default();
+        let shape_3d = matches!(config.pipeline, ShapePipelineType::Shape3d);
+        let layers: Vec<_> = animation
+            .layers
+            .iter()
+            .map(|layer| {
+                let mut spawned = commands.spawn((
+                    SpatialBundle::from_transform(layer.transform),
+                    material.clone(),
+                    render_layers,
+                    layer.shape.clone(),
+                ));
+                if shape_3d {
+                    spawned.insert(Shape3d);
+                }
+                spawned.set_parent(entity).id()
+            })
+            .collect();
+        player.layers = layers;
+        player.duration = animation.duration();
+    }
+}
+
+/// Advances each [`VectorAnimationPlayer`] and applies the animation's keyframes to it's layers.
+pub fn play_vector_animations(
+    time: Res<Time>,
+    animations: Res<Assets<VectorAnimation>>,
+    mut players: Query<(&Handle<VectorAnimation>, &mut VectorAnimationPlayer)>,
+    mut layers: Query<(&mut DynamicShape, &mut Transform)>,
+) {
+    for (handle, mut player) in &mut players {
+        let Some(animation) = animations.get(handle) else {
+            continue;
+        };
+        if !player.paused {
+            player.time += time.delta_seconds() * player.speed;
+        }
+        let duration = animation.duration();
+        if player.looping && duration > 0.0 {
+            player.time = player.time.rem_euclid(duration);
+        } else {
+            player.time = player.time.clamp(0.0, duration);
+        }
+
+        for (layer, entity) in animation.layers.iter().zip(&player.layers) {
+            let Ok((mut shape, mut transform)) = layers.get_mut(*entity) else {
+                continue;
+            };
+            layer.apply(player.time, &mut shape, &mut transform);
+        }
+    }
+}
+
+/// Plugin that loads and plays [`VectorAnimation`] assets, requires the `vector_animation` feature.
+pub struct VectorAnimationPlugin;
+
+impl Plugin for VectorAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<VectorAnimation>()
+            .init_asset_loader::<VectorAnimationLoader>()
+            .add_systems((spawn_vector_animations, play_vector_animations).chain());
+    }
+}
@@ -0,0 +1,261 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::{
+    parry::shape::TypedShape,
+    prelude::{
+        Collider, Collision, FixedJoint, Joint, Position, PrismaticJoint, RevoluteJoint, Rotation,
+    },
+};
+
+use crate::prelude::*;
+
+/// Resource configuring how [`XpbdDebugPlugin`] draws bevy_xpbd's colliders, joints and contacts, requires the `xpbd_2d` feature.
+///
+/// Each category is skipped when it's color is unset.
+#[derive(Resource, Clone, Debug)]
+pub struct XpbdDebugConfig {
+    pub collider_color: Option<Color>,
+    pub joint_color: Option<Color>,
+    pub contact_color: Option<Color>,
+    /// Thickness of outlines, joints and contact normals.
+    pub thickness: f32,
+    pub thickness_type: ThicknessType,
+    /// Alpha of the fill of colliders relative to their outline, 0.0 disables fills.
+    pub fill_alpha: f32,
+    /// Radius of joint anchors and contact points in world units.
+    pub point_radius: f32,
+    /// Length of contact normals in world units.
+    pub normal_length: f32,
+}
+
+impl Default for XpbdDebugConfig {
+    fn default() -> Self {
+        Self {
+            collider_color: Some(Color::ORANGE),
+            joint_color: Some(Color::CYAN),
+            contact_color: Some(Color::RED),
+            thickness: 1.5,
+            thickness_type: ThicknessType::Pixels,
+            fill_alpha: 0.25,
+            point_radius: 2.0,
+            normal_length: 10.0,
+        }
+    }
+}
+
+/// Angle of a direction measured clockwise from positive y, the convention used by arcs.
+fn clock_angle(direction: Vec2) -> f32 {
+    direction.x.atan2(direction.y)
+}
+
+/// Fills a convex outline as a fan of triangles and draws it's edges.
+fn draw_polygon(painter: &mut ShapePainter, points: &[Vec2], color: Color, fill_alpha: f32) {
+    if fill_alpha > 0.0 && points.len() >= 3 {
+        painter.hollow = false;
+        painter.color = color;
+        painter.color.set_a(color.a() * fill_alpha);
+        let last = points.len() - 2;
+        for i in 1..=last {
+            let mut edges = Triangle::EDGE_BC;
+            if i == 1 {
+                edges |= Triangle::EDGE_AB;
+            }
+            if i == last {
+                edges |= Triangle::EDGE_CA;
+            }
+            let data = TriangleData::with_edges(
+                painter.config(),
+                [points[0], points[i], points[i + 1]],
+                edges,
+            );
+            painter.send(data);
+        }
+    }
+
+    painter.color = color;
+    for (i, start) in points.iter().enumerate() {
+        let end = points[(i + 1) % points.len()];
+        painter.line(start.extend(0.0), end.extend(0.0));
+    }
+}
+
+/// Draws the shape of each collider at it's position and rotation.
+pub fn draw_xpbd_colliders(
+    mut painter: ShapePainter,
+    config: Res<XpbdDebugConfig>,
+    colliders: Query<(&Collider, &Position, &Rotation)>,
+) {
+    let Some(color) = config.collider_color else {
+        return;
+    };
+    painter.thickness = config.thickness;
+    painter.thickness_type = config.thickness_type;
+    painter.cap = Cap::Round;
+
+    for (collider, position, rotation) in &colliders {
+        painter.transform = Transform::from_translation(position.0.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(rotation.as_radians()));
+        match collider.get_shape().as_typed_shape() {
+            TypedShape::Ball(ball) => {
+                if config.fill_alpha > 0.0 {
+                    painter.hollow = false;
+                    painter.color = color;
+                    painter.color.set_a(color.a() * config.fill_alpha);
+                    painter.circle(ball.radius);
+                }
+                painter.hollow = true;
+                painter.color = color;
+                painter.circle(ball.radius);
+            }
+            TypedShape::Cuboid(cuboid) => {
+                let half = Vec2::new(cuboid.half_extents.x, cuboid.half_extents.y);
+                let corners = [
+                    Vec2::new(-half.x, -half.y),
+                    Vec2::new(half.x, -half.y),
+                    half,
+                    Vec2::new(-half.x, half.y),
+                ];
+                draw_polygon(&mut painter, &corners, color, config.fill_alpha);
+            }
+            TypedShape::Capsule(capsule) => {
+                let a = Vec2::new(capsule.segment.a.x, capsule.segment.a.y);
+                let b = Vec2::new(capsule.segment.b.x, capsule.segment.b.y);
+                let radius = capsule.radius;
+                if config.fill_alpha > 0.0 {
+                    // A round capped line as thick as the capsule covers it exactly
+                    let mut fill = painter.config().clone();
+                    fill.thickness = radius * 2.0;
+                    fill.thickness_type = ThicknessType::World;
+                    fill.color = color;
+                    fill.color.set_a(color.a() * config.fill_alpha);
+                    painter.send_with_config(
+                        &fill,
+                        LineData::new(&fill, a.extend(0.0), b.extend(0.0)),
+                    );
+                }
+
+                painter.color = color;
+                painter.hollow = true;
+                let side = (b - a).normalize_or_zero().perp() * radius;
+                painter.line((a + side).extend(0.0), (b + side).extend(0.0));
+                painter.line((a - side).extend(0.0), (b - side).extend(0.0));
+                let transform = painter.transform;
+                for (center, start) in [(b, side), (a, -side)] {
+                    painter.translate(center.extend(0.0));
+                    let start_angle = clock_angle(start);
+                    painter.arc(radius, start_angle, start_angle + PI);
+                    painter.transform = transform;
+                }
+            }
+            TypedShape::Triangle(triangle) => {
+                let points = [triangle.a, triangle.b, triangle.c].map(|p| Vec2::new(p.x, p.y));
+                draw_polygon(&mut painter, &points, color, config.fill_alpha);
+            }
+            TypedShape::ConvexPolygon(polygon) => {
+                let points: Vec<_> = polygon
+                    .points()
+                    .iter()
+                    .map(|p| Vec2::new(p.x, p.y))
+                    .collect();
+                draw_polygon(&mut painter, &points, color, config.fill_alpha);
+            }
+            TypedShape::Segment(segment) => {
+                painter.color = color;
+                painter.line(
+                    Vec3::new(segment.a.x, segment.a.y, 0.0),
+                    Vec3::new(segment.b.x, segment.b.y, 0.0),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws each joint of the given type as lines from it's bodies to their anchors.
+pub fn draw_xpbd_joints<T: Joint + Component>(
+    mut painter: ShapePainter,
+    config: Res<XpbdDebugConfig>,
+    joints: Query<&T>,
+    bodies: Query<(&Position, &Rotation)>,
+) {
+    let Some(color) = config.joint_color else {
+        return;
+    };
+    painter.color = color;
+    painter.thickness = config.thickness;
+    painter.thickness_type = config.thickness_type;
+    painter.cap = Cap::Round;
+
+    for joint in &joints {
+        let [entity_1, entity_2] = joint.entities();
+        let (Ok((position_1, rotation_1)), Ok((position_2, rotation_2))) =
+            (bodies.get(entity_1), bodies.get(entity_2))
+        else {
+            continue;
+        };
+        let anchor_1 = position_1.0 + rotation_1.rotate(joint.local_anchor_1());
+        let anchor_2 = position_2.0 + rotation_2.rotate(joint.local_anchor_2());
+        for (start, end) in [
+            (position_1.0, anchor_1),
+            (anchor_1, anchor_2),
+            (position_2.0, anchor_2),
+        ] {
+            painter.transform = Transform::IDENTITY;
+            painter.line(start.extend(0.0), end.extend(0.0));
+        }
+        painter.hollow = false;
+        for anchor in [anchor_1, anchor_2] {
+            painter.transform = Transform::from_translation(anchor.extend(0.0));
+            painter.circle(config.point_radius);
+        }
+    }
+}
+
+/// Draws the point and normal of each contact reported this frame.
+pub fn draw_xpbd_contacts(
+    mut painter: ShapePainter,
+    config: Res<XpbdDebugConfig>,
+    mut collisions: EventReader<Collision>,
+) {
+    let Some(color) = config.contact_color else {
+        collisions.clear();
+        return;
+    };
+    painter.color = color;
+    painter.thickness = config.thickness;
+    painter.thickness_type = config.thickness_type;
+    painter.cap = Cap::Round;
+
+    for Collision(contact) in collisions.iter() {
+        painter.transform = Transform::IDENTITY;
+        let end = contact.point1 + contact.normal * config.normal_length;
+        painter.line(contact.point1.extend(0.0), end.extend(0.0));
+        painter.hollow = false;
+        for point in [contact.point1, contact.point2] {
+            painter.transform = Transform::from_translation(point.extend(0.0));
+            painter.circle(config.point_radius);
+        }
+    }
+}
+
+/// Plugin drawing bevy_xpbd's colliders, joints and contacts with the [`ShapePainter`], requires the `xpbd_2d` feature.
+///
+/// Replaces bevy_xpbd's own debug rendering with thick, anti-aliased and filled shapes, configured by [`XpbdDebugConfig`].
+/// Requires [`Shape2dPlugin`] or [`ShapePlugin`].
+pub struct XpbdDebugPlugin;
+
+impl Plugin for XpbdDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<XpbdDebugConfig>().add_systems(
+            (
+                draw_xpbd_colliders,
+                draw_xpbd_joints::<FixedJoint>,
+                draw_xpbd_joints::<RevoluteJoint>,
+                draw_xpbd_joints::<PrismaticJoint>,
+                draw_xpbd_contacts,
+            )
+                .in_base_set(CoreSet::Last),
+        );
+    }
+}